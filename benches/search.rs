@@ -0,0 +1,108 @@
+//! Compares nav mesh triangle search, running on A* with search buffers pooled per thread,
+//! against petgraph A* run on the same triangles graph, for many small queries.
+//!
+//! Run with `cargo bench --bench search`.
+
+use navmesh::*;
+use petgraph::{algo::astar, graph::NodeIndex, Graph, Undirected};
+use std::{collections::HashMap, hint::black_box, time::Instant};
+
+const SIZE: usize = 32;
+const QUERIES: usize = 20_000;
+
+fn grid_mesh(cols: usize, rows: usize) -> NavMesh {
+    let mut vertices = Vec::with_capacity((cols + 1) * (rows + 1));
+    for y in 0..=rows {
+        for x in 0..=cols {
+            vertices.push(NavVec3::new(x as Scalar, y as Scalar, 0.0));
+        }
+    }
+    let mut triangles = Vec::with_capacity(cols * rows * 2);
+    for y in 0..rows {
+        for x in 0..cols {
+            let a = (y * (cols + 1) + x) as u32;
+            let b = a + 1;
+            let c = a + cols as u32 + 2;
+            let d = a + cols as u32 + 1;
+            triangles.push((a, b, c).into());
+            triangles.push((c, d, a).into());
+        }
+    }
+    NavMesh::new(vertices, triangles).unwrap()
+}
+
+// Triangles adjacency graph weighted the same way as nav mesh one, with squared distance
+// between triangles centers.
+fn triangles_graph(mesh: &NavMesh) -> (Graph<(), Scalar, Undirected>, Vec<NodeIndex>) {
+    let vertices = mesh.vertices();
+    let centers = mesh
+        .triangles()
+        .iter()
+        .map(|t| {
+            (vertices[t.first as usize] + vertices[t.second as usize] + vertices[t.third as usize])
+                / 3.0
+        })
+        .collect::<Vec<_>>();
+    let mut graph = Graph::default();
+    let nodes = centers
+        .iter()
+        .map(|_| graph.add_node(()))
+        .collect::<Vec<_>>();
+    let mut edges = HashMap::<(u32, u32), usize>::new();
+    for (index, t) in mesh.triangles().iter().enumerate() {
+        for (a, b) in [(t.first, t.second), (t.second, t.third), (t.third, t.first)] {
+            match edges.remove(&(a.max(b), a.min(b))) {
+                Some(other) => {
+                    let weight = (centers[index] - centers[other]).sqr_magnitude();
+                    graph.add_edge(nodes[index], nodes[other], weight);
+                }
+                None => {
+                    edges.insert((a.max(b), a.min(b)), index);
+                }
+            }
+        }
+    }
+    (graph, nodes)
+}
+
+fn main() {
+    let mesh = grid_mesh(SIZE, SIZE);
+    let (graph, nodes) = triangles_graph(&mesh);
+    let count = mesh.triangles().len();
+    // short queries between nearby triangles, as issued by many agents every frame.
+    let queries = (0..QUERIES)
+        .map(|index| {
+            let from = (index * 7919) % count;
+            let to = (from + 2 * SIZE + 3) % count;
+            (from, to)
+        })
+        .collect::<Vec<_>>();
+
+    let timer = Instant::now();
+    for (from, to) in &queries {
+        black_box(mesh.find_path_triangles(*from, *to));
+    }
+    let pooled = timer.elapsed();
+
+    let timer = Instant::now();
+    for (from, to) in &queries {
+        let goal = nodes[*to];
+        black_box(astar(
+            &graph,
+            nodes[*from],
+            |node| node == goal,
+            |edge| *edge.weight(),
+            |_| 0.0,
+        ));
+    }
+    let petgraph = timer.elapsed();
+
+    println!(
+        "{} queries: pooled {:?} ({:?} per query), petgraph {:?} ({:?} per query)",
+        QUERIES,
+        pooled,
+        pooled / QUERIES as u32,
+        petgraph,
+        petgraph / QUERIES as u32,
+    );
+}
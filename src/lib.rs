@@ -2,15 +2,42 @@
 #[macro_use]
 extern crate approx;
 
+mod nav_breadcrumbs;
+mod nav_capacity;
+#[cfg(feature = "conformance")]
+mod nav_conformance;
+#[cfg(feature = "debug")]
+mod nav_debug;
 mod nav_grid;
+mod nav_heuristic;
+mod nav_influence;
 mod nav_islands;
 mod nav_mesh;
 mod nav_net;
+mod nav_occupancy;
+mod nav_path;
+mod nav_path_cache;
+mod nav_poly_mesh;
+mod nav_replan;
+mod nav_search;
+mod nav_tiled_mesh;
 mod nav_vec3;
 
-pub use crate::{nav_grid::*, nav_islands::*, nav_mesh::*, nav_net::*, nav_vec3::*};
+pub use crate::{
+    nav_breadcrumbs::*, nav_capacity::*, nav_grid::*, nav_heuristic::*, nav_influence::*,
+    nav_islands::*, nav_mesh::*, nav_net::*, nav_occupancy::*, nav_path::*, nav_path_cache::*,
+    nav_poly_mesh::*, nav_replan::*, nav_tiled_mesh::*, nav_vec3::*,
+};
+
+#[cfg(feature = "conformance")]
+pub use crate::nav_conformance::*;
+#[cfg(feature = "debug")]
+pub use crate::nav_debug::*;
 
+#[cfg(feature = "random")]
+use rand_core::RngCore;
 use serde::{Deserialize, Serialize};
+use spade::{rtree::RTree, SpatialObject};
 use std::{
     hash::{Hash, Hasher},
     result::Result as StdResult,
@@ -40,9 +67,45 @@ pub enum Error {
     /// Either cols or rows count is zero.
     /// (cols count, rows count)
     EmptyCells(usize, usize),
+    /// Either chunk cols or rows count is zero.
+    /// (chunk cols count, chunk rows count)
+    EmptyChunk(usize, usize),
     /// Trying to use cell coordinate out of bounds.
     /// (col, row, cols count, rows count)
     InvalidCellCoordinate(usize, usize, usize, usize),
+    /// Trying to use vertices range out of shared vertices pool bounds.
+    /// (range start, range end, pool vertices count)
+    VerticesRangeOutOfBounds(usize, usize, usize),
+    /// Trying to construct nav mesh with area types count not matching triangles count.
+    /// (area types count, triangles count)
+    AreaTypesCountDoesNotMatchTriangles(usize, usize),
+    /// Trying to use area type out of supported range.
+    /// (triangle index, area type)
+    AreaTypeOutOfBounds(u32, u8),
+    /// Trying to construct polygon with less than 3 vertices.
+    /// (polygon index, vertices count)
+    PolygonVerticesCountTooSmall(u32, usize),
+    /// Trying to construct polygon with vertice index out of vertices list.
+    /// (polygon index, local vertice index, global vertice index)
+    PolygonVerticeIndexOutOfBounds(u32, u32, u32),
+    /// Trying to construct polygon that is not convex.
+    /// (polygon index)
+    PolygonNotConvex(u32),
+    /// Trying to load chunk grid with size not matching chunk size.
+    /// (grid cols count, grid rows count, chunk cols count, chunk rows count)
+    ChunkSizeMismatch(usize, usize, usize, usize),
+    /// Trying to use layer index out of layers list.
+    /// (layer index, layers count)
+    InvalidLayer(usize, usize),
+    /// Trying to restore dynamic state with areas count not matching triangles count.
+    /// (state areas count, triangles count)
+    DynamicStateAreasCountDoesNotMatchTriangles(usize, usize),
+    /// Trying to attach payloads to nav net with payloads count not matching vertices count.
+    /// (payloads count, vertices count)
+    PayloadsCountDoesNotMatchVertices(usize, usize),
+    /// Could not attach off-mesh link end points to mirrored or merged nav mesh triangles.
+    /// (link index in source nav mesh)
+    CouldNotAttachOffMeshLink(usize),
 }
 
 /// Result data.
@@ -71,10 +134,143 @@ impl PartialEq for NavConnection {
 }
 
 pub(crate) const ZERO_TRESHOLD: Scalar = 1e-6;
+pub(crate) const SCALAR_MAX: Scalar = Scalar::MAX;
+
+/// Numerical tolerance settings of navigation structure.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NavTolerance {
+    /// Size of world unit used by structure data, relative to the unit internal thresholds are
+    /// tuned for (i.e. `0.01` for data authored in centimeters when thresholds are tuned for
+    /// meters).
+    #[serde(default = "NavTolerance::default_unit_scale")]
+    pub unit_scale: Scalar,
+    /// Zero threshold (epsilon) used by same point, raycast and side tests, before scaling by
+    /// `unit_scale`. Defaults to `1e-6`.
+    #[serde(default = "NavTolerance::default_epsilon")]
+    pub epsilon: Scalar,
+}
+
+impl Default for NavTolerance {
+    fn default() -> Self {
+        Self {
+            unit_scale: Self::default_unit_scale(),
+            epsilon: Self::default_epsilon(),
+        }
+    }
+}
+
+impl NavTolerance {
+    fn default_unit_scale() -> Scalar {
+        1.0
+    }
+
+    fn default_epsilon() -> Scalar {
+        ZERO_TRESHOLD
+    }
+
+    /// Zero threshold of quantity with given dimension in world units (`0` for unitless values,
+    /// `1` for lengths, `2` for areas and squared lengths and so on).
+    #[inline]
+    pub fn threshold(&self, dimension: i32) -> Scalar {
+        self.epsilon * self.unit_scale.powi(dimension)
+    }
+}
+
+/// Deterministic pseudo-random value in `[-1; 1]` range for given seed and connection.
+/// Connection order does not matter.
+pub(crate) fn connection_noise(seed: u64, connection: NavConnection) -> Scalar {
+    let first = connection.0.min(connection.1) as u64;
+    let second = connection.0.max(connection.1) as u64;
+    let mut v = seed ^ (first << 32 | second);
+    // splitmix64 finalizer.
+    v = v.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    v = (v ^ (v >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    v = (v ^ (v >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    v ^= v >> 31;
+    (v >> 11) as Scalar / (1u64 << 53) as Scalar * 2.0 - 1.0
+}
+
+/// Spatial objects together with RTree built over them, used by closest point queries.
+#[derive(Debug, Clone)]
+pub(crate) struct NavSpatialIndex<T: SpatialObject> {
+    pub rtree: RTree<T>,
+    pub objects: Vec<T>,
+}
+
+impl<T: SpatialObject + Clone> NavSpatialIndex<T> {
+    pub fn new(objects: Vec<T>) -> Self {
+        let mut rtree = RTree::new();
+        for object in &objects {
+            rtree.insert(object.clone());
+        }
+        Self { rtree, objects }
+    }
+}
+
+/// Uniformly distributed pseudo-random value in `[0; 1)` range.
+#[cfg(feature = "random")]
+pub(crate) fn random_unit<R: RngCore>(rng: &mut R) -> Scalar {
+    (rng.next_u32() >> 8) as Scalar / (1u32 << 24) as Scalar
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand_core::RngCore;
+
+    fn grid_mesh(cols: usize, rows: usize) -> NavMesh {
+        let mut vertices = Vec::with_capacity((cols + 1) * (rows + 1));
+        for y in 0..=rows {
+            for x in 0..=cols {
+                vertices.push(NavVec3::new(x as Scalar, y as Scalar, 0.0));
+            }
+        }
+        let mut triangles = Vec::with_capacity(cols * rows * 2);
+        for y in 0..rows {
+            for x in 0..cols {
+                let a = (y * (cols + 1) + x) as u32;
+                let b = a + 1;
+                let c = a + cols as u32 + 2;
+                let d = a + cols as u32 + 1;
+                triangles.push((a, b, c).into());
+                triangles.push((c, d, a).into());
+            }
+        }
+        NavMesh::new(vertices, triangles).unwrap()
+    }
+
+    // Wide room on the left and narrow door on the right, both connecting bottom and top areas.
+    fn door_mesh() -> NavMesh {
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(), // 0
+            (4.0, 0.0, 0.0).into(), // 1
+            (4.0, 1.0, 0.0).into(), // 2
+            (0.0, 1.0, 0.0).into(), // 3
+            (0.0, 2.0, 0.0).into(), // 4
+            (2.0, 2.0, 0.0).into(), // 5
+            (2.0, 1.0, 0.0).into(), // 6
+            (3.8, 1.0, 0.0).into(), // 7
+            (3.8, 2.0, 0.0).into(), // 8
+            (4.0, 2.0, 0.0).into(), // 9
+            (0.0, 3.0, 0.0).into(), // 10
+            (4.0, 3.0, 0.0).into(), // 11
+        ];
+        let triangles = vec![
+            (0, 1, 7).into(),   // 0
+            (1, 2, 7).into(),   // 1
+            (0, 7, 6).into(),   // 2
+            (0, 6, 3).into(),   // 3
+            (3, 6, 5).into(),   // 4
+            (5, 4, 3).into(),   // 5
+            (7, 2, 9).into(),   // 6
+            (9, 8, 7).into(),   // 7
+            (10, 4, 5).into(),  // 8
+            (10, 5, 8).into(),  // 9
+            (10, 8, 11).into(), // 10
+            (8, 9, 11).into(),  // 11
+        ];
+        NavMesh::new(vertices, triangles).unwrap()
+    }
 
     #[test]
     fn test_send_sync() {
@@ -161,62 +357,47 @@ mod tests {
 
     #[test]
     fn test_line_between_points() {
-        assert_eq!(
-            true,
-            NavVec3::is_line_between_points(
-                (0.0, -1.0, 0.0).into(),
-                (0.0, 1.0, 0.0).into(),
-                (-1.0, 0.0, 0.0).into(),
-                (1.0, 0.0, 0.0).into(),
-                (0.0, 0.0, 1.0).into(),
-            ),
-        );
-        assert_eq!(
-            false,
-            NavVec3::is_line_between_points(
-                (-2.0, -1.0, 0.0).into(),
-                (-2.0, 1.0, 0.0).into(),
-                (-1.0, 0.0, 0.0).into(),
-                (1.0, 0.0, 0.0).into(),
-                (0.0, 0.0, 1.0).into(),
-            ),
-        );
-        assert_eq!(
-            false,
-            NavVec3::is_line_between_points(
-                (2.0, -1.0, 0.0).into(),
-                (2.0, 1.0, 0.0).into(),
-                (-1.0, 0.0, 0.0).into(),
-                (1.0, 0.0, 0.0).into(),
-                (0.0, 0.0, 1.0).into(),
-            ),
-        );
-        assert_eq!(
-            true,
-            NavVec3::is_line_between_points(
-                (-1.0, -1.0, 0.0).into(),
-                (-1.0, 1.0, 0.0).into(),
-                (-1.0, 0.0, 0.0).into(),
-                (1.0, 0.0, 0.0).into(),
-                (0.0, 0.0, 1.0).into(),
-            ),
-        );
-        assert_eq!(
-            true,
-            NavVec3::is_line_between_points(
-                (1.0, -1.0, 0.0).into(),
-                (1.0, 1.0, 0.0).into(),
-                (-1.0, 0.0, 0.0).into(),
-                (1.0, 0.0, 0.0).into(),
-                (0.0, 0.0, 1.0).into(),
-            ),
-        );
+        assert!(NavVec3::is_line_between_points(
+            (0.0, -1.0, 0.0).into(),
+            (0.0, 1.0, 0.0).into(),
+            (-1.0, 0.0, 0.0).into(),
+            (1.0, 0.0, 0.0).into(),
+            (0.0, 0.0, 1.0).into(),
+        ));
+        assert!(!NavVec3::is_line_between_points(
+            (-2.0, -1.0, 0.0).into(),
+            (-2.0, 1.0, 0.0).into(),
+            (-1.0, 0.0, 0.0).into(),
+            (1.0, 0.0, 0.0).into(),
+            (0.0, 0.0, 1.0).into(),
+        ));
+        assert!(!NavVec3::is_line_between_points(
+            (2.0, -1.0, 0.0).into(),
+            (2.0, 1.0, 0.0).into(),
+            (-1.0, 0.0, 0.0).into(),
+            (1.0, 0.0, 0.0).into(),
+            (0.0, 0.0, 1.0).into(),
+        ));
+        assert!(NavVec3::is_line_between_points(
+            (-1.0, -1.0, 0.0).into(),
+            (-1.0, 1.0, 0.0).into(),
+            (-1.0, 0.0, 0.0).into(),
+            (1.0, 0.0, 0.0).into(),
+            (0.0, 0.0, 1.0).into(),
+        ));
+        assert!(NavVec3::is_line_between_points(
+            (1.0, -1.0, 0.0).into(),
+            (1.0, 1.0, 0.0).into(),
+            (-1.0, 0.0, 0.0).into(),
+            (1.0, 0.0, 0.0).into(),
+            (0.0, 0.0, 1.0).into(),
+        ));
     }
 
     #[test]
     fn test_spatials() {
         {
-            let vertices = vec![
+            let vertices = [
                 (0.0, 0.0, 0.0).into(),
                 (2.0, 0.0, 0.0).into(),
                 (0.0, 2.0, 0.0).into(),
@@ -226,7 +407,7 @@ mod tests {
             assert_eq!(s.normal(), (0.0, 0.0, 1.0).into());
         }
         {
-            let vertices = vec![
+            let vertices = [
                 (0.0, 0.0, 0.0).into(),
                 (2.0, 0.0, 2.0).into(),
                 (0.0, 2.0, 0.0).into(),
@@ -236,7 +417,7 @@ mod tests {
             assert_eq!(s.normal(), NavVec3::new(-1.0, 0.0, 1.0).normalize());
         }
         {
-            let vertices = vec![
+            let vertices = [
                 (1.0, 2.0, 0.0).into(),
                 (2.0, 2.0, 0.0).into(),
                 (2.0, 3.0, 0.0).into(),
@@ -661,6 +842,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::excessive_precision)]
     fn test_thicken() {
         let source = NavMesh::new(
             vec![
@@ -705,7 +887,7 @@ mod tests {
         )
         .unwrap();
         let thickened = source.thicken(1.0).unwrap();
-        let expected = vec![
+        let expected = [
             NavVec3 {
                 x: -5.333333333333333,
                 y: -5.666666666666667,
@@ -753,122 +935,4608 @@ mod tests {
     }
 
     #[test]
-    fn test_grid() {
-        let grid = NavGrid::new(
-            3,
-            3,
-            vec![true, true, true, true, false, true, true, true, true],
-        )
-        .unwrap();
-        let path = grid.find_path((0, 0), (1, 2)).unwrap();
-        assert_eq!(path, vec![(0, 0), (0, 1), (0, 2), (1, 2)]);
-        assert_eq!(grid.find_path((0, 0), (1, 1)), None);
+    fn test_jitter() {
+        let mesh = grid_mesh(6, 6);
+        let (from, to) = (0, mesh.triangles().len() - 1);
+        let plain = mesh.find_path_triangles(from, to).unwrap().0;
+        let options = NavPathOptions {
+            jitter: Some(NavPathJitter {
+                seed: 0,
+                amount: 0.0,
+            }),
+            ..Default::default()
+        };
+        let path = mesh
+            .find_path_triangles_with_options(from, to, &options, |_, _, _| true)
+            .unwrap()
+            .0;
+        assert_eq!(path, plain);
 
-        let grid = NavGrid::with_connections(
-            2,
-            2,
-            vec![
-                NavGridConnection {
-                    from: (0, 0),
-                    to: (1, 0),
-                },
-                NavGridConnection {
-                    from: (1, 0),
-                    to: (1, 1),
-                },
-                NavGridConnection {
-                    from: (1, 1),
-                    to: (0, 1),
-                },
-                NavGridConnection {
-                    from: (0, 1),
-                    to: (0, 0),
-                },
-            ],
-        )
-        .unwrap();
-        let path = grid.find_path((0, 0), (0, 1)).unwrap();
-        assert_eq!(path, vec![(0, 0), (1, 0), (1, 1), (0, 1)]);
-        let mut islands = grid.find_islands();
-        for island in &mut islands {
-            island.sort();
-        }
-        assert_eq!(islands, vec![vec![(0, 0), (0, 1), (1, 0), (1, 1)]]);
+        let paths = (0..8)
+            .map(|seed| {
+                let options = NavPathOptions {
+                    jitter: Some(NavPathJitter { seed, amount: 0.5 }),
+                    ..Default::default()
+                };
+                let path = mesh
+                    .find_path_triangles_with_options(from, to, &options, |_, _, _| true)
+                    .unwrap()
+                    .0;
+                let again = mesh
+                    .find_path_triangles_with_options(from, to, &options, |_, _, _| true)
+                    .unwrap()
+                    .0;
+                assert_eq!(path, again);
+                assert_eq!(path.first(), Some(&from));
+                assert_eq!(path.last(), Some(&to));
+                path
+            })
+            .collect::<Vec<_>>();
+        assert!(paths.iter().any(|path| path != &paths[0]));
+    }
 
-        let grid = NavGrid::new(
-            3,
-            3,
-            vec![true, true, true, false, false, false, true, true, true],
-        )
-        .unwrap();
-        let mut islands = grid.find_islands();
-        for island in &mut islands {
-            island.sort();
-        }
-        assert_eq!(
-            islands,
-            vec![vec![(0, 0), (1, 0), (2, 0)], vec![(0, 2), (1, 2), (2, 2)]]
+    #[test]
+    fn test_corridor_widths() {
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(), // 0
+            (2.0, 0.0, 0.0).into(), // 1
+            (2.0, 1.0, 0.0).into(), // 2
+            (1.0, 1.0, 0.0).into(), // 3
+            (0.0, 2.0, 0.0).into(), // 4
+        ];
+        let triangles = vec![
+            (0, 3, 4).into(), // 0
+            (0, 1, 3).into(), // 1
+            (1, 2, 3).into(), // 2
+        ];
+        let mesh = NavMesh::new(vertices, triangles).unwrap();
+        let path = mesh.find_path_triangles(0, 2).unwrap().0;
+        let widths = mesh.corridor_widths(&path).unwrap();
+        assert_eq!(widths.len(), 2);
+        assert_relative_eq!(widths[0], (2.0 as Scalar).sqrt());
+        assert_relative_eq!(widths[1], (2.0 as Scalar).sqrt());
+        assert_relative_eq!(
+            mesh.corridor_min_width(&path).unwrap(),
+            (2.0 as Scalar).sqrt()
         );
-
-        let grid = NavFreeGrid::new(vec![
-            NavFreeGridConnection {
-                from: (0, 0),
-                to: (0, 2),
-            },
-            NavFreeGridConnection {
-                from: (0, 2),
-                to: (-1, -1),
-            },
-        ]);
-        let path = grid.find_path((0, 0), (-1, -1)).unwrap();
-        assert_eq!(path, vec![(0, 0), (0, 2), (-1, -1)]);
+        assert_eq!(mesh.corridor_min_width(&[0]), None);
+        assert_eq!(mesh.corridor_widths(&[0, 2]), None);
     }
 
     #[test]
-    fn test_islands() {
-        let grid_a = NavGrid::new(2, 2, vec![true, true, true, false]).unwrap();
-        let grid_b = NavGrid::new(2, 2, vec![true, true, false, true]).unwrap();
-        let island_a = NavIslandPortal {
-            island: grid_a.id(),
-            portal: None,
+    fn test_min_passage_width() {
+        let mesh = door_mesh();
+        let from = NavVec3::new(3.9, 0.5, 0.0);
+        let to = NavVec3::new(3.9, 2.5, 0.0);
+        let start = mesh
+            .find_closest_triangle(from, NavQuery::Accuracy)
+            .unwrap();
+        let end = mesh.find_closest_triangle(to, NavQuery::Accuracy).unwrap();
+        let narrow = mesh.find_path_triangles(start, end).unwrap().0;
+        assert!(mesh.corridor_min_width(&narrow).unwrap() < 0.5);
+        let options = NavPathOptions {
+            min_passage_width: 0.5,
+            ..Default::default()
         };
-        let island_a_portal = NavIslandPortal {
-            island: grid_a.id(),
-            portal: Some((1, 0)),
+        let wide = mesh
+            .find_path_triangles_with_options(start, end, &options, |_, _, _| true)
+            .unwrap()
+            .0;
+        assert!(mesh.corridor_min_width(&wide).unwrap() >= 0.5);
+        let options = NavPathOptions {
+            min_passage_width: 10.0,
+            ..Default::default()
         };
-        let island_b_portal = NavIslandPortal {
-            island: grid_b.id(),
-            portal: Some((0, 0)),
+        assert!(mesh
+            .find_path_with_options(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::MidPoints,
+                &options,
+                |_, _, _| true
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_tabu_list() {
+        let mesh = door_mesh();
+        let from = NavVec3::new(3.9, 0.5, 0.0);
+        let to = NavVec3::new(3.9, 2.5, 0.0);
+        let start = mesh
+            .find_closest_triangle(from, NavQuery::Accuracy)
+            .unwrap();
+        let end = mesh.find_closest_triangle(to, NavQuery::Accuracy).unwrap();
+        let mut options = NavPathOptions {
+            tabu: Some(NavTabuList::new(100.0, 2.0)),
+            ..Default::default()
         };
-        let island_b = NavIslandPortal {
-            island: grid_b.id(),
-            portal: None,
+        let find = |options: &NavPathOptions| {
+            mesh.find_path_triangles_with_options(start, end, options, |_, _, _| true)
+                .unwrap()
+                .0
         };
-        let islands = NavIslands::new(
-            vec![
-                NavIslandsConnection {
-                    from: island_a.clone(),
-                    to: island_a_portal.clone(),
-                    distance: 1.0,
-                },
-                NavIslandsConnection {
-                    from: island_a_portal.clone(),
-                    to: island_b_portal.clone(),
-                    distance: 0.0,
-                },
-                NavIslandsConnection {
-                    from: island_b_portal.clone(),
-                    to: island_b.clone(),
-                    distance: 1.0,
-                },
-            ],
-            true,
+        let narrow = find(&options);
+        assert!(narrow.contains(&7));
+
+        // agent got stuck in the door.
+        let tabu = options.tabu.as_mut().unwrap();
+        tabu.report(7, 6);
+        tabu.report(6, 7);
+        assert_eq!(tabu.len(), 1);
+        assert!(tabu.is_tabu(6, 7));
+        assert_eq!(tabu.factor(7, 6), 100.0);
+        assert_eq!(tabu.factor(0, 2), 1.0);
+        let wide = find(&options);
+        assert!(!wide.contains(&7));
+        assert!(mesh.corridor_min_width(&wide).unwrap() >= 0.5);
+
+        let tabu = options.tabu.as_mut().unwrap();
+        tabu.update(1.0);
+        assert!(tabu.is_tabu(6, 7));
+        tabu.update(1.5);
+        assert!(tabu.is_empty());
+        assert_eq!(find(&options), narrow);
+    }
+
+    #[test]
+    fn test_funnel_collinear() {
+        // portal points collinear with funnel apex tighten funnel instead of becoming corners.
+        let mesh = grid_mesh(3, 3);
+        let find = |from: (Scalar, Scalar), to: (Scalar, Scalar)| {
+            mesh.find_path(
+                (from.0, from.1, 0.0).into(),
+                (to.0, to.1, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+            )
+            .unwrap()
+            .len()
+        };
+        // path starting on portal edge.
+        assert_eq!(find((1.0, 0.5), (2.5, 0.5)), 2);
+        assert_eq!(find((1.0, 1.5), (2.9, 1.5)), 2);
+        // straight path going exactly through portals vertices.
+        assert_eq!(find((0.2, 0.2), (2.8, 2.8)), 2);
+        assert_eq!(find((0.5, 0.5), (2.5, 2.5)), 2);
+    }
+
+    #[test]
+    fn test_funnel() {
+        fn quantize(path: Vec<NavVec3>) -> Vec<(i32, i32, i32)> {
+            path.into_iter()
+                .map(|v| {
+                    (
+                        (v.x * 10.0).round() as i32,
+                        (v.y * 10.0).round() as i32,
+                        (v.z * 10.0).round() as i32,
+                    )
+                })
+                .collect()
+        }
+
+        let mesh = door_mesh();
+        let path = mesh
+            .find_path(
+                (3.9, 0.5, 0.0).into(),
+                (3.0, 2.5, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+            )
+            .unwrap();
+        assert_eq!(quantize(path), vec![(39, 5, 0), (38, 20, 0), (30, 25, 0)]);
+        let path = mesh
+            .find_path(
+                (0.5, 0.5, 0.0).into(),
+                (0.5, 2.5, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+            )
+            .unwrap();
+        assert_eq!(quantize(path), vec![(5, 5, 0), (5, 25, 0)]);
+
+        let mesh = grid_mesh(4, 4);
+        let path = mesh
+            .find_path(
+                (0.5, 0.5, 0.0).into(),
+                (3.5, 3.0, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+            )
+            .unwrap();
+        assert_eq!(quantize(path), vec![(5, 5, 0), (35, 30, 0)]);
+
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(), // 0
+            (1.0, 0.0, 0.0).into(), // 1
+            (2.0, 0.0, 1.0).into(), // 2
+            (0.0, 1.0, 0.0).into(), // 3
+            (1.0, 1.0, 0.0).into(), // 4
+            (2.0, 1.0, 1.0).into(), // 5
+        ];
+        let triangles = vec![
+            (0, 1, 4).into(), // 0
+            (4, 3, 0).into(), // 1
+            (1, 2, 5).into(), // 2
+            (5, 4, 1).into(), // 3
+        ];
+        let mesh = NavMesh::new(vertices, triangles).unwrap();
+        let path = mesh
+            .find_path(
+                (0.0, 0.5, 0.0).into(),
+                (2.0, 0.5, 1.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+            )
+            .unwrap();
+        assert_eq!(quantize(path), vec![(0, 5, 0), (10, 5, 0), (20, 5, 10)]);
+    }
+
+    #[test]
+    fn test_sweep_path() {
+        let mut mesh = door_mesh();
+        let path = mesh
+            .find_path(
+                (3.9, 0.5, 0.0).into(),
+                (3.9, 2.5, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+            )
+            .unwrap();
+        assert_eq!(mesh.sweep_path(&path, 0.05), None);
+        let hit = mesh.sweep_path(&path, 0.5).unwrap();
+        assert_eq!(hit.segment, 0);
+        assert!(matches!(hit.obstacle, NavSweepObstacle::Boundary(_, _)));
+        assert!(hit.distance < 0.5 + 1.0e-3);
+
+        let id = mesh.add_blocker(NavBlocker {
+            position: (3.9, 1.5, 0.0).into(),
+            radius: 0.1,
+        });
+        let hit = mesh.sweep_path(&path, 0.05).unwrap();
+        assert_eq!(hit.obstacle, NavSweepObstacle::Blocker(id));
+        assert_relative_eq!(hit.position.y, 1.35, epsilon = 1.0e-3);
+        assert!(mesh.remove_blocker(id).is_some());
+        assert_eq!(mesh.sweep_path(&path, 0.05), None);
+
+        // capsule touches end wall and side wall exactly where expected.
+        let mesh = grid_mesh(4, 1);
+        let path = [NavVec3::new(1.0, 0.5, 0.0), NavVec3::new(5.0, 0.5, 0.0)];
+        let hit = mesh.sweep_path(&path, 0.25).unwrap();
+        assert!(matches!(hit.obstacle, NavSweepObstacle::Boundary(_, _)));
+        assert_relative_eq!(hit.position.x, 3.75, epsilon = 1.0e-4);
+        assert_relative_eq!(hit.distance, 2.75, epsilon = 1.0e-4);
+        let path = [NavVec3::new(1.0, 0.5, 0.0), NavVec3::new(5.0, 1.5, 0.0)];
+        let hit = mesh.sweep_path(&path, 0.25).unwrap();
+        assert_relative_eq!(hit.position.y, 0.75, epsilon = 1.0e-4);
+    }
+
+    #[test]
+    fn test_mesh_raycast() {
+        let mesh = door_mesh();
+        assert_eq!(
+            mesh.raycast((0.5, 0.5, 0.0).into(), (3.5, 0.5, 0.0).into()),
+            None
         );
-        let (distance, path) = islands.find_path(&island_a, &island_b).unwrap();
         assert_eq!(
-            path,
-            vec![&island_a, &island_a_portal, &island_b_portal, &island_b]
+            mesh.raycast((1.0, 0.5, 0.0).into(), (1.0, 2.5, 0.0).into()),
+            None
         );
-        assert!((distance - 2.0).abs() < 1.0e-6);
+        let hit = mesh
+            .raycast((3.0, 0.5, 0.0).into(), (3.0, 2.5, 0.0).into())
+            .unwrap();
+        assert_relative_eq!(hit.point.x, 3.0, epsilon = 1.0e-4);
+        assert_relative_eq!(hit.point.y, 1.0, epsilon = 1.0e-4);
+        assert_eq!(hit.edge, ((3.8, 1.0, 0.0).into(), (2.0, 1.0, 0.0).into()));
+        let hit = mesh
+            .raycast((0.5, 2.5, 0.0).into(), (0.5, 4.0, 0.0).into())
+            .unwrap();
+        assert_relative_eq!(hit.point.y, 3.0, epsilon = 1.0e-4);
+    }
+
+    #[test]
+    fn test_mesh_raycast_3d() {
+        let mut vertices = door_mesh().vertices().to_vec();
+        let mut triangles = door_mesh().triangles().to_vec();
+        // overhang above first room.
+        let offset = vertices.len() as u32;
+        vertices.extend([
+            NavVec3::new(0.0, 0.0, 2.0),
+            NavVec3::new(2.0, 0.0, 2.0),
+            NavVec3::new(0.0, 1.0, 2.0),
+        ]);
+        triangles.push((offset, offset + 1, offset + 2).into());
+        let mesh = NavMesh::new(vertices, triangles).unwrap();
+
+        let down = NavVec3::new(0.0, 0.0, -1.0);
+        let hit = mesh
+            .raycast_3d((0.5, 0.5, 10.0).into(), down * 3.0, 20.0)
+            .unwrap();
+        assert_eq!(hit.triangle, 12);
+        assert_relative_eq!(hit.point.z, 2.0, epsilon = 1.0e-4);
+        assert_relative_eq!(hit.distance, 8.0, epsilon = 1.0e-4);
+        let hit = mesh.raycast_3d((0.5, 0.5, 1.0).into(), down, 20.0).unwrap();
+        assert!(mesh.triangles()[hit.triangle].first != offset);
+        assert_relative_eq!(hit.point.z, 0.0, epsilon = 1.0e-4);
+        let hit = mesh
+            .raycast_3d((3.0, 2.5, -1.0).into(), -down, 20.0)
+            .unwrap();
+        assert_relative_eq!(hit.point.x, 3.0, epsilon = 1.0e-4);
+        assert_relative_eq!(hit.point.y, 2.5, epsilon = 1.0e-4);
+        assert_relative_eq!(hit.distance, 1.0, epsilon = 1.0e-4);
+        let hit = mesh
+            .raycast_3d((-1.0, 2.5, 2.0).into(), (1.0, 0.0, -1.0).into(), 20.0)
+            .unwrap();
+        assert_relative_eq!(hit.point.x, 1.0, epsilon = 1.0e-4);
+        assert!(mesh
+            .raycast_3d((5.0, 2.5, 10.0).into(), down, 20.0)
+            .is_none());
+        assert!(mesh
+            .raycast_3d((3.0, 1.5, 10.0).into(), down, 20.0)
+            .is_none());
+        assert!(mesh
+            .raycast_3d((0.5, 0.5, 10.0).into(), -down, 20.0)
+            .is_none());
+
+        // grazing ray walks through many triangles before hitting.
+        let mesh = grid_mesh(20, 20);
+        let hit = mesh
+            .raycast_3d((-1.0, 0.5, 0.5).into(), (1.0, 0.0, -0.05).into(), 1.0e6)
+            .unwrap();
+        assert_relative_eq!(hit.point.x, 9.0, epsilon = 1.0e-4);
+        assert_relative_eq!(hit.point.y, 0.5, epsilon = 1.0e-4);
+        assert!(mesh
+            .raycast_3d((-1.0, 0.5, 0.5).into(), (1.0, 0.0, -0.05).into(), 5.0)
+            .is_none());
+        assert!(mesh
+            .raycast_3d((-1.0e6, 0.5, 0.5).into(), (1.0, 0.0, 0.0).into(), 2.0e6)
+            .is_none());
+    }
+
+    #[test]
+    fn test_pick_destination() {
+        let mut mesh = door_mesh();
+        // lower room is water.
+        for index in 0..6 {
+            mesh.set_area_type(index, 2).unwrap();
+        }
+        let options = NavPathOptions {
+            area_mask: 1,
+            ..Default::default()
+        };
+        let down = NavVec3::new(0.0, 0.0, -1.0);
+        let goal = mesh
+            .pick_destination((1.0, 2.5, 10.0).into(), down, 20.0, 0.5, &options)
+            .unwrap();
+        assert_relative_eq!(goal.point.x, 1.0, epsilon = 1.0e-4);
+        assert_relative_eq!(goal.point.y, 2.5, epsilon = 1.0e-4);
+        assert!(matches!(goal.triangle, 8..=10));
+        let goal = mesh
+            .pick_destination((3.7, 0.8, 10.0).into(), down, 20.0, 0.5, &options)
+            .unwrap();
+        assert!(matches!(goal.triangle, 6 | 7));
+        assert_relative_eq!(goal.point.x, 3.8, epsilon = 1.0e-4);
+        assert_relative_eq!(goal.point.y, 1.0, epsilon = 1.0e-4);
+        assert!(mesh
+            .pick_destination((1.0, 0.5, 10.0).into(), down, 20.0, 0.5, &options)
+            .is_none());
+        assert!(mesh
+            .pick_destination((1.0, 2.5, 10.0).into(), down, 5.0, 0.5, &options)
+            .is_none());
+        assert!(mesh
+            .pick_destination(
+                (1.0, 0.5, 10.0).into(),
+                down,
+                20.0,
+                0.5,
+                &Default::default()
+            )
+            .is_some());
+
+        // blocked roof above walkable floor.
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(),
+            (2.0, 0.0, 0.0).into(),
+            (2.0, 2.0, 0.0).into(),
+            (0.0, 2.0, 0.0).into(),
+            (0.0, 0.0, 3.0).into(),
+            (2.0, 0.0, 3.0).into(),
+            (2.0, 2.0, 3.0).into(),
+            (0.0, 2.0, 3.0).into(),
+        ];
+        let triangles = vec![
+            (0, 1, 2).into(),
+            (2, 3, 0).into(),
+            (4, 5, 6).into(),
+            (6, 7, 4).into(),
+        ];
+        let mesh = NavMesh::with_area_types(vertices, triangles, vec![0, 0, 1, 1]).unwrap();
+        let options = NavPathOptions {
+            area_mask: 1,
+            ..Default::default()
+        };
+        let goal = mesh
+            .pick_destination((1.5, 0.5, 10.0).into(), down, 20.0, 0.0, &options)
+            .unwrap();
+        assert_eq!(goal.triangle, 0);
+        assert_relative_eq!(goal.point.z, 0.0, epsilon = 1.0e-4);
+        assert!(mesh
+            .pick_destination((1.5, 0.5, 10.0).into(), down, 8.0, 0.0, &options)
+            .is_none());
+    }
+
+    #[test]
+    fn test_mesh_portals() {
+        let mesh = door_mesh();
+        assert_eq!(mesh.portals().len(), 12);
+        for (triangles, portal) in mesh.portals() {
+            let a = mesh.vertices()[portal.edge.0 as usize];
+            let b = mesh.vertices()[portal.edge.1 as usize];
+            assert_relative_eq!(portal.length, (b - a).magnitude(), epsilon = 1.0e-6);
+            assert_eq!(portal.midpoint, (a + b) * 0.5);
+            assert_eq!(
+                mesh.portal(triangles.1 as usize, triangles.0 as usize)
+                    .map(|other| other.edge),
+                Some(portal.edge)
+            );
+        }
+        // door between rooms.
+        let portal = mesh.portal(6, 1).unwrap();
+        assert_eq!(portal.edge, NavConnection(2, 7));
+        assert_relative_eq!(portal.length, 0.2, epsilon = 1.0e-6);
+        assert_relative_eq!(portal.midpoint.x, 3.9, epsilon = 1.0e-6);
+        assert_relative_eq!(portal.midpoint.y, 1.0, epsilon = 1.0e-6);
+        assert_eq!(mesh.portal_width(1, 6), Some(portal.length));
+        assert_eq!(mesh.portal(0, 11), None);
+        assert_eq!(mesh.portal_width(0, 11), None);
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn test_bias_regions() {
+        use rand_core::SeedableRng;
+
+        let mut mesh = grid_mesh(4, 1);
+        let centroid = mesh.centroid().unwrap();
+        assert_relative_eq!(centroid.x, 2.0, epsilon = 1.0e-4);
+        assert_relative_eq!(centroid.y, 0.5, epsilon = 1.0e-4);
+
+        mesh.add_bias_region(NavBiasRegion {
+            center: (0.0, 0.5, 0.0).into(),
+            radius: 2.0,
+            weight: 0.0,
+        });
+        mesh.add_bias_region(NavBiasRegion {
+            center: (4.0, 0.5, 0.0).into(),
+            radius: 1.0,
+            weight: 10.0,
+        });
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(42);
+        let mut near = 0;
+        for _ in 0..200 {
+            let point = mesh.sample_biased_point(&mut rng).unwrap();
+            assert!(point.x >= 2.0 && point.x <= 4.0);
+            assert!(point.y >= 0.0 && point.y <= 1.0);
+            if point.x >= 3.0 {
+                near += 1;
+            }
+        }
+        assert!(near > 150);
+
+        mesh.clear_bias_regions();
+        mesh.add_bias_region(NavBiasRegion {
+            center: (2.0, 0.5, 0.0).into(),
+            radius: 10.0,
+            weight: 0.0,
+        });
+        assert_eq!(mesh.sample_biased_point(&mut rng), None);
+    }
+
+    #[test]
+    fn test_closest_point_ex() {
+        let mesh = grid_mesh(2, 1);
+        let closest = mesh
+            .closest_point_ex((1.75, 0.25, 3.0).into(), NavQuery::Accuracy)
+            .unwrap();
+        assert_eq!(closest.point, (1.75, 0.25, 0.0).into());
+        let t = mesh.triangles()[closest.triangle];
+        let [u, v, w] = closest.barycentric;
+        let rebuilt = mesh.vertices()[t.first as usize] * u
+            + mesh.vertices()[t.second as usize] * v
+            + mesh.vertices()[t.third as usize] * w;
+        assert!(rebuilt.same_as(closest.point));
+        assert_relative_eq!(u + v + w, 1.0, epsilon = 1.0e-5);
+        assert!(u >= 0.0 && v >= 0.0 && w >= 0.0);
+    }
+
+    #[test]
+    fn test_topology_only() {
+        let mesh = NavMesh::new_topology_only(
+            grid_mesh(2, 2).vertices().to_vec(),
+            grid_mesh(2, 2).triangles().to_vec(),
+        )
+        .unwrap();
+        assert!(!mesh.has_spatials());
+        assert!(mesh.find_path_triangles(0, 7).is_some());
+        assert!(!mesh.has_spatials());
+        let expected = grid_mesh(2, 2)
+            .find_path(
+                (0.1, 0.1, 0.0).into(),
+                (1.9, 1.9, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+            )
+            .unwrap();
+        let path = mesh
+            .find_path(
+                (0.1, 0.1, 0.0).into(),
+                (1.9, 1.9, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+            )
+            .unwrap();
+        assert!(mesh.has_spatials());
+        assert_eq!(path, expected);
+
+        let net = NavNet::new_topology_only(
+            vec![(0.0, 0.0, 0.0).into(), (1.0, 0.0, 0.0).into()],
+            vec![NavConnection(0, 1)],
+        )
+        .unwrap();
+        assert!(!net.has_spatials());
+        assert_eq!(
+            net.closest_point((0.5, 1.0, 0.0).into()),
+            Some((0.5, 0.0, 0.0).into())
+        );
+        assert!(net.has_spatials());
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn test_random_point() {
+        use rand_core::SeedableRng;
+
+        let mesh = grid_mesh(4, 4);
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(7);
+        let mut left = 0;
+        for _ in 0..400 {
+            let point = mesh.random_point(&mut rng).unwrap();
+            assert!((0.0..=4.0).contains(&point.x));
+            assert!((0.0..=4.0).contains(&point.y));
+            if point.x < 2.0 {
+                left += 1;
+            }
+        }
+        assert!((150..250).contains(&left));
+
+        let center = NavVec3::new(1.0, 3.0, 0.0);
+        for _ in 0..100 {
+            let point = mesh.random_point_around(center, 0.75, &mut rng).unwrap();
+            assert!((point - center).magnitude() <= 0.75);
+        }
+        assert_eq!(
+            mesh.random_point_around((10.0, 10.0, 0.0).into(), 1.0, &mut rng),
+            None
+        );
+    }
+
+    #[cfg(feature = "random")]
+    #[test]
+    fn test_wander_target() {
+        use rand_core::SeedableRng;
+
+        // U-shaped mesh with wall between top-left and top-right cells.
+        let mut vertices = vec![];
+        for y in 0..=3 {
+            for x in 0..=3 {
+                vertices.push(NavVec3::new(x as Scalar, y as Scalar, 0.0));
+            }
+        }
+        let mut triangles = vec![];
+        for y in 0..3 {
+            for x in 0..3 {
+                if x == 1 && y > 0 {
+                    continue;
+                }
+                let a = (y * 4 + x) as u32;
+                triangles.push((a, a + 1, a + 5).into());
+                triangles.push((a + 5, a + 4, a).into());
+            }
+        }
+        let mesh = NavMesh::new(vertices, triangles).unwrap();
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(3);
+        let origin = NavVec3::new(0.5, 2.5, 0.0);
+        let mut far_side = 0;
+        for _ in 0..20 {
+            let point = mesh.wander_target(origin, 3.5, 5.0, &mut rng).unwrap();
+            let length = mesh
+                .find_path_ex(
+                    origin,
+                    point,
+                    NavQuery::Accuracy,
+                    NavPathMode::Funnel,
+                    &NavPathOptions::default(),
+                    |_, _, _| true,
+                )
+                .unwrap()
+                .length;
+            assert!((3.5..=5.0).contains(&length));
+            if point.x > 2.0 {
+                far_side += 1;
+            }
+        }
+        assert_eq!(far_side, 20);
+        assert_eq!(mesh.wander_target(origin, 2.0, 1.0, &mut rng), None);
+    }
+
+    #[test]
+    fn test_shared_vertices() {
+        let source = grid_mesh(2, 1);
+        let pool: std::sync::Arc<[NavVec3]> = source.vertices().to_vec().into();
+        let mesh =
+            NavMesh::with_shared_vertices(pool.clone(), 0..pool.len(), source.triangles().to_vec())
+                .unwrap();
+        assert_eq!(mesh.vertices(), source.vertices());
+        assert_eq!(
+            mesh.find_path(
+                (0.1, 0.5, 0.0).into(),
+                (1.9, 0.5, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+            ),
+            Some(vec![(0.1, 0.5, 0.0).into(), (1.9, 0.5, 0.0).into()])
+        );
+        assert!(matches!(
+            NavMesh::with_shared_vertices(pool.clone(), 2..10, vec![]),
+            Err(Error::VerticesRangeOutOfBounds(2, 10, 6))
+        ));
+        assert!(matches!(
+            NavMesh::with_shared_vertices(pool, 3..6, vec![(0, 1, 3).into()]),
+            Err(Error::TriangleVerticeIndexOutOfBounds(0, 2, 3))
+        ));
+    }
+
+    #[test]
+    fn test_half_edges() {
+        let mesh = grid_mesh(2, 1);
+        let half_edges = mesh.half_edges();
+        assert_eq!(half_edges.len(), mesh.triangles().len() * 3);
+        for (index, edge) in half_edges.iter().enumerate() {
+            assert_eq!(edge.triangle, index / 3);
+            assert_eq!(half_edges[NavHalfEdge::next(index)].origin, edge.target);
+            assert_eq!(half_edges[NavHalfEdge::prev(index)].target, edge.origin);
+            if let Some(twin) = edge.twin {
+                assert_eq!(half_edges[twin].twin, Some(index));
+                assert_eq!(half_edges[twin].origin, edge.target);
+                assert_eq!(half_edges[twin].target, edge.origin);
+            }
+        }
+        // 2x1 quads grid has 3 interior edges shared by two triangles and 6 boundary edges.
+        assert_eq!(half_edges.iter().filter(|e| e.is_boundary()).count(), 6);
+        assert_eq!(half_edges.iter().filter(|e| !e.is_boundary()).count(), 6);
+    }
+
+    #[test]
+    fn test_area_types() {
+        // 3x3 quads grid with middle column of water and middle cell of road.
+        let source = grid_mesh(3, 3);
+        let area_types = (0..source.triangles().len())
+            .map(|i| {
+                let cell = i / 2;
+                match (cell % 3, cell / 3) {
+                    (1, 1) => 2,
+                    (1, _) => 1,
+                    _ => 0,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mesh = NavMesh::with_area_types(
+            source.vertices().to_vec(),
+            source.triangles().to_vec(),
+            area_types,
+        )
+        .unwrap();
+        assert_eq!(mesh.areas()[8].area_type, 2);
+        let from = (0.5, 1.5, 0.0).into();
+        let to = (2.5, 1.5, 0.0).into();
+
+        let no_swim = NavPathOptions {
+            area_mask: 0b101,
+            ..Default::default()
+        };
+        let path = mesh
+            .find_path_with_options(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &no_swim,
+                |_, _, _| true,
+            )
+            .unwrap();
+        assert_eq!(path, vec![from, to]);
+        let no_swim_no_road = NavPathOptions {
+            area_mask: 0b001,
+            ..Default::default()
+        };
+        assert!(mesh
+            .find_path_with_options(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &no_swim_no_road,
+                |_, _, _| true,
+            )
+            .is_none());
+
+        let (_, cheap) = mesh
+            .find_path_triangles_with_options(6, 11, &Default::default(), |_, _, _| true)
+            .unwrap();
+        let expensive_road = NavPathOptions {
+            area_costs: vec![1.0, 1.0, 100.0],
+            ..Default::default()
+        };
+        let (triangles, cost) = mesh
+            .find_path_triangles_with_options(6, 11, &expensive_road, |_, _, _| true)
+            .unwrap();
+        assert!(!triangles.contains(&8) && !triangles.contains(&9));
+        assert!(cost > cheap);
+
+        assert!(matches!(
+            NavMesh::with_area_types(
+                source.vertices().to_vec(),
+                source.triangles().to_vec(),
+                vec![0]
+            ),
+            Err(Error::AreaTypesCountDoesNotMatchTriangles(1, 18))
+        ));
+        assert!(matches!(
+            NavMesh::with_area_types(
+                source.vertices().to_vec(),
+                source.triangles().to_vec(),
+                vec![64; 18]
+            ),
+            Err(Error::AreaTypeOutOfBounds(0, 64))
+        ));
+    }
+
+    #[test]
+    fn test_off_mesh_links() {
+        // two separate 2x1 platforms: [0; 2] and [3; 5] on X axis.
+        let left = grid_mesh(2, 1);
+        let mut vertices = left.vertices().to_vec();
+        let mut triangles = left.triangles().to_vec();
+        let offset = vertices.len() as u32;
+        vertices.extend(
+            left.vertices()
+                .iter()
+                .map(|v| *v + NavVec3::new(3.0, 0.0, 0.0)),
+        );
+        triangles.extend(left.triangles().iter().map(|t| NavTriangle {
+            first: t.first + offset,
+            second: t.second + offset,
+            third: t.third + offset,
+        }));
+        let mut mesh = NavMesh::new(vertices, triangles).unwrap();
+        let from = NavVec3::new(0.5, 0.5, 0.0);
+        let to = NavVec3::new(4.5, 0.5, 0.0);
+        let find = |mesh: &NavMesh, from, to| {
+            mesh.find_path_annotated(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &Default::default(),
+                |_, _, _| true,
+            )
+        };
+        assert_eq!(find(&mesh, from, to), None);
+
+        let jump = mesh
+            .add_off_mesh_link(NavOffMeshLink {
+                from: (2.0, 0.5, 0.0).into(),
+                to: (3.0, 0.5, 0.0).into(),
+                cost: 1.0,
+                bidirectional: false,
+                kind: NavOffMeshLinkKind::Jump,
+            })
+            .unwrap();
+        let path = find(&mesh, from, to).unwrap();
+        assert_eq!(
+            path.points,
+            vec![from, (2.0, 0.5, 0.0).into(), (3.0, 0.5, 0.0).into(), to,]
+        );
+        assert_eq!(path.segments.len(), 3);
+        assert_eq!(
+            path.links().collect::<Vec<_>>(),
+            vec![(
+                1,
+                &NavPathLink {
+                    id: jump,
+                    kind: NavOffMeshLinkKind::Jump
+                }
+            )]
+        );
+        assert_relative_eq!(path.length(), 4.0, epsilon = 1.0e-5);
+        assert_eq!(find(&mesh, to, from), None);
+        assert_eq!(
+            mesh.find_path(from, to, NavQuery::Accuracy, NavPathMode::Funnel),
+            Some(path.points.clone())
+        );
+
+        let ladder = mesh
+            .add_off_mesh_link(NavOffMeshLink {
+                from: (3.0, 0.75, 0.0).into(),
+                to: (2.0, 0.75, 0.0).into(),
+                cost: 1.0,
+                bidirectional: true,
+                kind: NavOffMeshLinkKind::Ladder,
+            })
+            .unwrap();
+        let path = find(&mesh, to, from).unwrap();
+        assert_eq!(path.links().next().unwrap().1.id, ladder);
+        // filter is asked only during search, so stateful filters see connections once.
+        let mut asked = std::collections::HashSet::new();
+        let path = mesh
+            .find_path_annotated(
+                to,
+                from,
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &Default::default(),
+                |_, a, b| asked.insert((a, b)),
+            )
+            .unwrap();
+        assert_eq!(path.links().next().unwrap().1.id, ladder);
+        assert!(mesh.remove_off_mesh_link(jump).is_some());
+        assert!(mesh.remove_off_mesh_link(jump).is_none());
+        let path = find(&mesh, from, to).unwrap();
+        assert_eq!(
+            path.links().next().unwrap().1.kind,
+            NavOffMeshLinkKind::Ladder
+        );
+        assert!(mesh.remove_off_mesh_link(ladder).is_some());
+        assert_eq!(find(&mesh, from, to), None);
+        assert_eq!(mesh.off_mesh_links().count(), 0);
+
+        // corridors going through removed link are not rebuilt as if triangles were adjacent.
+        let jump = mesh
+            .add_off_mesh_link(NavOffMeshLink {
+                from: (2.0, 0.5, 0.0).into(),
+                to: (3.0, 0.5, 0.0).into(),
+                cost: 1.0,
+                bidirectional: false,
+                kind: NavOffMeshLinkKind::Jump,
+            })
+            .unwrap();
+        let mut corridor = mesh
+            .find_path_corridor(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &Default::default(),
+            )
+            .unwrap();
+        let path = corridor.path().clone();
+        assert!(mesh.remove_off_mesh_link(jump).is_some());
+        assert!(!corridor.move_position(&mesh, (0.75, 0.5, 0.0).into(), NavQuery::Accuracy));
+        assert!(!corridor.move_target(&mesh, (4.25, 0.5, 0.0).into(), NavQuery::Accuracy));
+        assert!(!corridor.replan(&mesh));
+        assert_eq!(corridor.path(), &path);
+        // outdated corridor falls back to full search.
+        let teleport = mesh
+            .add_off_mesh_link(NavOffMeshLink {
+                from: (0.5, 0.25, 0.0).into(),
+                to: (4.5, 0.25, 0.0).into(),
+                cost: 1.0,
+                bidirectional: false,
+                kind: NavOffMeshLinkKind::Teleport,
+            })
+            .unwrap();
+        assert!(corridor.move_position(&mesh, (0.75, 0.5, 0.0).into(), NavQuery::Accuracy));
+        assert_eq!(corridor.path().links().next().unwrap().1.id, teleport);
+    }
+
+    #[test]
+    fn test_shorten_path() {
+        let mesh = grid_mesh(4, 4);
+        let from = NavVec3::new(0.2, 0.3, 0.0);
+        let to = NavVec3::new(3.7, 3.1, 0.0);
+        let zigzag = [from, (1.5, 0.5, 0.0).into(), (2.5, 2.5, 0.0).into(), to];
+        assert_eq!(mesh.shorten_path(&zigzag), vec![from, to]);
+        let options = NavPathOptions {
+            shorten: true,
+            ..Default::default()
+        };
+        let path = mesh
+            .find_path_with_options(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::MidPoints,
+                &options,
+                |_, _, _| true,
+            )
+            .unwrap();
+        assert_eq!(path, vec![from, to]);
+
+        // waypoint at the door corner must stay.
+        let mesh = door_mesh();
+        let from = NavVec3::new(3.9, 0.5, 0.0);
+        let to = NavVec3::new(3.0, 2.5, 0.0);
+        let path = mesh
+            .find_path_with_options(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::MidPoints,
+                &options,
+                |_, _, _| true,
+            )
+            .unwrap();
+        assert!(path.len() > 2);
+        for pair in path.windows(2) {
+            assert!(mesh.raycast(pair[0], pair[1]).is_none());
+        }
+    }
+
+    #[test]
+    fn test_query_options() {
+        let mesh = grid_mesh(2, 2);
+        let above = NavVec3::new(1.0, 1.0, 0.5);
+        let outside = NavVec3::new(3.0, 1.0, 0.0);
+        assert_eq!(
+            mesh.closest_point(above, NavQuery::Accuracy),
+            Some((1.0, 1.0, 0.0).into())
+        );
+        let options = NavQueryOptions {
+            vertical_tolerance: 0.25,
+            snap_distance: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(mesh.closest_point(above, options), None);
+        assert_eq!(mesh.closest_point(outside, options), None);
+        assert_eq!(
+            mesh.closest_point((2.25, 1.0, 0.1).into(), options),
+            Some((2.0, 1.0, 0.0).into())
+        );
+        let options = NavQueryOptions {
+            on_mesh_tolerance: 0.01,
+            ..Default::default()
+        };
+        assert_eq!(
+            mesh.closest_point((1.0, 1.0, 0.005).into(), options),
+            Some((1.0, 1.0, 0.005).into())
+        );
+        assert!(mesh
+            .find_path(
+                (0.5, 0.5, 0.0).into(),
+                outside,
+                NavQueryOptions {
+                    snap_distance: 0.5,
+                    ..Default::default()
+                },
+                NavPathMode::Accuracy,
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_tiled_mesh() {
+        // 2x2 tiles of 2x2 quads each, with top right tile streamed in later.
+        let tile = |x: Scalar, y: Scalar| {
+            let source = grid_mesh(2, 2);
+            let vertices = source
+                .vertices()
+                .iter()
+                .map(|v| *v + NavVec3::new(x, y, 0.0))
+                .collect();
+            NavMesh::new(vertices, source.triangles().to_vec()).unwrap()
+        };
+        let mut mesh = NavTiledMesh::new(2.0);
+        assert!(mesh.add_tile((0, 0), tile(0.0, 0.0)).is_none());
+        assert!(mesh.add_tile((1, 0), tile(2.0, 0.0)).is_none());
+        assert!(mesh.add_tile((0, 1), tile(0.0, 2.0)).is_none());
+        let from = NavVec3::new(3.5, 0.5, 0.0);
+        let to = NavVec3::new(1.5, 3.5, 0.0);
+        let path = mesh.find_path(from, to, NavQuery::Accuracy).unwrap();
+        assert_eq!(path.len(), 3);
+        assert_relative_eq!(path[1].x, 2.0, epsilon = 1.0e-5);
+
+        assert!(mesh.add_tile((1, 1), tile(2.0, 2.0)).is_none());
+        let to = NavVec3::new(3.5, 3.5, 0.0);
+        let path = mesh.find_path(from, to, NavQuery::Accuracy).unwrap();
+        assert_eq!(path, vec![from, to]);
+
+        assert!(mesh.add_tile((1, 1), tile(2.0, 2.0)).is_some());
+        assert_eq!(
+            mesh.find_path(from, to, NavQuery::Accuracy),
+            Some(vec![from, to])
+        );
+        assert!(mesh.remove_tile((0, 1)).is_some());
+        assert!(mesh.remove_tile((1, 0)).is_some());
+        assert!(mesh.find_path(from, to, NavQuery::Accuracy).is_none());
+    }
+
+    #[test]
+    fn test_unit_scale() {
+        // 2x1 meters mesh authored in kilometers.
+        let mut mesh = grid_mesh(2, 1)
+            .scale(NavVec3::new(0.001, 0.001, 0.001), Some(NavVec3::default()))
+            .unwrap();
+        let from = NavVec3::new(0.0001, 0.0005, 0.0);
+        let to = NavVec3::new(0.0004, 0.0005, 0.0);
+        assert!(mesh
+            .find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .is_none());
+        mesh.set_unit_scale(0.001);
+        assert_eq!(mesh.unit_scale(), 0.001);
+        assert_eq!(
+            mesh.find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy),
+            Some(vec![from, to])
+        );
+        assert_relative_eq!(mesh.tolerance().threshold(2), 1.0e-12);
+    }
+
+    #[test]
+    fn test_find_path_sliced() {
+        let mesh = grid_mesh(8, 8);
+        let from = NavVec3::new(0.5, 0.5, 0.0);
+        let to = NavVec3::new(7.5, 6.5, 0.0);
+        let options = NavPathOptions::default();
+        let expected = mesh
+            .find_path_annotated(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &options,
+                |_, _, _| true,
+            )
+            .unwrap();
+        let mut query = mesh
+            .find_path_sliced(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &options,
+            )
+            .unwrap();
+        let mut updates = 0;
+        while query.update(4) == NavSlicedPathStatus::InProgress {
+            updates += 1;
+        }
+        assert!(updates > 1);
+        assert!(query.expansions() > 4);
+        assert_eq!(query.status(), NavSlicedPathStatus::Found);
+        assert_eq!(query.update(4), NavSlicedPathStatus::Found);
+        assert_eq!(query.into_path().unwrap(), expected);
+
+        let options = NavPathOptions {
+            area_mask: 0,
+            ..Default::default()
+        };
+        let mut query = mesh
+            .find_path_sliced(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(query.update(usize::MAX), NavSlicedPathStatus::NotFound);
+        assert!(query.path().is_none());
+    }
+
+    #[test]
+    fn test_path_corridor() {
+        let mesh = grid_mesh(8, 8);
+        let options = NavPathOptions::default();
+        let fresh = |from: NavVec3, to: NavVec3| {
+            mesh.find_path_annotated(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &options,
+                |_, _, _| true,
+            )
+            .unwrap()
+        };
+        let from = NavVec3::new(0.5, 0.5, 0.0);
+        let mut target = NavVec3::new(6.5, 0.5, 0.0);
+        let mut corridor = mesh
+            .find_path_corridor(
+                from,
+                target,
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(corridor.path(), &fresh(from, target));
+        for _ in 0..6 {
+            target.y += 1.0;
+            assert!(corridor.move_target(&mesh, target, NavQuery::Accuracy));
+            assert_eq!(corridor.target(), target);
+            assert_eq!(corridor.path().points.first(), Some(&from));
+            assert_eq!(corridor.path().points.last(), Some(&target));
+            assert!(corridor.path().length() >= fresh(from, target).length() - 1.0e-3);
+        }
+        assert!(corridor.replan(&mesh));
+        assert!((corridor.path().length() - fresh(from, target).length()).abs() < 1.0e-3);
+        let position = NavVec3::new(3.5, 3.5, 0.0);
+        assert!(corridor.move_position(&mesh, position, NavQuery::Accuracy));
+        assert_eq!(corridor.path().points.first(), Some(&position));
+        assert!((corridor.path().length() - fresh(position, target).length()).abs() < 1.0e-3);
+        let triangles = corridor.triangles().to_vec();
+        assert!(corridor.move_position(&mesh, NavVec3::new(3.6, 3.5, 0.0), NavQuery::Accuracy));
+        assert_eq!(corridor.triangles(), triangles.as_slice());
+        assert!(!corridor.move_target(&grid_mesh(1, 1), target, NavQuery::Accuracy));
+
+        let mut data = serde_json::to_value(&corridor).unwrap();
+        data["triangles"] = serde_json::Value::Array(vec![]);
+        let mut empty = serde_json::from_value::<NavPathCorridor>(data).unwrap();
+        assert!(!empty.move_position(&mesh, position, NavQuery::Accuracy));
+        assert!(!empty.move_target(&mesh, target, NavQuery::Accuracy));
+        assert!(!empty.replan(&mesh));
+    }
+
+    #[test]
+    fn test_mesh_merge() {
+        let room = |offset: Scalar| {
+            NavMesh::new(
+                vec![
+                    (offset, 0.0, 0.0).into(),
+                    (offset + 1.0, 0.0, 0.0).into(),
+                    (offset + 1.0, 1.0, 0.0).into(),
+                    (offset, 1.0, 0.0).into(),
+                ],
+                vec![(0, 1, 2).into(), (2, 3, 0).into()],
+            )
+            .unwrap()
+        };
+        let a = room(0.0);
+        let mut b = room(1.0 + 1.0e-4);
+        b.set_area_cost(0, 5.0);
+        b.set_area_type(1, 3);
+        b.add_off_mesh_link(NavOffMeshLink {
+            from: (1.5, 0.5, 0.0).into(),
+            to: (1.2, 0.8, 0.0).into(),
+            cost: 1.0,
+            bidirectional: false,
+            kind: NavOffMeshLinkKind::Jump,
+        })
+        .unwrap();
+        let separated = NavMesh::merge(&[a.clone(), b.clone()]).unwrap();
+        assert_eq!(separated.vertices().len(), 8);
+        assert!(separated.find_path_triangles(0, 3).is_none());
+
+        // links of triangles collapsed by welding can not be attached.
+        assert!(matches!(
+            NavMesh::merge_with_weld_distance(&[b.clone()], 10.0),
+            Err(Error::CouldNotAttachOffMeshLink(0))
+        ));
+
+        let mesh = NavMesh::merge_with_weld_distance(&[a, b], 1.0e-3).unwrap();
+        assert_eq!(mesh.vertices().len(), 6);
+        assert_eq!(mesh.triangles().len(), 4);
+        assert_eq!(mesh.areas()[2].cost, 5.0);
+        assert_eq!(mesh.areas()[3].area_type, 3);
+        assert_eq!(mesh.off_mesh_links().count(), 1);
+        assert!(mesh
+            .find_path(
+                (0.1, 0.1, 0.0).into(),
+                (1.9, 0.9, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+            )
+            .is_some());
+        assert!(NavMesh::merge(&[]).unwrap().triangles().is_empty());
+    }
+
+    #[test]
+    fn test_path_content_hash() {
+        let mut mesh = door_mesh();
+        let find = |mesh: &NavMesh, to: NavVec3| {
+            mesh.find_path_ex(
+                (0.5, 0.5, 0.0).into(),
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &NavPathOptions::default(),
+                |_, _, _| true,
+            )
+            .unwrap()
+        };
+        let a = find(&mesh, (0.5, 2.5, 0.0).into());
+        let b = find(&mesh, (0.5, 2.5 + 1.0e-4, 0.0).into());
+        assert_eq!(a.version, mesh.topology_version());
+        assert_eq!(
+            a.content_hash(0.01),
+            find(&mesh, (0.5, 2.5, 0.0).into()).content_hash(0.01)
+        );
+        assert_eq!(a.content_hash(0.01), b.content_hash(0.01));
+        assert_ne!(a.content_hash(0.0), b.content_hash(0.0));
+        assert_eq!(a.path.content_hash(0.01), b.path.content_hash(0.01));
+        assert_ne!(
+            a.content_hash(0.01),
+            find(&mesh, (1.5, 2.5, 0.0).into()).content_hash(0.01)
+        );
+
+        // the same path found with changed nav data is reported as different.
+        mesh.set_area_cost(0, 2.0);
+        let c = find(&mesh, (0.5, 2.5, 0.0).into());
+        assert_eq!(c.path, a.path);
+        assert_eq!(c.path.content_hash(0.01), a.path.content_hash(0.01));
+        assert_ne!(c.content_hash(0.01), a.content_hash(0.01));
+
+        let link = NavPathLink {
+            id: NavOffMeshLinkID::new(),
+            kind: NavOffMeshLinkKind::Jump,
+        };
+        let mut walk = NavPath::new(vec![(0.0, 0.0, 0.0).into(), (2.0, 0.0, 0.0).into()]);
+        let mut jump = walk.clone();
+        walk.push((2.0, 3.0, 0.0).into(), None);
+        jump.push((2.0, 3.0, 0.0).into(), Some(link));
+        assert_ne!(walk.content_hash(0.01), jump.content_hash(0.01));
+        let saved = serde_json::to_string(&jump).unwrap();
+        let loaded = serde_json::from_str::<NavPath>(&saved).unwrap();
+        assert_eq!(loaded.content_hash(0.01), jump.content_hash(0.01));
+
+        // hashes are the same on every target, so they can be compared across network.
+        let result = NavPathResult {
+            path: walk,
+            triangles: vec![0, 6, 8],
+            ..Default::default()
+        };
+        assert_eq!(result.content_hash(0.01), 0xc77d_0073_c413_6b5c);
+        assert_eq!(
+            NavPath::new(vec![(0.0, -0.0, 0.0).into()]).content_hash(0.0),
+            NavPath::new(vec![(0.0, 0.0, 0.0).into()]).content_hash(0.0)
+        );
+    }
+
+    #[test]
+    fn test_path_utilities() {
+        let link = NavPathLink {
+            id: NavOffMeshLinkID::new(),
+            kind: NavOffMeshLinkKind::Jump,
+        };
+        let mut path = NavPath::new(vec![(0.0, 0.0, 0.0).into(), (2.0, 0.0, 0.0).into()]);
+        path.push((2.0, 3.0, 0.0).into(), Some(link));
+        path.push((4.0, 3.0, 0.0).into(), None);
+        assert_eq!(path.length(), 7.0);
+
+        let reversed = path.reversed();
+        assert_eq!(reversed.points[0], (4.0, 3.0, 0.0).into());
+        assert_eq!(reversed.segments[1].link, Some(link));
+        assert_eq!(reversed.length(), path.length());
+        assert_eq!(reversed.reversed(), path);
+
+        let patrol = path.concat(&reversed);
+        assert_eq!(patrol.points.len(), 7);
+        assert_eq!(patrol.length(), 14.0);
+        assert_eq!(
+            patrol.links().map(|(index, _)| index).collect::<Vec<_>>(),
+            vec![1, 4]
+        );
+        let joined = path.concat(&NavPath::new(vec![(4.0, 4.0, 0.0).into()]));
+        assert_eq!(joined.length(), 8.0);
+
+        let trimmed = path.trim_to_distance(1.5);
+        assert_eq!(
+            trimmed.points,
+            vec![(0.0, 0.0, 0.0).into(), (1.5, 0.0, 0.0).into()]
+        );
+        assert_eq!(trimmed.length(), 1.5);
+        assert_eq!(path.trim_to_distance(3.0).points.len(), 2);
+        assert_eq!(path.trim_to_distance(6.0).length(), 6.0);
+        assert_eq!(path.trim_to_distance(100.0), path);
+        assert_eq!(path.trim_to_distance(0.0).points.len(), 1);
+    }
+
+    #[test]
+    fn test_approach_point() {
+        // 4x4 mesh with building occupying cells (1, 1) to (2, 2).
+        let full = grid_mesh(4, 4);
+        let triangles = full
+            .triangles()
+            .chunks(2)
+            .enumerate()
+            .filter(|(index, _)| !matches!((index % 4, index / 4), (1..=2, 1..=2)))
+            .flat_map(|(_, pair)| pair.iter().copied())
+            .collect::<Vec<_>>();
+        let mesh = NavMesh::new(full.vertices().to_vec(), triangles).unwrap();
+        let target = NavVec3::new(2.0, 2.5, 0.0);
+        let approach = mesh.approach_point(target, 2.0, |_, _| true).unwrap();
+        assert_eq!(approach.point, (2.0, 3.0, 0.0).into());
+
+        // wall blocks sight from top side of the building.
+        let approach = mesh
+            .approach_point(target, 2.0, |from, _| from.y < 2.5)
+            .unwrap();
+        assert!((approach.point - target).magnitude() <= 2.0);
+        assert!(approach.point.y < 2.5);
+        assert!(mesh.approach_point(target, 0.25, |_, _| true).is_none());
+        assert!(mesh.approach_point(target, 2.0, |_, _| false).is_none());
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn test_debug_sink() {
+        use std::sync::{Arc, Mutex};
+
+        let mesh = grid_mesh(4, 1);
+        let id = mesh.id();
+        let frames = Arc::new(Mutex::new(vec![]));
+        let frames2 = frames.clone();
+        set_debug_sink(Some(Arc::new(move |frame: NavDebugFrame| {
+            let mesh = match &frame {
+                NavDebugFrame::SnappedEndpoints { mesh, .. }
+                | NavDebugFrame::Corridor { mesh, .. }
+                | NavDebugFrame::FunnelApexes { mesh, .. }
+                | NavDebugFrame::Path { mesh, .. } => *mesh,
+            };
+            if mesh == id {
+                frames2.lock().unwrap().push(frame);
+            }
+        })));
+        assert!(has_debug_sink());
+        let path = mesh
+            .find_path(
+                (0.5, 0.5, 0.0).into(),
+                (3.5, 0.5, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+            )
+            .unwrap();
+        set_debug_sink(None);
+        let frames = frames.lock().unwrap();
+        assert_eq!(frames.len(), 4);
+        assert!(matches!(frames[0], NavDebugFrame::SnappedEndpoints { .. }));
+        assert!(matches!(frames[1], NavDebugFrame::Corridor { .. }));
+        assert!(matches!(frames[2], NavDebugFrame::FunnelApexes { .. }));
+        match &frames[3] {
+            NavDebugFrame::Path { path: frame, .. } => assert_eq!(frame.points, path),
+            _ => panic!("expected path frame"),
+        }
+    }
+
+    #[test]
+    fn test_poly_mesh() {
+        // 4x4 quads grid with middle 2x2 quads merged into single polygon.
+        let mesh = grid_mesh(4, 4);
+        let vertices = mesh.vertices().to_vec();
+        let quad = |col: u32, row: u32| -> NavPolygon {
+            let a = row * 5 + col;
+            vec![a, a + 1, a + 6, a + 5].into()
+        };
+        let mut polygons = (0..16)
+            .map(|i| (i % 4, i / 4))
+            .filter(|c| !matches!(c, (1..=2, 1..=2)))
+            .map(|(col, row)| quad(col, row))
+            .collect::<Vec<_>>();
+        polygons.push(vec![6, 7, 8, 13, 18, 17, 16, 11].into());
+        let poly = NavPolyMesh::new(vertices.clone(), polygons).unwrap();
+        assert_eq!(poly.polygons().len(), 13);
+        assert_eq!(poly.neighbors(12).count(), 8);
+        assert_eq!(
+            poly.triangle_polygon(poly.nav_mesh().triangles().len() - 1),
+            Some(12)
+        );
+
+        let from = NavVec3::new(0.5, 0.5, 0.0);
+        let to = NavVec3::new(3.5, 2.5, 0.0);
+        let expected = mesh
+            .find_path(from, to, NavQuery::Accuracy, NavPathMode::Funnel)
+            .unwrap();
+        let path = poly.find_path(from, to, NavQuery::Accuracy).unwrap();
+        assert_eq!(path.first(), Some(&from));
+        assert_eq!(path.last(), Some(&to));
+        // polygons corridor is not guaranteed to contain the shortest path.
+        let (length, shortest) = (NavMesh::path_length(&path), NavMesh::path_length(&expected));
+        assert!(length >= shortest - 1.0e-4 && length <= shortest * 1.2);
+        let inside = poly
+            .find_path(
+                (1.5, 1.5, 0.0).into(),
+                (2.5, 2.5, 0.0).into(),
+                NavQuery::Accuracy,
+            )
+            .unwrap();
+        assert_eq!(inside.len(), 2);
+        assert_eq!(
+            poly.find_path(from, from, NavQuery::Accuracy),
+            Some(vec![from, from])
+        );
+
+        assert!(matches!(
+            NavPolyMesh::new(vertices.clone(), vec![vec![0, 1].into()]),
+            Err(Error::PolygonVerticesCountTooSmall(0, 2))
+        ));
+        assert!(matches!(
+            NavPolyMesh::new(vertices.clone(), vec![vec![0, 1, 100].into()]),
+            Err(Error::PolygonVerticeIndexOutOfBounds(0, 2, 100))
+        ));
+        assert!(matches!(
+            NavPolyMesh::new(vertices, vec![vec![0, 2, 6, 12, 10].into()]),
+            Err(Error::PolygonNotConvex(0))
+        ));
+    }
+
+    #[test]
+    fn test_swim_depth() {
+        // 3x1 strip with river in the middle column.
+        let mut mesh = grid_mesh(3, 1);
+        mesh.set_area_depth(2, 1.5);
+        assert_eq!(mesh.set_area_depth(3, 1.5), 0.0);
+        assert_eq!(mesh.areas()[2].depth, 1.5);
+        let find = |options: &NavPathOptions| {
+            mesh.find_path_with_options(
+                (0.5, 0.5, 0.0).into(),
+                (2.5, 0.5, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                options,
+                |_, _, _| true,
+            )
+        };
+        assert!(find(&NavPathOptions::default()).is_some());
+        let land_only = NavPathOptions {
+            can_swim: false,
+            ..Default::default()
+        };
+        assert!(find(&land_only).is_none());
+        let shallow_swimmer = NavPathOptions {
+            max_depth: 1.0,
+            ..Default::default()
+        };
+        assert!(find(&shallow_swimmer).is_none());
+        let amphibious = NavPathOptions {
+            max_depth: 2.0,
+            ..Default::default()
+        };
+        assert!(find(&amphibious).is_some());
+    }
+
+    #[test]
+    fn test_boundary_loops() {
+        // 3x3 grid with middle cell removed.
+        let full = grid_mesh(3, 3);
+        let triangles = full
+            .triangles()
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| index / 2 != 4)
+            .map(|(_, triangle)| *triangle)
+            .collect::<Vec<_>>();
+        let mesh = NavMesh::new(full.vertices().to_vec(), triangles).unwrap();
+        let mut loops = mesh.boundary_loops();
+        loops.sort_by_key(|l| l.len());
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].len(), 4);
+        assert_eq!(loops[1].len(), 12);
+        let signed_area = |l: &[u32]| {
+            let v = mesh.vertices();
+            (0..l.len()).fold(0.0, |a, i| {
+                let p = v[l[i] as usize];
+                let q = v[l[(i + 1) % l.len()] as usize];
+                a + p.x * q.y - q.x * p.y
+            }) * 0.5
+        };
+        assert_eq!(signed_area(&loops[0]), -1.0);
+        assert_eq!(signed_area(&loops[1]), 9.0);
+        let mut hole = loops[0].clone();
+        hole.sort();
+        assert_eq!(hole, vec![5, 6, 9, 10]);
+    }
+
+    #[test]
+    fn test_step_links() {
+        let platform = |x: Scalar, z: Scalar| {
+            vec![
+                NavVec3::new(x, 0.0, z),
+                NavVec3::new(x + 1.0, 0.0, z),
+                NavVec3::new(x + 1.0, 1.0, z),
+                NavVec3::new(x, 1.0, z),
+            ]
+        };
+        let build = |z: Scalar| {
+            let mut vertices = platform(0.0, 0.0);
+            vertices.extend(platform(1.1, z));
+            NavMesh::new(
+                vertices,
+                vec![
+                    (0, 1, 2).into(),
+                    (2, 3, 0).into(),
+                    (4, 5, 6).into(),
+                    (6, 7, 4).into(),
+                ],
+            )
+            .unwrap()
+        };
+        let mut mesh = build(0.3);
+        assert_eq!(mesh.generate_step_links(&Default::default()).len(), 1);
+        let path = mesh
+            .find_path_annotated(
+                (1.6, 0.5, 0.3).into(),
+                (0.5, 0.5, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &Default::default(),
+                |_, _, _| true,
+            )
+            .unwrap();
+        let kinds = path.links().map(|(_, link)| link.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, vec![NavOffMeshLinkKind::Step]);
+        // generating again does not duplicate links.
+        assert!(mesh.generate_step_links(&Default::default()).is_empty());
+        assert_eq!(mesh.off_mesh_links().count(), 1);
+
+        let mut mesh = build(1.0);
+        assert!(mesh.generate_step_links(&Default::default()).is_empty());
+        let settings = NavStepLinkSettings {
+            max_gap: 0.05,
+            ..Default::default()
+        };
+        assert!(build(0.3).generate_step_links(&settings).is_empty());
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let mesh = grid_mesh(2, 2);
+        assert_eq!(
+            mesh.contains_point((1.5, 0.25, 0.0).into(), 0.0, 0.0),
+            Some(2)
+        );
+        assert!(mesh
+            .contains_point((1.0, 1.0, 0.0).into(), 0.0, 0.0)
+            .is_some());
+        assert_eq!(mesh.contains_point((0.5, 0.5, -0.2).into(), 0.0, 0.1), None);
+        assert_eq!(
+            mesh.contains_point((0.5, 0.5, -0.2).into(), 0.0, 0.3),
+            Some(1)
+        );
+        assert_eq!(mesh.contains_point((2.5, 0.5, 0.0).into(), 0.4, 0.0), None);
+        assert_eq!(
+            mesh.contains_point((2.5, 0.5, 0.0).into(), 0.6, 0.0),
+            Some(2)
+        );
+        assert_eq!(
+            mesh.contains_point((-5.0, -5.0, 0.0).into(), 1.0, 1.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_net_nearest_connections() {
+        // three parallel roads along X axis at Y = 0, 2 and 6.
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (10.0, 0.0, 0.0).into(),
+                (0.0, 2.0, 0.0).into(),
+                (10.0, 2.0, 0.0).into(),
+                (0.0, 6.0, 0.0).into(),
+                (10.0, 6.0, 0.0).into(),
+            ],
+            vec![
+                NavConnection(0, 1),
+                NavConnection(2, 3),
+                NavConnection(4, 5),
+            ],
+        )
+        .unwrap();
+        let point = NavVec3::new(5.0, 0.5, 0.0);
+        assert_eq!(net.connections_within(point, 5.0), vec![0, 1]);
+        assert_eq!(net.connections_within(point, 6.0), vec![0, 1, 2]);
+        assert!(net
+            .connections_within((5.0, 20.0, 0.0).into(), 5.0)
+            .is_empty());
+        assert_eq!(net.k_nearest_connections(point, 2), vec![0, 1]);
+        assert_eq!(net.k_nearest_connections(point, 5), vec![0, 1, 2]);
+        assert!(net.k_nearest_connections(point, 0).is_empty());
+    }
+
+    #[test]
+    fn test_net_path_annotated() {
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (4.0, 0.0, 0.0).into(),
+                (4.0, 3.0, 0.0).into(),
+            ],
+            vec![NavConnection(0, 1), NavConnection(1, 2)],
+        )
+        .unwrap();
+        let from = NavVec3::new(1.0, 0.0, 0.0);
+        let to = NavVec3::new(4.0, 2.0, 0.0);
+        let path = net.find_path_annotated(from, to, |_, _, _| true).unwrap();
+        assert_eq!(path.points, net.find_path(from, to).unwrap());
+        assert_eq!(
+            path.segments
+                .iter()
+                .map(|segment| segment.length)
+                .collect::<Vec<_>>(),
+            vec![3.0, 2.0]
+        );
+        assert!(path.segments.iter().all(|segment| segment.link.is_none()));
+        assert_eq!(path.length(), 5.0);
+    }
+
+    #[test]
+    fn test_net_from_navmesh() {
+        let mesh = grid_mesh(2, 1);
+        let net = NavNet::from_navmesh(&mesh, NavNetWaypoints::TriangleCenters).unwrap();
+        assert_eq!(net.vertices().len(), 4);
+        assert_eq!(net.vertices()[1], mesh.areas()[1].center);
+        let mut connections = net
+            .connections()
+            .iter()
+            .map(|c| (c.0.min(c.1), c.0.max(c.1)))
+            .collect::<Vec<_>>();
+        connections.sort();
+        assert_eq!(connections, vec![(0, 1), (0, 3), (2, 3)]);
+
+        let net = NavNet::from_navmesh(&mesh, NavNetWaypoints::EdgeMidpoints).unwrap();
+        let mut vertices = net
+            .vertices()
+            .iter()
+            .map(|v| ((v.x * 2.0) as i32, (v.y * 2.0) as i32))
+            .collect::<Vec<_>>();
+        vertices.sort();
+        assert_eq!(vertices, vec![(1, 1), (2, 1), (3, 1)]);
+        assert_eq!(net.connections().len(), 2);
+        let path = net
+            .find_path((0.5, 0.5, 0.0).into(), (1.5, 0.5, 0.0).into())
+            .unwrap();
+        assert_eq!(path.first(), Some(&(0.5, 0.5, 0.0).into()));
+        assert_eq!(path.last(), Some(&(1.5, 0.5, 0.0).into()));
+    }
+
+    #[test]
+    fn test_net_find_path_custom() {
+        // square loop with bridge going out of its top right corner.
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (4.0, 0.0, 0.0).into(),
+                (4.0, 4.0, 0.0).into(),
+                (0.0, 4.0, 0.0).into(),
+                (6.0, 7.0, 0.0).into(),
+            ],
+            vec![
+                NavConnection(0, 1),
+                NavConnection(1, 2),
+                NavConnection(2, 3),
+                NavConnection(3, 0),
+                NavConnection(2, 4),
+            ],
+        )
+        .unwrap();
+        let tunnel = |a: usize, b: usize| a.min(b) == 1 && a.max(b) == 2;
+        let from = NavVec3::new(2.0, 0.0, 0.0);
+        let to = NavVec3::new(4.0, 3.0, 0.0);
+        let path = net.find_path(from, to).unwrap();
+        assert!(path.contains(&(4.0, 0.0, 0.0).into()));
+
+        // collapsed tunnel makes path go around and snap target to connection next to it.
+        let path = net
+            .find_path_custom(from, to, |_, a, b| !tunnel(a, b))
+            .unwrap();
+        assert!(!path.contains(&(4.0, 0.0, 0.0).into()));
+        assert!(path.contains(&(0.0, 4.0, 0.0).into()));
+        assert_eq!(path.first(), Some(&from));
+
+        let bridge = |a: usize, b: usize| a.max(b) == 4;
+        let path = net
+            .find_path_custom(from, (6.0, 7.0, 0.0).into(), |_, a, b| {
+                !tunnel(a, b) && !bridge(a, b)
+            })
+            .unwrap();
+        assert!(!path.contains(&(6.0, 7.0, 0.0).into()));
+        assert!(net.find_path_custom(from, to, |_, _, _| false).is_none());
+    }
+
+    #[test]
+    fn test_net_islands() {
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (1.0, 0.0, 0.0).into(),
+                (1.0, 1.0, 0.0).into(),
+                (5.0, 0.0, 0.0).into(),
+                (6.0, 0.0, 0.0).into(),
+                (9.0, 9.0, 0.0).into(),
+            ],
+            vec![
+                NavConnection(0, 1),
+                NavConnection(1, 2),
+                NavConnection(3, 4),
+            ],
+        )
+        .unwrap();
+        let mut islands = net
+            .find_vertices_islands()
+            .into_iter()
+            .map(|mut island| {
+                island.sort();
+                island
+            })
+            .collect::<Vec<_>>();
+        islands.sort();
+        assert_eq!(islands, vec![vec![0, 1, 2], vec![3, 4], vec![5]]);
+        assert_eq!(net.find_islands().len(), 3);
+        assert!(net.are_vertices_connected(0, 2));
+        assert!(net.are_vertices_connected(4, 3));
+        assert!(!net.are_vertices_connected(2, 3));
+        assert!(!net.are_vertices_connected(0, 5));
+        assert!(!net.are_vertices_connected(0, 6));
+    }
+
+    #[test]
+    fn test_net_payloads() {
+        let vertices: Vec<NavVec3> = vec![
+            (0.0, 0.0, 0.0).into(),
+            (4.0, 0.0, 0.0).into(),
+            (4.0, 4.0, 0.0).into(),
+        ];
+        let connections = vec![NavConnection(0, 1), NavConnection(1, 2)];
+        let mut net = NavNet::new(vertices.clone(), connections.clone()).unwrap();
+        assert_eq!(net.payloads().len(), 3);
+        let index = net.add_vertex_with_payload((0.0, 4.0, 0.0).into(), ());
+        assert_eq!(net.payloads().len(), 4);
+        assert_eq!(net.payload(index), Some(&()));
+        let net = NavNet::new(vertices.clone(), connections.clone()).unwrap();
+        assert!(matches!(
+            net.with_payloads(vec!["a"]),
+            Err(Error::PayloadsCountDoesNotMatchVertices(1, 3))
+        ));
+        let mut net = NavNet::new(vertices, connections)
+            .unwrap()
+            .with_payloads(vec!["start", "corner", "goal"])
+            .unwrap();
+        assert_eq!(net.payload(1), Some(&"corner"));
+        assert_eq!(net.payload(3), None);
+        let indices = net
+            .find_path_vertices((0.0, 0.0, 0.0).into(), (4.0, 4.0, 0.0).into(), |_, _, _| {
+                true
+            })
+            .unwrap();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert_eq!(
+            net.find_path_payloads((0.0, 0.0, 0.0).into(), (4.0, 4.0, 0.0).into(), |_, _, _| {
+                true
+            }),
+            Some(vec![&"start", &"corner", &"goal"])
+        );
+        assert_eq!(
+            net.find_path_vertices((1.0, 0.0, 0.0).into(), (2.0, 0.0, 0.0).into(), |_, _, _| {
+                true
+            }),
+            Some(vec![])
+        );
+
+        *net.payload_mut(2).unwrap() = "exit";
+        let index = net.add_vertex((0.0, 4.0, 0.0).into());
+        assert_eq!(net.payload(index), Some(&""));
+        assert_eq!(
+            net.add_vertex_with_payload((8.0, 4.0, 0.0).into(), "far"),
+            4
+        );
+        assert_eq!(net.payloads(), &["start", "corner", "exit", "", "far"]);
+
+        let net = NavNet::new(net.vertices().to_vec(), net.connections().to_vec())
+            .unwrap()
+            .with_payloads(vec![1u32, 2, 3, 4, 5])
+            .unwrap();
+        let text = serde_json::to_string(&net).unwrap();
+        let loaded = serde_json::from_str::<NavNet<u32>>(&text).unwrap();
+        assert_eq!(loaded.payloads(), net.payloads());
+        assert_eq!(loaded.vertices(), net.vertices());
+    }
+
+    #[test]
+    fn test_net_smoothing() {
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (4.0, 0.0, 0.0).into(),
+                (4.0, 4.0, 0.0).into(),
+            ],
+            vec![NavConnection(0, 1), NavConnection(1, 2)],
+        )
+        .unwrap();
+        let from = NavVec3::new(0.0, 0.0, 0.0);
+        let to = NavVec3::new(4.0, 4.0, 0.0);
+        let raw = net.find_path(from, to).unwrap();
+        assert_eq!(
+            net.find_path_smoothed(from, to, NavNetSmoothing::None, |_, _, _| true),
+            Some(raw.clone())
+        );
+
+        let path = net
+            .find_path_smoothed(
+                from,
+                to,
+                NavNetSmoothing::CornerCut { max_deviation: 0.5 },
+                |_, _, _| true,
+            )
+            .unwrap();
+        assert_eq!(path.len(), 4);
+        assert_eq!(path.first(), Some(&from));
+        assert_eq!(path.last(), Some(&to));
+        assert!(!path.contains(&(4.0, 0.0, 0.0).into()));
+        let chord = (path[1] + path[2]) * 0.5;
+        assert_relative_eq!(
+            (chord - NavVec3::new(4.0, 0.0, 0.0)).magnitude(),
+            0.5,
+            epsilon = 1.0e-4
+        );
+        // cuts never pass over half of segment.
+        let path = NavNetSmoothing::CornerCut {
+            max_deviation: 100.0,
+        }
+        .apply(&raw);
+        assert_eq!(path[1], NavVec3::new(2.0, 0.0, 0.0));
+        assert_eq!(path[2], NavVec3::new(4.0, 2.0, 0.0));
+        // straight paths are left intact.
+        let straight = vec![
+            from,
+            NavVec3::new(1.0, 0.0, 0.0),
+            NavVec3::new(2.0, 0.0, 0.0),
+        ];
+        assert_eq!(
+            NavNetSmoothing::CornerCut { max_deviation: 0.5 }.apply(&straight),
+            straight
+        );
+
+        let path = NavNetSmoothing::Chaikin { iterations: 2 }.apply(&raw);
+        assert_eq!(path.len(), 12);
+        assert_eq!(path.first(), Some(&from));
+        assert_eq!(path.last(), Some(&to));
+        assert!(path
+            .windows(3)
+            .all(|w| (w[1] - w[0]).normalize().dot((w[2] - w[1]).normalize()) > 0.5));
+    }
+
+    #[test]
+    fn test_net_editing() {
+        let mut net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (4.0, 0.0, 0.0).into(),
+                (4.0, 4.0, 0.0).into(),
+            ],
+            vec![NavConnection(0, 1), NavConnection(1, 2)],
+        )
+        .unwrap();
+        // build spatial index first so it gets updated in place.
+        assert_eq!(net.find_closest_connection((4.5, 2.0, 0.0).into()), Some(1));
+        let index = net.add_vertex((0.0, 4.0, 0.0).into());
+        assert_eq!(index, 3);
+        assert!(net.add_connection(NavConnection(3, 9)).is_err());
+        assert_eq!(net.add_connection(NavConnection(0, 3)).unwrap(), 2);
+        assert_eq!(net.add_connection(NavConnection(3, 2)).unwrap(), 3);
+        assert_eq!(
+            net.find_closest_connection((-0.5, 2.0, 0.0).into()),
+            Some(2)
+        );
+        assert_eq!(net.remove_connection(1), Some(NavConnection(1, 2)));
+        assert_eq!(net.remove_connection(5), None);
+        assert_eq!(net.connections().len(), 3);
+        assert_eq!(net.connections()[1], NavConnection(3, 2));
+        assert_eq!(net.find_closest_connection((2.0, 4.5, 0.0).into()), Some(1));
+        assert_eq!(
+            net.find_closest_connection((-0.5, 2.0, 0.0).into()),
+            Some(2)
+        );
+
+        let expected = NavNet::new(net.vertices().to_vec(), net.connections().to_vec()).unwrap();
+        let from = NavVec3::new(4.0, 0.0, 0.0);
+        let to = NavVec3::new(4.0, 4.0, 0.0);
+        let path = net.find_path(from, to).unwrap();
+        assert_eq!(path, expected.find_path(from, to).unwrap());
+        assert_eq!(path.first(), Some(&from));
+        assert_eq!(path.last(), Some(&to));
+        assert!(path.contains(&(0.0, 4.0, 0.0).into()));
+    }
+
+    #[test]
+    fn test_net_lanes() {
+        let mut net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (4.0, 0.0, 0.0).into(),
+                (4.0, 4.0, 0.0).into(),
+            ],
+            vec![NavConnection(0, 1), NavConnection(1, 2)],
+        )
+        .unwrap();
+        let up = NavVec3::new(0.0, 0.0, 1.0);
+        assert_eq!(net.lanes(0), NavLanes::default());
+        for index in 0..2 {
+            net.set_lanes(
+                index,
+                NavLanes {
+                    forward: 2,
+                    backward: 1,
+                    width: 1.0,
+                },
+            )
+            .unwrap();
+        }
+        assert!(net.set_lanes(2, NavLanes::default()).is_none());
+
+        let path = vec![NavVec3::new(1.0, 0.0, 0.0), NavVec3::new(3.0, 0.0, 0.0)];
+        let forward = net.offset_path_by_lane(&path, 0, up);
+        assert_eq!(
+            forward,
+            vec![(1.0, -0.5, 0.0).into(), (3.0, -0.5, 0.0).into()]
+        );
+        let outer = net.offset_path_by_lane(&path, 5, up);
+        assert_eq!(outer[0], (1.0, -1.5, 0.0).into());
+        let backward = net.offset_path_by_lane(&[path[1], path[0]], 5, up);
+        assert_eq!(backward[0], (3.0, 0.5, 0.0).into());
+
+        let path = net
+            .find_path((0.0, 0.0, 0.0).into(), (4.0, 4.0, 0.0).into())
+            .unwrap();
+        let offset = net.offset_path_by_lane(&path, 0, up);
+        assert_eq!(offset.len(), path.len());
+        assert_eq!(offset[1], (4.25, -0.25, 0.0).into());
+    }
+
+    #[test]
+    fn test_replan_policy() {
+        let mut mesh = grid_mesh(4, 1);
+        let version = mesh.topology_version();
+        let path = vec![NavVec3::new(0.0, 0.5, 0.0), NavVec3::new(4.0, 0.5, 0.0)];
+        let policy = NavReplanPolicy {
+            max_drift: Some(0.5),
+            max_age: Some(10.0),
+            check_topology: false,
+            blocked_radius: Some(0.1),
+        };
+        let position = NavVec3::new(1.0, 0.5, 0.0);
+        assert_eq!(policy.evaluate(&mesh, &path, position, 1.0, version), None);
+        assert_eq!(
+            policy.evaluate(&mesh, &path, position, 11.0, version),
+            Some(NavReplanReason::Age)
+        );
+        assert_eq!(
+            policy.evaluate(&mesh, &path, NavVec3::new(1.0, 1.5, 0.0), 1.0, version),
+            Some(NavReplanReason::Drift)
+        );
+        let id = mesh.add_blocker(NavBlocker {
+            position: NavVec3::new(3.0, 0.5, 0.0),
+            radius: 0.2,
+        });
+        assert!(mesh.topology_version() > version);
+        assert_eq!(
+            policy.evaluate(&mesh, &path, position, 1.0, version),
+            Some(NavReplanReason::Blocked)
+        );
+        let policy = NavReplanPolicy {
+            check_topology: true,
+            ..policy
+        };
+        assert_eq!(
+            policy.evaluate(&mesh, &path, position, 1.0, version),
+            Some(NavReplanReason::TopologyChanged)
+        );
+        mesh.remove_blocker(id);
+        let version = mesh.topology_version();
+        assert_eq!(policy.evaluate(&mesh, &path, position, 1.0, version), None);
+        // unregistered blockers do not change topology.
+        assert!(mesh.remove_blocker(id).is_none());
+        assert!(mesh.blocker_mut(id).is_none());
+        assert_eq!(mesh.topology_version(), version);
+    }
+
+    #[test]
+    fn test_serialize_query_options() {
+        assert_eq!(
+            serde_json::to_string(&NavQuery::ClosestFirst).unwrap(),
+            "\"closest_first\""
+        );
+        assert_eq!(
+            serde_json::to_string(&NavPathMode::MidPoints).unwrap(),
+            "\"mid_points\""
+        );
+        assert_eq!(
+            serde_json::from_str::<NavPathMode>("\"Funnel\"").unwrap(),
+            NavPathMode::Funnel
+        );
+        assert_eq!(
+            serde_json::to_string(&NavPathMode::ClearanceMidPoints).unwrap(),
+            "\"clearance_mid_points\""
+        );
+
+        let options: NavPathOptions =
+            serde_json::from_str(r#"{ "area_mask": 3, "can_swim": false }"#).unwrap();
+        assert_eq!(options.area_mask, 3);
+        assert!(!options.can_swim);
+        assert_eq!(options.max_depth, SCALAR_MAX);
+        assert!(options.jitter.is_none());
+        let text = serde_json::to_string(&options).unwrap();
+        assert_eq!(
+            serde_json::from_str::<NavPathOptions>(&text).unwrap(),
+            options
+        );
+
+        let options: NavQueryOptions =
+            serde_json::from_str(r#"{ "query": "closest", "snap_distance": 2.0 }"#).unwrap();
+        assert_eq!(options.query, NavQuery::Closest);
+        assert_eq!(options.snap_distance, 2.0);
+        assert_eq!(options.vertical_tolerance, SCALAR_MAX);
+    }
+
+    #[test]
+    fn test_find_path_filter_excludes() {
+        let mesh = grid_mesh(3, 1);
+        let from = NavVec3::new(0.5, 0.5, 0.0);
+        let to = NavVec3::new(2.5, 0.5, 0.0);
+        let blocked = |_: Scalar, _: usize, b: usize| b != 2 && b != 3;
+        assert!(mesh
+            .find_path_excluding(from, to, NavQuery::Accuracy, NavPathMode::Funnel, blocked)
+            .is_none());
+        assert!(mesh.find_path_triangles_excluding(0, 5, blocked).is_none());
+        // rejected connections are still used by custom filters when there is no other way.
+        assert!(mesh
+            .find_path_custom(from, to, NavQuery::Accuracy, NavPathMode::Funnel, blocked)
+            .is_some());
+        let (triangles, cost) = mesh.find_path_triangles_custom(0, 5, blocked).unwrap();
+        assert_eq!((triangles.first(), triangles.last()), (Some(&0), Some(&5)));
+        assert!(cost >= SCALAR_MAX);
+
+        let mesh = grid_mesh(3, 2);
+        let blocked = |_: Scalar, _: usize, b: usize| b != 2 && b != 3;
+        for (triangles, _) in [
+            mesh.find_path_triangles_excluding(0, 5, blocked).unwrap(),
+            mesh.find_path_triangles_custom(0, 5, blocked).unwrap(),
+        ] {
+            assert!(!triangles.contains(&2) && !triangles.contains(&3));
+        }
+        let path = mesh
+            .find_path_excluding(from, to, NavQuery::Accuracy, NavPathMode::Funnel, blocked)
+            .unwrap();
+        assert!(path.iter().any(|p| p.y > 1.0 - ZERO_TRESHOLD));
+    }
+
+    #[test]
+    fn test_which_triangle() {
+        let mesh = grid_mesh(4, 4);
+        let points = (0..16)
+            .map(|i| NavVec3::new((i % 4) as Scalar + 0.75, (i / 4) as Scalar + 0.25, 0.1))
+            .chain(std::iter::once(NavVec3::new(-1.0, 2.0, 0.0)))
+            .collect::<Vec<_>>();
+        let result = mesh.which_triangle(&points);
+        assert_eq!(result.len(), 17);
+        for (i, triangle) in result.iter().take(16).enumerate() {
+            assert_eq!(*triangle, Some(i * 2));
+        }
+        assert_eq!(result[16], None);
+        // points far above nav mesh are still projected onto it.
+        assert_eq!(
+            mesh.which_triangle(&[NavVec3::new(0.75, 0.25, 1.0e6)]),
+            vec![Some(0)]
+        );
+    }
+
+    #[test]
+    fn test_occupancy() {
+        let mut occupancy = NavOccupancy::default();
+        occupancy.enter(2);
+        occupancy.enter(3);
+        occupancy.relocate(None, Some(3));
+        assert_eq!(occupancy.count(3), 2);
+        assert_eq!(occupancy.occupied().count(), 2);
+        assert!(occupancy.leave(2));
+        assert!(!occupancy.leave(2));
+        occupancy.enter(2);
+
+        let mesh = grid_mesh(3, 2);
+        let from = NavVec3::new(0.5, 0.5, 0.0);
+        let to = NavVec3::new(2.5, 0.5, 0.0);
+        let options = NavPathOptions::default();
+        let free = NavOccupancy::default();
+        let path = mesh
+            .find_path_congested(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &options,
+                &free,
+                10.0,
+            )
+            .unwrap();
+        assert!(path.iter().all(|p| p.y < 1.0));
+        let path = mesh
+            .find_path_congested(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &options,
+                &occupancy,
+                10.0,
+            )
+            .unwrap();
+        assert!(path.iter().any(|p| p.y > 1.0 - ZERO_TRESHOLD));
+
+        let grid = NavGrid::new(3, 2, vec![true; 6]).unwrap();
+        assert_eq!(
+            grid.find_path_congested((0, 0), (2, 0), &free, 10.0),
+            Some(vec![(0, 0), (1, 0), (2, 0)])
+        );
+        let mut occupancy = NavOccupancy::default();
+        occupancy.enter(grid.index(1, 0).unwrap());
+        assert_eq!(
+            grid.find_path_congested((0, 0), (2, 0), &occupancy, 10.0),
+            Some(vec![(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)])
+        );
+    }
+
+    #[test]
+    fn test_validate_path() {
+        let mut mesh = grid_mesh(3, 2);
+        let version = mesh.topology_version();
+        let options = NavPathOptions {
+            area_mask: 1,
+            ..Default::default()
+        };
+        let path = mesh
+            .find_path_annotated(
+                NavVec3::new(0.5, 0.5, 0.0),
+                NavVec3::new(2.5, 1.5, 0.0),
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &options,
+                |_, _, _| true,
+            )
+            .unwrap();
+        assert_eq!(mesh.validate_path(&path, &options, version), None);
+        // change unrelated to path keeps it valid.
+        mesh.set_area_cost(6, 2.0);
+        assert_eq!(mesh.validate_path(&path, &options, version), None);
+        // end point area becomes disallowed.
+        for triangle in [10, 11] {
+            mesh.set_area_type(triangle, 1);
+        }
+        assert_eq!(
+            mesh.validate_path(&path, &options, version),
+            Some(path.segments.len() - 1)
+        );
+        assert_eq!(
+            mesh.validate_path(&path, &options, mesh.topology_version()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_validate_path_thin_gap() {
+        let mut mesh = grid_mesh(8, 1);
+        let version = mesh.topology_version();
+        let options = NavPathOptions {
+            area_mask: 1,
+            ..Default::default()
+        };
+        let path = mesh
+            .find_path_annotated(
+                NavVec3::new(0.5, 0.5, 0.0),
+                NavVec3::new(7.5, 0.5, 0.0),
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &options,
+                |_, _, _| true,
+            )
+            .unwrap();
+        assert_eq!(path.segments.len(), 1);
+        // column between segment ends and its middle point is not sampled by any point.
+        for triangle in [2, 3] {
+            mesh.set_area_type(triangle, 1);
+        }
+        assert_eq!(mesh.validate_path(&path, &options, version), Some(0));
+    }
+
+    #[test]
+    fn test_detour_factor() {
+        let mesh = door_mesh();
+        let options = NavPathOptions::default();
+        let factor = mesh
+            .detour_factor(
+                NavVec3::new(0.5, 0.5, 0.0),
+                NavVec3::new(3.5, 0.5, 0.0),
+                NavQuery::Accuracy,
+                &options,
+            )
+            .unwrap();
+        assert!((factor - 1.0).abs() < 1.0e-4);
+        // wall between bottom and top areas forces path through the door.
+        let factor = mesh
+            .detour_factor(
+                NavVec3::new(3.0, 0.5, 0.0),
+                NavVec3::new(3.0, 2.5, 0.0),
+                NavQuery::Accuracy,
+                &options,
+            )
+            .unwrap();
+        assert!(factor > 1.4 && factor < 1.5);
+        let point = NavVec3::new(0.5, 0.5, 0.0);
+        assert_eq!(
+            mesh.detour_factor(point, point, NavQuery::Accuracy, &options),
+            None
+        );
+    }
+
+    #[test]
+    fn test_path_hazards() {
+        let mesh = grid_mesh(5, 3);
+        let from = NavVec3::new(0.5, 1.5, 0.0);
+        let to = NavVec3::new(4.5, 1.5, 0.0);
+        let find = |hazards: Vec<NavHazard>| {
+            let options = NavPathOptions {
+                hazards,
+                speed: 2.0,
+                ..Default::default()
+            };
+            mesh.find_path_ex(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &options,
+                |_, _, _| true,
+            )
+            .unwrap()
+            .triangles
+        };
+        let crosses_middle = |triangles: &[usize]| triangles.iter().any(|t| *t == 14 || *t == 15);
+        assert!(crosses_middle(&find(vec![])));
+        let hazard = NavHazard {
+            position: NavVec3::new(2.5, 1.5, 0.0),
+            radius: 0.6,
+            start_time: 0.0,
+            end_time: 5.0,
+            cost: 10.0,
+        };
+        assert!(!crosses_middle(&find(vec![hazard])));
+        // hazard is over before agent gets there.
+        let hazard = NavHazard {
+            start_time: 0.0,
+            end_time: 0.1,
+            ..hazard
+        };
+        assert!(crosses_middle(&find(vec![hazard])));
+    }
+
+    #[test]
+    fn test_geodesic_distance() {
+        let mesh = door_mesh();
+        let from = NavVec3::new(3.0, 0.5, 0.0);
+        let to = NavVec3::new(3.0, 2.5, 0.0);
+        let length = mesh
+            .find_path_ex(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &NavPathOptions::default(),
+                |_, _, _| true,
+            )
+            .unwrap()
+            .length;
+        let distance = mesh
+            .geodesic_distance(from, to, NavQuery::Accuracy, None)
+            .unwrap();
+        assert!((distance - length).abs() < 1.0e-4);
+        assert!(mesh
+            .geodesic_distance(from, to, NavQuery::Accuracy, Some(length + 0.01))
+            .is_some());
+        assert_eq!(
+            mesh.geodesic_distance(from, to, NavQuery::Accuracy, Some(2.5)),
+            None
+        );
+        assert_eq!(
+            mesh.geodesic_distance(from, from, NavQuery::Accuracy, None),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn test_connection_capacity() {
+        let mesh = door_mesh();
+        let from = NavVec3::new(3.0, 0.5, 0.0);
+        let to = NavVec3::new(3.0, 2.5, 0.0);
+        let mut capacity = NavCapacity::default();
+        // door portal between triangles 1 and 6.
+        let door = NavConnection(1, 6);
+        capacity.set_limit(door, Some(2));
+        let find = |capacity: &NavCapacity<NavConnection>| {
+            mesh.find_path_ex(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &NavPathOptions::default(),
+                |_, a, b| !capacity.is_full(&NavConnection(a as u32, b as u32)),
+            )
+            .unwrap()
+            .triangles
+        };
+        for _ in 0..2 {
+            let triangles = find(&capacity);
+            assert!(triangles.contains(&6));
+            for pair in triangles.windows(2) {
+                assert!(capacity.check_in(NavConnection(pair[0] as u32, pair[1] as u32)));
+            }
+        }
+        assert!(capacity.is_full(&door));
+        assert!(!find(&capacity).contains(&6));
+        assert!(capacity.check_out(&NavConnection(6, 1)));
+        assert!(find(&capacity).contains(&6));
+        assert_eq!(capacity.users(&door), 1);
+        capacity.clear_users();
+        assert_eq!(capacity.used().count(), 0);
+        assert_eq!(capacity.limit(&door), Some(2));
+    }
+
+    #[test]
+    fn test_heuristic() {
+        let mesh = door_mesh();
+        let options = NavPathOptions::default();
+        let (expected, cost) = mesh
+            .find_path_triangles_with_options(1, 10, &options, |_, _, _| true)
+            .unwrap();
+        assert!(expected.contains(&6));
+        let calls = std::cell::Cell::new(0);
+        let counting = |_: usize, to: usize, _: NavVec3, _: NavVec3| {
+            assert_eq!(to, 10);
+            calls.set(calls.get() + 1);
+            0.0
+        };
+        let (path, found_cost) = mesh
+            .find_path_triangles_with_heuristic(1, 10, &options, &counting, |_, _, _| true)
+            .unwrap();
+        assert_eq!(path, expected);
+        assert_eq!(found_cost, cost);
+        assert!(calls.get() > 0);
+        // overestimating heuristic steers search away from door triangles.
+        let avoid_door = |from: usize, _: usize, _: NavVec3, _: NavVec3| {
+            if from == 6 || from == 7 {
+                1000.0
+            } else {
+                0.0
+            }
+        };
+        let (path, _) = mesh
+            .find_path_triangles_with_heuristic(1, 10, &options, &avoid_door, |_, _, _| true)
+            .unwrap();
+        assert!(!path.contains(&6));
+
+        let net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (2.0, 0.0, 0.0).into(),
+                (2.0, 2.0, 0.0).into(),
+                (0.0, 2.0, 0.0).into(),
+            ],
+            vec![
+                NavConnection(0, 1),
+                NavConnection(1, 2),
+                NavConnection(0, 3),
+                NavConnection(3, 2),
+            ],
+        )
+        .unwrap();
+        let from = NavVec3::new(0.5, 0.0, 0.0);
+        let to = NavVec3::new(2.0, 1.5, 0.0);
+        let path = net
+            .find_path_with_heuristic(from, to, &NavEuclideanHeuristic::default(), |_, _, _| true)
+            .unwrap();
+        assert_eq!(path.len(), 3);
+        let avoid_corner = |from: usize, _: usize, _: NavVec3, _: NavVec3| {
+            if from == 1 {
+                1000.0
+            } else {
+                0.0
+            }
+        };
+        assert_eq!(
+            net.find_path_with_heuristic(from, to, &avoid_corner, |_, _, _| true)
+                .unwrap(),
+            vec![from, NavVec3::new(0.0, 2.0, 0.0), to]
+        );
+    }
+
+    #[test]
+    fn test_max_expansions() {
+        let mesh = grid_mesh(10, 1);
+        let from = NavVec3::new(0.5, 0.5, 0.0);
+        let to = NavVec3::new(9.5, 0.5, 0.0);
+        let find = |max_expansions| {
+            mesh.find_path_ex(
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &NavPathOptions {
+                    max_expansions,
+                    ..Default::default()
+                },
+                |_, _, _| true,
+            )
+            .unwrap()
+        };
+        let full = find(None);
+        assert!(!full.partial);
+        let budget = find(Some(100));
+        assert!(!budget.partial);
+        assert_eq!(budget.triangles, full.triangles);
+        assert_eq!(budget.cost, full.cost);
+        let partial = find(Some(6));
+        assert!(partial.partial);
+        assert!(partial.triangles.len() < full.triangles.len());
+        assert_eq!(
+            partial.triangles[..],
+            full.triangles[..partial.triangles.len()]
+        );
+        let end = *partial.points().last().unwrap();
+        assert!(end.x > 1.0 && end.x < 9.0);
+        // partial corridors are not reported as complete ones.
+        let options = NavPathOptions {
+            max_expansions: Some(6),
+            ..Default::default()
+        };
+        assert!(mesh
+            .find_path_triangles_with_options(0, 19, &options, |_, _, _| true)
+            .is_none());
+        assert!(mesh
+            .find_path_corridor(from, to, NavQuery::Accuracy, NavPathMode::Funnel, &options)
+            .is_none());
+        assert!(mesh
+            .find_path_triangles_with_options(
+                0,
+                19,
+                &NavPathOptions {
+                    max_expansions: Some(0),
+                    ..Default::default()
+                },
+                |_, _, _| true
+            )
+            .is_none());
+        // search with budget is still guided by heuristic.
+        let mesh = grid_mesh(10, 10);
+        let start = mesh
+            .find_closest_triangle(from, NavQuery::Accuracy)
+            .unwrap();
+        let end = mesh.find_closest_triangle(to, NavQuery::Accuracy).unwrap();
+        let options = NavPathOptions {
+            max_expansions: Some(40),
+            ..Default::default()
+        };
+        assert!(mesh
+            .find_path_triangles_with_options(start, end, &options, |_, _, _| true)
+            .is_none());
+        let euclidean = |_: usize, _: usize, a: NavVec3, b: NavVec3| (b - a).magnitude();
+        let (path, _) = mesh
+            .find_path_triangles_with_heuristic(start, end, &options, &euclidean, |_, _, _| true)
+            .unwrap();
+        assert_eq!(path.last(), Some(&end));
+    }
+
+    #[test]
+    fn test_render_mesh() {
+        let mesh = NavMesh::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (1.0, 0.0, 0.0).into(),
+                (1.0, 1.0, 0.0).into(),
+                (0.0, 1.0, 0.0).into(),
+                (5.0, 0.0, 0.0).into(),
+                (6.0, 0.0, 0.0).into(),
+                (6.0, 1.0, 0.0).into(),
+                (5.0, 1.0, 0.0).into(),
+            ],
+            vec![
+                (0, 1, 2).into(),
+                (4, 5, 6).into(),
+                (2, 3, 0).into(),
+                (6, 7, 4).into(),
+            ],
+        )
+        .unwrap();
+        let render = mesh.render_mesh(NavRenderSplit::Island);
+        assert_eq!(render.len(), 2);
+        for (group, render) in render.iter().enumerate() {
+            assert_eq!(render.group, group);
+            assert_eq!(render.vertices.len(), 4);
+            assert_eq!(render.indices, vec![0, 1, 2, 2, 3, 0]);
+        }
+        let mut lefts = render
+            .iter()
+            .map(|render| render.vertices[0].x)
+            .collect::<Vec<_>>();
+        lefts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(lefts, vec![0.0, 5.0]);
+        let render = mesh.render_mesh(NavRenderSplit::None);
+        assert_eq!(render[0].vertices.len(), 8);
+        assert_eq!(render[0].indices.len(), 12);
+    }
+
+    #[test]
+    fn test_mirrored() {
+        let left = grid_mesh(2, 1);
+        let mut vertices = left.vertices().to_vec();
+        let mut triangles = left.triangles().to_vec();
+        let offset = vertices.len() as u32;
+        vertices.extend(
+            left.vertices()
+                .iter()
+                .map(|v| *v + NavVec3::new(3.0, 0.0, 0.0)),
+        );
+        triangles.extend(left.triangles().iter().map(|t| NavTriangle {
+            first: t.first + offset,
+            second: t.second + offset,
+            third: t.third + offset,
+        }));
+        let mut mesh = NavMesh::new(vertices, triangles).unwrap();
+        mesh.set_area_type(0, 2);
+        mesh.add_off_mesh_link(NavOffMeshLink {
+            from: (2.0, 0.5, 0.0).into(),
+            to: (3.0, 0.5, 0.0).into(),
+            cost: 1.0,
+            bidirectional: false,
+            kind: NavOffMeshLinkKind::Jump,
+        })
+        .unwrap();
+        let mirrored = mesh
+            .mirrored(NavVec3::new(-0.5, 0.0, 0.0), NavVec3::new(2.0, 0.0, 0.0))
+            .unwrap();
+        assert_eq!(mirrored.areas()[0].area_type, 2);
+        let normal = |mesh: &NavMesh, index: usize| {
+            let t = mesh.triangles()[index];
+            let v = mesh.vertices();
+            let (a, b, c) = (
+                v[t.first as usize],
+                v[t.second as usize],
+                v[t.third as usize],
+            );
+            (b - a).cross(c - a).normalize()
+        };
+        for index in 0..mesh.triangles().len() {
+            assert_eq!(normal(&mirrored, index), normal(&mesh, index));
+        }
+        let (_, link) = mirrored.off_mesh_links().next().unwrap();
+        assert_eq!(link.from, NavVec3::new(-3.0, 0.5, 0.0));
+        assert_eq!(link.to, NavVec3::new(-4.0, 0.5, 0.0));
+        let from = NavVec3::new(-1.5, 0.5, 0.0);
+        let to = NavVec3::new(-5.5, 0.5, 0.0);
+        let path = mirrored
+            .find_path(from, to, NavQuery::Accuracy, NavPathMode::Funnel)
+            .unwrap();
+        assert_eq!(path, vec![from, link.from, link.to, to]);
+        assert_eq!(
+            mirrored.find_path(to, from, NavQuery::Accuracy, NavPathMode::Funnel),
+            None
+        );
+    }
+
+    #[test]
+    fn test_breadcrumbs() {
+        let mut breadcrumbs = NavBreadcrumbs::new(0.5);
+        // agents cut the corner of intended L-shaped route.
+        for i in 0..=4 {
+            let t = i as Scalar * 0.5;
+            assert!(breadcrumbs.record("a", (t, t, 0.0).into()));
+            assert!(!breadcrumbs.record("a", (t + 0.1, t, 0.0).into()));
+            breadcrumbs.record("b", (t + 0.1, t, 0.0).into());
+        }
+        assert!(breadcrumbs.end_trail(&"a"));
+        assert!(!breadcrumbs.end_trail(&"a"));
+        breadcrumbs.record("a", (5.0, 5.0, 0.0).into());
+        breadcrumbs.record("a", (5.0, 6.0, 0.0).into());
+        assert_eq!(breadcrumbs.trails().len(), 3);
+        assert_eq!(breadcrumbs.samples_count(), 12);
+        let net = breadcrumbs.to_nav_net().unwrap();
+        assert_eq!(net.vertices().len(), 7);
+        assert_eq!(net.connections().len(), 5);
+        let path = net
+            .find_path((0.0, 0.0, 0.0).into(), (2.0, 2.0, 0.0).into())
+            .unwrap();
+        assert_eq!(path.first(), Some(&NavVec3::new(0.0, 0.0, 0.0)));
+        assert_eq!(path.last(), Some(&NavVec3::new(2.0, 2.0, 0.0)));
+        assert_eq!(net.find_islands().len(), 2);
+        let saved = serde_json::to_string(&breadcrumbs).unwrap();
+        let loaded = serde_json::from_str::<NavBreadcrumbs<String>>(&saved).unwrap();
+        assert_eq!(loaded.samples_count(), 12);
+        breadcrumbs.clear();
+        assert_eq!(breadcrumbs.samples_count(), 0);
+    }
+
+    #[test]
+    fn test_clearance_midpoints() {
+        // L-shaped corridor with inner corner at vertice 3.
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(), // 0
+            (2.0, 0.0, 0.0).into(), // 1
+            (2.0, 1.0, 0.0).into(), // 2
+            (1.0, 1.0, 0.0).into(), // 3
+            (1.0, 2.0, 0.0).into(), // 4
+            (0.0, 2.0, 0.0).into(), // 5
+            (0.5, 0.5, 0.0).into(), // 6
+        ];
+        let triangles = vec![
+            (0, 1, 6).into(), // 0
+            (1, 2, 6).into(), // 1
+            (2, 3, 6).into(), // 2
+            (3, 4, 6).into(), // 3
+            (4, 5, 6).into(), // 4
+            (5, 0, 6).into(), // 5
+        ];
+        let mesh = NavMesh::new(vertices, triangles).unwrap();
+        assert_eq!(mesh.vertices_clearances()[3], 0.0);
+        assert_eq!(mesh.vertices_clearances()[6], 0.5);
+        let from = NavVec3::new(1.9, 0.9, 0.0);
+        let to = NavVec3::new(0.9, 1.9, 0.0);
+        let find = |mode| mesh.find_path(from, to, NavQuery::Accuracy, mode).unwrap();
+        let midpoints = find(NavPathMode::MidPoints);
+        let clearance = find(NavPathMode::ClearanceMidPoints);
+        assert_eq!(midpoints.len(), 3);
+        assert_eq!(clearance.len(), 3);
+        let corner = NavVec3::new(1.0, 1.0, 0.0);
+        let closest = |path: &[NavVec3]| {
+            path.windows(2)
+                .map(|pair| corner.distance_to_segment(pair[0], pair[1]))
+                .fold(SCALAR_MAX, Scalar::min)
+        };
+        assert!(closest(&clearance) > closest(&midpoints));
+    }
+
+    #[test]
+    fn test_area_names() {
+        // ground, water and ground again along 3x1 grid.
+        let mut mesh = grid_mesh(3, 1);
+        mesh.set_area_type(2, 1);
+        mesh.set_area_type(3, 1);
+        assert!(mesh.set_area_name("ground", 0));
+        assert!(mesh.set_area_name("swamp", 1));
+        assert!(mesh.set_area_name("water", 1));
+        assert!(!mesh.set_area_name("lava", NAV_AREA_TYPES_COUNT as u8));
+        assert_eq!(mesh.named_area_type("swamp"), None);
+        assert_eq!(
+            mesh.area_names().collect::<Vec<_>>(),
+            vec![("ground", 0), ("water", 1)]
+        );
+        assert_eq!(mesh.area_mask(&["ground", "water", "lava"]), 0b11);
+
+        let find = |mesh: &NavMesh, area_mask| {
+            mesh.find_path_ex(
+                (0.5, 0.5, 0.0).into(),
+                (2.5, 0.5, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Funnel,
+                &NavPathOptions {
+                    area_mask,
+                    ..Default::default()
+                },
+                |_, _, _| true,
+            )
+        };
+        assert!(find(&mesh, mesh.area_mask(&["ground"])).is_none());
+        let result = find(&mesh, mesh.area_mask(&["ground", "water"])).unwrap();
+        assert_eq!(
+            result
+                .transitions
+                .iter()
+                .map(|transition| (transition.from, transition.to))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (1, 0)]
+        );
+        for transition in &result.transitions {
+            let triangle = result.triangles[transition.index];
+            assert_eq!(mesh.areas()[triangle].area_type, transition.to);
+        }
+
+        let mirrored = mesh
+            .mirrored((0.0, 0.0, 0.0).into(), (1.0, 0.0, 0.0).into())
+            .unwrap();
+        assert_eq!(mirrored.named_area_type("water"), Some(1));
+        let merged = NavMesh::merge(&[mesh.clone(), mirrored]).unwrap();
+        assert_eq!(merged.area_names().count(), 2);
+        assert_eq!(mesh.remove_area_name("water"), Some(1));
+        assert_eq!(mesh.area_name(1), None);
+
+        // only changes between named area types are reported.
+        let corridor = (0..6).collect::<Vec<_>>();
+        assert!(mesh.area_transitions(&corridor).is_empty());
+        assert!(mesh.set_area_name("water", 1));
+        mesh.set_area_type(2, 2);
+        assert_eq!(
+            mesh.area_transitions(&corridor),
+            vec![
+                NavAreaTransition {
+                    index: 3,
+                    from: 0,
+                    to: 1
+                },
+                NavAreaTransition {
+                    index: 4,
+                    from: 1,
+                    to: 0
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pooled_search() {
+        let mesh = grid_mesh(6, 6);
+        let options = NavPathOptions::default();
+        let expected = mesh.find_path_triangles(0, 71).unwrap();
+        // searches started from search callbacks get their own buffers.
+        let mut nested = 0;
+        let result = mesh
+            .find_path_triangles_with_options(0, 71, &options, |_, a, b| {
+                if nested < 3 {
+                    nested += 1;
+                    assert_eq!(mesh.find_path_triangles(a, b).unwrap().0, vec![a, b]);
+                }
+                true
+            })
+            .unwrap();
+        assert_eq!(nested, 3);
+        assert_eq!(result.1, expected.1);
+        for _ in 0..10 {
+            assert_eq!(mesh.find_path_triangles(0, 71).unwrap(), expected);
+        }
+        assert_eq!(mesh.find_path_triangles(5, 5).unwrap().0, vec![5]);
+    }
+
+    #[test]
+    fn test_path_cache() {
+        let mut mesh = grid_mesh(4, 4);
+        let mut cache = NavPathCache::new(1.0, NavQuery::Accuracy, NavPathMode::Funnel);
+        let to = NavVec3::new(3.5, 3.5, 0.0);
+        for i in 0..5 {
+            // all start points lay in the same cell and triangle.
+            let from = NavVec3::new(0.1 + i as Scalar * 0.1, 0.05, 0.0);
+            let path = cache.find_path(&mesh, from, to).unwrap();
+            assert_eq!(path.first(), Some(&from));
+            assert_eq!(path.last(), Some(&to));
+        }
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (4, 1, 1));
+        assert!(cache.find_path(&mesh, to, to).is_none());
+        assert!(cache.find_path(&mesh, to, to).is_none());
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (5, 2, 2));
+        mesh.set_area_cost(0, 2.0);
+        cache
+            .find_path(&mesh, NavVec3::new(0.5, 0.5, 0.0), to)
+            .unwrap();
+        assert_eq!((cache.misses(), cache.len()), (3, 1));
+        cache.set_capacity(2);
+        cache
+            .find_path(&mesh, NavVec3::new(1.5, 0.5, 0.0), to)
+            .unwrap();
+        cache
+            .find_path(&mesh, NavVec3::new(2.5, 0.5, 0.0), to)
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+        cache
+            .find_path(&mesh, NavVec3::new(0.5, 0.5, 0.0), to)
+            .unwrap();
+        assert_eq!(cache.misses(), 6);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_path_cache_wall() {
+        let mesh = door_mesh();
+        // both rooms share lattice cell but are separated by wall between them.
+        let mut cache = NavPathCache::new(4.0, NavQuery::Accuracy, NavPathMode::Funnel);
+        let to = NavVec3::new(1.0, 2.5, 0.0);
+        let upper = NavVec3::new(2.5, 2.1, 0.0);
+        let lower = NavVec3::new(2.5, 0.9, 0.0);
+        assert_eq!(cache.find_path(&mesh, upper, to).unwrap(), vec![upper, to]);
+        let path = cache.find_path(&mesh, lower, to).unwrap();
+        assert_eq!(
+            path,
+            mesh.find_path(lower, to, NavQuery::Accuracy, NavPathMode::Funnel)
+                .unwrap()
+        );
+        assert!(path.len() > 2);
+        assert_eq!((cache.hits(), cache.misses()), (0, 2));
+    }
+
+    #[test]
+    fn test_probe() {
+        // two separate 2x1 platforms: [0; 2] and [3; 5] on X axis.
+        let left = grid_mesh(2, 1);
+        let mut vertices = left.vertices().to_vec();
+        let mut triangles = left.triangles().to_vec();
+        let offset = vertices.len() as u32;
+        vertices.extend(
+            left.vertices()
+                .iter()
+                .map(|v| *v + NavVec3::new(3.0, 0.0, 0.0)),
+        );
+        triangles.extend(left.triangles().iter().map(|t| NavTriangle {
+            first: t.first + offset,
+            second: t.second + offset,
+            third: t.third + offset,
+        }));
+        let mut mesh = NavMesh::new(vertices, triangles).unwrap();
+        mesh.set_area_type(7, 3);
+
+        let left = mesh.probe((1.0, 0.4, 0.0).into(), 0.1).unwrap();
+        let right = mesh.probe((4.25, 0.7, 0.0).into(), 0.1).unwrap();
+        assert!(left.on_mesh && right.on_mesh);
+        assert_eq!(right.triangle, 7);
+        assert_eq!(right.area_type, 3);
+        assert!((left.clearance - 0.4).abs() < 1.0e-6);
+        assert!((right.clearance - 0.3).abs() < 1.0e-6);
+        assert_eq!(right.boundary_normal, Some((0.0, -1.0, 0.0).into()));
+        assert_ne!(left.island, right.island);
+        assert_eq!(mesh.triangle_island(left.triangle), Some(left.island));
+        assert_eq!(mesh.triangle_island(8), None);
+
+        // point off nav mesh gets snapped to its edge.
+        let outside = mesh.probe((2.25, 0.5, 0.0).into(), 0.1).unwrap();
+        assert!(!outside.on_mesh);
+        assert_eq!(outside.point, (2.0, 0.5, 0.0).into());
+        assert_eq!(outside.clearance, 0.0);
+        assert_eq!(outside.boundary_normal, Some((-1.0, 0.0, 0.0).into()));
+
+        mesh.add_off_mesh_link(NavOffMeshLink {
+            from: (2.0, 0.5, 0.0).into(),
+            to: (3.0, 0.5, 0.0).into(),
+            cost: 1.0,
+            bidirectional: true,
+            kind: NavOffMeshLinkKind::Jump,
+        })
+        .unwrap();
+        let left = mesh.probe((1.0, 0.4, 0.0).into(), 0.1).unwrap();
+        let right = mesh.probe((4.25, 0.7, 0.0).into(), 0.1).unwrap();
+        assert_eq!(left.island, right.island);
+    }
+
+    #[test]
+    fn test_smooth_grid_path() {
+        // 3x3 cells with blocked center, both on grid and nav mesh.
+        let full = grid_mesh(3, 3);
+        let triangles = full
+            .triangles()
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| index / 2 != 4)
+            .map(|(_, triangle)| *triangle)
+            .collect::<Vec<_>>();
+        let mesh = NavMesh::new(full.vertices().to_vec(), triangles).unwrap();
+        let grid = NavGrid::from_fn(3, 3, |col, row| col != 1 || row != 1).unwrap();
+        let path = grid.find_path((0, 0), (2, 2)).unwrap();
+        assert_eq!(path.len(), 5);
+
+        let smooth = mesh.smooth_grid_path(&path, NavQuery::Accuracy, |(col, row)| {
+            NavVec3::new(col as Scalar + 0.5, row as Scalar + 0.5, 0.0)
+        });
+        assert_eq!(smooth.len(), 3);
+        assert_eq!(smooth[0], (0.5, 0.5, 0.0).into());
+        assert_eq!(smooth[2], (2.5, 2.5, 0.0).into());
+        assert!(smooth[1] == (0.5, 2.5, 0.0).into() || smooth[1] == (2.5, 0.5, 0.0).into());
+
+        // cells off nav mesh get skipped.
+        let smooth = mesh.smooth_grid_path(&path, NavQuery::Accuracy, |(col, row)| {
+            NavVec3::new(
+                col as Scalar + 0.5,
+                row as Scalar + 0.5,
+                (col * 10) as Scalar,
+            )
+        });
+        assert_eq!(smooth.first(), Some(&(0.5, 0.5, 0.0).into()));
+        assert!(smooth.len() < path.len());
+    }
+
+    #[test]
+    fn test_path_surface() {
+        let mesh = grid_mesh(3, 2);
+        assert!(mesh
+            .vertices_normals()
+            .iter()
+            .all(|normal| *normal == (0.0, 0.0, 1.0).into()));
+        let path = mesh
+            .find_path(
+                (0.5, 0.5, 0.0).into(),
+                (2.5, 1.5, 0.0).into(),
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+            )
+            .unwrap();
+        let surface = mesh.path_surface(&path, (0.0, 0.0, 2.0).into());
+        assert_eq!(surface.len(), path.len());
+        for (surface, point) in surface.iter().zip(path.iter()) {
+            assert_eq!(surface.point, *point);
+            assert_eq!(surface.slope, 0.0);
+            assert!(mesh.contains_point(*point, 0.0, 0.0).is_some());
+        }
+        assert_eq!(
+            surface[0].triangle,
+            mesh.find_closest_triangle(path[0], NavQuery::Accuracy)
+                .unwrap()
+        );
+        // the same mesh is a wall for worlds with Y axis up.
+        for surface in mesh.path_surface(&path, (0.0, 1.0, 0.0).into()) {
+            assert_relative_eq!(
+                surface.slope,
+                std::f64::consts::FRAC_PI_2 as Scalar,
+                epsilon = 1.0e-4
+            );
+        }
+    }
+
+    #[cfg(feature = "conformance")]
+    #[test]
+    fn test_conformance() {
+        let report = run_conformance(&mut NavDefaultConformanceAgent).unwrap();
+        assert_eq!(report.failures, vec![]);
+        assert_eq!(report.scenarios_count, 14);
+
+        // integration that does not snap points off nav mesh.
+        struct Strict;
+
+        impl NavConformanceAgent for Strict {
+            fn snap(&mut self, mesh: &NavMesh, point: NavVec3) -> Option<NavVec3> {
+                mesh.contains_point(point, 0.0, 0.0).map(|_| point)
+            }
+        }
+
+        let report = run_conformance(&mut Strict).unwrap();
+        assert!(!report.is_ok());
+        assert!(report
+            .failures
+            .iter()
+            .all(|failure| failure.map != "ramp" || failure.scenario == 0));
+        assert_eq!(report.failures.len(), 5);
+    }
+
+    #[test]
+    fn test_epsilon() {
+        let mut mesh = grid_mesh(2, 1);
+        assert_eq!(mesh.epsilon(), 1.0e-6);
+        let from = NavVec3::new(0.5, 0.5, 0.0);
+        let to = NavVec3::new(0.5, 0.52, 0.0);
+        assert!(mesh
+            .find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .is_some());
+        mesh.set_epsilon(1.0e-3);
+        assert!(mesh
+            .find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .is_none());
+        mesh.set_tolerance(Default::default());
+        assert_eq!(mesh.tolerance(), NavTolerance::default());
+
+        let mut net = NavNet::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (1.0, 0.0, 0.0).into(),
+                (1.0, 0.02, 0.0).into(),
+            ],
+            vec![NavConnection(0, 1), NavConnection(1, 2)],
+        )
+        .unwrap();
+        // points on different connections around the corner.
+        let from = NavVec3::new(0.99, 0.0, 0.0);
+        let to = NavVec3::new(1.0, 0.015, 0.0);
+        assert!(net.find_path(from, to).unwrap().len() > 1);
+        net.set_epsilon(1.0e-3);
+        assert_eq!(net.epsilon(), 1.0e-3);
+        assert_eq!(net.find_path(from, to).map(|path| path.len()), Some(1));
+        // points on the same connection are connected directly, even if they are the same.
+        assert_eq!(net.find_path(from, from), Some(vec![from, from]));
+        let near = NavVec3::new(0.9905, 0.0, 0.0);
+        assert_eq!(net.find_path(from, near), Some(vec![from, near]));
+    }
+
+    #[test]
+    fn test_grid_heuristic() {
+        let (cols, rows) = (20, 20);
+        let mut grid = NavGrid::new(cols, rows, vec![true; cols * rows]).unwrap();
+        for col in 0..cols {
+            for row in 0..rows {
+                grid.set_cell_cost(col, row, if row == 5 { 1.0 } else { 2.0 });
+            }
+        }
+        // cheap lane is worth the detour, so heuristic must not overestimate remaining cost.
+        let path = grid.find_path((0, 0), (19, 0)).unwrap();
+        assert_eq!(path.len(), 30);
+        assert!(path.iter().filter(|(_, row)| *row == 5).count() == 20);
+
+        let mut connections = (0..9)
+            .map(|col| NavGridConnection {
+                from: (col, 0),
+                to: (col + 1, 0),
+            })
+            .collect::<Vec<_>>();
+        connections.push(NavGridConnection {
+            from: (0, 0),
+            to: (9, 0),
+        });
+        let grid = NavGrid::with_connections(10, 1, connections).unwrap();
+        assert_eq!(grid.find_path((0, 0), (9, 0)), Some(vec![(0, 0), (9, 0)]));
+    }
+
+    #[test]
+    fn test_grid_eight_connectivity() {
+        let grid =
+            NavGrid::with_connectivity(5, 5, vec![true; 25], NavGridConnectivity::Eight).unwrap();
+        assert_eq!(grid.connectivity(), NavGridConnectivity::Eight);
+        assert_eq!(grid.neighbors(2, 2).unwrap().count(), 8);
+        assert_eq!(grid.neighbors(0, 0).unwrap().count(), 3);
+        let path = grid.find_path((0, 0), (4, 4)).unwrap();
+        assert_eq!(path, vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]);
+        let path = grid.find_path((0, 0), (4, 2)).unwrap();
+        assert_eq!(path.len(), 5);
+
+        // diagonal moves do not cut corners of unwalkable cells.
+        let cells = (0..25).map(|i| i != 6).collect::<Vec<_>>();
+        let grid = NavGrid::with_connectivity(5, 5, cells, NavGridConnectivity::Eight).unwrap();
+        assert!(grid.neighbors(0, 0).unwrap().all(|cell| cell != (1, 1)));
+        assert!(grid.neighbors(1, 0).unwrap().all(|cell| cell != (0, 1)));
+        assert_eq!(grid.find_path((0, 0), (2, 2)).unwrap().len(), 5);
+
+        // explicit diagonal connections cost only what their cells cost.
+        let grid = NavGrid::with_connections(
+            2,
+            2,
+            vec![NavGridConnection {
+                from: (0, 0),
+                to: (1, 1),
+            }],
+        )
+        .unwrap();
+        assert_eq!(
+            grid.find_path_with_cost((0, 0), (1, 1)),
+            Some((1.0, vec![(0, 0), (1, 1)]))
+        );
+    }
+
+    #[test]
+    fn test_grid_flow_field() {
+        // wall in the middle column with gap at the bottom row.
+        let cells = (0..25)
+            .map(|i| i % 5 != 2 || i / 5 == 4)
+            .collect::<Vec<_>>();
+        let mut grid = NavGrid::new(5, 5, cells).unwrap();
+        grid.set_cell_cost(0, 4, 3.0);
+        let field = grid.flow_field((4, 0)).unwrap();
+        assert_eq!(field.goal(), (4, 0));
+        assert_eq!(field.cost(4, 0), Some(0.0));
+        assert_eq!(field.next_cell(4, 0), None);
+        assert_eq!(field.cost(2, 0), None);
+        assert_eq!(field.direction(2, 4), Some((1, 0)));
+        assert_eq!(field.direction(3, 0), Some((1, 0)));
+        let path = field.path((0, 0)).unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(4, 0)));
+        assert_eq!(path.len(), grid.find_path((0, 0), (4, 0)).unwrap().len());
+        // expensive corner cell is avoided.
+        assert!(!path.contains(&(0, 4)));
+        assert!(grid.flow_field((2, 0)).is_none());
+    }
+
+    #[test]
+    fn test_grid_distance_map() {
+        let cells = (0..12).map(|i| i != 5).collect::<Vec<_>>();
+        let mut grid = NavGrid::new(4, 3, cells).unwrap();
+        grid.set_cell_cost(3, 2, 2.0);
+        let map = grid.distance_map(&[(0, 0), (3, 0), (10, 10)]);
+        assert_eq!(map.len(), 12);
+        assert_eq!(map[grid.index(0, 0).unwrap()], Some(0.0));
+        assert_eq!(map[grid.index(3, 0).unwrap()], Some(0.0));
+        assert_eq!(map[grid.index(1, 1).unwrap()], None);
+        assert_eq!(map[grid.index(1, 0).unwrap()], Some(1.0));
+        assert_eq!(map[grid.index(2, 1).unwrap()], Some(2.0));
+        assert_eq!(map[grid.index(3, 2).unwrap()], Some(1.0 + 2.0));
+        assert_eq!(map[grid.index(1, 2).unwrap()], Some(3.0));
+        assert!(grid.distance_map(&[]).iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_grid_set_cell() {
+        let same_neighbors = |a: &NavGrid, b: &NavGrid| {
+            (0..5).all(|col| {
+                (0..5).all(|row| match (a.neighbors(col, row), b.neighbors(col, row)) {
+                    (Some(a), Some(b)) => {
+                        let mut a = a.collect::<Vec<_>>();
+                        let mut b = b.collect::<Vec<_>>();
+                        a.sort_unstable();
+                        b.sort_unstable();
+                        a == b
+                    }
+                    (None, None) => true,
+                    _ => false,
+                })
+            })
+        };
+        for connectivity in [NavGridConnectivity::Four, NavGridConnectivity::Eight] {
+            let mut cells = vec![true; 25];
+            let mut grid = NavGrid::with_connectivity(5, 5, cells.clone(), connectivity).unwrap();
+            for (col, row, walkable) in [
+                (2, 2, false),
+                (0, 0, false),
+                (2, 1, false),
+                (4, 4, false),
+                (2, 2, true),
+                (0, 0, true),
+                (1, 3, false),
+            ] {
+                assert_eq!(
+                    grid.set_cell(col, row, walkable),
+                    Some(cells[row * 5 + col])
+                );
+                cells[row * 5 + col] = walkable;
+                let fresh = NavGrid::with_connectivity(5, 5, cells.clone(), connectivity).unwrap();
+                assert_eq!(grid.cells(), fresh.cells());
+                assert!(same_neighbors(&grid, &fresh));
+            }
+            assert_eq!(grid.set_cell(5, 0, true), None);
+            assert!(grid.find_path((0, 0), (4, 3)).is_some());
+        }
+
+        // one way connections, cells of bottom row are not connected at all.
+        let connections = vec![
+            NavGridConnection {
+                from: (0, 0),
+                to: (1, 0),
+            },
+            NavGridConnection {
+                from: (1, 0),
+                to: (2, 0),
+            },
+        ];
+        let fresh = NavGrid::with_connections(3, 2, connections).unwrap();
+        let mut grid = fresh.clone();
+        assert_eq!(grid.set_cell(1, 0, false), Some(true));
+        assert!(grid.find_path((0, 0), (2, 0)).is_none());
+        assert_eq!(grid.set_cell(1, 0, true), Some(false));
+        assert!(same_neighbors(&grid, &fresh));
+        assert_eq!(grid.set_cell(1, 1, true), Some(false));
+        assert_eq!(grid.neighbors(1, 1).unwrap().count(), 0);
+        assert_eq!(grid.find_path((0, 0), (2, 0)).unwrap().len(), 3);
+        assert!(grid.find_path((2, 0), (0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_grid_doors() {
+        // wall in the middle column with door in the middle row.
+        let cells = (0..25)
+            .map(|i| i % 5 != 2 || i / 5 == 2)
+            .collect::<Vec<_>>();
+        let mut grid = NavGrid::new(5, 5, cells).unwrap();
+        let door = grid
+            .add_door(NavGridDoor {
+                cell: (2, 2),
+                open: false,
+                lock: Some(7),
+            })
+            .unwrap();
+        assert_eq!(grid.door_at(2, 2), Some(door));
+        assert!(grid
+            .add_door(NavGridDoor {
+                cell: (2, 2),
+                open: true,
+                lock: None,
+            })
+            .is_none());
+        assert_eq!(grid.find_path((0, 0), (4, 0)), None);
+        assert!(grid.flow_field((4, 0)).unwrap().path((0, 0)).is_none());
+        assert!(grid.distance_map(&[(0, 0)])[grid.index(4, 0).unwrap()].is_none());
+        let keys = [7];
+        let can_open =
+            |_, door: &NavGridDoor| door.lock.map(|lock| keys.contains(&lock)).unwrap_or(true);
+        let path = grid
+            .find_path_with_doors((0, 0), (4, 0), can_open, |_, _| true)
+            .unwrap();
+        assert!(path.contains(&(2, 2)));
+        assert_eq!(
+            grid.find_path_with_doors((0, 0), (4, 0), |_, _| false, |_, _| true),
+            None
+        );
+
+        assert_eq!(grid.set_door_open(door, true), Some(false));
+        assert!(grid.door(door).unwrap().open);
+        assert!(grid.find_path((0, 0), (4, 0)).is_some());
+        assert!(grid.flow_field((4, 0)).unwrap().path((0, 0)).is_some());
+        assert_eq!(grid.doors().count(), 1);
+        grid.set_door_open(door, false);
+        assert!(grid.remove_door(door).is_some());
+        assert_eq!(grid.door_at(2, 2), None);
+        assert!(grid.find_path((0, 0), (4, 0)).is_some());
+    }
+
+    #[test]
+    fn test_chunked_grid() {
+        // 8x8 flat grid with wall at col 3 except the bottom row, split into 4x4 chunks.
+        let cells = (0..64)
+            .map(|i| i % 8 != 3 || i / 8 == 7)
+            .collect::<Vec<_>>();
+        let flat = NavGrid::new(8, 8, cells.clone()).unwrap();
+        let mut chunked = NavChunkedGrid::new(4, 4).unwrap();
+        for chunk in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let chunk_cells = (0..16)
+                .map(|i| cells[(chunk.1 * 4 + i / 4) * 8 + chunk.0 * 4 + i % 4])
+                .collect();
+            let grid = NavGrid::new(4, 4, chunk_cells).unwrap();
+            assert!(chunked.load_chunk(chunk, grid).unwrap().is_none());
+        }
+        assert!(chunked
+            .load_chunk((2, 0), NavGrid::new(2, 4, vec![true; 8]).unwrap())
+            .is_err());
+        assert_eq!(chunked.chunk_of(5, 2), (1, 0));
+        assert!(!chunked.is_walkable(3, 0));
+        assert!(!chunked.is_walkable(9, 0));
+
+        let path = chunked.find_path((0, 0), (7, 0)).unwrap();
+        assert_eq!(path.len(), flat.find_path((0, 0), (7, 0)).unwrap().len());
+        assert!(path
+            .windows(2)
+            .all(|pair| { pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1) == 1 }));
+        assert!(path.contains(&(3, 7)));
+
+        // per-chunk serialization.
+        let grid = chunked.unload_chunk((0, 1)).unwrap();
+        assert_eq!(chunked.find_path((0, 0), (7, 0)), None);
+        let text = serde_json::to_string(&grid).unwrap();
+        let grid = serde_json::from_str::<NavGrid>(&text).unwrap();
+        chunked.load_chunk((0, 1), grid).unwrap();
+        assert!(chunked.is_chunk_loaded((0, 1)));
+        assert_eq!(chunked.find_path((0, 0), (7, 0)).unwrap().len(), path.len());
+
+        let mut chunked = NavChunkedGrid::new(2, 2).unwrap();
+        for chunk in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let grid = NavGrid::with_connectivity(2, 2, vec![true; 4], NavGridConnectivity::Eight)
+                .unwrap();
+            chunked.load_chunk(chunk, grid).unwrap();
+        }
+        assert_eq!(
+            chunked.find_path((0, 0), (3, 3)),
+            Some(vec![(0, 0), (1, 1), (2, 2), (3, 3)])
+        );
+
+        // cached lowest cell cost of chunks follows changes of chunks cells.
+        let grid = chunked.chunk_mut((1, 1)).unwrap();
+        assert_eq!(grid.min_cell_cost(), 1.0);
+        grid.set_cell_cost(0, 0, 0.5);
+        assert_eq!(grid.min_cell_cost(), 0.5);
+        let state = grid.dynamic_state();
+        grid.set_cell(0, 0, false);
+        assert_eq!(grid.min_cell_cost(), 1.0);
+        grid.restore_dynamic_state(&state).unwrap();
+        assert_eq!(grid.min_cell_cost(), 0.5);
+        assert_eq!(
+            chunked.find_path((0, 0), (3, 3)),
+            Some(vec![(0, 0), (1, 1), (2, 2), (3, 3)])
+        );
+    }
+
+    #[test]
+    fn test_layered_grid() {
+        let ground = NavGrid::new(3, 3, vec![true; 9]).unwrap();
+        let floor = NavGrid::new(
+            3,
+            2,
+            vec![
+                true, false, true, //
+                true, true, true, //
+            ],
+        )
+        .unwrap();
+        let mut grid = NavLayeredGrid::new(vec![ground, floor]);
+        assert!(matches!(
+            grid.add_connection(NavGridLayerConnection {
+                from: (0, 0, 0),
+                to: (2, 0, 0),
+                cost: 1.0,
+                bidirectional: true,
+            }),
+            Err(Error::InvalidLayer(2, 2))
+        ));
+        assert!(matches!(
+            grid.add_connection(NavGridLayerConnection {
+                from: (0, 0, 0),
+                to: (1, 2, 2),
+                cost: 1.0,
+                bidirectional: true,
+            }),
+            Err(Error::InvalidCellCoordinate(2, 2, 3, 2))
+        ));
+        assert_eq!(grid.find_path((0, 0, 0), (1, 0, 0)), None);
+        let stairs = grid
+            .add_connection(NavGridLayerConnection {
+                from: (0, 2, 2),
+                to: (1, 2, 0),
+                cost: 2.0,
+                bidirectional: false,
+            })
+            .unwrap();
+        assert_eq!(
+            grid.find_path((0, 1, 2), (1, 0, 0)).unwrap(),
+            vec![
+                (0, 1, 2),
+                (0, 2, 2),
+                (1, 2, 0),
+                (1, 2, 1),
+                (1, 1, 1),
+                (1, 0, 1),
+                (1, 0, 0),
+            ]
+        );
+        assert_eq!(grid.find_path((1, 0, 0), (0, 1, 2)), None);
+        let elevator = grid
+            .add_connection(NavGridLayerConnection {
+                from: (0, 2, 2),
+                to: (1, 2, 0),
+                cost: 2.0,
+                bidirectional: true,
+            })
+            .unwrap();
+        // removing connection keeps identifiers of other connections.
+        assert!(grid.remove_connection(stairs).is_some());
+        assert!(grid.remove_connection(stairs).is_none());
+        assert!(grid.connection(elevator).unwrap().bidirectional);
+        assert_eq!(grid.connections().count(), 1);
+        assert_eq!(grid.find_path((1, 0, 0), (0, 1, 2)).unwrap().len(), 7);
+    }
+
+    #[test]
+    fn test_grid_islands_stats() {
+        let mut grid = NavGrid::new(
+            4,
+            3,
+            vec![
+                true, true, false, true, //
+                false, true, false, false, //
+                false, true, true, false, //
+            ],
+        )
+        .unwrap();
+        grid.set_cell_cost(2, 2, 3.0);
+        let mut stats = grid.islands_stats();
+        stats.sort_by_key(|stats| stats.cells_count);
+        assert_eq!(
+            stats,
+            vec![
+                NavGridIslandStats {
+                    cells_count: 1,
+                    total_cost: 1.0,
+                    bounds_min: (3, 0),
+                    bounds_max: (3, 0),
+                },
+                NavGridIslandStats {
+                    cells_count: 5,
+                    total_cost: 7.0,
+                    bounds_min: (0, 0),
+                    bounds_max: (2, 2),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grid_line_of_sight() {
+        let grid = NavGrid::new(
+            5,
+            4,
+            vec![
+                true, true, true, true, true, //
+                true, true, true, true, true, //
+                true, true, false, true, true, //
+                true, true, true, true, true, //
+            ],
+        )
+        .unwrap();
+        assert!(grid.line_of_sight((0, 0), (4, 1)));
+        assert!(!grid.line_of_sight((4, 3), (0, 0)));
+        assert!(grid.line_of_sight((4, 3), (0, 3)));
+        assert!(!grid.line_of_sight((0, 3), (4, 1)));
+        assert!(!grid.line_of_sight((1, 1), (3, 3)));
+        assert!(!grid.line_of_sight((2, 0), (2, 3)));
+        assert!(grid.line_of_sight((1, 0), (1, 3)));
+        assert!(!grid.line_of_sight((2, 2), (2, 2)));
+        let path = grid.find_path((0, 0), (4, 1)).unwrap();
+        assert_eq!(grid.smooth_path(&path), vec![(0, 0), (4, 1)]);
+        let path = grid.find_path((0, 3), (4, 0)).unwrap();
+        let smooth = grid.smooth_path(&path);
+        assert_eq!(smooth.first(), Some(&(0, 3)));
+        assert_eq!(smooth.last(), Some(&(4, 0)));
+        assert!(smooth.len() < path.len());
+        assert!(smooth
+            .windows(2)
+            .all(|pair| grid.line_of_sight(pair[0], pair[1])));
+    }
+
+    #[test]
+    fn test_grid_path_cost() {
+        let mut grid = NavGrid::new(3, 2, vec![true; 6]).unwrap();
+        grid.set_cell_cost(1, 0, 4.0);
+        let (cost, path) = grid.find_path_with_cost((0, 0), (2, 0)).unwrap();
+        assert_eq!(path, vec![(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)]);
+        assert_eq!(cost, 4.0);
+        let (cost, path) = grid
+            .find_path_custom_with_cost((0, 0), (2, 0), |a, b| a.1 == b.1)
+            .unwrap();
+        assert_eq!(path, vec![(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(cost, 8.0);
+        assert_eq!(grid.find_path((0, 0), (2, 0)).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_grid_multi_target() {
+        let mut grid = NavGrid::new(
+            5,
+            3,
+            vec![
+                true, true, true, true, true, //
+                true, false, false, false, true, //
+                true, true, true, true, true, //
+            ],
+        )
+        .unwrap();
+        let targets = [(4, 0), (3, 2), (1, 2)];
+        assert_eq!(
+            grid.find_path_to_any((0, 0), &targets).unwrap(),
+            vec![(0, 0), (0, 1), (0, 2), (1, 2)]
+        );
+        grid.set_cell_cost(0, 1, 10.0);
+        assert_eq!(
+            grid.find_path_to_any((0, 0), &targets).unwrap(),
+            vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]
+        );
+        assert_eq!(
+            grid.find_nearest_source(&targets, (4, 1)).unwrap(),
+            vec![(4, 0), (4, 1)]
+        );
+        assert_eq!(
+            grid.find_nearest_source(&[(0, 0), (1, 2)], (2, 2)).unwrap(),
+            vec![(1, 2), (2, 2)]
+        );
+        assert_eq!(grid.find_path_to_any((0, 0), &[(1, 1)]), None);
+        assert_eq!(grid.find_nearest_source(&[], (0, 0)), None);
+    }
+
+    #[test]
+    fn test_dynamic_state() {
+        let baked = NavGrid::new(3, 3, vec![true; 9]).unwrap();
+        let mut grid = baked.clone();
+        grid.set_cell(1, 1, false);
+        grid.set_cell_cost(0, 2, 5.0);
+        let door = grid
+            .add_door(NavGridDoor {
+                cell: (2, 1),
+                open: false,
+                lock: Some(7),
+            })
+            .unwrap();
+        let mut occupancy = NavOccupancy::default();
+        occupancy.enter(4);
+        let saved = serde_json::to_string(&(grid.dynamic_state(), &occupancy)).unwrap();
+
+        let (state, loaded_occupancy) =
+            serde_json::from_str::<(NavGridDynamicState, NavOccupancy)>(&saved).unwrap();
+        let mut loaded = baked.clone();
+        loaded.restore_dynamic_state(&state).unwrap();
+        assert_eq!(loaded.dynamic_state(), grid.dynamic_state());
+        assert_eq!(loaded_occupancy, occupancy);
+        assert_eq!(loaded.door_at(2, 1), Some(door));
+        assert_eq!(
+            loaded.find_path((0, 1), (2, 2)),
+            grid.find_path((0, 1), (2, 2))
+        );
+        assert_eq!(loaded.find_path((1, 0), (1, 2)).unwrap().len(), 5);
+        loaded
+            .restore_dynamic_state(&baked.dynamic_state())
+            .unwrap();
+        assert_eq!(loaded.door_at(2, 1), None);
+        assert_eq!(loaded.find_path((1, 0), (1, 2)).unwrap().len(), 3);
+        assert!(matches!(
+            loaded.restore_dynamic_state(&NavGridDynamicState::default()),
+            Err(Error::CellsCountDoesNotMatchColsRows(0, 3, 3))
+        ));
+
+        let baked = grid_mesh(2, 1);
+        let mut mesh = baked.clone();
+        mesh.set_area_type(0, 2);
+        mesh.set_area_depth(1, 0.5);
+        mesh.add_bias_region(NavBiasRegion {
+            center: NavVec3::new(0.5, 0.5, 0.0),
+            radius: 1.0,
+            weight: 0.0,
+        });
+        let blocker = mesh.add_blocker(NavBlocker {
+            position: NavVec3::new(1.5, 0.5, 0.0),
+            radius: 0.25,
+        });
+        let saved = serde_json::to_string(&mesh.dynamic_state()).unwrap();
+        let mut loaded = baked.clone();
+        let version = loaded.topology_version();
+        loaded
+            .restore_dynamic_state(&serde_json::from_str(&saved).unwrap())
+            .unwrap();
+        assert_ne!(loaded.topology_version(), version);
+        assert_eq!(loaded.areas()[0].area_type, 2);
+        assert_eq!(loaded.areas()[1].depth, 0.5);
+        assert_eq!(loaded.bias_regions().len(), 1);
+        assert_eq!(loaded.blocker(blocker).unwrap().radius, 0.25);
+        let mut state = mesh.dynamic_state();
+        state.area_costs.pop();
+        assert!(matches!(
+            loaded.restore_dynamic_state(&state),
+            Err(Error::DynamicStateAreasCountDoesNotMatchTriangles(3, 4))
+        ));
+    }
+
+    #[test]
+    fn test_grid_planner() {
+        use rand_core::SeedableRng;
+
+        fn path_cost(grid: &NavGrid, path: &[(usize, usize)]) -> Scalar {
+            let costs = grid.cells_costs();
+            path.windows(2)
+                .map(|pair| {
+                    let a = grid.index(pair[0].0, pair[0].1).unwrap();
+                    let b = grid.index(pair[1].0, pair[1].1).unwrap();
+                    costs[a] * costs[b]
+                })
+                .sum()
+        }
+
+        let mut grid = NavGrid::new(8, 8, vec![true; 64]).unwrap();
+        let mut planner = NavGridPlanner::new(&grid, (0, 0), (7, 7)).unwrap();
+        assert_eq!(planner.path(&grid).unwrap().len(), 15);
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(5);
+        let mut start = (0, 0);
+        for _ in 0..100 {
+            let col = rng.next_u32() as usize % 8;
+            let row = rng.next_u32() as usize % 8;
+            if (col, row) != (7, 7) && (col, row) != start {
+                if rng.next_u32() % 3 == 0 {
+                    grid.set_cell_cost(col, row, 1.0 + (rng.next_u32() % 4) as Scalar);
+                } else {
+                    let walkable = grid.cells()[grid.index(col, row).unwrap()];
+                    grid.set_cell(col, row, !walkable);
+                }
+                planner.update_cells(&grid, &[(col, row)]);
+            }
+            let expected = grid.find_path_with_cost(start, (7, 7));
+            let path = planner.path(&grid);
+            assert_eq!(path.is_some(), expected.is_some());
+            if let (Some(path), Some((cost, _))) = (path, expected) {
+                assert_eq!(path.first(), Some(&start));
+                assert_eq!(path.last(), Some(&(7, 7)));
+                assert_relative_eq!(path_cost(&grid, &path), cost, epsilon = 1.0e-4);
+                if path.len() > 1 {
+                    start = path[1];
+                    assert!(planner.move_start(&grid, start));
+                }
+            }
+        }
+        assert_eq!(planner.start(&grid), start);
+        assert_eq!(planner.goal(&grid), (7, 7));
+        assert_ne!(start, (0, 0));
+    }
+
+    #[test]
+    fn test_grid_connection_costs() {
+        let mut grid = NavGrid::new(3, 2, vec![true; 6]).unwrap();
+        assert_eq!(grid.connection_cost((0, 0), (1, 0)), Some(1.0));
+        assert_eq!(grid.connection_cost((0, 0), (1, 1)), None);
+        assert_eq!(grid.set_connection_cost((0, 0), (2, 0), 2.0), None);
+        // climbing over low wall between (0, 0) and (1, 0), jumping down is cheap.
+        assert_eq!(grid.set_connection_cost((0, 0), (1, 0), 5.0), Some(1.0));
+        assert_eq!(grid.set_connection_cost((1, 0), (0, 0), 0.5), Some(1.0));
+        let (cost, path) = grid.find_path_with_cost((0, 0), (2, 0)).unwrap();
+        assert_eq!(path[..3], [(0, 0), (0, 1), (1, 1)]);
+        assert_eq!(cost, 4.0);
+        let (cost, path) = grid.find_path_with_cost((2, 0), (0, 0)).unwrap();
+        assert_eq!(path, vec![(2, 0), (1, 0), (0, 0)]);
+        assert_eq!(cost, 1.5);
+        assert_eq!(grid.min_connection_cost(), 0.5);
+        let state = grid.dynamic_state();
+        grid.set_cell(0, 0, false);
+        grid.set_cell(0, 0, true);
+        assert_eq!(grid.connection_cost((0, 0), (1, 0)), Some(1.0));
+        assert_eq!(grid.min_connection_cost(), 1.0);
+        grid.restore_dynamic_state(&state).unwrap();
+        assert_eq!(grid.connection_cost((0, 0), (1, 0)), Some(5.0));
+        assert_eq!(grid.min_connection_cost(), 0.5);
+        assert_eq!(grid.set_connection_cost((1, 0), (0, 0), 1.0), Some(0.5));
+        assert_eq!(grid.min_connection_cost(), 1.0);
+        assert_eq!(grid.set_connection_cost((0, 0), (1, 0), 1.0), Some(5.0));
+        assert_eq!(grid.find_path((0, 0), (2, 0)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_grid_bidirectional() {
+        use rand_core::SeedableRng;
+
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(7);
+        for connectivity in [NavGridConnectivity::Four, NavGridConnectivity::Eight] {
+            let cells = (0..400).map(|_| rng.next_u32() % 4 != 0).collect();
+            let mut grid = NavGrid::with_connectivity(20, 20, cells, connectivity).unwrap();
+            for _ in 0..40 {
+                let (col, row) = (rng.next_u32() as usize % 20, rng.next_u32() as usize % 20);
+                grid.set_cell_cost(col, row, 1.0 + (rng.next_u32() % 4) as Scalar);
+            }
+            for _ in 0..50 {
+                let from = (rng.next_u32() as usize % 20, rng.next_u32() as usize % 20);
+                let to = (rng.next_u32() as usize % 20, rng.next_u32() as usize % 20);
+                let expected =
+                    grid.find_path_with_search(from, to, NavGridSearch::AStar, |_, _| true);
+                let result =
+                    grid.find_path_with_search(from, to, NavGridSearch::Bidirectional, |_, _| true);
+                assert_eq!(result.is_some(), expected.is_some());
+                if let (Some((cost, path)), Some((expected, _))) = (result, expected) {
+                    assert_relative_eq!(cost, expected, epsilon = 1.0e-4);
+                    assert_eq!(path.first(), Some(&from));
+                    assert_eq!(path.last(), Some(&to));
+                    for pair in path.windows(2) {
+                        assert!(grid
+                            .neighbors(pair[0].0, pair[0].1)
+                            .unwrap()
+                            .any(|cell| cell == pair[1]));
+                    }
+                }
+            }
+        }
+        let grid = NavGrid::new(3, 1, vec![true; 3]).unwrap();
+        assert_eq!(
+            grid.find_path_with_search((0, 0), (2, 0), NavGridSearch::Bidirectional, |_, _| true),
+            Some((2.0, vec![(0, 0), (1, 0), (2, 0)]))
+        );
+        assert_eq!(
+            grid.find_path_with_search((0, 0), (2, 0), NavGridSearch::Bidirectional, |_, to| {
+                to != (1, 0)
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_grid_goal_bounds() {
+        use rand_core::SeedableRng;
+
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(11);
+        for connectivity in [NavGridConnectivity::Four, NavGridConnectivity::Eight] {
+            let cells = (0..144).map(|_| rng.next_u32() % 4 != 0).collect();
+            let mut grid = NavGrid::with_connectivity(12, 12, cells, connectivity).unwrap();
+            for _ in 0..20 {
+                let (col, row) = (rng.next_u32() as usize % 12, rng.next_u32() as usize % 12);
+                grid.set_cell_cost(col, row, 1.0 + (rng.next_u32() % 4) as Scalar);
+            }
+            let mut bounded = grid.clone();
+            assert!(!bounded.has_goal_bounds());
+            bounded.build_goal_bounds();
+            assert!(bounded.has_goal_bounds());
+            for _ in 0..100 {
+                let from = (rng.next_u32() as usize % 12, rng.next_u32() as usize % 12);
+                let to = (rng.next_u32() as usize % 12, rng.next_u32() as usize % 12);
+                let expected = grid.find_path_with_cost(from, to);
+                let result = bounded.find_path_with_cost(from, to);
+                assert_eq!(result.is_some(), expected.is_some());
+                if let (Some((cost, path)), Some((expected, _))) = (result, expected) {
+                    assert_relative_eq!(cost, expected, epsilon = 1.0e-4);
+                    assert_eq!(path.first(), Some(&from));
+                    assert_eq!(path.last(), Some(&to));
+                }
+            }
+        }
+        let mut grid = NavGrid::new(3, 3, vec![true; 9]).unwrap();
+        grid.build_goal_bounds();
+        assert_eq!(grid.find_path((0, 0), (0, 0)).unwrap(), vec![(0, 0)]);
+        assert_eq!(grid.find_path((0, 0), (2, 2)).unwrap().len(), 5);
+        grid.set_cell_cost(1, 1, 1.0);
+        assert!(grid.has_goal_bounds());
+        grid.set_cell(1, 1, false);
+        assert!(!grid.has_goal_bounds());
+    }
+
+    #[test]
+    fn test_grid_from_fn() {
+        let grid = NavGrid::from_fn(4, 2, |col, row| (col, row) != (3, 0)).unwrap();
+        assert_eq!(grid.index(3, 0), Some(3));
+        assert!(!grid.cells()[3]);
+        assert_eq!(grid.cells().iter().filter(|cell| **cell).count(), 7);
+        let tiles = [
+            0u8, 0, 2, 0, //
+            0, 1, 1, 0, //
+        ];
+        let grid = NavGrid::from_tiles(4, 2, &tiles, |tile| *tile != 1).unwrap();
+        assert_eq!(
+            grid.find_path((0, 1), (3, 1)).unwrap(),
+            vec![(0, 1), (0, 0), (1, 0), (2, 0), (3, 0), (3, 1)]
+        );
+        assert!(matches!(
+            NavGrid::from_tiles(3, 3, &tiles, |tile| *tile == 0),
+            Err(Error::CellsCountDoesNotMatchColsRows(8, 3, 3))
+        ));
+    }
+
+    #[test]
+    fn test_mesh_distance_map() {
+        let mut mesh = grid_mesh(8, 8);
+        mesh.set_area_cost(20, 4.0);
+        let sources = [0, 100];
+        let expected = (0..mesh.triangles().len())
+            .map(|index| {
+                sources
+                    .iter()
+                    .filter_map(|source| mesh.find_path_triangles(*source, index))
+                    .map(|(_, cost)| cost)
+                    .fold(SCALAR_MAX, Scalar::min)
+            })
+            .collect::<Vec<_>>();
+        for chunk_size in [1, 5, 33, 128] {
+            let costs = mesh.integrate_chunked(&sources, chunk_size);
+            for (cost, expected) in costs.iter().zip(expected.iter()) {
+                assert!((cost - expected).abs() < 1.0e-4);
+            }
+            assert_eq!(mesh.integrate_chunked(&sources, chunk_size), costs);
+        }
+        let distances = mesh.distance_map(&sources);
+        assert_eq!(distances[0], Some(0.0));
+        assert!(distances.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn test_grid_integrate_chunked() {
+        use petgraph::Direction;
+
+        // serpentine walls make paths cross chunks borders many times.
+        let mut grid = NavGrid::from_fn(20, 20, |col, row| match row % 4 {
+            1 => col != 19,
+            3 => col != 0,
+            _ => true,
+        })
+        .unwrap();
+        grid.set_cell_cost(5, 0, 3.0);
+        grid.set_cell_cost(12, 8, 0.5);
+        let sources = [grid.index(0, 0).unwrap(), grid.index(10, 18).unwrap()];
+        let expected = grid.distance_map(&[(0, 0), (10, 18)]);
+        for chunk_rows in [1, 3, 7, 20] {
+            let (costs, parents) =
+                grid.integrate_chunked(&sources, Direction::Outgoing, chunk_rows);
+            for (index, cost) in costs.iter().enumerate() {
+                match expected[index] {
+                    Some(expected) => assert!((cost - expected).abs() < 1.0e-4),
+                    None => assert_eq!(*cost, SCALAR_MAX),
+                }
+                if let Some(parent) = parents[index] {
+                    assert!(costs[parent] <= *cost);
+                }
+            }
+            assert_eq!(parents[sources[0]], None);
+            assert_eq!(
+                grid.integrate_chunked(&sources, Direction::Outgoing, chunk_rows),
+                (costs, parents)
+            );
+        }
+
+        let goal = grid.index(19, 19).unwrap();
+        let flow = grid.flow_field((19, 19)).unwrap();
+        let (costs, _) = grid.integrate_chunked(&[goal], Direction::Incoming, 3);
+        for (index, cost) in costs.iter().enumerate() {
+            let (col, row) = grid.coord(index).unwrap();
+            match flow.cost(col, row) {
+                Some(expected) => assert!((cost - expected).abs() < 1.0e-4),
+                None => assert_eq!(*cost, SCALAR_MAX),
+            }
+        }
+    }
+
+    #[test]
+    fn test_grid_portal_cache() {
+        let (cols, rows) = (12, 12);
+        let cells = (0..cols * rows)
+            .map(|index| {
+                let (col, row) = (index % cols, index / cols);
+                !(col == 5 && row < 10)
+            })
+            .collect::<Vec<_>>();
+        let grid = NavGrid::new(cols, rows, cells).unwrap();
+        let cache = NavGridPortalCache::new(&grid, 4, 4).unwrap();
+        assert!(cache.cached_segments_count() > 0);
+        assert!(cache.portals(&grid).all(|(col, row)| col != 5 || row >= 10));
+
+        let is_valid = |path: &[(usize, usize)]| {
+            path.windows(2).all(|pair| {
+                grid.neighbors(pair[0].0, pair[0].1)
+                    .unwrap()
+                    .any(|cell| cell == pair[1])
+            })
+        };
+        let path = cache.find_path(&grid, (0, 0), (11, 0)).unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(11, 0)));
+        assert!(is_valid(&path));
+        assert!(path.iter().any(|(_, row)| *row >= 10));
+        let optimal = grid.find_path((0, 0), (11, 0)).unwrap();
+        assert!(path.len() >= optimal.len());
+        assert!(path.len() <= optimal.len() * 3 / 2);
+
+        let path = cache.find_path(&grid, (0, 0), (2, 3)).unwrap();
+        assert_eq!(path.len(), 6);
+        assert!(is_valid(&path));
+        assert_eq!(cache.find_path(&grid, (0, 0), (5, 0)), None);
+
+        let other = NavGrid::new(cols, rows, vec![true; cols * rows]).unwrap();
+        assert_eq!(cache.find_path(&other, (0, 0), (1, 0)), None);
+        assert!(matches!(
+            NavGridPortalCache::new(&grid, 0, 4),
+            Err(Error::EmptyChunk(0, 4))
+        ));
+
+        // full wall is crossed only through wrapped border.
+        let cells = (0..cols * 4).map(|index| index % cols != 5).collect();
+        let grid = NavGrid::new(cols, 4, cells).unwrap();
+        let cache = NavGridPortalCache::new(&grid, 4, 4).unwrap();
+        assert_eq!(cache.find_path(&grid, (0, 0), (11, 0)), None);
+        let cache = NavGridPortalCache::with_wrap(&grid, 4, 4, (true, false)).unwrap();
+        assert_eq!(cache.wrap(), (true, false));
+        let is_valid = |path: &[(usize, usize)]| {
+            path.windows(2).all(|pair| {
+                let wrapped = pair[0].0.abs_diff(pair[1].0) == cols - 1 && pair[0].1 == pair[1].1;
+                wrapped
+                    || grid
+                        .neighbors(pair[0].0, pair[0].1)
+                        .unwrap()
+                        .any(|cell| cell == pair[1])
+            })
+        };
+        for (from, to) in [((0, 0), (11, 0)), ((1, 2), (9, 1))] {
+            let path = cache.find_path(&grid, from, to).unwrap();
+            assert_eq!(path.first(), Some(&from));
+            assert_eq!(path.last(), Some(&to));
+            assert!(is_valid(&path));
+            assert!(path.iter().all(|(col, _)| *col != 5));
+            assert!(path.len() <= 8);
+        }
+    }
+
+    #[test]
+    fn test_grid_cover_points() {
+        // crate in the middle of 5x5 grid.
+        let cells = (0..25).map(|i| i != 12).collect::<Vec<_>>();
+        let grid = NavGrid::new(5, 5, cells).unwrap();
+        let points = grid.cover_points();
+        assert_eq!(points.len(), 4);
+        let left = points.iter().find(|p| p.cell == (1, 2)).unwrap();
+        assert!(left.is_covered_from(NavGridDirection::Right));
+        assert!(!left.is_covered_from(NavGridDirection::Left));
+        assert_eq!(grid.cover_points_against((4, 2)), vec![(1, 2)]);
+        assert_eq!(grid.cover_points_against((2, 0)), vec![(2, 3)]);
+        assert_eq!(grid.cover_points_against((0, 0)), vec![(3, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn test_influence_map() {
+        let mut map = NavInfluenceMap::default();
+        map.stamp((0, 0), 2.0, 1.0);
+        assert_eq!(map.values().count(), 9);
+        let mut other = NavInfluenceMap::default();
+        other.set_value(0, 0, 3.0);
+        other.set_value(5, 5, -1.0);
+        map.combine(&other, NavInfluenceCombine::Max);
+        assert_eq!(map.value(0, 0), 3.0);
+        assert_eq!(map.value(5, 5), 0.0);
+        map.combine(&other, NavInfluenceCombine::Add);
+        assert_eq!(map.value(0, 0), 6.0);
+        assert_eq!(map.value(5, 5), -1.0);
+        map.clear();
+        map.set_value(0, 0, 4.0);
+        map.blur(1.0);
+        assert_eq!(map.value(0, 0), 0.0);
+        assert_eq!(map.value(1, 0), 1.0);
+        map.decay(0.0);
+        assert_eq!(map.values().count(), 0);
+
+        // danger in the middle of open 5x3 grid pushes path to the side.
+        let grid = NavGrid::new(5, 3, vec![true; 15]).unwrap();
+        let mut danger = NavInfluenceMap::default();
+        danger.stamp((2, 1), 1.5, 10.0);
+        let path = grid
+            .find_path_weighted((0, 1), (4, 1), &danger, 1.0)
+            .unwrap();
+        assert!(!path.contains(&(2, 1)));
+        assert_eq!(
+            grid.find_path_weighted((0, 1), (4, 1), &danger, 0.0)
+                .unwrap()
+                .len(),
+            5
+        );
+        // free to walk row attracts path despite longer route.
+        let mut road = NavInfluenceMap::default();
+        for col in 0..5 {
+            road.set_value(col, 0, 10.0);
+        }
+        assert_eq!(road.min_cost_factor(1.0), 1.0);
+        assert_eq!(road.min_cost_factor(-0.1), 0.0);
+        let path = grid
+            .find_path_weighted((0, 1), (4, 1), &road, -0.1)
+            .unwrap();
+        assert_eq!(path.len(), 7);
+        assert!(path.contains(&(2, 0)));
+
+        let free = NavFreeGrid::new(
+            (0..4)
+                .map(|i| NavFreeGridConnection {
+                    from: (i, 0),
+                    to: (i + 1, 0),
+                })
+                .chain((0..4).map(|i| NavFreeGridConnection {
+                    from: (i, 1),
+                    to: (i + 1, 1),
+                }))
+                .chain((0..5).map(|i| NavFreeGridConnection {
+                    from: (i, 0),
+                    to: (i, 1),
+                }))
+                .collect(),
+        );
+        let mut danger = NavInfluenceMap::default();
+        danger.set_value(2, 0, 10.0);
+        let path = free
+            .find_path_weighted((0, 0), (4, 0), &danger, 1.0)
+            .unwrap();
+        assert!(!path.contains(&(2, 0)));
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = NavGrid::new(
+            3,
+            3,
+            vec![true, true, true, true, false, true, true, true, true],
+        )
+        .unwrap();
+        let path = grid.find_path((0, 0), (1, 2)).unwrap();
+        assert_eq!(path, vec![(0, 0), (0, 1), (0, 2), (1, 2)]);
+        assert_eq!(grid.find_path((0, 0), (1, 1)), None);
+
+        let grid = NavGrid::with_connections(
+            2,
+            2,
+            vec![
+                NavGridConnection {
+                    from: (0, 0),
+                    to: (1, 0),
+                },
+                NavGridConnection {
+                    from: (1, 0),
+                    to: (1, 1),
+                },
+                NavGridConnection {
+                    from: (1, 1),
+                    to: (0, 1),
+                },
+                NavGridConnection {
+                    from: (0, 1),
+                    to: (0, 0),
+                },
+            ],
+        )
+        .unwrap();
+        let path = grid.find_path((0, 0), (0, 1)).unwrap();
+        assert_eq!(path, vec![(0, 0), (1, 0), (1, 1), (0, 1)]);
+        let mut islands = grid.find_islands();
+        for island in &mut islands {
+            island.sort();
+        }
+        assert_eq!(islands, vec![vec![(0, 0), (0, 1), (1, 0), (1, 1)]]);
+
+        let grid = NavGrid::new(
+            3,
+            3,
+            vec![true, true, true, false, false, false, true, true, true],
+        )
+        .unwrap();
+        let mut islands = grid.find_islands();
+        for island in &mut islands {
+            island.sort();
+        }
+        assert_eq!(
+            islands,
+            vec![vec![(0, 0), (1, 0), (2, 0)], vec![(0, 2), (1, 2), (2, 2)]]
+        );
+
+        let grid = NavFreeGrid::new(vec![
+            NavFreeGridConnection {
+                from: (0, 0),
+                to: (0, 2),
+            },
+            NavFreeGridConnection {
+                from: (0, 2),
+                to: (-1, -1),
+            },
+        ]);
+        let path = grid.find_path((0, 0), (-1, -1)).unwrap();
+        assert_eq!(path, vec![(0, 0), (0, 2), (-1, -1)]);
+    }
+
+    #[test]
+    fn test_grid_filter_excludes() {
+        let grid = NavGrid::new(3, 1, vec![true; 3]).unwrap();
+        let blocked = |a, b| (a, b) != ((1, 0), (2, 0));
+        assert_eq!(
+            grid.find_path_custom((0, 0), (2, 0), blocked),
+            Some(vec![(0, 0), (1, 0), (2, 0)])
+        );
+        assert_eq!(grid.find_path_excluding((0, 0), (2, 0), blocked), None);
+        assert_eq!(
+            grid.find_path_excluding((0, 0), (1, 0), blocked),
+            Some(vec![(0, 0), (1, 0)])
+        );
+
+        let connection = |from, to| NavFreeGridConnection { from, to };
+        let grid = NavFreeGrid::new(vec![connection((0, 0), (1, 0)), connection((1, 0), (2, 0))]);
+        let blocked = |a, b| (a, b) != ((1, 0), (2, 0));
+        assert_eq!(
+            grid.find_path_custom((0, 0), (2, 0), blocked),
+            Some(vec![(0, 0), (1, 0), (2, 0)])
+        );
+        assert_eq!(grid.find_path_excluding((0, 0), (2, 0), blocked), None);
+    }
+
+    #[test]
+    fn test_free_grid_world() {
+        let connection = |from, to| NavFreeGridConnection { from, to };
+        let mut grid = NavFreeGrid::new(vec![
+            connection((0, 0), (1, 0)),
+            connection((1, 0), (2, 0)),
+            connection((0, 0), (0, 1)),
+            connection((0, 1), (2, 0)),
+        ]);
+        assert_eq!(grid.cell_position(0, 0), None);
+        assert_eq!(
+            grid.find_path_world(NavVec3::default(), NavVec3::default()),
+            None
+        );
+        grid.set_cell_size(Some((2.0, 3.0)));
+        assert_eq!(grid.cell_position(2, 0), Some(NavVec3::new(4.0, 0.0, 0.0)));
+        assert_eq!(
+            grid.set_cell_position(1, 0, Some(NavVec3::new(2.0, 5.0, 0.0))),
+            Some(None)
+        );
+        assert_eq!(
+            grid.set_cell_position(0, 1, Some(NavVec3::new(2.0, 0.0, 0.0))),
+            Some(None)
+        );
+        assert_eq!(grid.set_cell_position(3, 3, None), None);
+        assert_eq!(grid.nearest_cell(NavVec3::new(2.5, 4.0, 0.0)), Some((1, 0)));
+        assert_eq!(
+            grid.find_path_world(NavVec3::new(-0.5, 0.0, 0.0), NavVec3::new(4.5, 0.5, 0.0))
+                .unwrap(),
+            vec![
+                NavVec3::new(0.0, 0.0, 0.0),
+                NavVec3::new(2.0, 0.0, 0.0),
+                NavVec3::new(4.0, 0.0, 0.0),
+            ]
+        );
+        grid.set_cell_cost(0, 1, 10.0);
+        assert_eq!(
+            grid.find_path_world(NavVec3::new(-0.5, 0.0, 0.0), NavVec3::new(4.5, 0.5, 0.0))
+                .unwrap()[1],
+            NavVec3::new(2.0, 5.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_free_grid_index() {
+        let size = 100isize;
+        let connections = (0..size)
+            .flat_map(|row| {
+                (0..size - 1).map(move |col| NavFreeGridConnection {
+                    from: (col * 3, row * 5),
+                    to: (col * 3 + 3, row * 5),
+                })
+            })
+            .chain((0..size - 1).map(|row| NavFreeGridConnection {
+                from: (0, row * 5),
+                to: (0, row * 5 + 5),
+            }))
+            .collect::<Vec<_>>();
+        let grid = NavFreeGrid::new(connections);
+        assert_eq!(grid.cells().len(), (size * size) as usize);
+        let index = grid.index(6, 10).unwrap();
+        assert_eq!(grid.coord(index), Some((6, 10)));
+        assert_eq!(grid.index(1, 10), None);
+        let path = grid.find_path((297, 0), (297, 495)).unwrap();
+        assert_eq!(path.len(), 99 * 3 + 1);
+
+        let serialized = serde_json::to_string(&grid).unwrap();
+        let grid = serde_json::from_str::<NavFreeGrid>(&serialized).unwrap();
+        assert_eq!(grid.index(6, 10), Some(index));
+        assert_eq!(grid.neighbors(0, 5).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_islands() {
+        let grid_a = NavGrid::new(2, 2, vec![true, true, true, false]).unwrap();
+        let grid_b = NavGrid::new(2, 2, vec![true, true, false, true]).unwrap();
+        let island_a = NavIslandPortal {
+            island: grid_a.id(),
+            portal: None,
+        };
+        let island_a_portal = NavIslandPortal {
+            island: grid_a.id(),
+            portal: Some((1, 0)),
+        };
+        let island_b_portal = NavIslandPortal {
+            island: grid_b.id(),
+            portal: Some((0, 0)),
+        };
+        let island_b = NavIslandPortal {
+            island: grid_b.id(),
+            portal: None,
+        };
+        let islands = NavIslands::new(
+            vec![
+                NavIslandsConnection {
+                    from: island_a.clone(),
+                    to: island_a_portal.clone(),
+                    distance: 1.0,
+                },
+                NavIslandsConnection {
+                    from: island_a_portal.clone(),
+                    to: island_b_portal.clone(),
+                    distance: 0.0,
+                },
+                NavIslandsConnection {
+                    from: island_b_portal.clone(),
+                    to: island_b.clone(),
+                    distance: 1.0,
+                },
+            ],
+            true,
+        );
+        let (distance, path) = islands.find_path(&island_a, &island_b).unwrap();
+        assert_eq!(
+            path,
+            vec![&island_a, &island_a_portal, &island_b_portal, &island_b]
+        );
+        assert!((distance - 2.0).abs() < 1.0e-6);
+
+        let blocked = |a: &NavIslandPortal<_, _>, b: &NavIslandPortal<_, _>| {
+            a.island == b.island || b.portal.is_none()
+        };
+        assert_eq!(
+            islands
+                .find_path_custom(&island_a, &island_b, blocked)
+                .unwrap()
+                .1
+                .len(),
+            4
+        );
+        assert!(islands
+            .find_path_excluding(&island_a, &island_b, blocked)
+            .is_none());
+    }
+
+    #[test]
+    fn test_islands_travel_time() {
+        let portal = |island: u8, portal| NavIslandPortal {
+            island,
+            portal: Some(portal),
+        };
+        // two islands connected through two pairs of portals.
+        let points = [
+            NavVec3::new(2.0, 0.0, 0.0),
+            NavVec3::new(0.0, 2.0, 0.0),
+            NavVec3::new(4.0, 0.0, 0.0),
+            NavVec3::new(0.0, 4.0, 0.0),
+        ];
+        let portal_point =
+            |portal: &NavIslandPortal<u8, u8>| points.get(portal.portal? as usize).copied();
+        let connection = |from, to, distance| NavIslandsConnection { from, to, distance };
+        let mut islands = NavIslands::new(
+            vec![
+                connection(portal(0, 0), portal(1, 2), 2.0),
+                connection(portal(0, 1), portal(1, 3), 2.0),
+            ],
+            true,
+        );
+        let speed = |island: &u8| if *island == 0 { 2.0 } else { 0.5 };
+        let start = NavVec3::new(0.0, 0.0, 0.0);
+        let goal = NavVec3::new(4.0, 1.0, 0.0);
+        assert_eq!(
+            islands.estimate_travel_time(&0, start, &1, goal, portal_point, speed),
+            Some(4_000_000)
+        );
+        assert_eq!(
+            islands.estimate_travel_time(&1, goal, &0, start, portal_point, speed),
+            Some(7_000_000)
+        );
+        assert_eq!(
+            islands.estimate_travel_time(&0, start, &0, points[0], portal_point, speed),
+            Some(1_000_000)
+        );
+        // islands with zero speed cannot be traveled.
+        assert!(islands
+            .estimate_travel_time(&0, start, &1, goal, portal_point, |island| *island
+                as Scalar)
+            .is_none());
+        assert!(islands
+            .estimate_travel_time(&0, start, &2, goal, portal_point, speed)
+            .is_none());
+
+        assert!(islands.set_portal_cost(&portal(0, 0), 4.0).is_some());
+        assert_eq!(
+            islands.estimate_travel_time(&0, start, &1, goal, portal_point, speed),
+            Some(7_000_000)
+        );
+    }
+
+    #[test]
+    fn test_islands_from_grids() {
+        let room_a = NavGrid::new(3, 3, vec![true; 9]).unwrap();
+        #[rustfmt::skip]
+        let room_b = NavGrid::new(3, 3, vec![
+            false, true, true,
+            true, true, true,
+            false, true, false,
+        ]).unwrap();
+        #[rustfmt::skip]
+        let room_c = NavGrid::new(3, 3, vec![
+            false, true, false,
+            true, true, true,
+            true, true, true,
+        ]).unwrap();
+        let builder = NavGridIslandsBuilder::default()
+            .grid(&room_a, (0, 0))
+            .grid(&room_b, (3, 0))
+            .grid(&room_c, (3, 3));
+        let connections = builder.connections();
+        assert_eq!(connections.len(), 6);
+        let portal = |grid: &NavGrid, cell| NavIslandPortal {
+            island: grid.id(),
+            portal: Some(cell),
+        };
+        let islands = builder.build();
+        let from = portal(&room_a, (2, 1));
+        let to = portal(&room_c, (1, 0));
+        let (distance, path) = islands.find_path(&from, &to).unwrap();
+        assert_eq!(
+            path,
+            vec![
+                &from,
+                &portal(&room_b, (0, 1)),
+                &portal(&room_b, (1, 2)),
+                &to
+            ]
+        );
+        assert!((distance - 4.0).abs() < 1.0e-6);
+        assert!(islands.index(&portal(&room_a, (2, 0))).is_none());
+        // crossing between grids costs as much as step between their cells.
+        let mut expensive = room_b.clone();
+        expensive.set_cell_cost(0, 1, 2.0);
+        let (distance, _) = NavGridIslandsBuilder::default()
+            .grid(&room_a, (0, 0))
+            .grid(&expensive, (3, 0))
+            .grid(&room_c, (3, 3))
+            .build()
+            .find_path(&from, &to)
+            .unwrap();
+        assert!((distance - 6.0).abs() < 1.0e-6);
+        assert_eq!(islands.find_islands().len(), 1);
+
+        let mut resolver = builder.clone();
+        let path = islands
+            .resolve_path(&from, &to, None, None, &mut resolver)
+            .unwrap();
+        assert_eq!(
+            path,
+            vec![
+                NavVec3::new(2.0, 1.0, 0.0),
+                NavVec3::new(3.0, 1.0, 0.0),
+                NavVec3::new(4.0, 1.0, 0.0),
+                NavVec3::new(4.0, 2.0, 0.0),
+                NavVec3::new(4.0, 3.0, 0.0),
+            ]
+        );
+        assert_eq!(
+            islands.resolve_path(&from, &from, None, None, &mut resolver),
+            Some(vec![NavVec3::new(2.0, 1.0, 0.0)])
+        );
+        let unknown = NavIslandPortal {
+            island: NavGridID::new(),
+            portal: Some((0, 0)),
+        };
+        assert!(islands
+            .resolve_path(&from, &unknown, None, None, &mut resolver)
+            .is_none());
+    }
+
+    #[test]
+    fn test_islands_from_mesh() {
+        let mut vertices = vec![];
+        let mut triangles = vec![];
+        for room in 0..3 {
+            let x = room as Scalar * 10.0;
+            let offset = vertices.len() as u32;
+            vertices.extend([
+                NavVec3::new(x, 0.0, 0.0),
+                NavVec3::new(x + 2.0, 0.0, 0.0),
+                NavVec3::new(x + 2.0, 2.0, 0.0),
+                NavVec3::new(x, 2.0, 0.0),
+            ]);
+            triangles.push((offset, offset + 1, offset + 2).into());
+            triangles.push((offset + 2, offset + 3, offset).into());
+        }
+        let mesh = NavMesh::new(vertices, triangles).unwrap();
+        let components = mesh.triangle_components();
+        assert_eq!(components, vec![0, 0, 1, 1, 2, 2]);
+        let link = |from: (Scalar, Scalar), to: (Scalar, Scalar), bidirectional| NavOffMeshLink {
+            from: (from.0, from.1, 0.0).into(),
+            to: (to.0, to.1, 0.0).into(),
+            cost: 1.0,
+            bidirectional,
+            kind: NavOffMeshLinkKind::Custom(0),
+        };
+        let elevator = link((2.0, 1.0), (10.0, 1.0), true);
+        let boat = link((12.0, 1.0), (20.0, 1.0), false);
+        let islands = mesh.build_islands(&[elevator, boat]);
+        assert_eq!(islands.portals().len(), 4);
+        let portal = |island, portal| NavIslandPortal {
+            island,
+            portal: Some(portal),
+        };
+        let start = portal(0, NavLinkPortal::From(0));
+        let goal = portal(2, NavLinkPortal::To(1));
+        let (distance, path) = islands.find_path(&start, &goal).unwrap();
+        assert_eq!(
+            path,
+            vec![
+                &start,
+                &portal(1, NavLinkPortal::To(0)),
+                &portal(1, NavLinkPortal::From(1)),
+                &goal
+            ]
+        );
+        // links length plus distance between links ends in the middle room, measured through
+        // centers of both its triangles.
+        let center = |x: Scalar, y: Scalar| NavVec3::new(10.0 + x, y, 0.0);
+        let inside = (center(2.0 / 3.0, 4.0 / 3.0) - NavVec3::new(10.0, 1.0, 0.0)).magnitude()
+            + (center(4.0 / 3.0, 2.0 / 3.0) - center(2.0 / 3.0, 4.0 / 3.0)).magnitude()
+            + (NavVec3::new(12.0, 1.0, 0.0) - center(4.0 / 3.0, 2.0 / 3.0)).magnitude();
+        assert!((distance - 16.0 - inside).abs() < 1.0e-4);
+        assert!(islands.find_path(&goal, &start).is_none());
+        // off-mesh links registered on nav mesh do not shorten paths inside islands.
+        let mut shortcut = mesh.clone();
+        let mut teleport = link((10.0, 1.0), (12.0, 1.0), true);
+        teleport.cost = 0.0;
+        shortcut.add_off_mesh_link(teleport).unwrap();
+        let (other, _) = shortcut
+            .build_islands(&[elevator, boat])
+            .find_path(&start, &goal)
+            .unwrap();
+        assert_eq!(other, distance);
+        assert!(islands
+            .find_path(&portal(1, NavLinkPortal::From(1)), &start)
+            .is_some());
+
+        let point = |x| NavVec3::new(x, 1.0, 0.0);
+        let links = [elevator, boat];
+        let mut resolver =
+            NavMeshIslandResolver::new(&mesh, &links, NavQuery::Accuracy, NavPathMode::Funnel);
+        assert_eq!(
+            islands.resolve_path(&start, &goal, None, None, &mut resolver),
+            Some(vec![point(2.0), point(10.0), point(12.0), point(20.0)])
+        );
+        assert_eq!(
+            islands.resolve_path(
+                &start,
+                &goal,
+                Some(point(0.5)),
+                Some(point(21.5)),
+                &mut resolver
+            ),
+            Some(vec![
+                point(0.5),
+                point(2.0),
+                point(10.0),
+                point(12.0),
+                point(20.0),
+                point(21.5)
+            ])
+        );
+
+        // components ignore off-mesh links registered on nav mesh.
+        let mut mesh = mesh;
+        mesh.add_off_mesh_link(elevator).unwrap();
+        assert_eq!(mesh.triangle_island(0), mesh.triangle_island(2));
+        assert_eq!(mesh.triangle_components(), components);
+    }
+
+    #[test]
+    fn test_islands_plan() {
+        let rooms = (0..4)
+            .map(|_| NavGrid::new(3, 1, vec![true; 3]).unwrap())
+            .collect::<Vec<_>>();
+        let mut builder = NavGridIslandsBuilder::default();
+        for (index, room) in rooms.iter().enumerate() {
+            builder = builder.grid(room, (index as isize * 3, 0));
+        }
+        let islands = builder.build();
+        let portal = |grid: &NavGrid, col| NavIslandPortal {
+            island: grid.id(),
+            portal: Some((col, 0)),
+        };
+        let point = |x: Scalar| NavVec3::new(x, 0.0, 0.0);
+        let from = portal(&rooms[0], 2);
+        let to = portal(&rooms[3], 0);
+        let mut resolver = builder.clone();
+        let mut plan = NavIslandsPlan::new(&islands, &from, &to, 2.5, &mut resolver).unwrap();
+        assert_eq!(
+            plan.near(),
+            &[point(2.0), point(3.0), point(4.0), point(5.0)]
+        );
+        assert_eq!(
+            plan.far(),
+            &[
+                portal(&rooms[2], 0),
+                portal(&rooms[2], 2),
+                portal(&rooms[3], 0)
+            ]
+        );
+        assert!(!plan.is_complete());
+
+        plan.advance(point(3.5), &mut resolver).unwrap();
+        assert_eq!(
+            plan.near(),
+            &[point(3.5), point(4.0), point(5.0), point(6.0)]
+        );
+        assert_eq!(plan.far().len(), 2);
+        plan.advance(point(6.0), &mut resolver).unwrap();
+        assert_eq!(
+            plan.near(),
+            &[point(6.0), point(7.0), point(8.0), point(9.0)]
+        );
+        assert!(plan.far().is_empty());
+        assert!(plan.is_complete());
+
+        let full = islands
+            .resolve_path(&from, &to, None, None, &mut resolver)
+            .unwrap();
+        assert_eq!(
+            full,
+            (2..=9).map(|x| point(x as Scalar)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_islands_plan_u_turn() {
+        // portals of single island with path going there and back, finding paths can be failed.
+        struct Resolver(Vec<NavVec3>, bool);
+
+        impl NavIslandResolver<u8, u8> for Resolver {
+            fn portal_point(&mut self, portal: &NavIslandPortal<u8, u8>) -> Option<NavVec3> {
+                self.0.get(portal.portal? as usize).copied()
+            }
+
+            fn find_path(&mut self, _: &u8, from: NavVec3, to: NavVec3) -> Option<Vec<NavVec3>> {
+                (!self.1).then(|| vec![from, to])
+            }
+        }
+
+        let point = |x, y| NavVec3::new(x, y, 0.0);
+        let portal = |portal| NavIslandPortal {
+            island: 0,
+            portal: Some(portal),
+        };
+        let connections = (0..3)
+            .map(|index| NavIslandsConnection {
+                from: portal(index),
+                to: portal(index + 1),
+                distance: 1.0,
+            })
+            .collect();
+        let islands = NavIslands::new(connections, false);
+        let mut resolver = Resolver(
+            vec![
+                point(0.0, 0.0),
+                point(4.0, 0.0),
+                point(4.0, 1.0),
+                point(0.0, 1.0),
+            ],
+            false,
+        );
+        let mut plan =
+            NavIslandsPlan::new(&islands, &portal(0), &portal(3), 5.0, &mut resolver).unwrap();
+        assert_eq!(plan.near().len(), 3);
+        assert!((plan.near_length() - 5.0).abs() < 1.0e-6);
+        // agent drifted closer to way back than to way there.
+        plan.advance(point(1.0, 0.6), &mut resolver).unwrap();
+        assert_eq!(
+            plan.near(),
+            &[
+                point(1.0, 0.0),
+                point(4.0, 0.0),
+                point(4.0, 1.0),
+                point(0.0, 1.0)
+            ]
+        );
+        assert!((plan.near_length() - 8.0).abs() < 1.0e-6);
+
+        // failed refinement leaves plan unchanged.
+        let mut plan =
+            NavIslandsPlan::new(&islands, &portal(0), &portal(3), 2.0, &mut resolver).unwrap();
+        let before = plan.near().to_vec();
+        resolver.1 = true;
+        assert!(plan.advance(point(3.5, 0.0), &mut resolver).is_none());
+        assert_eq!(plan.near(), before.as_slice());
+        assert!(plan
+            .advance(point(Scalar::NAN, 0.0), &mut resolver)
+            .is_none());
+    }
+
+    #[test]
+    fn test_islands_interior_costs() {
+        let portal = |island: u8, portal: Option<u8>| NavIslandPortal { island, portal };
+        let start = portal(0, None);
+        let near = portal(0, Some(1));
+        let far = portal(0, Some(2));
+        let goal = portal(1, None);
+        let connection =
+            |from: &NavIslandPortal<u8, u8>, to: &NavIslandPortal<u8, u8>, distance| {
+                NavIslandsConnection {
+                    from: from.clone(),
+                    to: to.clone(),
+                    distance,
+                }
+            };
+        let mut islands = NavIslands::new(
+            vec![
+                connection(&start, &near, 1.0),
+                connection(&start, &far, 1.0),
+                connection(&near, &goal, 1.0),
+                connection(&far, &goal, 2.0),
+            ],
+            true,
+        );
+        let (distance, path) = islands.find_path(&start, &goal).unwrap();
+        assert_eq!(path, vec![&start, &near, &goal]);
+        assert!((distance - 2.0).abs() < 1.0e-6);
+
+        // reaching near portal requires long walk around island obstacles.
+        assert!(islands.set_interior_cost(&start, &near, 5.0));
+        assert!(!islands.set_interior_cost(&near, &goal, 5.0));
+        assert_eq!(islands.interior_cost(&start, &near), Some(5.0));
+        let (distance, path) = islands.find_path(&start, &goal).unwrap();
+        assert_eq!(path, vec![&start, &far, &goal]);
+        assert!((distance - 3.0).abs() < 1.0e-6);
+
+        let (distance, path) = islands
+            .find_path_with_interior_costs(
+                &start,
+                &goal,
+                |_, _| true,
+                |_, to| (to == &far).then_some(10.0),
+            )
+            .unwrap();
+        assert_eq!(path, vec![&start, &near, &goal]);
+        assert!((distance - 7.0).abs() < 1.0e-6);
+
+        islands.clear_interior_costs();
+        assert_eq!(islands.interior_cost(&start, &near), None);
+        let serialized = serde_json::to_string(&islands).unwrap();
+        let islands = serde_json::from_str::<NavIslands<u8, u8>>(&serialized).unwrap();
+        let (_, path) = islands.find_path(&start, &goal).unwrap();
+        assert_eq!(path, vec![&start, &near, &goal]);
     }
 }
@@ -0,0 +1,144 @@
+use crate::{NavConnection, NavNet, NavResult, NavVec3, Scalar};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// Opt-in recorder of agents traversal samples (breadcrumbs) kept next to nav structure agents
+/// walk on, so actual agents movement can be compared with intended navigation. Samples of each
+/// agent (addressed by any key) form trails, which can be exported as nav net of desire paths.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mut breadcrumbs = NavBreadcrumbs::new(1.0);
+/// for agent in 0..2 {
+///     for x in 0..=4 {
+///         breadcrumbs.record(agent, (x as Scalar * 0.5, agent as Scalar * 0.1, 0.0).into());
+///     }
+/// }
+/// assert_eq!(breadcrumbs.samples_count(), 6);
+/// let net = breadcrumbs.to_nav_net().unwrap();
+/// assert_eq!(net.vertices().len(), 3);
+/// assert_eq!(net.connections().len(), 2);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavBreadcrumbs<K>
+where
+    K: Hash + Eq,
+{
+    spacing: Scalar,
+    trails: Vec<Vec<NavVec3>>,
+    // index of trail that agent currently records into.
+    active: HashMap<K, usize>,
+}
+
+impl<K> NavBreadcrumbs<K>
+where
+    K: Hash + Eq,
+{
+    /// Create recorder that records agent samples at least `spacing` apart.
+    pub fn new(spacing: Scalar) -> Self {
+        Self {
+            spacing: spacing.max(0.0),
+            trails: vec![],
+            active: Default::default(),
+        }
+    }
+
+    #[inline]
+    pub fn spacing(&self) -> Scalar {
+        self.spacing
+    }
+
+    #[inline]
+    pub fn trails(&self) -> &[Vec<NavVec3>] {
+        &self.trails
+    }
+
+    pub fn samples_count(&self) -> usize {
+        self.trails.iter().map(|trail| trail.len()).sum()
+    }
+
+    /// Record agent position. Returns `true` if it was far enough from last sample of agent
+    /// trail to be stored.
+    pub fn record(&mut self, agent: K, point: NavVec3) -> bool {
+        let index = *self.active.entry(agent).or_insert_with(|| {
+            self.trails.push(vec![]);
+            self.trails.len() - 1
+        });
+        let trail = &mut self.trails[index];
+        if let Some(last) = trail.last() {
+            if (point - *last).sqr_magnitude() < self.spacing * self.spacing {
+                return false;
+            }
+        }
+        trail.push(point);
+        true
+    }
+
+    /// Finish agent trail (i.e. when agent reached its goal or teleported), so next recorded
+    /// sample starts new trail. Returns `false` if agent was not recorded.
+    pub fn end_trail(&mut self, agent: &K) -> bool {
+        self.active.remove(agent).is_some()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.trails.clear();
+        self.active.clear();
+    }
+
+    /// Export trails as nav net, where samples closer than spacing to already exported vertice
+    /// get merged into it, and consecutive trail samples get connected.
+    pub fn to_nav_net(&self) -> NavResult<NavNet> {
+        let cell_size = self.spacing.max(Scalar::EPSILON);
+        let cell = |point: NavVec3| {
+            (
+                (point.x / cell_size).floor() as i64,
+                (point.y / cell_size).floor() as i64,
+                (point.z / cell_size).floor() as i64,
+            )
+        };
+        let mut vertices = Vec::<NavVec3>::new();
+        let mut cells = HashMap::<(i64, i64, i64), Vec<usize>>::new();
+        let mut connections = HashSet::new();
+        for trail in &self.trails {
+            let mut previous: Option<usize> = None;
+            for point in trail {
+                let (x, y, z) = cell(*point);
+                let found = (-1..=1)
+                    .flat_map(|dx| {
+                        (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz)))
+                    })
+                    .filter_map(|(dx, dy, dz)| cells.get(&(x + dx, y + dy, z + dz)))
+                    .flatten()
+                    .map(|index| (*index, (vertices[*index] - *point).sqr_magnitude()))
+                    .filter(|(_, distance)| *distance < self.spacing * self.spacing)
+                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+                let index = match found {
+                    Some((index, _)) => index,
+                    None => {
+                        vertices.push(*point);
+                        cells.entry((x, y, z)).or_default().push(vertices.len() - 1);
+                        vertices.len() - 1
+                    }
+                };
+                if let Some(previous) = previous {
+                    if previous != index {
+                        connections.insert((previous.min(index), previous.max(index)));
+                    }
+                }
+                previous = Some(index);
+            }
+        }
+        let mut connections = connections
+            .into_iter()
+            .map(|(a, b)| NavConnection(a as u32, b as u32))
+            .collect::<Vec<_>>();
+        connections.sort_by_key(|connection| (connection.0, connection.1));
+        NavNet::new(vertices, connections)
+    }
+}
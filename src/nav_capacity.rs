@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, hash::Hash};
+
+/// Capacity limits of connections (max number of agents using them at once) together with their
+/// current users count. Connections are addressed by any key, i.e. `NavConnection` of triangles
+/// sharing nav mesh portal, `NavOffMeshLinkID` of off-mesh link or pair of nav islands portals
+/// indices. Connections without limit have unlimited capacity.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mut capacity = NavCapacity::default();
+/// let bridge = NavConnection(0, 1);
+/// capacity.set_limit(bridge, Some(1));
+/// assert!(capacity.check_in(NavConnection(1, 0)));
+/// assert!(capacity.is_full(&bridge));
+/// assert!(!capacity.check_in(bridge));
+/// assert!(capacity.check_in(NavConnection(1, 2)));
+/// assert!(capacity.check_out(&bridge));
+/// assert_eq!(capacity.available(&bridge), Some(1));
+/// assert_eq!(capacity.available(&NavConnection(1, 2)), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NavCapacity<K>
+where
+    K: Hash + Eq,
+{
+    limits: HashMap<K, usize>,
+    users: HashMap<K, usize>,
+}
+
+impl<K> Default for NavCapacity<K>
+where
+    K: Hash + Eq,
+{
+    fn default() -> Self {
+        Self {
+            limits: Default::default(),
+            users: Default::default(),
+        }
+    }
+}
+
+impl<K> NavCapacity<K>
+where
+    K: Hash + Eq,
+{
+    /// Set max users count of connection, `None` makes it unlimited. Returns old limit.
+    pub fn set_limit(&mut self, key: K, limit: Option<usize>) -> Option<usize> {
+        match limit {
+            Some(limit) => self.limits.insert(key, limit),
+            None => self.limits.remove(&key),
+        }
+    }
+
+    #[inline]
+    pub fn limit(&self, key: &K) -> Option<usize> {
+        self.limits.get(key).copied()
+    }
+
+    #[inline]
+    pub fn users(&self, key: &K) -> usize {
+        self.users.get(key).copied().unwrap_or(0)
+    }
+
+    /// Number of users that can still check in, `None` if connection is unlimited.
+    pub fn available(&self, key: &K) -> Option<usize> {
+        self.limit(key)
+            .map(|limit| limit.saturating_sub(self.users(key)))
+    }
+
+    #[inline]
+    pub fn is_full(&self, key: &K) -> bool {
+        self.available(key) == Some(0)
+    }
+
+    /// Register user of connection. Returns `false` (and does not register) if connection is
+    /// full.
+    pub fn check_in(&mut self, key: K) -> bool {
+        if self.is_full(&key) {
+            return false;
+        }
+        *self.users.entry(key).or_default() += 1;
+        true
+    }
+
+    /// Unregister user of connection. Returns `false` if connection had no users.
+    pub fn check_out(&mut self, key: &K) -> bool {
+        match self.users.get_mut(key) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.users.remove(key);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Iterator over used connections and their users count.
+    pub fn used(&self) -> impl Iterator<Item = (&K, usize)> + '_ {
+        self.users.iter().map(|(key, count)| (key, *count))
+    }
+
+    /// Unregister all users, keeping limits.
+    #[inline]
+    pub fn clear_users(&mut self) {
+        self.users.clear();
+    }
+}
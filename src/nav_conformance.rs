@@ -0,0 +1,400 @@
+use crate::{NavMesh, NavPathMode, NavQuery, NavResult, NavVec3, Scalar};
+
+/// Distance under which conformance check points are treated as the same.
+pub const NAV_CONFORMANCE_TOLERANCE: Scalar = 1.0e-3;
+
+/// Single query of conformance map together with its known-correct outcome.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NavConformanceScenario {
+    /// Snapping point on nav mesh gives `expected` point, `None` if point can not be snapped.
+    Snap {
+        point: NavVec3,
+        expected: Option<NavVec3>,
+    },
+    /// Shortest path between points has `expected` length, `None` if points are not connected.
+    Path {
+        from: NavVec3,
+        to: NavVec3,
+        expected: Option<Scalar>,
+    },
+    /// Following path between points keeps agent on nav mesh and ends at target.
+    Follow { from: NavVec3, to: NavVec3 },
+}
+
+/// Generated reference nav mesh with scenarios to validate navigation integration against.
+#[derive(Debug, Clone)]
+pub struct NavConformanceMap {
+    pub name: &'static str,
+    pub mesh: NavMesh,
+    pub scenarios: Vec<NavConformanceScenario>,
+}
+
+impl NavConformanceMap {
+    /// All reference maps.
+    pub fn all() -> NavResult<Vec<Self>> {
+        Ok(vec![
+            Self::open_room()?,
+            Self::door()?,
+            Self::islands()?,
+            Self::ramp()?,
+        ])
+    }
+
+    /// Single 4x4 square room.
+    pub fn open_room() -> NavResult<Self> {
+        let mesh = NavMesh::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (4.0, 0.0, 0.0).into(),
+                (4.0, 4.0, 0.0).into(),
+                (0.0, 4.0, 0.0).into(),
+            ],
+            vec![(0, 1, 2).into(), (2, 3, 0).into()],
+        )?;
+        Ok(Self {
+            name: "open_room",
+            mesh,
+            scenarios: vec![
+                NavConformanceScenario::Snap {
+                    point: (2.0, 2.0, 0.5).into(),
+                    expected: Some((2.0, 2.0, 0.0).into()),
+                },
+                NavConformanceScenario::Snap {
+                    point: (4.5, 2.0, 0.0).into(),
+                    expected: Some((4.0, 2.0, 0.0).into()),
+                },
+                NavConformanceScenario::Path {
+                    from: (0.5, 0.5, 0.0).into(),
+                    to: (3.5, 3.5, 0.0).into(),
+                    expected: Some(4.242_640_7),
+                },
+                NavConformanceScenario::Follow {
+                    from: (0.5, 3.5, 0.0).into(),
+                    to: (3.5, 0.5, 0.0).into(),
+                },
+            ],
+        })
+    }
+
+    /// Bottom and top areas connected by wide room on the left and narrow door on the right.
+    pub fn door() -> NavResult<Self> {
+        let mesh = NavMesh::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (4.0, 0.0, 0.0).into(),
+                (4.0, 1.0, 0.0).into(),
+                (0.0, 1.0, 0.0).into(),
+                (0.0, 2.0, 0.0).into(),
+                (2.0, 2.0, 0.0).into(),
+                (2.0, 1.0, 0.0).into(),
+                (3.8, 1.0, 0.0).into(),
+                (3.8, 2.0, 0.0).into(),
+                (4.0, 2.0, 0.0).into(),
+                (0.0, 3.0, 0.0).into(),
+                (4.0, 3.0, 0.0).into(),
+            ],
+            vec![
+                (0, 1, 7).into(),
+                (1, 2, 7).into(),
+                (0, 7, 6).into(),
+                (0, 6, 3).into(),
+                (3, 6, 5).into(),
+                (5, 4, 3).into(),
+                (7, 2, 9).into(),
+                (9, 8, 7).into(),
+                (10, 4, 5).into(),
+                (10, 5, 8).into(),
+                (10, 8, 11).into(),
+                (8, 9, 11).into(),
+            ],
+        )?;
+        Ok(Self {
+            name: "door",
+            mesh,
+            scenarios: vec![
+                NavConformanceScenario::Snap {
+                    point: (3.0, 1.4, 0.0).into(),
+                    expected: Some((3.0, 1.0, 0.0).into()),
+                },
+                NavConformanceScenario::Path {
+                    from: (3.9, 0.5, 0.0).into(),
+                    to: (3.9, 2.5, 0.0).into(),
+                    expected: Some(2.0),
+                },
+                NavConformanceScenario::Path {
+                    from: (3.0, 0.5, 0.0).into(),
+                    to: (3.0, 2.5, 0.0).into(),
+                    expected: Some(2.886_796),
+                },
+                NavConformanceScenario::Follow {
+                    from: (0.5, 0.5, 0.0).into(),
+                    to: (3.5, 2.5, 0.0).into(),
+                },
+            ],
+        })
+    }
+
+    /// Two separate platforms.
+    pub fn islands() -> NavResult<Self> {
+        let mesh = NavMesh::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (2.0, 0.0, 0.0).into(),
+                (2.0, 2.0, 0.0).into(),
+                (0.0, 2.0, 0.0).into(),
+                (4.0, 0.0, 0.0).into(),
+                (6.0, 0.0, 0.0).into(),
+                (6.0, 2.0, 0.0).into(),
+                (4.0, 2.0, 0.0).into(),
+            ],
+            vec![
+                (0, 1, 2).into(),
+                (2, 3, 0).into(),
+                (4, 5, 6).into(),
+                (6, 7, 4).into(),
+            ],
+        )?;
+        Ok(Self {
+            name: "islands",
+            mesh,
+            scenarios: vec![
+                NavConformanceScenario::Snap {
+                    point: (3.5, 1.0, 0.0).into(),
+                    expected: Some((4.0, 1.0, 0.0).into()),
+                },
+                NavConformanceScenario::Path {
+                    from: (1.0, 1.0, 0.0).into(),
+                    to: (5.0, 1.0, 0.0).into(),
+                    expected: None,
+                },
+                NavConformanceScenario::Path {
+                    from: (4.5, 0.5, 0.0).into(),
+                    to: (5.5, 1.0, 0.0).into(),
+                    expected: Some(1.118_034),
+                },
+            ],
+        })
+    }
+
+    /// Flat floor going into ramp rising along X axis.
+    pub fn ramp() -> NavResult<Self> {
+        let mesh = NavMesh::new(
+            vec![
+                (0.0, 0.0, 0.0).into(),
+                (1.0, 0.0, 0.0).into(),
+                (1.0, 1.0, 0.0).into(),
+                (0.0, 1.0, 0.0).into(),
+                (2.0, 0.0, 1.0).into(),
+                (2.0, 1.0, 1.0).into(),
+            ],
+            vec![
+                (0, 1, 2).into(),
+                (2, 3, 0).into(),
+                (1, 4, 5).into(),
+                (5, 2, 1).into(),
+            ],
+        )?;
+        Ok(Self {
+            name: "ramp",
+            mesh,
+            scenarios: vec![
+                NavConformanceScenario::Snap {
+                    point: (1.5, 0.5, 1.0).into(),
+                    expected: Some((1.75, 0.5, 0.75).into()),
+                },
+                NavConformanceScenario::Path {
+                    from: (0.5, 0.5, 0.0).into(),
+                    to: (1.5, 0.5, 0.5).into(),
+                    expected: Some(1.207_106_8),
+                },
+                NavConformanceScenario::Follow {
+                    from: (0.25, 0.25, 0.0).into(),
+                    to: (1.75, 0.75, 0.75).into(),
+                },
+            ],
+        })
+    }
+}
+
+/// Navigation integration validated by conformance runner. Default implementations use nav
+/// mesh queries directly, so override the ones your integration replaces or wraps.
+pub trait NavConformanceAgent {
+    /// Snap point on nav mesh.
+    fn snap(&mut self, mesh: &NavMesh, point: NavVec3) -> Option<NavVec3> {
+        mesh.closest_point(point, NavQuery::Accuracy)
+    }
+
+    /// Find path points between points.
+    fn find_path(&mut self, mesh: &NavMesh, from: NavVec3, to: NavVec3) -> Option<Vec<NavVec3>> {
+        mesh.find_path(from, to, NavQuery::Accuracy, NavPathMode::Funnel)
+    }
+
+    /// Follow path and give positions agent went through.
+    fn follow(&mut self, _mesh: &NavMesh, path: &[NavVec3]) -> Vec<NavVec3> {
+        let mut result = path.first().copied().into_iter().collect::<Vec<_>>();
+        for pair in path.windows(2) {
+            let steps = ((pair[1] - pair[0]).magnitude() / 0.1).ceil().max(1.0) as usize;
+            result.extend(
+                (1..=steps).map(|step| pair[0].lerp(pair[1], step as Scalar / steps as Scalar)),
+            );
+        }
+        result
+    }
+}
+
+/// Agent using only default nav mesh queries.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NavDefaultConformanceAgent;
+
+impl NavConformanceAgent for NavDefaultConformanceAgent {}
+
+/// Scenario that agent did not pass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavConformanceFailure {
+    /// Name of map.
+    pub map: &'static str,
+    /// Index of scenario in map.
+    pub scenario: usize,
+    /// Description of mismatch.
+    pub reason: String,
+}
+
+/// Result of running conformance scenarios.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NavConformanceReport {
+    /// Number of scenarios run.
+    pub scenarios_count: usize,
+    pub failures: Vec<NavConformanceFailure>,
+}
+
+impl NavConformanceReport {
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Run scenarios of all reference maps against agent.
+pub fn run_conformance<A>(agent: &mut A) -> NavResult<NavConformanceReport>
+where
+    A: NavConformanceAgent,
+{
+    let mut report = NavConformanceReport::default();
+    for map in NavConformanceMap::all()? {
+        run_conformance_map(agent, &map, &mut report);
+    }
+    Ok(report)
+}
+
+/// Run scenarios of single map against agent, appending results to report.
+pub fn run_conformance_map<A>(
+    agent: &mut A,
+    map: &NavConformanceMap,
+    report: &mut NavConformanceReport,
+) where
+    A: NavConformanceAgent,
+{
+    for (index, scenario) in map.scenarios.iter().enumerate() {
+        report.scenarios_count += 1;
+        if let Err(reason) = run_scenario(agent, &map.mesh, scenario) {
+            report.failures.push(NavConformanceFailure {
+                map: map.name,
+                scenario: index,
+                reason,
+            });
+        }
+    }
+}
+
+fn run_scenario<A>(
+    agent: &mut A,
+    mesh: &NavMesh,
+    scenario: &NavConformanceScenario,
+) -> Result<(), String>
+where
+    A: NavConformanceAgent,
+{
+    match *scenario {
+        NavConformanceScenario::Snap { point, expected } => {
+            let result = agent.snap(mesh, point);
+            match (result, expected) {
+                (None, None) => Ok(()),
+                (Some(result), Some(expected)) if is_same(result, expected) => Ok(()),
+                _ => Err(format!("snapped to {:?}, expected {:?}", result, expected)),
+            }
+        }
+        NavConformanceScenario::Path { from, to, expected } => {
+            let path = agent.find_path(mesh, from, to);
+            match (path, expected) {
+                (None, None) => Ok(()),
+                (Some(path), Some(expected)) => {
+                    check_path(mesh, &path, from, to)?;
+                    let length = path_length(&path);
+                    if (length - expected).abs() <= NAV_CONFORMANCE_TOLERANCE * expected.max(1.0) {
+                        Ok(())
+                    } else {
+                        Err(format!("path length is {}, expected {}", length, expected))
+                    }
+                }
+                (path, _) => Err(format!(
+                    "found path {:?}, expected length {:?}",
+                    path, expected
+                )),
+            }
+        }
+        NavConformanceScenario::Follow { from, to } => {
+            let path = agent
+                .find_path(mesh, from, to)
+                .ok_or_else(|| "path to follow was not found".to_owned())?;
+            let positions = agent.follow(mesh, &path);
+            if let Some(position) = positions
+                .iter()
+                .find(|position| !is_on_mesh(mesh, **position))
+            {
+                return Err(format!("agent left nav mesh at {:?}", position));
+            }
+            match positions.last() {
+                Some(last) if is_same(*last, to) => Ok(()),
+                last => Err(format!("agent stopped at {:?}, expected {:?}", last, to)),
+            }
+        }
+    }
+}
+
+// path has to connect query points and go along nav mesh surface.
+fn check_path(mesh: &NavMesh, path: &[NavVec3], from: NavVec3, to: NavVec3) -> Result<(), String> {
+    match (path.first(), path.last()) {
+        (Some(first), Some(last)) if is_same(*first, from) && is_same(*last, to) => {}
+        _ => {
+            return Err(format!(
+                "path {:?} does not connect {:?} with {:?}",
+                path, from, to
+            ))
+        }
+    }
+    for pair in path.windows(2) {
+        let steps = ((pair[1] - pair[0]).magnitude() / 0.1).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let point = pair[0].lerp(pair[1], step as Scalar / steps as Scalar);
+            if !is_on_mesh(mesh, point) {
+                return Err(format!("path leaves nav mesh at {:?}", point));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn path_length(path: &[NavVec3]) -> Scalar {
+    path.windows(2)
+        .map(|pair| (pair[1] - pair[0]).magnitude())
+        .sum()
+}
+
+fn is_on_mesh(mesh: &NavMesh, point: NavVec3) -> bool {
+    mesh.contains_point(point, NAV_CONFORMANCE_TOLERANCE, NAV_CONFORMANCE_TOLERANCE)
+        .is_some()
+}
+
+fn is_same(a: NavVec3, b: NavVec3) -> bool {
+    (a - b).magnitude() <= NAV_CONFORMANCE_TOLERANCE
+}
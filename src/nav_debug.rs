@@ -0,0 +1,72 @@
+use crate::{NavMeshID, NavPath, NavVec3};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+/// Debug frame describing single step of navigation query.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NavDebugFrame {
+    /// Query end points snapped to nav mesh.
+    SnappedEndpoints {
+        mesh: NavMeshID,
+        from: NavVec3,
+        from_triangle: usize,
+        to: NavVec3,
+        to_triangle: usize,
+    },
+    /// Triangles corridor found between query end points.
+    Corridor {
+        mesh: NavMeshID,
+        triangles: Vec<usize>,
+    },
+    /// Points produced by funnel algorithm, where inner points are funnel apexes.
+    FunnelApexes {
+        mesh: NavMeshID,
+        points: Vec<NavVec3>,
+    },
+    /// Final query path.
+    Path { mesh: NavMeshID, path: NavPath },
+}
+
+/// Receiver of navigation query debug frames, i.e. connection to external visual debugger.
+pub trait NavDebugSink: Send + Sync {
+    fn receive(&self, frame: NavDebugFrame);
+}
+
+impl<F> NavDebugSink for F
+where
+    F: Fn(NavDebugFrame) + Send + Sync,
+{
+    fn receive(&self, frame: NavDebugFrame) {
+        (self)(frame)
+    }
+}
+
+static SINK: RwLock<Option<Arc<dyn NavDebugSink>>> = RwLock::new(None);
+
+/// Set global sink that receives debug frames of all navigation queries, or remove it with
+/// `None`. Returns previous sink.
+pub fn set_debug_sink(sink: Option<Arc<dyn NavDebugSink>>) -> Option<Arc<dyn NavDebugSink>> {
+    match SINK.write() {
+        Ok(mut current) => std::mem::replace(&mut *current, sink),
+        Err(_) => None,
+    }
+}
+
+/// Tells if debug sink is set.
+pub fn has_debug_sink() -> bool {
+    SINK.read().map(|sink| sink.is_some()).unwrap_or(false)
+}
+
+// frame is built only when there is sink to receive it.
+pub(crate) fn emit<F>(frame: F)
+where
+    F: FnOnce() -> NavDebugFrame,
+{
+    let sink = match SINK.read() {
+        Ok(sink) => sink.clone(),
+        Err(_) => return,
+    };
+    if let Some(sink) = sink {
+        sink.receive(frame());
+    }
+}
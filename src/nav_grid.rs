@@ -1,16 +1,17 @@
-use crate::{Error, NavResult, Scalar};
-use petgraph::{
-    algo::{astar, tarjan_scc},
-    graph::NodeIndex,
-    visit::EdgeRef,
-    Directed, Graph, Undirected,
+use crate::{
+    nav_search::{astar, astar_keyed, integrate_chunked, NavSearchItem},
+    Error, NavInfluenceMap, NavIslandPortal, NavIslandResolver, NavIslands, NavIslandsConnection,
+    NavOccupancy, NavResult, NavVec3, Scalar, SCALAR_MAX,
 };
+use petgraph::{algo::tarjan_scc, graph::NodeIndex, Directed, Direction, Graph, Undirected};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
-#[cfg(not(feature = "scalar64"))]
-use std::f32::MAX as SCALAR_MAX;
-#[cfg(feature = "scalar64")]
-use std::f64::MAX as SCALAR_MAX;
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+    sync::OnceLock,
+};
 use typid::ID;
 
 #[cfg(feature = "parallel")]
@@ -35,6 +36,118 @@ pub struct NavGridConnection {
 /// Nav grid identifier.
 pub type NavGridID = ID<NavGrid>;
 
+/// Nav grid door identifier.
+pub type NavGridDoorID = ID<NavGridDoor>;
+
+/// Door placed on nav grid cell. Closed door cell cannot be entered by searches, unless agent
+/// is able to open it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NavGridDoor {
+    pub cell: (usize, usize),
+    pub open: bool,
+    /// Key required to open closed door, used by agents filters.
+    #[serde(default)]
+    pub lock: Option<u64>,
+}
+
+// cost multiplier of moving between diagonal neighbor cells.
+const DIAGONAL_COST: Scalar = std::f64::consts::SQRT_2 as Scalar;
+// rows count of grid chunks integrated independently by flow fields and distance maps.
+const INTEGRATE_CHUNK_ROWS: usize = 64;
+
+/// Which neighbor cells are connected when building nav grid from walkable cells.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavGridConnectivity {
+    /// Cells connect with left, right, up and down neighbors.
+    #[default]
+    Four,
+    /// Cells additionally connect with diagonal neighbors (with `sqrt(2)` cost multiplier),
+    /// unless any of two cells sharing that corner is unwalkable.
+    Eight,
+}
+
+/// Algorithm used to find path on nav grid.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavGridSearch {
+    /// A* search from start cell towards goal cell.
+    #[default]
+    AStar,
+    /// A* searches from both start and goal cells meeting in the middle, usually expanding about
+    /// half as many cells on long paths across large grids.
+    Bidirectional,
+}
+
+/// Direction on nav grid, where `Up` goes towards lower rows and `Left` towards lower cols.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NavGridDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl NavGridDirection {
+    pub const ALL: [Self; 4] = [Self::Left, Self::Right, Self::Up, Self::Down];
+
+    /// (col, row) offset of neighbor cell in this direction.
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Self::Left => (-1, 0),
+            Self::Right => (1, 0),
+            Self::Up => (0, -1),
+            Self::Down => (0, 1),
+        }
+    }
+
+    #[inline]
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Walkable cell next to unwalkable cells that give cover from some directions.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NavGridCoverPoint {
+    pub cell: (usize, usize),
+    /// Bit mask of directions with unwalkable neighbor cell.
+    pub directions: u8,
+}
+
+impl NavGridCoverPoint {
+    /// Tells if this point gives cover from threat located in given direction.
+    #[inline]
+    pub fn is_covered_from(&self, direction: NavGridDirection) -> bool {
+        self.directions & direction.bit() != 0
+    }
+}
+
+/// Snapshot of nav grid state that changes at runtime (cells walkability, cells and connections
+/// costs, doors), serialized separately from baked nav grid, i.e. into save games.
+///
+/// `NavGrid::set_cell` drops connection costs of cell made unwalkable and does not bring them
+/// back when it becomes walkable again, so snapshot holds only connection costs present at the
+/// moment of capture and restoring it brings back exactly these.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavGridDynamicState {
+    pub cells: Vec<bool>,
+    pub costs: Vec<Scalar>,
+    pub connection_costs: HashMap<usize, HashMap<usize, Scalar>>,
+    pub doors: Vec<(NavGridDoorID, NavGridDoor)>,
+}
+
+/// Statistics of nav grid cells island.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavGridIslandStats {
+    pub cells_count: usize,
+    /// Sum of island cells costs.
+    pub total_cost: Scalar,
+    /// (col, row) of bounding box top-left cell.
+    pub bounds_min: (usize, usize),
+    /// (col, row) of bounding box bottom-right cell.
+    pub bounds_max: (usize, usize),
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct NavGrid {
     id: NavGridID,
@@ -45,10 +158,90 @@ pub struct NavGrid {
     graph: Graph<(), (), Directed>,
     nodes: Vec<Option<NodeIndex>>,
     nodes_map: HashMap<NodeIndex, usize>,
+    // longest Manhattan distance between connected cells, zero disables search heuristic.
+    #[serde(default)]
+    max_step: usize,
+    #[serde(default)]
+    connectivity: NavGridConnectivity,
+    #[serde(default)]
+    doors: Vec<(NavGridDoorID, NavGridDoor)>,
+    // cell index of each door.
+    #[serde(default)]
+    door_cells: HashMap<usize, NavGridDoorID>,
+    // costs of connections between cells indices (from -> to -> cost), missing ones cost 1.
+    #[serde(default)]
+    connection_costs: HashMap<usize, HashMap<usize, Scalar>>,
+    // bounding box (min col, min row, max col, max row) of goals reached optimally through each
+    // connection of each cell (cell index -> next cell index -> box), empty if not built.
+    #[serde(default)]
+    goal_bounds: Vec<HashMap<usize, (usize, usize, usize, usize)>>,
+    // connections (from cell index, to cell index) of grid made of explicit connections, these
+    // get restored when cell becomes walkable again. `None` for grids connecting neighbor cells.
+    #[serde(default)]
+    connections: Option<Vec<(usize, usize)>>,
+    // lowest cost of walkable cell, computed on first use after cells or their costs change.
+    #[serde(skip)]
+    min_cell_cost: OnceLock<Scalar>,
+    // lowest cost of connection, computed on first use after connections costs change.
+    #[serde(skip)]
+    min_connection_cost: OnceLock<Scalar>,
 }
 
 impl NavGrid {
     pub fn new(cols: usize, rows: usize, cells: Vec<bool>) -> NavResult<Self> {
+        Self::with_connectivity(cols, rows, cells, NavGridConnectivity::Four)
+    }
+
+    /// Create nav grid by asking for walkability of each cell by its column and row.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let grid = NavGrid::from_fn(3, 2, |col, row| col != 1 || row != 0).unwrap();
+    /// assert_eq!(grid.cells(), &[true, false, true, true, true, true]);
+    /// ```
+    pub fn from_fn<F>(cols: usize, rows: usize, mut f: F) -> NavResult<Self>
+    where
+        F: FnMut(usize, usize) -> bool,
+    {
+        let cells = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .map(|(col, row)| f(col, row))
+            .collect();
+        Self::new(cols, rows, cells)
+    }
+
+    /// Create nav grid from row-major tilemap (i.e. tile ids or bitmasks), where `walkable`
+    /// tells if tile can be walked on.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// const WALL: u8 = 1 << 0;
+    /// const WATER: u8 = 1 << 1;
+    ///
+    /// let tiles = [
+    ///     0, WALL, 0, //
+    ///     0, WATER, 0, //
+    /// ];
+    /// let grid = NavGrid::from_tiles(3, 2, &tiles, |tile| tile & (WALL | WATER) == 0).unwrap();
+    /// assert_eq!(grid.find_path((0, 0), (2, 0)), None);
+    /// ```
+    pub fn from_tiles<T, F>(cols: usize, rows: usize, tiles: &[T], walkable: F) -> NavResult<Self>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        Self::new(cols, rows, tiles.iter().map(walkable).collect())
+    }
+
+    pub fn with_connectivity(
+        cols: usize,
+        rows: usize,
+        cells: Vec<bool>,
+        connectivity: NavGridConnectivity,
+    ) -> NavResult<Self> {
         if cols == 0 || rows == 0 {
             return Err(Error::EmptyCells(cols, rows));
         }
@@ -96,6 +289,29 @@ impl NavGrid {
                 }
             }
         }
+        if connectivity == NavGridConnectivity::Eight {
+            for c in 0..(cols - 1) {
+                for r in 0..(rows - 1) {
+                    let top_left = r * cols + c;
+                    let top_right = top_left + 1;
+                    let bottom_left = top_left + cols;
+                    let bottom_right = bottom_left + 1;
+                    if let (Some(na), Some(nb), Some(_), Some(_)) = (
+                        nodes[top_left],
+                        nodes[bottom_right],
+                        nodes[top_right],
+                        nodes[bottom_left],
+                    ) {
+                        graph.add_edge(na, nb, ());
+                        graph.add_edge(nb, na, ());
+                        let na = nodes[top_right].unwrap();
+                        let nb = nodes[bottom_left].unwrap();
+                        graph.add_edge(na, nb, ());
+                        graph.add_edge(nb, na, ());
+                    }
+                }
+            }
+        }
         let nodes_map = iter!(nodes)
             .enumerate()
             .filter_map(|(i, n)| n.map(|n| (n, i)))
@@ -109,6 +325,15 @@ impl NavGrid {
             graph,
             nodes,
             nodes_map,
+            max_step: 1,
+            connectivity,
+            doors: vec![],
+            door_cells: Default::default(),
+            connection_costs: Default::default(),
+            goal_bounds: vec![],
+            connections: None,
+            min_cell_cost: Default::default(),
+            min_connection_cost: Default::default(),
         })
     }
 
@@ -152,9 +377,16 @@ impl NavGrid {
                 }
             })
             .collect::<Vec<_>>();
-        for connection in connections {
-            let ia = connection.from.1 * cols + connection.from.0;
-            let ib = connection.to.1 * cols + connection.to.0;
+        let max_step = connections
+            .iter()
+            .map(|c| c.from.0.abs_diff(c.to.0) + c.from.1.abs_diff(c.to.1))
+            .max()
+            .unwrap_or(0);
+        let indices = connections
+            .iter()
+            .map(|c| (c.from.1 * cols + c.from.0, c.to.1 * cols + c.to.0))
+            .collect::<Vec<_>>();
+        for (ia, ib) in indices.iter().copied() {
             if let (Some(na), Some(nb)) = (nodes[ia], nodes[ib]) {
                 graph.add_edge(na, nb, ());
             }
@@ -172,6 +404,15 @@ impl NavGrid {
             graph,
             nodes,
             nodes_map,
+            max_step,
+            connectivity: NavGridConnectivity::Four,
+            doors: vec![],
+            door_cells: Default::default(),
+            connection_costs: Default::default(),
+            goal_bounds: vec![],
+            connections: Some(indices),
+            min_cell_cost: Default::default(),
+            min_connection_cost: Default::default(),
         })
     }
 
@@ -180,6 +421,11 @@ impl NavGrid {
         self.id
     }
 
+    #[inline]
+    pub fn connectivity(&self) -> NavGridConnectivity {
+        self.connectivity
+    }
+
     #[inline]
     pub fn cells(&self) -> &[bool] {
         &self.cells
@@ -196,184 +442,282 @@ impl NavGrid {
         let c = self.costs.get_mut(index)?;
         let old = *c;
         *c = cost.max(0.0);
+        if *c != old {
+            self.goal_bounds.clear();
+            self.min_cell_cost.take();
+        }
         Some(old)
     }
 
-    pub fn neighbors(
-        &self,
-        col: usize,
-        row: usize,
-    ) -> Option<impl Iterator<Item = (usize, usize)> + '_> {
-        let index = self.index(col, row)?;
-        let node = self.nodes[index]?;
-        Some(self.graph.neighbors(node).filter_map(|node| {
-            self.nodes_map
-                .get(&node)
-                .and_then(|index| self.coord(*index))
-        }))
-    }
-
-    pub fn find_path(
-        &self,
+    /// Set cost multiplier of moving from one cell to its connected neighbor, i.e. to make
+    /// climbing over low wall more expensive than walking between the same cells other way.
+    /// Connection is directed so both directions have to be set separately. Returns previous
+    /// cost or `None` if cells are not connected.
+    pub fn set_connection_cost(
+        &mut self,
         from: (usize, usize),
         to: (usize, usize),
-    ) -> Option<Vec<(usize, usize)>> {
-        self.find_path_custom(from, to, |_, _| true)
+        cost: Scalar,
+    ) -> Option<Scalar> {
+        let old = self.connection_cost(from, to)?;
+        let from = self.index(from.0, from.1)?;
+        let to = self.index(to.0, to.1)?;
+        self.goal_bounds.clear();
+        self.min_connection_cost.take();
+        if cost == 1.0 {
+            if let Some(costs) = self.connection_costs.get_mut(&from) {
+                costs.remove(&to);
+                if costs.is_empty() {
+                    self.connection_costs.remove(&from);
+                }
+            }
+        } else {
+            self.connection_costs
+                .entry(from)
+                .or_default()
+                .insert(to, cost.max(0.0));
+        }
+        Some(old)
     }
 
-    // filter params: first col-row, second col-row.
-    pub fn find_path_custom<F>(
-        &self,
-        from: (usize, usize),
-        to: (usize, usize),
-        mut filter: F,
-    ) -> Option<Vec<(usize, usize)>>
-    where
-        F: FnMut((usize, usize), (usize, usize)) -> bool,
-    {
-        let start_index = self.index(from.0, from.1)?;
-        let end_index = self.index(to.0, to.1)?;
-        let start_node = (*self.nodes.get(start_index)?)?;
-        let end_node = (*self.nodes.get(end_index)?)?;
-        let nodes = astar(
-            &self.graph,
-            start_node,
-            |n| n == end_node,
-            |e| {
-                let a = self.nodes_map[&e.source()];
-                let b = self.nodes_map[&e.target()];
-                if filter(self.coord(a).unwrap(), self.coord(b).unwrap()) {
-                    let a = self.costs[a];
-                    let b = self.costs[b];
-                    a * b
-                } else {
-                    SCALAR_MAX
-                }
-            },
-            |_| 0.0,
-        )?
-        .1;
-        Some(
-            nodes
-                .into_iter()
-                .filter_map(|n| self.coord(self.nodes_map[&n]))
-                .collect::<Vec<_>>(),
-        )
+    /// Cost multiplier of moving from one cell to its connected neighbor, `None` if cells are
+    /// not connected.
+    pub fn connection_cost(&self, from: (usize, usize), to: (usize, usize)) -> Option<Scalar> {
+        let from = self.index(from.0, from.1)?;
+        let to = self.index(to.0, to.1)?;
+        let (a, b) = (self.nodes[from]?, self.nodes[to]?);
+        self.graph.find_edge(a, b)?;
+        Some(self.connection_cost_between(from, to))
     }
 
-    pub fn find_islands(&self) -> Vec<Vec<(usize, usize)>> {
-        tarjan_scc(&self.graph)
-            .into_iter()
-            .map(|v| {
-                v.into_iter()
-                    .filter_map(|n| self.nodes_map.get(&n).and_then(|i| self.coord(*i)))
-                    .collect::<Vec<_>>()
-            })
-            .filter(|v| !v.is_empty())
-            .collect()
+    #[inline]
+    fn connection_cost_between(&self, from: usize, to: usize) -> Scalar {
+        self.connection_costs
+            .get(&from)
+            .and_then(|costs| costs.get(&to))
+            .copied()
+            .unwrap_or(1.0)
     }
 
-    pub fn index(&self, col: usize, row: usize) -> Option<usize> {
-        if col < self.cols && row < self.rows {
-            Some(row * self.cols + col)
-        } else {
-            None
+    /// Change cell walkability, updating connections of that cell in place instead of
+    /// rebuilding whole grid. Cell made walkable connects with its walkable neighbors according
+    /// to grid connectivity, or for grids made with `NavGrid::with_connections` gets back only
+    /// these of its original connections that lead to walkable cells. Making cell unwalkable
+    /// drops costs of all its connections (set with `NavGrid::set_connection_cost`) and they are
+    /// not restored when cell becomes walkable again - restored connections cost 1. Returns
+    /// previous walkability.
+    pub fn set_cell(&mut self, col: usize, row: usize, walkable: bool) -> Option<bool> {
+        let index = self.index(col, row)?;
+        let old = self.cells[index];
+        if old == walkable {
+            return Some(old);
+        }
+        self.goal_bounds.clear();
+        self.min_cell_cost.take();
+        self.cells[index] = walkable;
+        if walkable {
+            let node = self.graph.add_node(());
+            self.nodes[index] = Some(node);
+            self.nodes_map.insert(node, index);
+            if let Some(connections) = &self.connections {
+                for (from, to) in connections.iter().copied() {
+                    if from != index && to != index {
+                        continue;
+                    }
+                    if let (Some(na), Some(nb)) = (self.nodes[from], self.nodes[to]) {
+                        self.graph.add_edge(na, nb, ());
+                    }
+                }
+            } else {
+                let neighbors = [
+                    (col.wrapping_sub(1), row),
+                    (col + 1, row),
+                    (col, row.wrapping_sub(1)),
+                    (col, row + 1),
+                ];
+                for (c, r) in neighbors {
+                    if let Some(other) = self.index(c, r).and_then(|i| self.nodes[i]) {
+                        self.graph.add_edge(node, other, ());
+                        self.graph.add_edge(other, node, ());
+                    }
+                }
+                self.max_step = self.max_step.max(1);
+            }
+        } else if let Some(node) = self.nodes[index].take() {
+            self.min_connection_cost.take();
+            self.connection_costs.remove(&index);
+            self.connection_costs.retain(|_, costs| {
+                costs.remove(&index);
+                !costs.is_empty()
+            });
+            self.nodes_map.remove(&node);
+            // removing node moves last node into its place.
+            let last = NodeIndex::new(self.graph.node_count() - 1);
+            self.graph.remove_node(node);
+            if last != node {
+                if let Some(moved) = self.nodes_map.remove(&last) {
+                    self.nodes_map.insert(node, moved);
+                    self.nodes[moved] = Some(node);
+                }
+            }
         }
+        if self.connectivity == NavGridConnectivity::Eight {
+            for (c, r) in [
+                (col.wrapping_sub(1), row.wrapping_sub(1)),
+                (col, row.wrapping_sub(1)),
+                (col.wrapping_sub(1), row),
+                (col, row),
+            ] {
+                self.connect_diagonals(c, r);
+            }
+        }
+        Some(old)
     }
 
-    pub fn coord(&self, index: usize) -> Option<(usize, usize)> {
-        let col = index % self.cols;
-        let row = index / self.cols;
-        if col < self.cols && row < self.rows {
-            Some((col, row))
-        } else {
-            None
+    // Rebuild diagonal connections of 2x2 cells block with given top left cell.
+    fn connect_diagonals(&mut self, col: usize, row: usize) {
+        if col >= self.cols || row >= self.rows {
+            return;
+        }
+        let (Some(top_left), Some(top_right), Some(bottom_left), Some(bottom_right)) = (
+            self.index(col, row),
+            self.index(col + 1, row),
+            self.index(col, row + 1),
+            self.index(col + 1, row + 1),
+        ) else {
+            return;
+        };
+        for (a, b) in [(top_left, bottom_right), (top_right, bottom_left)] {
+            if let (Some(na), Some(nb)) = (self.nodes[a], self.nodes[b]) {
+                while let Some(edge) = self.graph.find_edge(na, nb) {
+                    self.graph.remove_edge(edge);
+                }
+                while let Some(edge) = self.graph.find_edge(nb, na) {
+                    self.graph.remove_edge(edge);
+                }
+            }
+        }
+        let nodes = [top_left, top_right, bottom_left, bottom_right].map(|i| self.nodes[i]);
+        if let [Some(tl), Some(tr), Some(bl), Some(br)] = nodes {
+            self.graph.add_edge(tl, br, ());
+            self.graph.add_edge(br, tl, ());
+            self.graph.add_edge(tr, bl, ());
+            self.graph.add_edge(bl, tr, ());
         }
     }
-}
-
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub struct NavFreeGridConnection {
-    pub from: (isize, isize),
-    pub to: (isize, isize),
-}
-
-/// Nav free grid identifier.
-pub type NavFreeGridID = ID<NavFreeGrid>;
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-pub struct NavFreeGrid {
-    id: NavFreeGridID,
-    cells: Vec<(isize, isize)>,
-    costs: Vec<Scalar>,
-    graph: Graph<(), (), Undirected>,
-    nodes: Vec<NodeIndex>,
-    nodes_map: HashMap<NodeIndex, usize>,
-}
+    /// Capture runtime state of nav grid.
+    pub fn dynamic_state(&self) -> NavGridDynamicState {
+        NavGridDynamicState {
+            cells: self.cells.clone(),
+            costs: self.costs.clone(),
+            connection_costs: self.connection_costs.clone(),
+            doors: self.doors.clone(),
+        }
+    }
 
-impl NavFreeGrid {
-    pub fn new(connections: Vec<NavFreeGridConnection>) -> Self {
-        let cells = connections
-            .iter()
-            .map(|c| c.from)
-            .chain(connections.iter().map(|c| c.to))
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect::<Vec<_>>();
-        let costs = vec![1.0; cells.len()];
-        let mut graph = Graph::<(), (), Undirected>::with_capacity(cells.len(), connections.len());
-        let nodes = (0..cells.len())
-            .map(|_| graph.add_node(()))
-            .collect::<Vec<_>>();
-        for connection in connections {
-            let ia = cells.iter().position(|c| connection.from == *c);
-            let ib = cells.iter().position(|c| connection.to == *c);
-            if let (Some(ia), Some(ib)) = (ia, ib) {
-                graph.add_edge(nodes[ia], nodes[ib], ());
+    /// Restore runtime state of nav grid captured with `dynamic_state`, keeping doors
+    /// identifiers. Nav grid is left unchanged if state does not match its size.
+    pub fn restore_dynamic_state(&mut self, state: &NavGridDynamicState) -> NavResult<()> {
+        for len in [state.cells.len(), state.costs.len()] {
+            if len != self.cells.len() {
+                return Err(Error::CellsCountDoesNotMatchColsRows(
+                    len, self.cols, self.rows,
+                ));
             }
         }
-        let nodes_map = iter!(nodes).enumerate().map(|(i, n)| (*n, i)).collect();
-        Self {
-            id: NavFreeGridID::new(),
-            cells,
-            costs,
-            graph,
-            nodes,
-            nodes_map,
+        let mut door_cells = HashMap::with_capacity(state.doors.len());
+        for (id, door) in &state.doors {
+            let (col, row) = door.cell;
+            let index = self
+                .index(col, row)
+                .ok_or(Error::InvalidCellCoordinate(col, row, self.cols, self.rows))?;
+            door_cells.insert(index, *id);
         }
+        for (index, walkable) in state.cells.iter().enumerate() {
+            self.set_cell(index % self.cols, index / self.cols, *walkable);
+        }
+        self.costs = state.costs.iter().map(|cost| cost.max(0.0)).collect();
+        self.min_cell_cost.take();
+        self.min_connection_cost.take();
+        self.connection_costs = state.connection_costs.clone();
+        self.doors = state.doors.clone();
+        self.door_cells = door_cells;
+        self.goal_bounds.clear();
+        Ok(())
     }
 
-    #[inline]
-    pub fn id(&self) -> NavFreeGridID {
-        self.id
+    /// Place door on cell. Returns `None` if cell is out of bounds or already has door.
+    pub fn add_door(&mut self, door: NavGridDoor) -> Option<NavGridDoorID> {
+        let index = self.index(door.cell.0, door.cell.1)?;
+        if self.door_cells.contains_key(&index) {
+            return None;
+        }
+        let id = NavGridDoorID::new();
+        if !door.open {
+            self.goal_bounds.clear();
+        }
+        self.doors.push((id, door));
+        self.door_cells.insert(index, id);
+        Some(id)
     }
 
-    #[inline]
-    pub fn cells(&self) -> &[(isize, isize)] {
-        &self.cells
+    pub fn remove_door(&mut self, id: NavGridDoorID) -> Option<NavGridDoor> {
+        let position = self.doors.iter().position(|(i, _)| *i == id)?;
+        let (_, door) = self.doors.swap_remove(position);
+        self.door_cells.retain(|_, i| *i != id);
+        if !door.open {
+            self.goal_bounds.clear();
+        }
+        Some(door)
     }
 
-    #[inline]
-    pub fn cells_costs(&self) -> &[Scalar] {
-        &self.costs
+    pub fn door(&self, id: NavGridDoorID) -> Option<&NavGridDoor> {
+        self.doors
+            .iter()
+            .find(|(i, _)| *i == id)
+            .map(|(_, door)| door)
     }
 
-    #[inline]
-    pub fn set_cell_cost(&mut self, col: isize, row: isize, cost: Scalar) -> Option<Scalar> {
-        let index = self.index(col, row)?;
-        let c = self.costs.get_mut(index)?;
-        let old = *c;
-        *c = cost.max(0.0);
+    pub fn doors(&self) -> impl Iterator<Item = (NavGridDoorID, &NavGridDoor)> {
+        self.doors.iter().map(|(id, door)| (*id, door))
+    }
+
+    pub fn door_at(&self, col: usize, row: usize) -> Option<NavGridDoorID> {
+        self.door_cells.get(&self.index(col, row)?).copied()
+    }
+
+    /// Open or close door. Returns previous state.
+    pub fn set_door_open(&mut self, id: NavGridDoorID, open: bool) -> Option<bool> {
+        let door = self
+            .doors
+            .iter_mut()
+            .find(|(i, _)| *i == id)
+            .map(|(_, door)| door)?;
+        let old = door.open;
+        door.open = open;
+        if old != open {
+            self.goal_bounds.clear();
+        }
         Some(old)
     }
 
+    // Tells if cell has no door or its door is open.
+    fn is_cell_open(&self, index: usize) -> bool {
+        self.door_cells
+            .get(&index)
+            .and_then(|id| self.door(*id))
+            .map(|door| door.open)
+            .unwrap_or(true)
+    }
+
     pub fn neighbors(
         &self,
-        col: isize,
-        row: isize,
-    ) -> Option<impl Iterator<Item = (isize, isize)> + '_> {
+        col: usize,
+        row: usize,
+    ) -> Option<impl Iterator<Item = (usize, usize)> + '_> {
         let index = self.index(col, row)?;
-        let node = self.nodes[index];
+        let node = self.nodes[index]?;
         Some(self.graph.neighbors(node).filter_map(|node| {
             self.nodes_map
                 .get(&node)
@@ -383,53 +727,2351 @@ impl NavFreeGrid {
 
     pub fn find_path(
         &self,
-        from: (isize, isize),
-        to: (isize, isize),
-    ) -> Option<Vec<(isize, isize)>> {
-        self.find_path_custom(from, to, |_, _| true)
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<Vec<(usize, usize)>> {
+        self.find_path_with_cost(from, to).map(|(_, path)| path)
     }
 
     // filter params: first col-row, second col-row.
     pub fn find_path_custom<F>(
         &self,
-        from: (isize, isize),
-        to: (isize, isize),
+        from: (usize, usize),
+        to: (usize, usize),
+        filter: F,
+    ) -> Option<Vec<(usize, usize)>>
+    where
+        F: FnMut((usize, usize), (usize, usize)) -> bool,
+    {
+        self.find_path_with_doors(from, to, |_, _| false, filter)
+    }
+
+    /// Same as `find_path_custom` but steps rejected by filter are excluded from search instead
+    /// of getting maximal cost, so path may not be found at all.
+    // filter params: first col-row, second col-row.
+    pub fn find_path_excluding<F>(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
         mut filter: F,
-    ) -> Option<Vec<(isize, isize)>>
+    ) -> Option<Vec<(usize, usize)>>
     where
-        F: FnMut((isize, isize), (isize, isize)) -> bool,
+        F: FnMut((usize, usize), (usize, usize)) -> bool,
     {
-        let start_index = self.index(from.0, from.1)?;
-        let end_index = self.index(to.0, to.1)?;
-        let start_node = *self.nodes.get(start_index)?;
-        let end_node = *self.nodes.get(end_index)?;
-        let nodes = astar(
-            &self.graph,
-            start_node,
-            |n| n == end_node,
-            |e| {
-                let a = self.nodes_map[&e.source()];
-                let b = self.nodes_map[&e.target()];
-                if filter(self.coord(a).unwrap(), self.coord(b).unwrap()) {
-                    let a = self.costs[a];
-                    let b = self.costs[b];
-                    a * b
-                } else {
-                    SCALAR_MAX
-                }
-            },
-            |_| 0.0,
-        )?
-        .1;
-        Some(
-            nodes
-                .into_iter()
-                .filter_map(|n| self.coord(self.nodes_map[&n]))
-                .collect::<Vec<_>>(),
-        )
+        self.search_with_doors(from, to, |_, _| false, |a, b| filter(a, b).then_some(0.0))
+            .map(|(_, path)| path)
     }
 
-    pub fn find_islands(&self) -> Vec<Vec<(isize, isize)>> {
+    /// Same as `find_path` but also returns total path cost.
+    pub fn find_path_with_cost(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<(Scalar, Vec<(usize, usize)>)> {
+        if self.has_goal_bounds() {
+            return self.search_goal_bounded(from, to);
+        }
+        self.find_path_custom_with_cost(from, to, |_, _| true)
+    }
+
+    /// Precompute for every connection bounding box of goal cells that are reached optimally
+    /// through it, which lets `find_path` and `find_path_with_cost` skip connections leading
+    /// away from goal. Takes quadratic time of walkable cells count, so it is meant to be baked
+    /// with static nav grids. Any change of cells, costs, connections costs or doors discards
+    /// precomputed bounds.
+    pub fn build_goal_bounds(&mut self) {
+        let sources = (0..self.cells.len()).collect::<Vec<_>>();
+        self.goal_bounds = iter!(sources)
+            .map(|source| {
+                let mut result = HashMap::<usize, (usize, usize, usize, usize)>::new();
+                if self.nodes[*source].is_none() {
+                    return result;
+                }
+                let (costs, parents, _) =
+                    self.integrate_until(&[*source], Direction::Outgoing, |_| false);
+                // first cell after source on optimal path to each cell.
+                let mut first = vec![None; self.cells.len()];
+                for (goal, cost) in costs.iter().enumerate() {
+                    if goal == *source || *cost >= SCALAR_MAX {
+                        continue;
+                    }
+                    let mut chain = vec![];
+                    let mut index = goal;
+                    let next = loop {
+                        if let Some(next) = first[index] {
+                            break next;
+                        }
+                        let parent = parents[index].unwrap();
+                        if parent == *source {
+                            break index;
+                        }
+                        chain.push(index);
+                        index = parent;
+                    };
+                    first[index] = Some(next);
+                    for index in chain {
+                        first[index] = Some(next);
+                    }
+                    let (col, row) = (goal % self.cols, goal / self.cols);
+                    result
+                        .entry(next)
+                        .and_modify(|bounds| {
+                            bounds.0 = bounds.0.min(col);
+                            bounds.1 = bounds.1.min(row);
+                            bounds.2 = bounds.2.max(col);
+                            bounds.3 = bounds.3.max(row);
+                        })
+                        .or_insert((col, row, col, row));
+                }
+                result
+            })
+            .collect();
+    }
+
+    #[inline]
+    pub fn has_goal_bounds(&self) -> bool {
+        !self.goal_bounds.is_empty()
+    }
+
+    #[inline]
+    pub fn clear_goal_bounds(&mut self) {
+        self.goal_bounds.clear();
+    }
+
+    // A* that follows only connections which goal bounds contain goal cell.
+    fn search_goal_bounded(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<(Scalar, Vec<(usize, usize)>)> {
+        let start = self.index(from.0, from.1)?;
+        let goal = self.index(to.0, to.1)?;
+        self.nodes[start]?;
+        self.nodes[goal]?;
+        let factor = self.heuristic_factor();
+        let mut costs = vec![SCALAR_MAX; self.cells.len()];
+        let mut parents = vec![None; self.cells.len()];
+        let mut open = BinaryHeap::new();
+        costs[start] = 0.0;
+        open.push(NavSearchItem(factor * self.distance(start, goal), start));
+        while let Some(NavSearchItem(_, index)) = open.pop() {
+            if index == goal {
+                return Some((
+                    costs[goal],
+                    self.unwind(&parents, goal).into_iter().rev().collect(),
+                ));
+            }
+            for (next, (min_col, min_row, max_col, max_row)) in &self.goal_bounds[index] {
+                if to.0 < *min_col || to.0 > *max_col || to.1 < *min_row || to.1 > *max_row {
+                    continue;
+                }
+                let next_cost = costs[index] + self.step_cost(index, *next);
+                if next_cost < costs[*next] {
+                    costs[*next] = next_cost;
+                    parents[*next] = Some(index);
+                    open.push(NavSearchItem(
+                        next_cost + factor * self.distance(*next, goal),
+                        *next,
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    /// Same as `find_path_custom` but also returns total path cost.
+    pub fn find_path_custom_with_cost<F>(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        filter: F,
+    ) -> Option<(Scalar, Vec<(usize, usize)>)>
+    where
+        F: FnMut((usize, usize), (usize, usize)) -> bool,
+    {
+        self.search_with_doors(from, to, |_, _| false, penalize_rejected(filter))
+    }
+
+    /// Same as `find_path_custom_with_cost` but with selectable search algorithm, where steps
+    /// rejected by filter are excluded from search (see `find_path_excluding`).
+    pub fn find_path_with_search<F>(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        search: NavGridSearch,
+        mut filter: F,
+    ) -> Option<(Scalar, Vec<(usize, usize)>)>
+    where
+        F: FnMut((usize, usize), (usize, usize)) -> bool,
+    {
+        match search {
+            NavGridSearch::AStar => {
+                self.search_with_doors(from, to, |_, _| false, |a, b| filter(a, b).then_some(0.0))
+            }
+            NavGridSearch::Bidirectional => self.search_bidirectional(from, to, filter),
+        }
+    }
+
+    /// Same as `find_path_custom` but closed doors for which `can_open` returns `true` (i.e.
+    /// agent has key matching door lock) can be passed through.
+    pub fn find_path_with_doors<O, F>(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        can_open: O,
+        filter: F,
+    ) -> Option<Vec<(usize, usize)>>
+    where
+        O: FnMut(NavGridDoorID, &NavGridDoor) -> bool,
+        F: FnMut((usize, usize), (usize, usize)) -> bool,
+    {
+        self.search_with_doors(from, to, can_open, penalize_rejected(filter))
+            .map(|(_, path)| path)
+    }
+
+    // filter gives extra cost of step, `None` excludes it from search.
+    fn search_with_doors<O, F>(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        mut can_open: O,
+        mut filter: F,
+    ) -> Option<(Scalar, Vec<(usize, usize)>)>
+    where
+        O: FnMut(NavGridDoorID, &NavGridDoor) -> bool,
+        F: FnMut((usize, usize), (usize, usize)) -> Option<Scalar>,
+    {
+        let end_index = self.index(to.0, to.1)?;
+        let factor = self.heuristic_factor();
+        self.search_cells(
+            from,
+            to,
+            |a, b| {
+                let can_enter = self.is_cell_open(b)
+                    || self
+                        .door_cells
+                        .get(&b)
+                        .and_then(|id| Some((*id, self.door(*id)?)))
+                        .map(|(id, door)| can_open(id, door))
+                        .unwrap_or(false);
+                if !can_enter {
+                    return None;
+                }
+                let extra = filter(self.coord(a).unwrap(), self.coord(b).unwrap())?;
+                Some(self.step_cost(a, b) + extra)
+            },
+            |index| factor * self.distance(index, end_index),
+        )
+    }
+
+    // A* over steps between cells with custom step cost and heuristic, steps of `None` cost
+    // are skipped.
+    fn search_cells<C, H>(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        mut cost: C,
+        heuristic: H,
+    ) -> Option<(Scalar, Vec<(usize, usize)>)>
+    where
+        C: FnMut(usize, usize) -> Option<Scalar>,
+        H: Fn(usize) -> Scalar,
+    {
+        let start_index = self.index(from.0, from.1)?;
+        let end_index = self.index(to.0, to.1)?;
+        self.nodes[start_index]?;
+        self.nodes[end_index]?;
+        let (total, indices) = astar(
+            start_index,
+            end_index,
+            |a, next| {
+                let Some(node) = self.nodes[a] else {
+                    return;
+                };
+                for node in self.graph.neighbors(node) {
+                    let b = self.nodes_map[&node];
+                    if let Some(cost) = cost(a, b) {
+                        next(b, cost);
+                    }
+                }
+            },
+            heuristic,
+        )?;
+        Some((
+            total,
+            indices
+                .into_iter()
+                .filter_map(|index| self.coord(index))
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    // Bidirectional A* with average of forward and backward heuristics as potential, which keeps
+    // both searches consistent so they can stop as soon as their queues tops cannot improve best
+    // path found where they met.
+    fn search_bidirectional<F>(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        mut filter: F,
+    ) -> Option<(Scalar, Vec<(usize, usize)>)>
+    where
+        F: FnMut((usize, usize), (usize, usize)) -> bool,
+    {
+        let start = self.index(from.0, from.1)?;
+        let goal = self.index(to.0, to.1)?;
+        self.nodes[start]?;
+        self.nodes[goal]?;
+        if start == goal {
+            return Some((0.0, vec![from]));
+        }
+        if !self.is_cell_open(goal) {
+            return None;
+        }
+        let factor = 0.5 * self.heuristic_factor();
+        let potential =
+            |index: usize| factor * (self.distance(index, goal) - self.distance(start, index));
+        let mut costs = [
+            vec![SCALAR_MAX; self.cells.len()],
+            vec![SCALAR_MAX; self.cells.len()],
+        ];
+        let mut parents = [vec![None; self.cells.len()], vec![None; self.cells.len()]];
+        let mut open = [BinaryHeap::new(), BinaryHeap::new()];
+        costs[0][start] = 0.0;
+        costs[1][goal] = 0.0;
+        open[0].push(NavSearchItem(potential(start), start));
+        open[1].push(NavSearchItem(-potential(goal), goal));
+        let mut best = SCALAR_MAX;
+        let mut meeting = None;
+        while let (Some(forward), Some(backward)) = (open[0].peek(), open[1].peek()) {
+            if forward.0 + backward.0 >= best {
+                break;
+            }
+            let side = if open[0].len() <= open[1].len() { 0 } else { 1 };
+            let (sign, direction) = if side == 0 {
+                (1.0, Direction::Outgoing)
+            } else {
+                (-1.0, Direction::Incoming)
+            };
+            let NavSearchItem(key, index) = open[side].pop().unwrap();
+            let cost = costs[side][index];
+            if key > cost + sign * potential(index) {
+                continue;
+            }
+            for node in self
+                .graph
+                .neighbors_directed(self.nodes[index].unwrap(), direction)
+            {
+                let next = self.nodes_map[&node];
+                let (a, b) = if side == 0 {
+                    (index, next)
+                } else {
+                    (next, index)
+                };
+                if !self.is_cell_open(b) || !filter(self.coord(a).unwrap(), self.coord(b).unwrap())
+                {
+                    continue;
+                }
+                let next_cost = cost + self.step_cost(a, b);
+                if next_cost < costs[side][next] {
+                    costs[side][next] = next_cost;
+                    parents[side][next] = Some(index);
+                    open[side].push(NavSearchItem(next_cost + sign * potential(next), next));
+                    let total = next_cost + costs[1 - side][next];
+                    if total < best {
+                        best = total;
+                        meeting = Some(next);
+                    }
+                }
+            }
+        }
+        let meeting = meeting?;
+        let mut path = self.unwind(&parents[0], meeting);
+        path.reverse();
+        path.extend(self.unwind(&parents[1], meeting).into_iter().skip(1));
+        Some((best, path))
+    }
+
+    /// Find walkable cells that can be approached from other walkable cells and lie next to
+    /// unwalkable cells (cells out of grid bounds do not give cover).
+    pub fn cover_points(&self) -> Vec<NavGridCoverPoint> {
+        (0..self.cells.len())
+            .filter_map(|index| {
+                let node = self.nodes[index]?;
+                self.graph
+                    .neighbors_directed(node, Direction::Incoming)
+                    .next()?;
+                let (col, row) = self.coord(index)?;
+                let directions = NavGridDirection::ALL
+                    .iter()
+                    .filter(|direction| {
+                        let (dc, dr) = direction.offset();
+                        let c = col as isize + dc;
+                        let r = row as isize + dr;
+                        c >= 0
+                            && r >= 0
+                            && self
+                                .index(c as usize, r as usize)
+                                .map(|i| !self.cells[i])
+                                .unwrap_or(false)
+                    })
+                    .fold(0, |a, direction| a | direction.bit());
+                if directions != 0 {
+                    Some(NavGridCoverPoint {
+                        cell: (col, row),
+                        directions,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Find cover points that give cover from threat located at given cell, judging by
+    /// dominant axis of direction towards it.
+    pub fn cover_points_against(&self, threat: (usize, usize)) -> Vec<(usize, usize)> {
+        self.cover_points()
+            .into_iter()
+            .filter(|point| {
+                let dc = threat.0 as isize - point.cell.0 as isize;
+                let dr = threat.1 as isize - point.cell.1 as isize;
+                if dc == 0 && dr == 0 {
+                    return false;
+                }
+                let direction = if dc.abs() >= dr.abs() {
+                    if dc < 0 {
+                        NavGridDirection::Left
+                    } else {
+                        NavGridDirection::Right
+                    }
+                } else if dr < 0 {
+                    NavGridDirection::Up
+                } else {
+                    NavGridDirection::Down
+                };
+                point.is_covered_from(direction)
+            })
+            .map(|point| point.cell)
+            .collect()
+    }
+
+    /// Find path using influence map as additional cost layer, where each step cost gets
+    /// multiplied by `1 + influence * weight` of target cell (clamped at zero). Positive weight
+    /// avoids influence, negative weight attracts.
+    pub fn find_path_weighted(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        influence: &NavInfluenceMap,
+        weight: Scalar,
+    ) -> Option<Vec<(usize, usize)>> {
+        let goal = self.index(to.0, to.1)?;
+        let factor = self.heuristic_factor() * influence.min_cost_factor(weight);
+        self.search_cells(
+            from,
+            to,
+            |a, b| {
+                let (col, row) = self.coord(b)?;
+                self.is_cell_open(b).then(|| {
+                    self.step_cost(a, b) * influence.cost_factor(col as isize, row as isize, weight)
+                })
+            },
+            |index| factor * self.distance(index, goal),
+        )
+        .map(|(_, path)| path)
+    }
+
+    /// Find path that avoids crowded cells: cost of entering cell is multiplied by its
+    /// occupancy cost factor (occupancy is addressed by cell index).
+    pub fn find_path_congested(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        occupancy: &NavOccupancy,
+        weight: Scalar,
+    ) -> Option<Vec<(usize, usize)>> {
+        self.search_cells(
+            from,
+            to,
+            |a, b| {
+                self.is_cell_open(b)
+                    .then(|| self.step_cost(a, b) * occupancy.cost_factor(b, weight))
+            },
+            |_| 0.0,
+        )
+        .map(|(_, path)| path)
+    }
+
+    /// Build flow field towards goal cell, integrating cells costs. Returns `None` if goal is
+    /// not walkable.
+    pub fn flow_field(&self, goal: (usize, usize)) -> Option<NavFlowField> {
+        let index = self.index(goal.0, goal.1)?;
+        self.nodes[index]?;
+        let (costs, next) = self.integrate(&[index], Direction::Incoming);
+        Some(NavFlowField {
+            grid: self.id,
+            cols: self.cols,
+            rows: self.rows,
+            goal,
+            costs,
+            next,
+        })
+    }
+
+    /// Calculate accumulated cost of reaching every cell from the nearest of source cells.
+    /// Result is indexed the same way as `cells`, with `None` for cells that cannot be reached.
+    pub fn distance_map(&self, sources: &[(usize, usize)]) -> Vec<Option<Scalar>> {
+        let sources = sources
+            .iter()
+            .filter_map(|(col, row)| self.index(*col, *row))
+            .collect::<Vec<_>>();
+        self.integrate(&sources, Direction::Outgoing)
+            .0
+            .into_iter()
+            .map(|cost| if cost < SCALAR_MAX { Some(cost) } else { None })
+            .collect()
+    }
+
+    /// Find path from cell to the nearest (by path cost) of target cells in single search.
+    /// Last path cell is the target reached.
+    pub fn find_path_to_any(
+        &self,
+        from: (usize, usize),
+        targets: &[(usize, usize)],
+    ) -> Option<Vec<(usize, usize)>> {
+        let targets = self.indices(targets);
+        let start = self.index(from.0, from.1)?;
+        let (_, parents, reached) = self.integrate_until(&[start], Direction::Outgoing, |index| {
+            targets.contains(&index)
+        });
+        let mut result = self.unwind(&parents, reached?);
+        result.reverse();
+        Some(result)
+    }
+
+    /// Find path to cell from the nearest (by path cost) of source cells in single search.
+    /// First path cell is the source found.
+    pub fn find_nearest_source(
+        &self,
+        sources: &[(usize, usize)],
+        to: (usize, usize),
+    ) -> Option<Vec<(usize, usize)>> {
+        let sources = self.indices(sources);
+        let goal = self.index(to.0, to.1)?;
+        let (_, parents, reached) = self.integrate_until(&[goal], Direction::Incoming, |index| {
+            sources.contains(&index)
+        });
+        Some(self.unwind(&parents, reached?))
+    }
+
+    fn indices(&self, cells: &[(usize, usize)]) -> HashSet<usize> {
+        cells
+            .iter()
+            .filter_map(|(col, row)| self.index(*col, *row))
+            .collect()
+    }
+
+    fn unwind(&self, parents: &[Option<usize>], mut index: usize) -> Vec<(usize, usize)> {
+        let mut result = vec![self.coord(index).unwrap()];
+        while let Some(parent) = parents[index] {
+            index = parent;
+            result.push(self.coord(index).unwrap());
+        }
+        result
+    }
+
+    fn integrate(
+        &self,
+        sources: &[usize],
+        direction: Direction,
+    ) -> (Vec<Scalar>, Vec<Option<usize>>) {
+        if cfg!(feature = "parallel") && self.rows > INTEGRATE_CHUNK_ROWS {
+            return self.integrate_chunked(sources, direction, INTEGRATE_CHUNK_ROWS);
+        }
+        let (costs, parents, _) = self.integrate_until(sources, direction, |_| false);
+        (costs, parents)
+    }
+
+    // Same as `integrate`, but grid is split into chunks of rows, each integrated separately
+    // (in parallel with `parallel` feature), see `nav_search::integrate_chunked`.
+    pub(crate) fn integrate_chunked(
+        &self,
+        sources: &[usize],
+        direction: Direction,
+        chunk_rows: usize,
+    ) -> (Vec<Scalar>, Vec<Option<usize>>) {
+        let sources = sources
+            .iter()
+            .copied()
+            .filter(|source| self.nodes.get(*source).copied().flatten().is_some())
+            .collect::<Vec<_>>();
+        integrate_chunked(
+            self.cells.len(),
+            &sources,
+            chunk_rows.max(1) * self.cols,
+            |index, next| {
+                let Some(node) = self.nodes[index] else {
+                    return;
+                };
+                for node in self.graph.neighbors_directed(node, direction) {
+                    let other = self.nodes_map[&node];
+                    if let Some(step) = self.integrate_step(index, other, direction) {
+                        next(other, step);
+                    }
+                }
+            },
+        )
+    }
+
+    // Cost of integration step from cell to its neighbor, going along (`Outgoing`) or against
+    // (`Incoming`) connections, `None` if step goes through closed door.
+    fn integrate_step(&self, index: usize, next: usize, direction: Direction) -> Option<Scalar> {
+        match direction {
+            Direction::Outgoing if self.is_cell_open(next) => Some(self.step_cost(index, next)),
+            Direction::Incoming if self.is_cell_open(index) => Some(self.step_cost(next, index)),
+            _ => None,
+        }
+    }
+
+    // Dijkstra from source cells, going along (`Outgoing`) or against (`Incoming`) connections.
+    // Gives accumulated cost and previous cell index of every cell, stops early at first cell
+    // for which `stop` returns `true` and gives its index.
+    fn integrate_until<S>(
+        &self,
+        sources: &[usize],
+        direction: Direction,
+        mut stop: S,
+    ) -> (Vec<Scalar>, Vec<Option<usize>>, Option<usize>)
+    where
+        S: FnMut(usize) -> bool,
+    {
+        let mut costs = vec![SCALAR_MAX; self.cells.len()];
+        let mut parents = vec![None; self.cells.len()];
+        let mut open = BinaryHeap::new();
+        for source in sources {
+            if self.nodes[*source].is_some() {
+                costs[*source] = 0.0;
+                open.push(NavSearchItem(0.0, *source));
+            }
+        }
+        while let Some(NavSearchItem(cost, index)) = open.pop() {
+            if cost > costs[index] {
+                continue;
+            }
+            if stop(index) {
+                return (costs, parents, Some(index));
+            }
+            for node in self
+                .graph
+                .neighbors_directed(self.nodes[index].unwrap(), direction)
+            {
+                let next = self.nodes_map[&node];
+                let Some(step) = self.integrate_step(index, next, direction) else {
+                    continue;
+                };
+                let next_cost = cost + step;
+                if next_cost < costs[next] {
+                    costs[next] = next_cost;
+                    parents[next] = Some(index);
+                    open.push(NavSearchItem(next_cost, next));
+                }
+            }
+        }
+        (costs, parents, None)
+    }
+
+    pub fn find_islands(&self) -> Vec<Vec<(usize, usize)>> {
+        tarjan_scc(&self.graph)
+            .into_iter()
+            .map(|v| {
+                v.into_iter()
+                    .filter_map(|n| self.nodes_map.get(&n).and_then(|i| self.coord(*i)))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+
+    /// Tells if straight line between cells centers goes only through walkable cells that are
+    /// not behind closed doors. Line passing exactly through cells corner requires both cells
+    /// sharing that corner to be walkable.
+    pub fn line_of_sight(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        let is_open = |col: isize, row: isize| {
+            col >= 0
+                && row >= 0
+                && self
+                    .index(col as usize, row as usize)
+                    .map(|index| self.cells[index] && self.is_cell_open(index))
+                    .unwrap_or(false)
+        };
+        let (mut col, mut row) = (from.0 as isize, from.1 as isize);
+        if !is_open(col, row) {
+            return false;
+        }
+        let (dc, dr) = (to.0 as isize - col, to.1 as isize - row);
+        let (steps_col, steps_row) = (dc.abs(), dr.abs());
+        let (sign_col, sign_row) = (dc.signum(), dr.signum());
+        let (mut step_col, mut step_row) = (0, 0);
+        while step_col < steps_col || step_row < steps_row {
+            // compares where line crosses next vertical and next horizontal cell border.
+            let decision = (1 + 2 * step_col) * steps_row - (1 + 2 * step_row) * steps_col;
+            if decision == 0 {
+                if !is_open(col + sign_col, row) || !is_open(col, row + sign_row) {
+                    return false;
+                }
+                col += sign_col;
+                row += sign_row;
+                step_col += 1;
+                step_row += 1;
+            } else if decision < 0 {
+                col += sign_col;
+                step_col += 1;
+            } else {
+                row += sign_row;
+                step_row += 1;
+            }
+            if !is_open(col, row) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Remove path waypoints that can be skipped by going in straight line (any-angle path).
+    /// Cells costs are not taken into account.
+    pub fn smooth_path(&self, path: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        if path.len() <= 2 {
+            return path.to_vec();
+        }
+        let mut result = vec![path[0]];
+        for pair in path[1..].windows(2) {
+            if !self.line_of_sight(*result.last().unwrap(), pair[1]) {
+                result.push(pair[0]);
+            }
+        }
+        result.push(*path.last().unwrap());
+        result
+    }
+
+    /// Statistics of every island, in the same order as `find_islands` returns them.
+    pub fn islands_stats(&self) -> Vec<NavGridIslandStats> {
+        self.find_islands()
+            .into_iter()
+            .map(|island| NavGridIslandStats {
+                cells_count: island.len(),
+                total_cost: island
+                    .iter()
+                    .map(|(col, row)| self.costs[row * self.cols + col])
+                    .sum(),
+                bounds_min: island.iter().fold((usize::MAX, usize::MAX), |a, b| {
+                    (a.0.min(b.0), a.1.min(b.1))
+                }),
+                bounds_max: island
+                    .iter()
+                    .fold((0, 0), |a, b| (a.0.max(b.0), a.1.max(b.1))),
+            })
+            .collect()
+    }
+
+    // Scale of cells distance that never overestimates cost of moving between cells: every step
+    // covers at most `max_step` cells and costs at least squared minimal cell cost multiplied by
+    // minimal connection cost.
+    fn heuristic_factor(&self) -> Scalar {
+        if self.max_step == 0 {
+            return 0.0;
+        }
+        let min_cost = self.min_cell_cost();
+        if min_cost >= SCALAR_MAX {
+            return 0.0;
+        }
+        min_cost * min_cost * self.min_connection_cost() / self.max_step as Scalar
+    }
+
+    // Lowest cost of walkable cell, `SCALAR_MAX` if there are no walkable cells.
+    pub(crate) fn min_cell_cost(&self) -> Scalar {
+        *self.min_cell_cost.get_or_init(|| {
+            self.costs
+                .iter()
+                .zip(self.cells.iter())
+                .filter(|(_, cell)| **cell)
+                .map(|(cost, _)| *cost)
+                .fold(SCALAR_MAX, Scalar::min)
+        })
+    }
+
+    // Lowest cost of connection, connections without explicit cost cost 1.
+    pub(crate) fn min_connection_cost(&self) -> Scalar {
+        *self.min_connection_cost.get_or_init(|| {
+            self.connection_costs
+                .values()
+                .flat_map(|costs| costs.values())
+                .copied()
+                .fold(1.0, Scalar::min)
+        })
+    }
+
+    // Manhattan distance between cells, or octile distance for 8-way connectivity.
+    fn distance(&self, from: usize, to: usize) -> Scalar {
+        let (fc, fr) = (from % self.cols, from / self.cols);
+        let (tc, tr) = (to % self.cols, to / self.cols);
+        self.offset_distance(fc.abs_diff(tc), fr.abs_diff(tr))
+    }
+
+    // Same as `distance`, but for cols and rows differences between cells.
+    fn offset_distance(&self, dc: usize, dr: usize) -> Scalar {
+        match self.connectivity {
+            NavGridConnectivity::Four => (dc + dr) as Scalar,
+            NavGridConnectivity::Eight => {
+                let diagonal = dc.min(dr);
+                (dc.max(dr) - diagonal) as Scalar + diagonal as Scalar * DIAGONAL_COST
+            }
+        }
+    }
+
+    // Cost of moving between connected cells, diagonal neighbors of 8-way connected grid cost
+    // more. Connections of grids made with `NavGrid::with_connections` cost only what their cells
+    // and `NavGrid::set_connection_cost` say.
+    fn step_cost(&self, from: usize, to: usize) -> Scalar {
+        let cost = self.costs[from] * self.costs[to] * self.connection_cost_between(from, to);
+        if self.connectivity != NavGridConnectivity::Eight {
+            return cost;
+        }
+        let dc = (from % self.cols).abs_diff(to % self.cols);
+        let dr = (from / self.cols).abs_diff(to / self.cols);
+        if dc == 1 && dr == 1 {
+            cost * DIAGONAL_COST
+        } else {
+            cost
+        }
+    }
+
+    pub fn index(&self, col: usize, row: usize) -> Option<usize> {
+        if col < self.cols && row < self.rows {
+            Some(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    pub fn coord(&self, index: usize) -> Option<(usize, usize)> {
+        let col = index % self.cols;
+        let row = index / self.cols;
+        if col < self.cols && row < self.rows {
+            Some((col, row))
+        } else {
+            None
+        }
+    }
+}
+
+/// Flow field of nav grid leading every reachable cell towards single goal cell, so many agents
+/// heading to the same goal can sample it instead of finding path each.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavFlowField {
+    grid: NavGridID,
+    cols: usize,
+    rows: usize,
+    goal: (usize, usize),
+    // integrated cost of reaching goal from each cell, `SCALAR_MAX` if unreachable.
+    costs: Vec<Scalar>,
+    // next cell index on the cheapest way to goal.
+    next: Vec<Option<usize>>,
+}
+
+impl NavFlowField {
+    #[inline]
+    pub fn grid_id(&self) -> NavGridID {
+        self.grid
+    }
+
+    #[inline]
+    pub fn goal(&self) -> (usize, usize) {
+        self.goal
+    }
+
+    /// Cost of reaching goal from cell, `None` if cell cannot reach goal.
+    pub fn cost(&self, col: usize, row: usize) -> Option<Scalar> {
+        let cost = *self.costs.get(self.index(col, row)?)?;
+        if cost < SCALAR_MAX {
+            Some(cost)
+        } else {
+            None
+        }
+    }
+
+    /// Next cell to move to from given cell, `None` at goal or when cell cannot reach goal.
+    pub fn next_cell(&self, col: usize, row: usize) -> Option<(usize, usize)> {
+        let next = (*self.next.get(self.index(col, row)?)?)?;
+        Some((next % self.cols, next / self.cols))
+    }
+
+    /// Col-row offset to next cell.
+    pub fn direction(&self, col: usize, row: usize) -> Option<(isize, isize)> {
+        let (c, r) = self.next_cell(col, row)?;
+        Some((c as isize - col as isize, r as isize - row as isize))
+    }
+
+    /// Follow flow field from cell to goal.
+    pub fn path(&self, from: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        self.cost(from.0, from.1)?;
+        let mut result = vec![from];
+        let mut current = from;
+        while let Some(next) = self.next_cell(current.0, current.1) {
+            result.push(next);
+            current = next;
+        }
+        Some(result)
+    }
+
+    fn index(&self, col: usize, row: usize) -> Option<usize> {
+        if col < self.cols && row < self.rows {
+            Some(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct NavGridPlannerItem((Scalar, Scalar), usize);
+
+impl Eq for NavGridPlannerItem {}
+
+impl PartialOrd for NavGridPlannerItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NavGridPlannerItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.partial_cmp(&self.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Incremental path planner of nav grid (D* Lite), for agents that keep moving towards the same
+/// goal while grid changes. After changing cells costs, walkability or doors, tell planner
+/// which cells have changed and it repairs only affected part of its search.
+#[derive(Debug, Clone)]
+pub struct NavGridPlanner {
+    grid: NavGridID,
+    start: usize,
+    goal: usize,
+    // accumulated heuristic offset of start moves, keeps old queue keys valid.
+    km: Scalar,
+    factor: Scalar,
+    g: Vec<Scalar>,
+    rhs: Vec<Scalar>,
+    // key of every queued cell, heap entries with other keys are stale.
+    queued: Vec<Option<(Scalar, Scalar)>>,
+    open: BinaryHeap<NavGridPlannerItem>,
+}
+
+impl NavGridPlanner {
+    /// Create planner, returns `None` if start or goal cell is out of grid.
+    pub fn new(grid: &NavGrid, start: (usize, usize), goal: (usize, usize)) -> Option<Self> {
+        let start = grid.index(start.0, start.1)?;
+        let goal = grid.index(goal.0, goal.1)?;
+        let mut result = Self {
+            grid: grid.id,
+            start,
+            goal,
+            km: 0.0,
+            factor: 0.0,
+            g: vec![],
+            rhs: vec![],
+            queued: vec![],
+            open: BinaryHeap::new(),
+        };
+        result.reset(grid);
+        Some(result)
+    }
+
+    #[inline]
+    pub fn grid_id(&self) -> NavGridID {
+        self.grid
+    }
+
+    pub fn start(&self, grid: &NavGrid) -> (usize, usize) {
+        grid.coord(self.start).unwrap()
+    }
+
+    pub fn goal(&self, grid: &NavGrid) -> (usize, usize) {
+        grid.coord(self.goal).unwrap()
+    }
+
+    /// Move agent to another cell, keeping search results. Returns `false` if cell is out of
+    /// grid.
+    pub fn move_start(&mut self, grid: &NavGrid, start: (usize, usize)) -> bool {
+        let Some(start) = grid.index(start.0, start.1) else {
+            return false;
+        };
+        self.km += self.heuristic(grid, self.start, start);
+        self.start = start;
+        true
+    }
+
+    /// Notify planner about cells with changed cost, walkability or door state.
+    pub fn update_cells(&mut self, grid: &NavGrid, cells: &[(usize, usize)]) {
+        if grid.heuristic_factor() < self.factor {
+            // heuristic could overestimate now, so old search results are useless.
+            self.reset(grid);
+            return;
+        }
+        let radius = grid.max_step.max(1);
+        for (col, row) in cells {
+            for r in row.saturating_sub(radius)..=(row + radius) {
+                for c in col.saturating_sub(radius)..=(col + radius) {
+                    if let Some(index) = grid.index(c, r) {
+                        self.update_vertex(grid, index);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Find path from current start cell to goal, reusing previous searches.
+    pub fn path(&mut self, grid: &NavGrid) -> Option<Vec<(usize, usize)>> {
+        if grid.id != self.grid {
+            return None;
+        }
+        self.compute(grid);
+        if self.g[self.start] >= SCALAR_MAX {
+            return None;
+        }
+        let mut current = self.start;
+        let mut result = vec![grid.coord(current)?];
+        while current != self.goal {
+            if result.len() > grid.cells.len() {
+                return None;
+            }
+            current = self
+                .successors(grid, current)
+                .filter(|(next, _)| self.g[*next] < SCALAR_MAX)
+                .map(|(next, cost)| (next, cost + self.g[next]))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())?
+                .0;
+            result.push(grid.coord(current)?);
+        }
+        Some(result)
+    }
+
+    fn reset(&mut self, grid: &NavGrid) {
+        let count = grid.cells.len();
+        self.km = 0.0;
+        self.factor = grid.heuristic_factor();
+        self.g = vec![SCALAR_MAX; count];
+        self.rhs = vec![SCALAR_MAX; count];
+        self.queued = vec![None; count];
+        self.open.clear();
+        self.rhs[self.goal] = 0.0;
+        self.enqueue(grid, self.goal);
+    }
+
+    fn heuristic(&self, grid: &NavGrid, from: usize, to: usize) -> Scalar {
+        self.factor * grid.distance(from, to)
+    }
+
+    fn key(&self, grid: &NavGrid, index: usize) -> (Scalar, Scalar) {
+        let cost = self.g[index].min(self.rhs[index]);
+        if cost >= SCALAR_MAX {
+            return (SCALAR_MAX, SCALAR_MAX);
+        }
+        (
+            cost + self.heuristic(grid, self.start, index) + self.km,
+            cost,
+        )
+    }
+
+    fn enqueue(&mut self, grid: &NavGrid, index: usize) {
+        let key = self.key(grid, index);
+        self.queued[index] = Some(key);
+        self.open.push(NavGridPlannerItem(key, index));
+    }
+
+    fn top(&mut self) -> Option<NavGridPlannerItem> {
+        while let Some(item) = self.open.peek().copied() {
+            if self.queued[item.1] == Some(item.0) {
+                return Some(item);
+            }
+            self.open.pop();
+        }
+        None
+    }
+
+    fn successors<'a>(
+        &self,
+        grid: &'a NavGrid,
+        index: usize,
+    ) -> impl Iterator<Item = (usize, Scalar)> + 'a {
+        grid.nodes[index]
+            .into_iter()
+            .flat_map(|node| grid.graph.neighbors(node))
+            .map(|node| grid.nodes_map[&node])
+            .filter(|next| grid.is_cell_open(*next))
+            .map(move |next| (next, grid.step_cost(index, next)))
+    }
+
+    fn update_vertex(&mut self, grid: &NavGrid, index: usize) {
+        if index != self.goal {
+            self.rhs[index] = if grid.is_cell_open(index) {
+                self.successors(grid, index)
+                    .filter(|(next, _)| self.g[*next] < SCALAR_MAX)
+                    .map(|(next, cost)| cost + self.g[next])
+                    .fold(SCALAR_MAX, Scalar::min)
+            } else {
+                SCALAR_MAX
+            };
+        }
+        self.queued[index] = None;
+        if self.g[index] != self.rhs[index] {
+            self.enqueue(grid, index);
+        }
+    }
+
+    fn compute(&mut self, grid: &NavGrid) {
+        while let Some(NavGridPlannerItem(key, index)) = self.top() {
+            let start_key = self.key(grid, self.start);
+            if key >= start_key && self.rhs[self.start] == self.g[self.start] {
+                break;
+            }
+            self.open.pop();
+            self.queued[index] = None;
+            let new_key = self.key(grid, index);
+            if key < new_key {
+                self.enqueue(grid, index);
+            } else if self.g[index] > self.rhs[index] {
+                self.g[index] = self.rhs[index];
+                self.update_predecessors(grid, index);
+            } else {
+                self.g[index] = SCALAR_MAX;
+                self.update_vertex(grid, index);
+                self.update_predecessors(grid, index);
+            }
+        }
+    }
+
+    fn update_predecessors(&mut self, grid: &NavGrid, index: usize) {
+        let Some(node) = grid.nodes[index] else {
+            return;
+        };
+        let predecessors = grid
+            .graph
+            .neighbors_directed(node, Direction::Incoming)
+            .map(|node| grid.nodes_map[&node])
+            .collect::<Vec<_>>();
+        for predecessor in predecessors {
+            self.update_vertex(grid, predecessor);
+        }
+    }
+}
+
+/// Chunk coordinate of nav grid portal cache: (chunk col, chunk row).
+pub type NavGridChunk = (usize, usize);
+
+/// Hierarchical path finding cache of nav grid divided into chunks.
+///
+/// Cells on chunk borders that lead to neighbor chunks are treated as portals, and paths between
+/// portals of the same chunk are computed once and reused by every query, so long distance
+/// queries only have to search inside first and last chunk. Found paths are near-optimal.
+/// Cache has to be rebuilt when grid cells costs or doors states change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavGridPortalCache {
+    grid: NavGridID,
+    chunk_cols: usize,
+    chunk_rows: usize,
+    // cell index of each portal.
+    portals: Vec<usize>,
+    portals_map: HashMap<usize, usize>,
+    chunk_portals: HashMap<NavGridChunk, Vec<usize>>,
+    // (portal index, cost) of outgoing abstract edges of each portal.
+    edges: Vec<Vec<(usize, Scalar)>>,
+    // cached cell paths between portals of the same chunk.
+    segments: HashMap<(usize, usize), Vec<usize>>,
+    // tells if last and first cols (rows) of grid are neighbors.
+    #[serde(default)]
+    wrap: (bool, bool),
+}
+
+impl NavGridPortalCache {
+    pub fn new(grid: &NavGrid, chunk_cols: usize, chunk_rows: usize) -> NavResult<Self> {
+        Self::with_wrap(grid, chunk_cols, chunk_rows, (false, false))
+    }
+
+    /// Create cache of grid wrapped around (i.e. map of planet), where `wrap` tells if last col
+    /// (row) of grid neighbors with its first col (row). Walkable cells on both sides of wrapped
+    /// border are connected, and paths crossing it jump between them.
+    pub fn with_wrap(
+        grid: &NavGrid,
+        chunk_cols: usize,
+        chunk_rows: usize,
+        wrap: (bool, bool),
+    ) -> NavResult<Self> {
+        if chunk_cols == 0 || chunk_rows == 0 {
+            return Err(Error::EmptyChunk(chunk_cols, chunk_rows));
+        }
+        let mut result = Self {
+            grid: grid.id(),
+            chunk_cols,
+            chunk_rows,
+            portals: vec![],
+            portals_map: Default::default(),
+            chunk_portals: Default::default(),
+            edges: vec![],
+            segments: Default::default(),
+            wrap,
+        };
+        // vertical chunk borders.
+        for col in (chunk_cols..grid.cols).step_by(chunk_cols) {
+            result.add_entrances(
+                grid,
+                (0..grid.rows).map(|row| (col - 1, row, col, row)),
+                false,
+            );
+        }
+        if wrap.0 && grid.cols > 1 {
+            let last = grid.cols - 1;
+            result.add_entrances(grid, (0..grid.rows).map(|row| (last, row, 0, row)), true);
+        }
+        // horizontal chunk borders.
+        for row in (chunk_rows..grid.rows).step_by(chunk_rows) {
+            result.add_entrances(
+                grid,
+                (0..grid.cols).map(|col| (col, row - 1, col, row)),
+                false,
+            );
+        }
+        if wrap.1 && grid.rows > 1 {
+            let last = grid.rows - 1;
+            result.add_entrances(grid, (0..grid.cols).map(|col| (col, last, col, 0)), true);
+        }
+        let chunks = result.chunk_portals.clone();
+        for (chunk, portals) in chunks {
+            for from in portals.iter().copied() {
+                let (costs, parents) =
+                    result.search_chunk(grid, chunk, result.portals[from], false);
+                for to in portals.iter().copied() {
+                    if from == to {
+                        continue;
+                    }
+                    let cell = result.portals[to];
+                    if let Some(cost) = costs.get(&cell) {
+                        result.edges[from].push((to, *cost));
+                        let mut path = Self::unwind(&parents, cell);
+                        path.reverse();
+                        result.segments.insert((from, to), path);
+                    }
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    #[inline]
+    pub fn grid_id(&self) -> NavGridID {
+        self.grid
+    }
+
+    #[inline]
+    pub fn chunk_size(&self) -> (usize, usize) {
+        (self.chunk_cols, self.chunk_rows)
+    }
+
+    #[inline]
+    pub fn wrap(&self) -> (bool, bool) {
+        self.wrap
+    }
+
+    pub fn chunk(&self, col: usize, row: usize) -> NavGridChunk {
+        (col / self.chunk_cols, row / self.chunk_rows)
+    }
+
+    pub fn portals(&self, grid: &NavGrid) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let cols = grid.cols;
+        self.portals
+            .iter()
+            .map(move |index| (index % cols, index / cols))
+    }
+
+    #[inline]
+    pub fn cached_segments_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn find_path(
+        &self,
+        grid: &NavGrid,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<Vec<(usize, usize)>> {
+        if grid.id() != self.grid {
+            return None;
+        }
+        let start = grid.index(from.0, from.1)?;
+        let end = grid.index(to.0, to.1)?;
+        grid.nodes[start]?;
+        grid.nodes[end]?;
+        let start_chunk = self.chunk(from.0, from.1);
+        let end_chunk = self.chunk(to.0, to.1);
+        let (start_costs, start_parents) = self.search_chunk(grid, start_chunk, start, false);
+        let (end_costs, end_parents) = self.search_chunk(grid, end_chunk, end, true);
+        // abstract graph nodes: portals, then start and end.
+        let start_node = self.portals.len();
+        let end_node = start_node + 1;
+        let empty = vec![];
+        let factor = grid.heuristic_factor();
+        let (_, nodes) = astar(
+            start_node,
+            end_node,
+            |node, next| {
+                if node == start_node {
+                    if let Some(cost) = start_costs.get(&end) {
+                        next(end_node, *cost);
+                    }
+                    for portal in self.chunk_portals.get(&start_chunk).unwrap_or(&empty) {
+                        if let Some(cost) = start_costs.get(&self.portals[*portal]) {
+                            next(*portal, *cost);
+                        }
+                    }
+                } else if node != end_node {
+                    for (portal, weight) in &self.edges[node] {
+                        next(*portal, *weight);
+                    }
+                    if let Some(cost) = end_costs.get(&self.portals[node]) {
+                        if self.chunk_of_cell(grid, self.portals[node]) == end_chunk {
+                            next(end_node, *cost);
+                        }
+                    }
+                }
+            },
+            |node| match node {
+                node if node == start_node => factor * self.distance(grid, start, end),
+                node if node == end_node => 0.0,
+                node => factor * self.distance(grid, self.portals[node], end),
+            },
+        )?;
+        let mut cells = vec![];
+        for pair in nodes.windows(2) {
+            let segment = match (pair[0], pair[1]) {
+                (a, b) if a == start_node && b == end_node => {
+                    let mut path = Self::unwind(&start_parents, end);
+                    path.reverse();
+                    path
+                }
+                (a, b) if a == start_node => {
+                    let mut path = Self::unwind(&start_parents, self.portals[b]);
+                    path.reverse();
+                    path
+                }
+                (a, b) if b == end_node => Self::unwind(&end_parents, self.portals[a]),
+                (a, b) => match self.segments.get(&(a, b)) {
+                    Some(path) => path.clone(),
+                    None => vec![self.portals[a], self.portals[b]],
+                },
+            };
+            for cell in segment {
+                if cells.last() != Some(&cell) {
+                    cells.push(cell);
+                }
+            }
+        }
+        Some(
+            cells
+                .into_iter()
+                .filter_map(|index| grid.coord(index))
+                .collect(),
+        )
+    }
+
+    fn chunk_of_cell(&self, grid: &NavGrid, index: usize) -> NavGridChunk {
+        self.chunk(index % grid.cols, index / grid.cols)
+    }
+
+    fn add_portal(&mut self, grid: &NavGrid, index: usize) -> usize {
+        if let Some(portal) = self.portals_map.get(&index) {
+            return *portal;
+        }
+        let portal = self.portals.len();
+        self.portals.push(index);
+        self.portals_map.insert(index, portal);
+        self.edges.push(vec![]);
+        self.chunk_portals
+            .entry(self.chunk_of_cell(grid, index))
+            .or_default()
+            .push(portal);
+        portal
+    }
+
+    // Cells distance that takes wrapped borders into account.
+    fn distance(&self, grid: &NavGrid, from: usize, to: usize) -> Scalar {
+        let (fc, fr) = (from % grid.cols, from / grid.cols);
+        let (tc, tr) = (to % grid.cols, to / grid.cols);
+        let (mut dc, mut dr) = (fc.abs_diff(tc), fr.abs_diff(tr));
+        if self.wrap.0 {
+            dc = dc.min(grid.cols - dc);
+        }
+        if self.wrap.1 {
+            dr = dr.min(grid.rows - dr);
+        }
+        grid.offset_distance(dc, dr)
+    }
+
+    // Portals along border between pairs of cells, cells of `wrapped` border are not connected
+    // on grid itself. Adds single portal pair at the middle of each continuous run of connected
+    // border cells.
+    fn add_entrances(
+        &mut self,
+        grid: &NavGrid,
+        pairs: impl Iterator<Item = (usize, usize, usize, usize)>,
+        wrapped: bool,
+    ) {
+        let pairs = pairs
+            .map(|(ca, ra, cb, rb)| (ra * grid.cols + ca, rb * grid.cols + cb))
+            .collect::<Vec<_>>();
+        for (a, b) in [(0, 1), (1, 0)] {
+            let mut run = vec![];
+            for pair in pairs
+                .iter()
+                .map(|pair| [pair.0, pair.1])
+                .chain(Some([0, 0]))
+            {
+                let (from, to) = (pair[a], pair[b]);
+                let connected = from != to
+                    && grid.is_cell_open(to)
+                    && match (grid.nodes[from], grid.nodes[to]) {
+                        (Some(_), Some(_)) if wrapped => true,
+                        (Some(na), Some(nb)) => grid.graph.find_edge(na, nb).is_some(),
+                        _ => false,
+                    };
+                // runs are split where border crosses to another pair of chunks.
+                let same_chunk = run.last().map_or(true, |(last, _)| {
+                    self.chunk_of_cell(grid, *last) == self.chunk_of_cell(grid, from)
+                });
+                if (!connected || !same_chunk) && !run.is_empty() {
+                    let (from, to) = run[run.len() / 2];
+                    let pa = self.add_portal(grid, from);
+                    let pb = self.add_portal(grid, to);
+                    let cost = grid.step_cost(from, to);
+                    self.edges[pa].push((pb, cost));
+                    run.clear();
+                }
+                if connected {
+                    run.push((from, to));
+                }
+            }
+        }
+    }
+
+    // Dijkstra search limited to single chunk, going against edges if `reverse` is set.
+    fn search_chunk(
+        &self,
+        grid: &NavGrid,
+        chunk: NavGridChunk,
+        from: usize,
+        reverse: bool,
+    ) -> (HashMap<usize, Scalar>, HashMap<usize, usize>) {
+        let direction = if reverse {
+            Direction::Incoming
+        } else {
+            Direction::Outgoing
+        };
+        let mut costs = HashMap::new();
+        let mut parents = HashMap::new();
+        let mut open = BinaryHeap::new();
+        costs.insert(from, 0.0);
+        open.push(NavSearchItem(0.0, from));
+        while let Some(NavSearchItem(cost, index)) = open.pop() {
+            if cost > costs.get(&index).copied().unwrap_or(SCALAR_MAX) {
+                continue;
+            }
+            let node = match grid.nodes[index] {
+                Some(node) => node,
+                None => continue,
+            };
+            for next in grid.graph.neighbors_directed(node, direction) {
+                let next = grid.nodes_map[&next];
+                if self.chunk_of_cell(grid, next) != chunk {
+                    continue;
+                }
+                let entered = if reverse { index } else { next };
+                if !grid.is_cell_open(entered) {
+                    continue;
+                }
+                let next_cost = cost + grid.step_cost(index, next);
+                if next_cost < costs.get(&next).copied().unwrap_or(SCALAR_MAX) {
+                    costs.insert(next, next_cost);
+                    parents.insert(next, index);
+                    open.push(NavSearchItem(next_cost, next));
+                }
+            }
+        }
+        (costs, parents)
+    }
+
+    // cells from `index` back to search origin.
+    fn unwind(parents: &HashMap<usize, usize>, mut index: usize) -> Vec<usize> {
+        let mut result = vec![index];
+        while let Some(parent) = parents.get(&index) {
+            index = *parent;
+            result.push(index);
+        }
+        result
+    }
+}
+
+// (cell, other grid index, other grid cell).
+type NavGridTouch = ((usize, usize), usize, (usize, usize));
+
+/// Builds nav islands out of nav grids placed in the world at cell offsets, one island per grid.
+/// Walkable border cells that touch walkable cells of another grid become portals connected
+/// with each other, and portals of the same grid are connected with their path costs.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let left = NavGrid::new(2, 1, vec![true, true]).unwrap();
+/// let right = NavGrid::new(2, 1, vec![true, true]).unwrap();
+/// let islands = NavGridIslandsBuilder::default()
+///     .grid(&left, (0, 0))
+///     .grid(&right, (2, 0))
+///     .build();
+/// let from = NavIslandPortal {
+///     island: left.id(),
+///     portal: Some((1, 0)),
+/// };
+/// let to = NavIslandPortal {
+///     island: right.id(),
+///     portal: Some((0, 0)),
+/// };
+/// assert_eq!(islands.find_path(&from, &to).unwrap().1, vec![&from, &to]);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct NavGridIslandsBuilder<'a> {
+    grids: Vec<(&'a NavGrid, (isize, isize))>,
+}
+
+impl<'a> NavGridIslandsBuilder<'a> {
+    /// Add grid with world offset (in cells) of its first cell.
+    pub fn grid(mut self, grid: &'a NavGrid, offset: (isize, isize)) -> Self {
+        self.grids.push((grid, offset));
+        self
+    }
+
+    pub fn connections(&self) -> Vec<NavIslandsConnection<NavGridID, (usize, usize)>> {
+        let portal = |grid: &NavGrid, cell| NavIslandPortal {
+            island: grid.id(),
+            portal: Some(cell),
+        };
+        let mut result = vec![];
+        let mut portals = vec![vec![]; self.grids.len()];
+        for (index, (grid, _)) in self.grids.iter().enumerate() {
+            for (cell, other_index, other_cell) in self.touching_cells(index) {
+                if !portals[index].contains(&cell) {
+                    portals[index].push(cell);
+                }
+                // crossing between grids costs the same as step between cells of one grid.
+                let other = self.grids[other_index].0;
+                let distance = grid.costs[cell.1 * grid.cols + cell.0]
+                    * other.costs[other_cell.1 * other.cols + other_cell.0];
+                result.push(NavIslandsConnection {
+                    from: portal(grid, cell),
+                    to: portal(other, other_cell),
+                    distance,
+                });
+            }
+        }
+        for ((grid, _), portals) in self.grids.iter().zip(portals.iter()) {
+            for from in portals.iter().copied() {
+                let distances = grid.distance_map(&[from]);
+                for to in portals.iter().copied() {
+                    if from == to {
+                        continue;
+                    }
+                    if let Some(distance) = distances[to.1 * grid.cols + to.0] {
+                        result.push(NavIslandsConnection {
+                            from: portal(grid, from),
+                            to: portal(grid, to),
+                            distance,
+                        });
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    pub fn build(&self) -> NavIslands<NavGridID, (usize, usize)> {
+        NavIslands::new(self.connections(), false)
+    }
+
+    // walkable border cells touching walkable cells of other grids.
+    fn touching_cells(&self, index: usize) -> Vec<NavGridTouch> {
+        let (grid, (ox, oy)) = self.grids[index];
+        let mut result = vec![];
+        for row in 0..grid.rows {
+            for col in 0..grid.cols {
+                let border = col == 0 || row == 0 || col + 1 == grid.cols || row + 1 == grid.rows;
+                if !border || !grid.cells[row * grid.cols + col] {
+                    continue;
+                }
+                let (x, y) = (ox + col as isize, oy + row as isize);
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let (x, y) = (x + dx, y + dy);
+                    for (other_index, (other, (px, py))) in self.grids.iter().enumerate() {
+                        if other_index == index {
+                            continue;
+                        }
+                        let (other_col, other_row) = (x - px, y - py);
+                        if other_col < 0
+                            || other_row < 0
+                            || other_col as usize >= other.cols
+                            || other_row as usize >= other.rows
+                        {
+                            continue;
+                        }
+                        let other_cell = (other_col as usize, other_row as usize);
+                        if other.cells[other_cell.1 * other.cols + other_cell.0] {
+                            result.push(((col, row), other_index, other_cell));
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Resolves paths of nav islands built by this builder, where world points are cells
+/// coordinates offset by grids offsets. Portals without cell have no world position.
+impl NavIslandResolver<NavGridID, (usize, usize)> for NavGridIslandsBuilder<'_> {
+    fn portal_point(
+        &mut self,
+        portal: &NavIslandPortal<NavGridID, (usize, usize)>,
+    ) -> Option<NavVec3> {
+        let (_, (ox, oy)) = self
+            .grids
+            .iter()
+            .find(|(grid, _)| grid.id() == portal.island)?;
+        let (col, row) = portal.portal?;
+        Some(NavVec3::new(
+            (ox + col as isize) as Scalar,
+            (oy + row as isize) as Scalar,
+            0.0,
+        ))
+    }
+
+    fn find_path(
+        &mut self,
+        island: &NavGridID,
+        from: NavVec3,
+        to: NavVec3,
+    ) -> Option<Vec<NavVec3>> {
+        let (grid, (ox, oy)) = *self.grids.iter().find(|(grid, _)| grid.id() == *island)?;
+        let cell = |point: NavVec3| {
+            let col = point.x.round() as isize - ox;
+            let row = point.y.round() as isize - oy;
+            (col >= 0 && row >= 0).then_some((col as usize, row as usize))
+        };
+        let path = grid.find_path(cell(from)?, cell(to)?)?;
+        Some(
+            path.into_iter()
+                .map(|(col, row)| {
+                    NavVec3::new(
+                        (ox + col as isize) as Scalar,
+                        (oy + row as isize) as Scalar,
+                        0.0,
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Nav chunked grid identifier.
+pub type NavChunkedGridID = ID<NavChunkedGrid>;
+
+/// Very large nav grid composed of fixed-size chunks (regular nav grids) that can be loaded and
+/// unloaded at runtime. Cells are addressed with global col-row coordinates and paths can cross
+/// chunk borders, but only through loaded chunks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavChunkedGrid {
+    id: NavChunkedGridID,
+    chunk_cols: usize,
+    chunk_rows: usize,
+    chunks: HashMap<NavGridChunk, NavGrid>,
+}
+
+impl NavChunkedGrid {
+    pub fn new(chunk_cols: usize, chunk_rows: usize) -> NavResult<Self> {
+        if chunk_cols == 0 || chunk_rows == 0 {
+            return Err(Error::EmptyChunk(chunk_cols, chunk_rows));
+        }
+        Ok(Self {
+            id: NavChunkedGridID::new(),
+            chunk_cols,
+            chunk_rows,
+            chunks: Default::default(),
+        })
+    }
+
+    #[inline]
+    pub fn id(&self) -> NavChunkedGridID {
+        self.id
+    }
+
+    #[inline]
+    pub fn chunk_size(&self) -> (usize, usize) {
+        (self.chunk_cols, self.chunk_rows)
+    }
+
+    /// Chunk containing cell with given global coordinate.
+    #[inline]
+    pub fn chunk_of(&self, col: usize, row: usize) -> NavGridChunk {
+        (col / self.chunk_cols, row / self.chunk_rows)
+    }
+
+    /// Load chunk grid (i.e. deserialized from chunk file), replacing and returning already
+    /// loaded grid of that chunk.
+    pub fn load_chunk(&mut self, chunk: NavGridChunk, grid: NavGrid) -> NavResult<Option<NavGrid>> {
+        if grid.cols != self.chunk_cols || grid.rows != self.chunk_rows {
+            return Err(Error::ChunkSizeMismatch(
+                grid.cols,
+                grid.rows,
+                self.chunk_cols,
+                self.chunk_rows,
+            ));
+        }
+        Ok(self.chunks.insert(chunk, grid))
+    }
+
+    /// Unload chunk grid, returning it so it can be serialized.
+    pub fn unload_chunk(&mut self, chunk: NavGridChunk) -> Option<NavGrid> {
+        self.chunks.remove(&chunk)
+    }
+
+    #[inline]
+    pub fn chunk(&self, chunk: NavGridChunk) -> Option<&NavGrid> {
+        self.chunks.get(&chunk)
+    }
+
+    #[inline]
+    pub fn chunk_mut(&mut self, chunk: NavGridChunk) -> Option<&mut NavGrid> {
+        self.chunks.get_mut(&chunk)
+    }
+
+    pub fn loaded_chunks(&self) -> impl Iterator<Item = NavGridChunk> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    #[inline]
+    pub fn is_chunk_loaded(&self, chunk: NavGridChunk) -> bool {
+        self.chunks.contains_key(&chunk)
+    }
+
+    /// Tells if cell is walkable, cells of unloaded chunks are not.
+    pub fn is_walkable(&self, col: usize, row: usize) -> bool {
+        self.local(col, row)
+            .map(|(grid, index)| grid.cells[index])
+            .unwrap_or(false)
+    }
+
+    /// Find path between global cell coordinates, going through loaded chunks only.
+    pub fn find_path(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<Vec<(usize, usize)>> {
+        if !self.is_walkable(from.0, from.1) || !self.is_walkable(to.0, to.1) {
+            return None;
+        }
+        let min_cost = self
+            .chunks
+            .values()
+            .map(|grid| grid.min_cell_cost())
+            .fold(SCALAR_MAX, Scalar::min);
+        let max_step = self
+            .chunks
+            .values()
+            .map(|grid| grid.max_step)
+            .fold(1, usize::max);
+        let min_connection_cost = self
+            .chunks
+            .values()
+            .map(|grid| grid.min_connection_cost())
+            .fold(1.0, Scalar::min);
+        let factor = if self.chunks.values().any(|grid| grid.max_step == 0) {
+            0.0
+        } else {
+            min_cost * min_cost * min_connection_cost / max_step as Scalar
+        };
+        // octile distance never overestimates both 4-way and 8-way steps.
+        let heuristic = |cell: (usize, usize)| {
+            let (dc, dr) = (cell.0.abs_diff(to.0), cell.1.abs_diff(to.1));
+            let diagonal = dc.min(dr);
+            factor * ((dc.max(dr) - diagonal) as Scalar + diagonal as Scalar * DIAGONAL_COST)
+        };
+        astar_keyed(
+            from,
+            to,
+            |cell, next| {
+                for (cell, step) in self.neighbors_with_costs(cell) {
+                    next(cell, step);
+                }
+            },
+            heuristic,
+        )
+        .map(|(_, path)| path)
+    }
+
+    fn local(&self, col: usize, row: usize) -> Option<(&NavGrid, usize)> {
+        let grid = self.chunks.get(&self.chunk_of(col, row))?;
+        let index = grid.index(col % self.chunk_cols, row % self.chunk_rows)?;
+        Some((grid, index))
+    }
+
+    // Connected cells with step costs, including connections across chunk borders.
+    fn neighbors_with_costs(&self, cell: (usize, usize)) -> Vec<((usize, usize), Scalar)> {
+        let (grid, index) = match self.local(cell.0, cell.1) {
+            Some(local) => local,
+            None => return vec![],
+        };
+        let (base_col, base_row) = (cell.0 - index % grid.cols, cell.1 - index / grid.cols);
+        let mut result = match grid.nodes[index] {
+            Some(node) => grid
+                .graph
+                .neighbors(node)
+                .map(|node| grid.nodes_map[&node])
+                .filter(|next| grid.is_cell_open(*next))
+                .map(|next| {
+                    let global = (base_col + next % grid.cols, base_row + next / grid.cols);
+                    (global, grid.step_cost(index, next))
+                })
+                .collect::<Vec<_>>(),
+            None => return vec![],
+        };
+        let offsets: &[(isize, isize)] = match grid.connectivity {
+            NavGridConnectivity::Four => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+            NavGridConnectivity::Eight => &[
+                (-1, 0),
+                (1, 0),
+                (0, -1),
+                (0, 1),
+                (-1, -1),
+                (1, -1),
+                (-1, 1),
+                (1, 1),
+            ],
+        };
+        let chunk = self.chunk_of(cell.0, cell.1);
+        for (dc, dr) in offsets {
+            let (Some(col), Some(row)) = (
+                cell.0.checked_add_signed(*dc),
+                cell.1.checked_add_signed(*dr),
+            ) else {
+                continue;
+            };
+            if self.chunk_of(col, row) == chunk {
+                continue;
+            }
+            let Some((other, next)) = self.local(col, row) else {
+                continue;
+            };
+            if !other.cells[next] || !other.is_cell_open(next) {
+                continue;
+            }
+            let diagonal = *dc != 0 && *dr != 0;
+            if diagonal && !(self.is_walkable(col, cell.1) && self.is_walkable(cell.0, row)) {
+                continue;
+            }
+            let mut cost = grid.costs[index] * other.costs[next];
+            if diagonal {
+                cost *= DIAGONAL_COST;
+            }
+            result.push(((col, row), cost));
+        }
+        result
+    }
+}
+
+/// Cell of layered nav grid: (layer, col, row).
+pub type NavGridLayerCell = (usize, usize, usize);
+
+/// Connection between cells of nav grid layers, i.e. stairs or elevator.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavGridLayerConnection {
+    pub from: NavGridLayerCell,
+    pub to: NavGridLayerCell,
+    /// Cost of traversing connection.
+    pub cost: Scalar,
+    /// Connection can be traversed from `to` cell to `from` cell too.
+    pub bidirectional: bool,
+}
+
+/// Nav grid layer connection identifier.
+pub type NavGridLayerConnectionID = ID<NavGridLayerConnection>;
+
+/// Nav layered grid identifier.
+pub type NavLayeredGridID = ID<NavLayeredGrid>;
+
+/// Stack of nav grids (i.e. building floors) connected with explicit connections between
+/// their cells. Layers can have different sizes.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NavLayeredGrid {
+    id: NavLayeredGridID,
+    layers: Vec<NavGrid>,
+    connections: Vec<(NavGridLayerConnectionID, NavGridLayerConnection)>,
+}
+
+impl NavLayeredGrid {
+    pub fn new(layers: Vec<NavGrid>) -> Self {
+        Self {
+            id: NavLayeredGridID::new(),
+            layers,
+            connections: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> NavLayeredGridID {
+        self.id
+    }
+
+    #[inline]
+    pub fn layers(&self) -> &[NavGrid] {
+        &self.layers
+    }
+
+    #[inline]
+    pub fn layer(&self, layer: usize) -> Option<&NavGrid> {
+        self.layers.get(layer)
+    }
+
+    #[inline]
+    pub fn layer_mut(&mut self, layer: usize) -> Option<&mut NavGrid> {
+        self.layers.get_mut(layer)
+    }
+
+    pub fn connections(
+        &self,
+    ) -> impl Iterator<Item = (NavGridLayerConnectionID, &NavGridLayerConnection)> {
+        self.connections
+            .iter()
+            .map(|(id, connection)| (*id, connection))
+    }
+
+    pub fn connection(&self, id: NavGridLayerConnectionID) -> Option<&NavGridLayerConnection> {
+        self.connections
+            .iter()
+            .find(|(i, _)| *i == id)
+            .map(|(_, connection)| connection)
+    }
+
+    /// Add connection between layers cells, returning its identifier.
+    pub fn add_connection(
+        &mut self,
+        connection: NavGridLayerConnection,
+    ) -> NavResult<NavGridLayerConnectionID> {
+        for (layer, col, row) in [connection.from, connection.to] {
+            let grid = self
+                .layers
+                .get(layer)
+                .ok_or(Error::InvalidLayer(layer, self.layers.len()))?;
+            if grid.index(col, row).is_none() {
+                return Err(Error::InvalidCellCoordinate(col, row, grid.cols, grid.rows));
+            }
+        }
+        let id = NavGridLayerConnectionID::new();
+        self.connections.push((id, connection));
+        Ok(id)
+    }
+
+    pub fn remove_connection(
+        &mut self,
+        id: NavGridLayerConnectionID,
+    ) -> Option<NavGridLayerConnection> {
+        let position = self.connections.iter().position(|(i, _)| *i == id)?;
+        Some(self.connections.swap_remove(position).1)
+    }
+
+    /// Tells if cell is walkable and not behind closed door.
+    pub fn is_walkable(&self, cell: NavGridLayerCell) -> bool {
+        self.local(cell)
+            .map(|(grid, index)| grid.cells[index] && grid.is_cell_open(index))
+            .unwrap_or(false)
+    }
+
+    /// Find path between layers cells. Layers do not share coordinates space, so search is
+    /// not guided by heuristic.
+    pub fn find_path(
+        &self,
+        from: NavGridLayerCell,
+        to: NavGridLayerCell,
+    ) -> Option<Vec<NavGridLayerCell>> {
+        if !self.is_walkable(from) || !self.is_walkable(to) {
+            return None;
+        }
+        let mut links = HashMap::<_, Vec<_>>::new();
+        for (_, connection) in &self.connections {
+            links
+                .entry(connection.from)
+                .or_default()
+                .push((connection.to, connection.cost));
+            if connection.bidirectional {
+                links
+                    .entry(connection.to)
+                    .or_default()
+                    .push((connection.from, connection.cost));
+            }
+        }
+        astar_keyed(
+            from,
+            to,
+            |cell, next| {
+                let Some((grid, index)) = self.local(cell) else {
+                    return;
+                };
+                for node in grid.nodes[index]
+                    .into_iter()
+                    .flat_map(|node| grid.graph.neighbors(node))
+                {
+                    let other = grid.nodes_map[&node];
+                    if grid.is_cell_open(other) {
+                        let cell = (cell.0, other % grid.cols, other / grid.cols);
+                        next(cell, grid.step_cost(index, other));
+                    }
+                }
+                for (cell, cost) in links.get(&cell).into_iter().flatten() {
+                    if self.is_walkable(*cell) {
+                        next(*cell, *cost);
+                    }
+                }
+            },
+            |_| 0.0,
+        )
+        .map(|(_, path)| path)
+    }
+
+    fn local(&self, (layer, col, row): NavGridLayerCell) -> Option<(&NavGrid, usize)> {
+        let grid = self.layers.get(layer)?;
+        let index = grid.index(col, row)?;
+        Some((grid, index))
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NavFreeGridConnection {
+    pub from: (isize, isize),
+    pub to: (isize, isize),
+}
+
+/// Nav free grid identifier.
+pub type NavFreeGridID = ID<NavFreeGrid>;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NavFreeGrid {
+    id: NavFreeGridID,
+    cells: Vec<(isize, isize)>,
+    // index of every cell, rebuilt on first lookup after deserialization.
+    #[serde(skip)]
+    cells_map: OnceLock<HashMap<(isize, isize), usize>>,
+    costs: Vec<Scalar>,
+    graph: Graph<(), (), Undirected>,
+    nodes: Vec<NodeIndex>,
+    nodes_map: HashMap<NodeIndex, usize>,
+    // explicit world position of each cell.
+    #[serde(default)]
+    positions: Vec<Option<NavVec3>>,
+    #[serde(default)]
+    cell_size: Option<(Scalar, Scalar)>,
+}
+
+impl NavFreeGrid {
+    pub fn new(connections: Vec<NavFreeGridConnection>) -> Self {
+        let cells = connections
+            .iter()
+            .map(|c| c.from)
+            .chain(connections.iter().map(|c| c.to))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let cells_map = Self::map_cells(&cells);
+        let costs = vec![1.0; cells.len()];
+        let mut graph = Graph::<(), (), Undirected>::with_capacity(cells.len(), connections.len());
+        let nodes = (0..cells.len())
+            .map(|_| graph.add_node(()))
+            .collect::<Vec<_>>();
+        for connection in connections {
+            let ia = cells_map[&connection.from];
+            let ib = cells_map[&connection.to];
+            graph.add_edge(nodes[ia], nodes[ib], ());
+        }
+        let nodes_map = iter!(nodes).enumerate().map(|(i, n)| (*n, i)).collect();
+        Self {
+            id: NavFreeGridID::new(),
+            cells,
+            cells_map: OnceLock::from(cells_map),
+            costs,
+            graph,
+            nodes,
+            nodes_map,
+            positions: vec![],
+            cell_size: None,
+        }
+    }
+
+    #[inline]
+    pub fn id(&self) -> NavFreeGridID {
+        self.id
+    }
+
+    #[inline]
+    pub fn cells(&self) -> &[(isize, isize)] {
+        &self.cells
+    }
+
+    #[inline]
+    pub fn cells_costs(&self) -> &[Scalar] {
+        &self.costs
+    }
+
+    #[inline]
+    pub fn set_cell_cost(&mut self, col: isize, row: isize, cost: Scalar) -> Option<Scalar> {
+        let index = self.index(col, row)?;
+        let c = self.costs.get_mut(index)?;
+        let old = *c;
+        *c = cost.max(0.0);
+        Some(old)
+    }
+
+    /// Set explicit world position of cell, overriding position calculated from cell size.
+    /// Returns old explicit position or `None` if cell does not exist.
+    pub fn set_cell_position(
+        &mut self,
+        col: isize,
+        row: isize,
+        position: Option<NavVec3>,
+    ) -> Option<Option<NavVec3>> {
+        let index = self.index(col, row)?;
+        self.positions.resize(self.cells.len(), None);
+        Some(std::mem::replace(&mut self.positions[index], position))
+    }
+
+    #[inline]
+    pub fn cell_size(&self) -> Option<(Scalar, Scalar)> {
+        self.cell_size
+    }
+
+    /// Set size of cells on XY plane, used to calculate world position of cells without
+    /// explicit position.
+    #[inline]
+    pub fn set_cell_size(&mut self, cell_size: Option<(Scalar, Scalar)>) {
+        self.cell_size = cell_size;
+    }
+
+    /// World position of cell: explicit one if set, otherwise calculated from cell size.
+    pub fn cell_position(&self, col: isize, row: isize) -> Option<NavVec3> {
+        self.position(self.index(col, row)?)
+    }
+
+    /// Find cell with world position closest to given point.
+    pub fn nearest_cell(&self, point: NavVec3) -> Option<(isize, isize)> {
+        (0..self.cells.len())
+            .filter_map(|index| Some((index, (self.position(index)? - point).sqr_magnitude())))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(index, _)| self.cells[index])
+    }
+
+    /// Find path between cells nearest to given world points, going only through cells that
+    /// have world position. Step cost is distance between cells positions multiplied by their
+    /// costs, and search is guided by Euclidean distance heuristic.
+    ///
+    /// # Returns
+    /// `Some` with world positions of path cells or `None` if path is not found.
+    pub fn find_path_world(&self, from: NavVec3, to: NavVec3) -> Option<Vec<NavVec3>> {
+        let (col, row) = self.nearest_cell(from)?;
+        let start_index = self.index(col, row)?;
+        let (col, row) = self.nearest_cell(to)?;
+        let end_index = self.index(col, row)?;
+        let goal = self.position(end_index)?;
+        let min_cost = (0..self.cells.len())
+            .filter(|index| self.position(*index).is_some())
+            .map(|index| self.costs[index])
+            .fold(SCALAR_MAX, Scalar::min);
+        let indices = self.search(
+            start_index,
+            end_index,
+            |a, b| {
+                let distance = (self.position(b)? - self.position(a)?).magnitude();
+                Some(distance * self.costs[a] * self.costs[b])
+            },
+            |index| {
+                self.position(index)
+                    .map(|position| (goal - position).magnitude() * min_cost * min_cost)
+                    .unwrap_or(0.0)
+            },
+        )?;
+        indices
+            .into_iter()
+            .map(|index| self.position(index))
+            .collect()
+    }
+
+    // A* over cells indices with steps of `None` cost skipped, giving indices of path cells.
+    fn search<C, H>(
+        &self,
+        start: usize,
+        goal: usize,
+        mut cost: C,
+        heuristic: H,
+    ) -> Option<Vec<usize>>
+    where
+        C: FnMut(usize, usize) -> Option<Scalar>,
+        H: FnMut(usize) -> Scalar,
+    {
+        astar(
+            start,
+            goal,
+            |a, next| {
+                for node in self.graph.neighbors(self.nodes[a]) {
+                    let b = self.nodes_map[&node];
+                    if let Some(cost) = cost(a, b) {
+                        next(b, cost);
+                    }
+                }
+            },
+            heuristic,
+        )
+        .map(|(_, indices)| indices)
+    }
+
+    fn position(&self, index: usize) -> Option<NavVec3> {
+        if let Some(position) = self.positions.get(index).copied().flatten() {
+            return Some(position);
+        }
+        let (col, row) = self.cells.get(index)?;
+        let (width, height) = self.cell_size?;
+        Some(NavVec3::new(
+            *col as Scalar * width,
+            *row as Scalar * height,
+            0.0,
+        ))
+    }
+
+    pub fn neighbors(
+        &self,
+        col: isize,
+        row: isize,
+    ) -> Option<impl Iterator<Item = (isize, isize)> + '_> {
+        let index = self.index(col, row)?;
+        let node = self.nodes[index];
+        Some(self.graph.neighbors(node).filter_map(|node| {
+            self.nodes_map
+                .get(&node)
+                .and_then(|index| self.coord(*index))
+        }))
+    }
+
+    pub fn find_path(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+    ) -> Option<Vec<(isize, isize)>> {
+        self.find_path_custom(from, to, |_, _| true)
+    }
+
+    // filter params: first col-row, second col-row.
+    pub fn find_path_custom<F>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        filter: F,
+    ) -> Option<Vec<(isize, isize)>>
+    where
+        F: FnMut((isize, isize), (isize, isize)) -> bool,
+    {
+        self.search_filtered(from, to, penalize_rejected(filter))
+    }
+
+    /// Same as `find_path_custom` but steps rejected by filter are excluded from search instead
+    /// of getting maximal cost, so path may not be found at all.
+    // filter params: first col-row, second col-row.
+    pub fn find_path_excluding<F>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        mut filter: F,
+    ) -> Option<Vec<(isize, isize)>>
+    where
+        F: FnMut((isize, isize), (isize, isize)) -> bool,
+    {
+        self.search_filtered(from, to, |a, b| filter(a, b).then_some(0.0))
+    }
+
+    // filter gives extra cost of step, `None` excludes it from search.
+    fn search_filtered<F>(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        mut filter: F,
+    ) -> Option<Vec<(isize, isize)>>
+    where
+        F: FnMut((isize, isize), (isize, isize)) -> Option<Scalar>,
+    {
+        let start_index = self.index(from.0, from.1)?;
+        let end_index = self.index(to.0, to.1)?;
+        let indices = self.search(
+            start_index,
+            end_index,
+            |a, b| {
+                let extra = filter(self.coord(a)?, self.coord(b)?)?;
+                Some(self.costs[a] * self.costs[b] + extra)
+            },
+            |_| 0.0,
+        )?;
+        Some(
+            indices
+                .into_iter()
+                .filter_map(|index| self.coord(index))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Find path using influence map as additional cost layer, where each step cost gets
+    /// multiplied by `1 + influence * weight` of target cell (clamped at zero). Positive weight
+    /// avoids influence, negative weight attracts.
+    pub fn find_path_weighted(
+        &self,
+        from: (isize, isize),
+        to: (isize, isize),
+        influence: &NavInfluenceMap,
+        weight: Scalar,
+    ) -> Option<Vec<(isize, isize)>> {
+        let start_index = self.index(from.0, from.1)?;
+        let end_index = self.index(to.0, to.1)?;
+        // cells connections are arbitrary, so all that is known is that goal is at least one
+        // step away.
+        let min_cost = self.costs.iter().copied().fold(SCALAR_MAX, Scalar::min);
+        let min_step = min_cost * min_cost * influence.min_cost_factor(weight);
+        let indices = self.search(
+            start_index,
+            end_index,
+            |a, b| {
+                let (col, row) = self.coord(b)?;
+                Some(self.costs[a] * self.costs[b] * influence.cost_factor(col, row, weight))
+            },
+            |index| if index == end_index { 0.0 } else { min_step },
+        )?;
+        Some(
+            indices
+                .into_iter()
+                .filter_map(|index| self.coord(index))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    pub fn find_islands(&self) -> Vec<Vec<(isize, isize)>> {
         tarjan_scc(&self.graph)
             .into_iter()
             .map(|v| {
@@ -442,11 +3084,30 @@ impl NavFreeGrid {
     }
 
     pub fn index(&self, col: isize, row: isize) -> Option<usize> {
-        let coord = (col, row);
-        self.cells.iter().position(|c| coord == *c)
+        self.cells_map
+            .get_or_init(|| Self::map_cells(&self.cells))
+            .get(&(col, row))
+            .copied()
+    }
+
+    fn map_cells(cells: &[(isize, isize)]) -> HashMap<(isize, isize), usize> {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| (*cell, index))
+            .collect()
     }
 
     pub fn coord(&self, index: usize) -> Option<(isize, isize)> {
         self.cells.get(index).copied()
     }
 }
+
+// Turns filter of `find_path_custom` into search filter that gives rejected steps maximal cost
+// instead of excluding them from search.
+fn penalize_rejected<C, F>(mut filter: F) -> impl FnMut(C, C) -> Option<Scalar>
+where
+    F: FnMut(C, C) -> bool,
+{
+    move |a, b| Some(if filter(a, b) { 0.0 } else { SCALAR_MAX })
+}
@@ -0,0 +1,74 @@
+use crate::{NavVec3, Scalar};
+
+/// Estimate of cost of reaching search goal, used to guide path finding towards it. To keep
+/// found paths shortest, estimate must never be bigger than actual cost of the best path.
+///
+/// Nav mesh and nav net connection costs are squared distances (scaled by area costs), which
+/// straight line distance can overestimate, so their default query methods use
+/// `NavZeroHeuristic`. Closures taking the same arguments as `estimate` are heuristics too.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// // precomputed lower bounds of cost between regions of two vertices each.
+/// let regions = [[0.0, 2.0], [2.0, 0.0]];
+/// let heuristic = |from: usize, to: usize, _: NavVec3, _: NavVec3| regions[from / 2][to / 2];
+/// assert_eq!(heuristic.estimate(1, 3, NavVec3::default(), NavVec3::default()), 2.0);
+///
+/// let euclidean = NavEuclideanHeuristic { scale: 0.5 };
+/// assert_eq!(
+///     euclidean.estimate(0, 1, (0.0, 0.0, 0.0).into(), (0.0, 4.0, 0.0).into()),
+///     2.0,
+/// );
+/// ```
+pub trait NavHeuristic {
+    /// Estimate cost of reaching goal.
+    ///
+    /// # Arguments
+    /// * `from` - index of search node (nav mesh triangle or nav net vertice).
+    /// * `to` - index of goal node.
+    /// * `from_point` - position of search node (triangle center or vertice point).
+    /// * `to_point` - position of goal node.
+    fn estimate(&self, from: usize, to: usize, from_point: NavVec3, to_point: NavVec3) -> Scalar;
+}
+
+/// Heuristic that does not guide search at all (turns A* into Dijkstra search).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct NavZeroHeuristic;
+
+impl NavHeuristic for NavZeroHeuristic {
+    #[inline]
+    fn estimate(&self, _: usize, _: usize, _: NavVec3, _: NavVec3) -> Scalar {
+        0.0
+    }
+}
+
+/// Straight line distance between nodes multiplied by scale.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NavEuclideanHeuristic {
+    pub scale: Scalar,
+}
+
+impl Default for NavEuclideanHeuristic {
+    fn default() -> Self {
+        Self { scale: 1.0 }
+    }
+}
+
+impl NavHeuristic for NavEuclideanHeuristic {
+    #[inline]
+    fn estimate(&self, _: usize, _: usize, from_point: NavVec3, to_point: NavVec3) -> Scalar {
+        (to_point - from_point).magnitude() * self.scale
+    }
+}
+
+impl<F> NavHeuristic for F
+where
+    F: Fn(usize, usize, NavVec3, NavVec3) -> Scalar,
+{
+    #[inline]
+    fn estimate(&self, from: usize, to: usize, from_point: NavVec3, to_point: NavVec3) -> Scalar {
+        self(from, to, from_point, to_point)
+    }
+}
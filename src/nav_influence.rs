@@ -0,0 +1,163 @@
+use crate::{Scalar, ZERO_TRESHOLD};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Operation used to combine influence maps.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavInfluenceCombine {
+    Add,
+    Subtract,
+    Multiply,
+    Max,
+    Min,
+}
+
+impl NavInfluenceCombine {
+    fn apply(self, a: Scalar, b: Scalar) -> Scalar {
+        match self {
+            Self::Add => a + b,
+            Self::Subtract => a - b,
+            Self::Multiply => a * b,
+            Self::Max => a.max(b),
+            Self::Min => a.min(b),
+        }
+    }
+}
+
+/// Sparse map of influence values layered over `NavGrid` or `NavFreeGrid` cells (cells of
+/// `NavGrid` are addressed with the same col-row coordinates). Cells not stored have zero
+/// influence.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mut threat = NavInfluenceMap::default();
+/// threat.stamp((2, 2), 2.0, 4.0);
+/// assert_eq!(threat.value(2, 2), 4.0);
+/// assert_eq!(threat.value(3, 2), 2.0);
+/// assert_eq!(threat.value(4, 2), 0.0);
+/// threat.decay(0.5);
+/// assert_eq!(threat.value(2, 2), 2.0);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavInfluenceMap {
+    values: HashMap<(isize, isize), Scalar>,
+}
+
+impl NavInfluenceMap {
+    #[inline]
+    pub fn value(&self, col: isize, row: isize) -> Scalar {
+        self.values.get(&(col, row)).copied().unwrap_or(0.0)
+    }
+
+    pub fn set_value(&mut self, col: isize, row: isize, value: Scalar) {
+        if value.abs() < ZERO_TRESHOLD {
+            self.values.remove(&(col, row));
+        } else {
+            self.values.insert((col, row), value);
+        }
+    }
+
+    /// Iterator over cells with non-zero influence.
+    pub fn values(&self) -> impl Iterator<Item = ((isize, isize), Scalar)> + '_ {
+        self.values.iter().map(|(coord, value)| (*coord, *value))
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Add influence source that linearly fades from `strength` at center cell to zero at
+    /// `radius` distance.
+    pub fn stamp(&mut self, center: (isize, isize), radius: Scalar, strength: Scalar) {
+        if radius <= 0.0 {
+            return;
+        }
+        let extent = radius.ceil() as isize;
+        for row in (center.1 - extent)..=(center.1 + extent) {
+            for col in (center.0 - extent)..=(center.0 + extent) {
+                let dc = (col - center.0) as Scalar;
+                let dr = (row - center.1) as Scalar;
+                let distance = (dc * dc + dr * dr).sqrt();
+                if distance < radius {
+                    let value = self.value(col, row) + strength * (1.0 - distance / radius);
+                    self.set_value(col, row, value);
+                }
+            }
+        }
+    }
+
+    /// Scale all influence values, i.e. to fade influence over time.
+    pub fn decay(&mut self, factor: Scalar) {
+        for value in self.values.values_mut() {
+            *value *= factor;
+        }
+        self.values.retain(|_, value| value.abs() >= ZERO_TRESHOLD);
+    }
+
+    /// Spread influence to neighbor cells, blending each cell value with average of its 4
+    /// neighbors by `amount` in range `[0; 1]`.
+    pub fn blur(&mut self, amount: Scalar) {
+        let amount = amount.clamp(0.0, 1.0);
+        let mut coords = self.values.keys().copied().collect::<Vec<_>>();
+        coords.extend(self.values.keys().flat_map(|(col, row)| {
+            [
+                (col - 1, *row),
+                (col + 1, *row),
+                (*col, row - 1),
+                (*col, row + 1),
+            ]
+        }));
+        coords.sort_unstable();
+        coords.dedup();
+        let values = coords
+            .into_iter()
+            .map(|(col, row)| {
+                let average = (self.value(col - 1, row)
+                    + self.value(col + 1, row)
+                    + self.value(col, row - 1)
+                    + self.value(col, row + 1))
+                    * 0.25;
+                let value = self.value(col, row);
+                ((col, row), value + (average - value) * amount)
+            })
+            .collect::<Vec<_>>();
+        self.values.clear();
+        for ((col, row), value) in values {
+            self.set_value(col, row, value);
+        }
+    }
+
+    /// Combine other influence map into this one, cell by cell.
+    pub fn combine(&mut self, other: &Self, operation: NavInfluenceCombine) {
+        let mut coords = self
+            .values
+            .keys()
+            .chain(other.values.keys())
+            .copied()
+            .collect::<Vec<_>>();
+        coords.sort_unstable();
+        coords.dedup();
+        for (col, row) in coords {
+            let value = operation.apply(self.value(col, row), other.value(col, row));
+            self.set_value(col, row, value);
+        }
+    }
+
+    /// Traverse cost multiplier of cell when influence is used as path cost layer.
+    #[inline]
+    pub fn cost_factor(&self, col: isize, row: isize, weight: Scalar) -> Scalar {
+        (1.0 + self.value(col, row) * weight).max(0.0)
+    }
+
+    /// Lowest traverse cost multiplier of all cells (cells without influence have multiplier of
+    /// 1), which keeps path search heuristic from overestimating.
+    pub fn min_cost_factor(&self, weight: Scalar) -> Scalar {
+        self.values
+            .values()
+            .map(|value| (1.0 + value * weight).max(0.0))
+            .fold(1.0, Scalar::min)
+    }
+}
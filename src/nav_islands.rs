@@ -1,16 +1,10 @@
-use crate::Scalar;
-use petgraph::{
-    algo::{astar, tarjan_scc},
-    graph::NodeIndex,
-    visit::EdgeRef,
-    Directed, Graph,
-};
+use crate::{nav_search::astar, NavVec3, Scalar, SCALAR_MAX};
+use petgraph::{algo::tarjan_scc, graph::NodeIndex, visit::EdgeRef, Directed, Graph};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-#[cfg(not(feature = "scalar64"))]
-use std::f32::MAX as SCALAR_MAX;
-#[cfg(feature = "scalar64")]
-use std::f64::MAX as SCALAR_MAX;
 use std::{
+    cmp::Ordering,
     collections::{HashMap, HashSet},
     hash::Hash,
 };
@@ -61,6 +55,32 @@ where
     pub distance: Scalar,
 }
 
+/// Turns islands level path into world space path by querying local path finders of islands
+/// (i.e. their nav meshes or nav grids), see `NavIslands::resolve_path`.
+pub trait NavIslandResolver<Island, Portal>
+where
+    Island: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+    Portal: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+{
+    /// World position of portal.
+    fn portal_point(&mut self, portal: &NavIslandPortal<Island, Portal>) -> Option<NavVec3>;
+
+    /// Find path points inside island.
+    fn find_path(&mut self, island: &Island, from: NavVec3, to: NavVec3) -> Option<Vec<NavVec3>>;
+
+    /// Find path points of connection between portals of different islands, straight line by
+    /// default.
+    fn connect(
+        &mut self,
+        _from: &NavIslandPortal<Island, Portal>,
+        _to: &NavIslandPortal<Island, Portal>,
+        from_point: NavVec3,
+        to_point: NavVec3,
+    ) -> Option<Vec<NavVec3>> {
+        Some(vec![from_point, to_point])
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct NavIslands<Island, Portal>
 where
@@ -76,6 +96,9 @@ where
     graph: Graph<(), Scalar, Directed>,
     nodes: Vec<NodeIndex>,
     nodes_map: HashMap<NodeIndex, usize>,
+    // {from portal index: {to portal index: island interior traversal cost}}
+    #[serde(default)]
+    interior_costs: HashMap<usize, HashMap<usize, Scalar>>,
 }
 
 impl<Island, Portal> NavIslands<Island, Portal>
@@ -115,6 +138,7 @@ where
             graph,
             nodes,
             nodes_map,
+            interior_costs: Default::default(),
         }
     }
 
@@ -146,6 +170,43 @@ where
         Some(old)
     }
 
+    /// Cache cost of traversing island interior between its two portals, added to weight of
+    /// connection between them when finding path. Returns `false` if portals do not exist or
+    /// belong to different islands.
+    pub fn set_interior_cost(
+        &mut self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        cost: Scalar,
+    ) -> bool {
+        if from.island != to.island {
+            return false;
+        }
+        let (Some(a), Some(b)) = (self.index(from), self.index(to)) else {
+            return false;
+        };
+        self.interior_costs
+            .entry(a)
+            .or_default()
+            .insert(b, cost.max(0.0));
+        true
+    }
+
+    pub fn interior_cost(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+    ) -> Option<Scalar> {
+        let a = self.index(from)?;
+        let b = self.index(to)?;
+        self.interior_costs.get(&a)?.get(&b).copied()
+    }
+
+    #[inline]
+    pub fn clear_interior_costs(&mut self) {
+        self.interior_costs.clear();
+    }
+
     pub fn neighbors(
         &self,
         portal: &NavIslandPortal<Island, Portal>,
@@ -169,6 +230,21 @@ where
 
     // filter params: first island-portal, second island-portal.
     pub fn find_path_custom<F>(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        filter: F,
+    ) -> Option<(Scalar, Vec<&NavIslandPortal<Island, Portal>>)>
+    where
+        F: FnMut(&NavIslandPortal<Island, Portal>, &NavIslandPortal<Island, Portal>) -> bool,
+    {
+        self.find_path_with_interior_costs(from, to, filter, |_, _| None)
+    }
+
+    /// Same as `find_path_custom` but connections rejected by filter are excluded from search
+    /// instead of getting maximal cost, so path may not be found at all.
+    // filter params: first island-portal, second island-portal.
+    pub fn find_path_excluding<F>(
         &self,
         from: &NavIslandPortal<Island, Portal>,
         to: &NavIslandPortal<Island, Portal>,
@@ -176,38 +252,205 @@ where
     ) -> Option<(Scalar, Vec<&NavIslandPortal<Island, Portal>>)>
     where
         F: FnMut(&NavIslandPortal<Island, Portal>, &NavIslandPortal<Island, Portal>) -> bool,
+    {
+        self.search(from, to, |a, b| filter(a, b).then_some(0.0), |_, _| None)
+    }
+
+    /// Find path where connections between portals of the same island get island interior
+    /// traversal cost added to their weight. Cost is given by `interior` callback, falling back
+    /// to costs cached with `set_interior_cost`.
+    // interior params: first island-portal, second island-portal.
+    pub fn find_path_with_interior_costs<F, C>(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        mut filter: F,
+        interior: C,
+    ) -> Option<(Scalar, Vec<&NavIslandPortal<Island, Portal>>)>
+    where
+        F: FnMut(&NavIslandPortal<Island, Portal>, &NavIslandPortal<Island, Portal>) -> bool,
+        C: FnMut(
+            &NavIslandPortal<Island, Portal>,
+            &NavIslandPortal<Island, Portal>,
+        ) -> Option<Scalar>,
+    {
+        // rejected connections get maximal cost instead of being excluded from search.
+        self.search(
+            from,
+            to,
+            |a, b| Some(if filter(a, b) { 0.0 } else { SCALAR_MAX }),
+            interior,
+        )
+    }
+
+    // filter gives extra cost of connection, `None` excludes it from search.
+    fn search<F, C>(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        mut filter: F,
+        mut interior: C,
+    ) -> Option<(Scalar, Vec<&NavIslandPortal<Island, Portal>>)>
+    where
+        F: FnMut(
+            &NavIslandPortal<Island, Portal>,
+            &NavIslandPortal<Island, Portal>,
+        ) -> Option<Scalar>,
+        C: FnMut(
+            &NavIslandPortal<Island, Portal>,
+            &NavIslandPortal<Island, Portal>,
+        ) -> Option<Scalar>,
     {
         let start_index = self.index(from)?;
         let end_index = self.index(to)?;
-        let start_node = *self.nodes.get(start_index)?;
-        let end_node = *self.nodes.get(end_index)?;
-        let (distance, nodes) = astar(
-            &self.graph,
-            start_node,
-            |n| n == end_node,
-            |e| {
-                let a = self.nodes_map[&e.source()];
-                let b = self.nodes_map[&e.target()];
-                let w = *e.weight();
-                if filter(self.portal(a).unwrap(), self.portal(b).unwrap()) {
-                    let a = self.costs[a];
-                    let b = self.costs[b];
-                    w * a * b
-                } else {
-                    SCALAR_MAX
+        let (distance, indices) = astar(
+            start_index,
+            end_index,
+            |a, next| {
+                for e in self.graph.edges(self.nodes[a]) {
+                    let b = self.nodes_map[&e.target()];
+                    let mut w = *e.weight();
+                    let (pa, pb) = (&self.portals[a], &self.portals[b]);
+                    let Some(extra) = filter(pa, pb) else {
+                        continue;
+                    };
+                    if pa.island == pb.island {
+                        w += interior(pa, pb)
+                            .or_else(|| {
+                                self.interior_costs
+                                    .get(&a)
+                                    .and_then(|costs| costs.get(&b))
+                                    .copied()
+                            })
+                            .unwrap_or(0.0)
+                            .max(0.0);
+                    }
+                    next(b, w * self.costs[a] * self.costs[b] + extra);
                 }
             },
             |_| 0.0,
         )?;
         Some((
             distance,
-            nodes
+            indices
                 .into_iter()
-                .filter_map(|n| self.portal(self.nodes_map[&n]))
+                .filter_map(|index| self.portal(index))
                 .collect::<Vec<_>>(),
         ))
     }
 
+    /// Find islands level path and resolve it into one stitched world space path, with
+    /// resolver finding paths inside islands and between them. Path starts and ends at given
+    /// world points, or at points of `from` and `to` portals given by resolver if these are
+    /// `None`. Explicit point of portal without portal value (`portal: None`) is used in place
+    /// of that portal, otherwise path goes from it to that portal inside its island.
+    pub fn resolve_path<R>(
+        &self,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        from_point: Option<NavVec3>,
+        to_point: Option<NavVec3>,
+        resolver: &mut R,
+    ) -> Option<Vec<NavVec3>>
+    where
+        R: NavIslandResolver<Island, Portal>,
+    {
+        NavIslandsPlan::with_points(self, from, to, from_point, to_point, SCALAR_MAX, resolver)
+            .map(|plan| plan.near)
+    }
+
+    /// Estimate travel time in microseconds between two world points of given islands, using
+    /// only islands graph and coarse average speeds of islands (world units per second), without
+    /// finding detailed paths. World points are connected with straight lines to portals of
+    /// their islands, positioned by `portal_point`. Connection is traveled with speed of island
+    /// it starts in and includes cached interior cost, islands with zero or negative speed
+    /// cannot be traveled.
+    // portal point params: island-portal.
+    // speed params: island.
+    pub fn estimate_travel_time<P, S>(
+        &self,
+        from_island: &Island,
+        from: NavVec3,
+        to_island: &Island,
+        to: NavVec3,
+        mut portal_point: P,
+        mut speed: S,
+    ) -> Option<u64>
+    where
+        P: FnMut(&NavIslandPortal<Island, Portal>) -> Option<NavVec3>,
+        S: FnMut(&Island) -> Scalar,
+    {
+        let mut speeds = HashMap::<&Island, Scalar>::new();
+        for island in self.portals.iter().map(|portal| &portal.island) {
+            speeds.entry(island).or_insert_with(|| speed(island));
+        }
+        speeds
+            .entry(from_island)
+            .or_insert_with(|| speed(from_island));
+        let points = self
+            .portals
+            .iter()
+            .map(|portal| {
+                if &portal.island == from_island || &portal.island == to_island {
+                    portal_point(portal)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        let walkable = |island: &Island| speeds.get(island).copied().filter(|speed| *speed > 0.0);
+        // world points get indices following portals indices.
+        let (start, goal) = (self.portals.len(), self.portals.len() + 1);
+        let (time, _) = astar(
+            start,
+            goal,
+            |node, next| {
+                if node == goal {
+                    return;
+                }
+                let (island, point) = if node == start {
+                    (from_island, Some(from))
+                } else {
+                    (&self.portals[node].island, points[node])
+                };
+                let Some(speed) = walkable(island) else {
+                    return;
+                };
+                if let Some(point) = point {
+                    if island == to_island {
+                        next(goal, (to - point).magnitude() / speed);
+                    }
+                }
+                if node == start {
+                    for (index, portal) in self.portals.iter().enumerate() {
+                        if &portal.island != from_island {
+                            continue;
+                        }
+                        if let Some(point) = points[index] {
+                            next(index, (point - from).magnitude() / speed);
+                        }
+                    }
+                    return;
+                }
+                for edge in self.graph.edges(self.nodes[node]) {
+                    let target = self.nodes_map[&edge.target()];
+                    let interior = self
+                        .interior_costs
+                        .get(&node)
+                        .and_then(|costs| costs.get(&target))
+                        .copied()
+                        .unwrap_or(0.0);
+                    next(
+                        target,
+                        (*edge.weight() + interior) * self.costs[node] * self.costs[target] / speed,
+                    );
+                }
+            },
+            |_| 0.0,
+        )?;
+        Some((time * 1_000_000.0).round() as u64)
+    }
+
     pub fn find_islands(&self) -> Vec<Vec<&NavIslandPortal<Island, Portal>>> {
         tarjan_scc(&self.graph)
             .into_iter()
@@ -228,3 +471,219 @@ where
         self.portals.get(index)
     }
 }
+
+/// Path plan split into detailed near plan, covering only first part of path up to horizon
+/// distance, and coarse far plan of islands portals that remain to be visited. Near plan is
+/// refined lazily as agent advances, so large worlds do not need full detailed path upfront.
+#[derive(Debug, Clone)]
+pub struct NavIslandsPlan<Island, Portal>
+where
+    Island: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+    Portal: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+{
+    horizon: Scalar,
+    portals: Vec<NavIslandPortal<Island, Portal>>,
+    portals_points: Vec<NavVec3>,
+    // index of last portal reached by near plan.
+    resolved: usize,
+    near: Vec<NavVec3>,
+    near_length: Scalar,
+}
+
+impl<Island, Portal> NavIslandsPlan<Island, Portal>
+where
+    Island: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+    Portal: std::fmt::Debug + Clone + Eq + Hash + Send + Sync,
+{
+    /// Find islands level path and resolve its first part, at least `horizon` long.
+    pub fn new<R>(
+        islands: &NavIslands<Island, Portal>,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        horizon: Scalar,
+        resolver: &mut R,
+    ) -> Option<Self>
+    where
+        R: NavIslandResolver<Island, Portal>,
+    {
+        Self::with_points(islands, from, to, None, None, horizon, resolver)
+    }
+
+    fn with_points<R>(
+        islands: &NavIslands<Island, Portal>,
+        from: &NavIslandPortal<Island, Portal>,
+        to: &NavIslandPortal<Island, Portal>,
+        from_point: Option<NavVec3>,
+        to_point: Option<NavVec3>,
+        horizon: Scalar,
+        resolver: &mut R,
+    ) -> Option<Self>
+    where
+        R: NavIslandResolver<Island, Portal>,
+    {
+        let (_, path) = islands.find_path(from, to)?;
+        let mut portals = Vec::with_capacity(path.len() + 2);
+        let mut portals_points = Vec::with_capacity(path.len() + 2);
+        // explicit points of actual portals are reached inside their islands through extra
+        // portals without value.
+        if let (Some(point), Some(_)) = (from_point, &from.portal) {
+            portals.push(NavIslandPortal {
+                island: from.island.clone(),
+                portal: None,
+            });
+            portals_points.push(point);
+        }
+        let last = path.len() - 1;
+        for (index, portal) in path.into_iter().enumerate() {
+            let point = match (index, &portal.portal) {
+                (0, None) if from_point.is_some() => from_point,
+                (index, None) if index == last && last > 0 && to_point.is_some() => to_point,
+                _ => Some(resolver.portal_point(portal)?),
+            };
+            portals.push(portal.clone());
+            portals_points.push(point?);
+        }
+        if let Some(point) = to_point {
+            if to.portal.is_some() || last == 0 {
+                portals.push(NavIslandPortal {
+                    island: to.island.clone(),
+                    portal: None,
+                });
+                portals_points.push(point);
+            }
+        }
+        let mut result = Self {
+            horizon: horizon.max(0.0),
+            near: vec![*portals_points.first()?],
+            portals,
+            portals_points,
+            resolved: 0,
+            near_length: 0.0,
+        };
+        result.refine(resolver)?;
+        Some(result)
+    }
+
+    /// Minimal length of near plan, that plan gets refined up to.
+    #[inline]
+    pub fn horizon(&self) -> Scalar {
+        self.horizon
+    }
+
+    /// Detailed path points, starting at agent position.
+    #[inline]
+    pub fn near(&self) -> &[NavVec3] {
+        &self.near
+    }
+
+    /// Portals left to visit after reaching end of near plan.
+    #[inline]
+    pub fn far(&self) -> &[NavIslandPortal<Island, Portal>] {
+        &self.portals[(self.resolved + 1).min(self.portals.len())..]
+    }
+
+    /// Tells if near plan reaches path goal.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.resolved + 1 >= self.portals.len()
+    }
+
+    /// Length of near plan.
+    #[inline]
+    pub fn near_length(&self) -> Scalar {
+        self.near_length
+    }
+
+    /// Drop part of near plan that agent already passed and refine plan so its near part is at
+    /// least horizon long again. Agent is snapped to near plan by searching forward from its
+    /// first segment for the closest one, so parts of path passing close to each other (i.e.
+    /// U-turns) are not skipped. Returns `None` if resolver fails to find part of path, leaving
+    /// plan unchanged.
+    pub fn advance<R>(&mut self, position: NavVec3, resolver: &mut R) -> Option<()>
+    where
+        R: NavIslandResolver<Island, Portal>,
+    {
+        if self.near.len() < 2 {
+            return self.refine(resolver);
+        }
+        let distance =
+            |index: usize| position.distance_to_segment(self.near[index], self.near[index + 1]);
+        let mut index = 0;
+        let mut best = distance(0);
+        while index + 2 < self.near.len() {
+            let next = distance(index + 1);
+            // also stops at NaN distances.
+            if next.partial_cmp(&best) != Some(Ordering::Less) {
+                break;
+            }
+            index += 1;
+            best = next;
+        }
+        let (a, b) = (self.near[index], self.near[index + 1]);
+        let point = NavVec3::unproject(a, b, position.project(a, b).clamp(0.0, 1.0));
+        let passed = self.near[..=index + 1]
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).magnitude())
+            .sum::<Scalar>();
+        let length = self.near_length - passed + (b - point).magnitude();
+        let (points, resolved, length) =
+            self.resolve_ahead(*self.near.last()?, length.max(0.0), resolver)?;
+        self.near.drain(..=index);
+        if !point.same_as(self.near[0]) {
+            self.near.insert(0, point);
+        }
+        self.near.extend(points);
+        self.resolved = resolved;
+        self.near_length = length;
+        Some(())
+    }
+
+    fn refine<R>(&mut self, resolver: &mut R) -> Option<()>
+    where
+        R: NavIslandResolver<Island, Portal>,
+    {
+        let (points, resolved, length) =
+            self.resolve_ahead(*self.near.last()?, self.near_length, resolver)?;
+        self.near.extend(points);
+        self.resolved = resolved;
+        self.near_length = length;
+        Some(())
+    }
+
+    // Resolve next parts of path following last near plan point, until near plan of given
+    // length gets at least horizon long. Returns new points, index of last resolved portal and
+    // new near plan length, without changing plan.
+    fn resolve_ahead<R>(
+        &self,
+        mut last: NavVec3,
+        mut length: Scalar,
+        resolver: &mut R,
+    ) -> Option<(Vec<NavVec3>, usize, Scalar)>
+    where
+        R: NavIslandResolver<Island, Portal>,
+    {
+        let mut points = vec![];
+        let mut resolved = self.resolved;
+        while resolved + 1 < self.portals.len() && length < self.horizon {
+            let (from, to) = (&self.portals[resolved], &self.portals[resolved + 1]);
+            let (from_point, to_point) = (
+                self.portals_points[resolved],
+                self.portals_points[resolved + 1],
+            );
+            let segment = if from.island == to.island {
+                resolver.find_path(&from.island, from_point, to_point)?
+            } else {
+                resolver.connect(from, to, from_point, to_point)?
+            };
+            for point in segment {
+                if !last.same_as(point) {
+                    length += (point - last).magnitude();
+                    last = point;
+                    points.push(point);
+                }
+            }
+            resolved += 1;
+        }
+        Some((points, resolved, length))
+    }
+}
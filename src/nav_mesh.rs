@@ -1,21 +1,35 @@
-use crate::{Error, NavConnection, NavResult, NavVec3, Scalar, ZERO_TRESHOLD};
+#[cfg(feature = "random")]
+use crate::random_unit;
+use crate::{
+    connection_noise,
+    nav_search::{astar, integrate_chunked},
+    Error, NavAreaTransition, NavConnection, NavHeuristic, NavIslandPortal, NavIslandResolver,
+    NavIslands, NavIslandsConnection, NavOccupancy, NavPath, NavPathLink, NavPathResult, NavResult,
+    NavSpatialIndex, NavTolerance, NavVec3, NavZeroHeuristic, Scalar, SCALAR_MAX, ZERO_TRESHOLD,
+};
 use petgraph::{
-    algo::{astar, tarjan_scc},
-    graph::NodeIndex,
+    algo::tarjan_scc,
+    graph::{EdgeIndex, NodeIndex},
     visit::EdgeRef,
-    Graph, Undirected,
+    Directed, Graph,
 };
+#[cfg(feature = "random")]
+use rand_core::RngCore;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use spade::{rtree::RTree, BoundingRect, SpatialObject};
-use std::collections::HashMap;
-#[cfg(not(feature = "scalar64"))]
-use std::f32::MAX as SCALAR_MAX;
-#[cfg(feature = "scalar64")]
-use std::f64::MAX as SCALAR_MAX;
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+    ops::{Deref, Range},
+    sync::{Arc, OnceLock},
+};
 use typid::ID;
 
+// number of consecutive triangles integrated together when building distance maps in parallel.
+const INTEGRATE_CHUNK_TRIANGLES: usize = 4096;
+
 #[cfg(feature = "parallel")]
 macro_rules! iter {
     ($v:expr) => {
@@ -73,6 +87,9 @@ impl From<[u32; 3]> for NavTriangle {
     }
 }
 
+/// Number of distinct nav mesh area types that can be selected by area mask.
+pub const NAV_AREA_TYPES_COUNT: usize = 64;
+
 /// Nav mesh area descriptor. Nav mesh area holds information about specific nav mesh triangle.
 #[repr(C)]
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -90,6 +107,13 @@ pub struct NavArea {
     pub radius: Scalar,
     /// Squared version of `radius`.
     pub radius_sqr: Scalar,
+    /// Area type (i.e. ground, water, road), in range `[0; NAV_AREA_TYPES_COUNT)`.
+    #[serde(default)]
+    pub area_type: u8,
+    /// Water depth over this triangle. Zero or less means dry land, greater values mark area
+    /// that has to be swam through.
+    #[serde(default)]
+    pub depth: Scalar,
 }
 
 impl NavArea {
@@ -207,42 +231,855 @@ impl SpatialObject for NavSpatialObject {
 }
 
 /// Quality of querying a point on nav mesh.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// Variants are serialized with stable snake case names (`accuracy`, `closest`,
+/// `closest_first`), so they can be specified in data files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NavQuery {
     /// Best quality, totally accurate.
+    #[serde(rename = "accuracy", alias = "Accuracy")]
     Accuracy,
     /// Medium quality, finds point in closest triangle.
+    #[serde(rename = "closest", alias = "Closest")]
     Closest,
     /// Low quality, finds first triangle in range of query.
+    #[serde(rename = "closest_first", alias = "ClosestFirst")]
     ClosestFirst,
 }
 
+/// Nav mesh point query options, with explicit snapping tolerances. Missing fields are
+/// deserialized with default values.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavQueryOptions {
+    /// Query quality used to find closest triangle.
+    pub query: NavQuery,
+    /// Maximal distance along nav mesh surface between query point and its projection on nav
+    /// mesh. Points farther away are not snapped.
+    pub snap_distance: Scalar,
+    /// Distance from nav mesh surface under which query point is treated as already being on
+    /// nav mesh and is used as is instead of its projection.
+    pub on_mesh_tolerance: Scalar,
+    /// Maximal distance above or below nav mesh surface (along triangle normal) between query
+    /// point and its projection on nav mesh. Points farther away are not snapped.
+    pub vertical_tolerance: Scalar,
+}
+
+impl Default for NavQueryOptions {
+    fn default() -> Self {
+        NavQuery::Accuracy.into()
+    }
+}
+
+impl From<NavQuery> for NavQueryOptions {
+    fn from(query: NavQuery) -> Self {
+        Self {
+            query,
+            snap_distance: SCALAR_MAX,
+            on_mesh_tolerance: 0.0,
+            vertical_tolerance: SCALAR_MAX,
+        }
+    }
+}
+
 /// Quality of finding path.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// Variants are serialized with stable snake case names (`accuracy`, `mid_points`,
+/// `clearance_mid_points`, `funnel`), so they can be specified in data files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NavPathMode {
     /// Best quality, finds shortest path.
+    #[serde(rename = "accuracy", alias = "Accuracy")]
     Accuracy,
     /// Medium quality, finds shortest path througs triangles midpoints.
+    #[serde(rename = "mid_points", alias = "MidPoints")]
     MidPoints,
+    /// Medium quality, same as `MidPoints` but crossing points are moved along portal edges
+    /// towards vertice with more clearance, so medium size agents clip corners less.
+    #[serde(rename = "clearance_mid_points", alias = "ClearanceMidPoints")]
+    ClearanceMidPoints,
+    /// Best quality, pulls path string tight through triangles corridor portals (funnel
+    /// algorithm), which gives the shortest polyline inside the corridor.
+    #[serde(rename = "funnel", alias = "Funnel")]
+    Funnel,
+}
+
+/// Seeded random perturbation of connections costs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavPathJitter {
+    /// Perturbation seed - the same seed always gives the same costs.
+    pub seed: u64,
+    /// Maximal relative change of connection cost, clamped to `[0; 1)` range.
+    pub amount: Scalar,
+}
+
+impl NavPathJitter {
+    /// Calculate connection cost factor.
+    ///
+    /// # Arguments
+    /// * `first` - first triangle index.
+    /// * `second` - second triangle index.
+    ///
+    /// # Returns
+    /// Factor in `[1 - amount; 1 + amount]` range.
+    pub fn factor(&self, first: usize, second: usize) -> Scalar {
+        let amount = self.amount.clamp(0.0, 1.0 - ZERO_TRESHOLD);
+        1.0 + amount * connection_noise(self.seed, NavConnection(first as u32, second as u32))
+    }
+}
+
+/// Per-agent memory of triangle connections that recently led to failure (i.e. agent got stuck
+/// crossing them), penalized by path queries so agent does not keep re-choosing route it can not
+/// complete.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavTabuList {
+    /// Cost factor of tabu connections.
+    pub penalty: Scalar,
+    /// Time for which reported connections stay tabu.
+    pub duration: Scalar,
+    // [(smaller triangle index, bigger triangle index, time left)]
+    entries: Vec<(usize, usize, Scalar)>,
+}
+
+impl Default for NavTabuList {
+    fn default() -> Self {
+        Self::new(10.0, 10.0)
+    }
+}
+
+impl NavTabuList {
+    pub fn new(penalty: Scalar, duration: Scalar) -> Self {
+        Self {
+            penalty: penalty.max(1.0),
+            duration: duration.max(0.0),
+            entries: vec![],
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Mark connection between two triangles as tabu for `duration` time, in both directions.
+    pub fn report(&mut self, first: usize, second: usize) {
+        let (first, second) = (first.min(second), first.max(second));
+        match self
+            .entries
+            .iter_mut()
+            .find(|(a, b, _)| *a == first && *b == second)
+        {
+            Some(entry) => entry.2 = self.duration,
+            None => self.entries.push((first, second, self.duration)),
+        }
+    }
+
+    /// Advance time, forgetting connections whose tabu time has passed.
+    pub fn update(&mut self, delta_time: Scalar) {
+        for entry in &mut self.entries {
+            entry.2 -= delta_time;
+        }
+        self.entries.retain(|(_, _, time)| *time > 0.0);
+    }
+
+    pub fn is_tabu(&self, first: usize, second: usize) -> bool {
+        let (first, second) = (first.min(second), first.max(second));
+        self.entries
+            .iter()
+            .any(|(a, b, _)| *a == first && *b == second)
+    }
+
+    /// Calculate connection cost factor: `penalty` for tabu connections, `1` otherwise.
+    pub fn factor(&self, first: usize, second: usize) -> Scalar {
+        if self.is_tabu(first, second) {
+            self.penalty
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Predicted area of effect (i.e. telegraphed attack) that paths should avoid while it lasts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NavHazard {
+    /// Hazard center point.
+    pub position: NavVec3,
+    /// Hazard radius.
+    pub radius: Scalar,
+    /// Time (counted from path query) when hazard starts.
+    pub start_time: Scalar,
+    /// Time (counted from path query) when hazard ends.
+    pub end_time: Scalar,
+    /// Penalty added to cost factor of connections traversed inside of hazard while it lasts.
+    pub cost: Scalar,
+}
+
+impl NavHazard {
+    /// Tells if segment traversed in given time range intersects this hazard.
+    pub fn intersects(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        start_time: Scalar,
+        end_time: Scalar,
+    ) -> bool {
+        start_time <= self.end_time
+            && end_time >= self.start_time
+            && self.position.distance_to_segment(from, to) < self.radius
+    }
+}
+
+/// Additional nav mesh path finding options. Missing fields are deserialized with default
+/// values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavPathOptions {
+    /// Random perturbation of connections costs, so agents given the same order spread across
+    /// slightly different routes.
+    pub jitter: Option<NavPathJitter>,
+    /// Minimal width of portal edges that path can cross. Narrower passages are excluded from
+    /// the search. Zero or less disables this check.
+    pub min_passage_width: Scalar,
+    /// Bit mask of area types that path can cross - bit `n` enables area type `n`.
+    pub area_mask: u64,
+    /// Traverse cost multipliers of area types, indexed by area type. Missing entries are
+    /// treated as `1.0`.
+    pub area_costs: Vec<Scalar>,
+    /// Remove path waypoints whenever straight segment between their neighbors is clear (see
+    /// `NavMesh::shorten_path`).
+    pub shorten: bool,
+    /// Tells if agent can swim through areas covered with water.
+    pub can_swim: bool,
+    /// Maximal water depth that agent can swim through.
+    pub max_depth: Scalar,
+    /// Predicted hazards to avoid. Traversal time of connections is estimated from distance
+    /// traveled along triangles centers and `speed`.
+    pub hazards: Vec<NavHazard>,
+    /// Agent speed used to estimate when connections are traversed.
+    pub speed: Scalar,
+    /// Maximal number of triangles expanded by search. When exceeded, `NavMesh::find_path_ex`
+    /// gives up and returns partial path (flagged with `NavPathResult::partial`) leading to
+    /// expanded triangle closest to the goal, while other queries fail.
+    pub max_expansions: Option<usize>,
+    /// Agent memory of recently failed connections, whose costs get penalized.
+    pub tabu: Option<NavTabuList>,
+}
+
+impl Default for NavPathOptions {
+    fn default() -> Self {
+        Self {
+            jitter: None,
+            min_passage_width: 0.0,
+            area_mask: Self::default_area_mask(),
+            area_costs: vec![],
+            shorten: false,
+            can_swim: Self::default_can_swim(),
+            max_depth: Self::default_max_depth(),
+            hazards: vec![],
+            speed: 1.0,
+            max_expansions: None,
+            tabu: None,
+        }
+    }
+}
+
+impl NavPathOptions {
+    fn default_area_mask() -> u64 {
+        u64::MAX
+    }
+
+    fn default_can_swim() -> bool {
+        true
+    }
+
+    fn default_max_depth() -> Scalar {
+        SCALAR_MAX
+    }
+
+    /// Tells if agent can traverse area with given water depth.
+    #[inline]
+    pub fn is_depth_allowed(&self, depth: Scalar) -> bool {
+        depth <= 0.0 || (self.can_swim && depth <= self.max_depth)
+    }
+
+    /// Tells if area type is enabled by area mask.
+    #[inline]
+    pub fn is_area_type_allowed(&self, area_type: u8) -> bool {
+        (area_type as usize) < NAV_AREA_TYPES_COUNT && self.area_mask & (1 << area_type) != 0
+    }
+
+    /// Get traverse cost multiplier of area type.
+    #[inline]
+    pub fn area_type_cost(&self, area_type: u8) -> Scalar {
+        self.area_costs
+            .get(area_type as usize)
+            .copied()
+            .unwrap_or(1.0)
+    }
+}
+
+/// Pull path string tight through portals (simple stupid funnel algorithm).
+///
+/// # Arguments
+/// * `from` - path start point.
+/// * `to` - path end point.
+/// * `portals` - list of (left, right, normal) portal triplets oriented along travel direction.
+/// * `normal` - normal of surface under path end point.
+/// * `threshold` - squared distance under which points are treated as the same.
+///
+/// # Returns
+/// Path points. Portals between surfaces of different normals get extra points where path
+/// crosses them, so path follows the surface.
+pub(crate) fn funnel(
+    from: NavVec3,
+    to: NavVec3,
+    portals: &[(NavVec3, NavVec3, NavVec3)],
+    normal: NavVec3,
+    threshold: Scalar,
+) -> Vec<NavVec3> {
+    #[inline]
+    fn area(a: NavVec3, b: NavVec3, c: NavVec3, normal: NavVec3) -> Scalar {
+        (b - a).cross(c - a).dot(normal)
+    }
+
+    let mut list = Vec::with_capacity(portals.len() + 2);
+    list.push((from, from, portals.first().map(|p| p.2).unwrap_or(normal)));
+    list.extend_from_slice(portals);
+    list.push((to, to, normal));
+
+    // (point, index of portal that point lies on)
+    let mut corners = vec![(from, 0)];
+    let mut apex = from;
+    let (mut left, mut right) = (from, from);
+    let (mut left_index, mut right_index) = (0, 0);
+    let mut i = 1;
+    while i < list.len() {
+        let (l, r, n) = list[i];
+        if area(apex, right, r, n) >= 0.0 {
+            if apex.same_as_threshold(right, threshold) || area(apex, left, r, n) <= 0.0 {
+                right = r;
+                right_index = i;
+            } else {
+                apex = left;
+                right = apex;
+                right_index = left_index;
+                corners.push((apex, left_index));
+                i = left_index + 1;
+                continue;
+            }
+        }
+        if area(apex, left, l, n) <= 0.0 {
+            if apex.same_as_threshold(left, threshold) || area(apex, right, l, n) >= 0.0 {
+                left = l;
+                left_index = i;
+            } else {
+                apex = right;
+                left = apex;
+                left_index = right_index;
+                corners.push((apex, right_index));
+                i = right_index + 1;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    corners.push((to, list.len() - 1));
+
+    let mut points = Vec::with_capacity(corners.len() * 2);
+    points.push(from);
+    for pair in corners.windows(2) {
+        let (mut start, first) = pair[0];
+        let (end, last) = pair[1];
+        for index in (first + 1)..last {
+            let (a, b, n) = list[index];
+            let m = list[index + 1].2;
+            if n.dot(m) < 1.0 - ZERO_TRESHOLD {
+                let n = (b - a).normalize().cross(n);
+                if let Some(point) = NavVec3::raycast_line(start, end, a, b, n) {
+                    points.push(point);
+                    start = point;
+                }
+            }
+        }
+        points.push(end);
+    }
+    points.dedup_by(|a, b| a.same_as_threshold(*b, threshold));
+    points
+}
+
+/// Nav mesh off-mesh link identifier.
+pub type NavOffMeshLinkID = ID<NavOffMeshLink>;
+
+/// Kind of off-mesh link, used by agents to decide how to traverse it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NavOffMeshLinkKind {
+    /// Jump pad or gap to jump over.
+    Jump,
+    /// Ladder to climb.
+    Ladder,
+    /// Instant teleport.
+    Teleport,
+    /// Small height discontinuity (curb, step), usually generated by
+    /// `NavMesh::generate_step_links`.
+    Step,
+    /// User defined kind.
+    Custom(u32),
+}
+
+/// Off-mesh link end used as nav islands portal (see `NavMesh::build_islands`), holding index of
+/// link in list given to builder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NavLinkPortal {
+    /// Link start point.
+    From(usize),
+    /// Link end point.
+    To(usize),
+}
+
+/// Resolver of nav islands built with `NavMesh::build_islands`, finding paths inside islands on
+/// nav mesh without leaving them through off-mesh links registered on nav mesh. It has to be
+/// given the same links that islands were built from.
+#[derive(Debug, Clone)]
+pub struct NavMeshIslandResolver<'a> {
+    mesh: &'a NavMesh,
+    links: &'a [NavOffMeshLink],
+    components: Vec<usize>,
+    query: NavQueryOptions,
+    mode: NavPathMode,
+}
+
+impl<'a> NavMeshIslandResolver<'a> {
+    pub fn new(
+        mesh: &'a NavMesh,
+        links: &'a [NavOffMeshLink],
+        query: impl Into<NavQueryOptions>,
+        mode: NavPathMode,
+    ) -> Self {
+        Self {
+            mesh,
+            links,
+            components: mesh.triangle_components(),
+            query: query.into(),
+            mode,
+        }
+    }
+}
+
+impl NavIslandResolver<usize, NavLinkPortal> for NavMeshIslandResolver<'_> {
+    fn portal_point(&mut self, portal: &NavIslandPortal<usize, NavLinkPortal>) -> Option<NavVec3> {
+        let point = match portal.portal? {
+            NavLinkPortal::From(index) => self.links.get(index)?.from,
+            NavLinkPortal::To(index) => self.links.get(index)?.to,
+        };
+        self.mesh
+            .snap_point(point, &NavQuery::Accuracy.into())
+            .map(|(_, point)| point)
+    }
+
+    fn find_path(&mut self, island: &usize, from: NavVec3, to: NavVec3) -> Option<Vec<NavVec3>> {
+        let components = &self.components;
+        self.mesh
+            .find_path_excluding(from, to, self.query, self.mode, |_, a, b| {
+                components.get(a) == Some(island) && components.get(b) == Some(island)
+            })
+    }
+}
+
+/// Explicit point-to-point connection between nav mesh triangles.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct NavOffMeshLink {
+    /// Link start point.
+    pub from: NavVec3,
+    /// Link end point.
+    pub to: NavVec3,
+    /// Traverse cost factor, same as nav area cost it scales squared link length.
+    pub cost: Scalar,
+    /// Tells if link can be traversed in both directions.
+    pub bidirectional: bool,
+    /// Link kind.
+    pub kind: NavOffMeshLinkKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NavOffMeshLinkData {
+    link: NavOffMeshLink,
+    from_triangle: usize,
+    to_triangle: usize,
+    // link end points projected on nav mesh.
+    from_point: NavVec3,
+    to_point: NavVec3,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct NavGraphEdge {
+    // squared distance between connected triangles centers or link end points.
+    weight: Scalar,
+    link: Option<NavOffMeshLinkID>,
+}
+
+// Triangles found by path search.
+struct NavCorridor {
+    triangles: Vec<usize>,
+    // off-mesh link taken to enter each triangle.
+    links: Vec<Option<NavOffMeshLinkID>>,
+    cost: Scalar,
+}
+
+/// Settings of step links generation (see `NavMesh::generate_step_links`). Up axis is Z.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavStepLinkSettings {
+    /// Maximal vertical offset between boundary edges.
+    pub max_height: Scalar,
+    /// Maximal horizontal gap between boundary edges.
+    pub max_gap: Scalar,
+    /// Minimal absolute cosine of angle between boundary edges directions on horizontal plane,
+    /// `1.0` means only exactly parallel edges are connected.
+    pub min_parallel: Scalar,
+    /// Minimal length of part of boundary edges that lies next to each other.
+    pub min_overlap: Scalar,
+    /// Traverse cost factor of generated links.
+    pub cost: Scalar,
+}
+
+impl Default for NavStepLinkSettings {
+    fn default() -> Self {
+        Self {
+            max_height: 0.5,
+            max_gap: 0.25,
+            min_parallel: 0.95,
+            min_overlap: 0.1,
+            cost: 1.0,
+        }
+    }
+}
+
+/// Nav mesh blocker identifier.
+pub type NavBlockerID = ID<NavBlocker>;
+
+/// Dynamic spherical blocker registered on nav mesh.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct NavBlocker {
+    /// Blocker center point.
+    pub position: NavVec3,
+    /// Blocker radius.
+    pub radius: Scalar,
+}
+
+/// Spherical region that scales probability of sampling points from triangles which centers
+/// are inside of it.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct NavBiasRegion {
+    /// Region center point.
+    pub center: NavVec3,
+    /// Region radius.
+    pub radius: Scalar,
+    /// Sampling weight factor. Zero excludes covered triangles from sampling, values bigger than
+    /// one make covered triangles more likely to be sampled.
+    pub weight: Scalar,
+}
+
+impl NavBiasRegion {
+    /// Tells if region covers given point.
+    #[inline]
+    pub fn contains(&self, point: NavVec3) -> bool {
+        (point - self.center).sqr_magnitude() <= self.radius * self.radius
+    }
+}
+
+/// Grouping of triangles into separate render buffers.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavRenderSplit {
+    /// All triangles go to single buffer.
+    #[default]
+    None,
+    /// Triangles are grouped by their area type.
+    AreaType,
+    /// Triangles are grouped by islands (see `NavMesh::find_triangle_islands`).
+    Island,
+}
+
+/// Indexed triangle list ready for upload to GPU buffers.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavRenderMesh {
+    /// Group of triangles: area type or island index, zero when triangles are not split.
+    pub group: usize,
+    /// Unique vertices positions.
+    pub vertices: Vec<NavVec3>,
+    /// Vertices indices, three per triangle.
+    pub indices: Vec<u32>,
+}
+
+/// Snapshot of nav mesh state that changes at runtime (areas properties, blockers and bias
+/// regions), serialized separately from baked nav mesh, i.e. into save games.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavMeshDynamicState {
+    /// Cost of every triangle area.
+    pub area_costs: Vec<Scalar>,
+    /// Type of every triangle area.
+    pub area_types: Vec<u8>,
+    /// Water depth of every triangle area.
+    pub area_depths: Vec<Scalar>,
+    /// Registered blockers, together with their identifiers.
+    pub blockers: Vec<(NavBlockerID, NavBlocker)>,
+    /// Spawn bias regions.
+    pub bias_regions: Vec<NavBiasRegion>,
+}
+
+/// Obstacle found by sweeping a shape along the path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NavSweepObstacle {
+    /// Nav mesh boundary edge: (from, to).
+    Boundary(NavVec3, NavVec3),
+    /// Registered blocker.
+    Blocker(NavBlockerID),
+}
+
+/// Result of sweeping a shape along the path.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavSweepHit {
+    /// Index of path segment where first penetration happens.
+    pub segment: usize,
+    /// Shape center point at the moment of first penetration.
+    pub position: NavVec3,
+    /// Distance from path start point to `position`.
+    pub distance: Scalar,
+    /// Penetrated obstacle.
+    pub obstacle: NavSweepObstacle,
+}
+
+/// Closest point on nav mesh with its triangle information.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavClosestPoint {
+    /// Point on nav mesh.
+    pub point: NavVec3,
+    /// Index of triangle that contains `point`.
+    pub triangle: usize,
+    /// Barycentric coordinates of `point` relative to triangle first, second and third vertice.
+    pub barycentric: [Scalar; 3],
+}
+
+/// Path point with nav mesh surface orientation at it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavSurfacePoint {
+    /// Path point.
+    pub point: NavVec3,
+    /// Index of triangle that `point` lies on.
+    pub triangle: usize,
+    /// Surface normal interpolated from triangle vertices normals.
+    pub normal: NavVec3,
+    /// Angle (in radians) between surface normal and up axis.
+    pub slope: Scalar,
+}
+
+/// Result of raycasting along nav mesh surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavRaycastHit {
+    /// Point where ray hits nav mesh boundary.
+    pub point: NavVec3,
+    /// Blocking boundary edge: (from, to).
+    pub edge: (NavVec3, NavVec3),
+    /// Index of triangle that owns blocking edge.
+    pub triangle: usize,
+}
+
+/// Result of raycasting nav mesh triangles in 3D space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavRay3dHit {
+    /// Point where ray hits nav mesh surface.
+    pub point: NavVec3,
+    /// Index of hit triangle.
+    pub triangle: usize,
+    /// Distance from ray origin to `point`.
+    pub distance: Scalar,
+}
+
+/// Walkability of nav mesh around agent position, gathered in single query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavProbe {
+    /// Tells if position lies within probe radius from nav mesh surface.
+    pub on_mesh: bool,
+    /// Closest point on nav mesh.
+    pub point: NavVec3,
+    /// Index of triangle that contains `point`.
+    pub triangle: usize,
+    /// Area type of `triangle`.
+    pub area_type: u8,
+    /// Distance from `point` to the nearest nav mesh boundary edge.
+    pub clearance: Scalar,
+    /// Index of triangle island that contains `triangle`.
+    pub island: usize,
+    /// Normal of the nearest nav mesh boundary edge, pointing inside nav mesh, or `None` if nav
+    /// mesh has no boundary.
+    pub boundary_normal: Option<NavVec3>,
+}
+
+/// Edge shared by two neighbor triangles, crossed by paths going from one to another.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NavPortal {
+    /// Vertices indices of portal edge.
+    pub edge: NavConnection,
+    /// Length of portal edge.
+    pub length: Scalar,
+    /// Midpoint of portal edge.
+    pub midpoint: NavVec3,
+}
+
+/// Half-edge of nav mesh triangle. Half-edge with index `i` belongs to triangle `i / 3` and goes
+/// from its `i % 3` local vertice to the next one in triangle winding order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NavHalfEdge {
+    /// Origin vertice index.
+    pub origin: u32,
+    /// Target vertice index.
+    pub target: u32,
+    /// Owning triangle index.
+    pub triangle: usize,
+    /// Index of half-edge of neighbor triangle that shares the same vertices, `None` for nav mesh
+    /// boundary.
+    pub twin: Option<usize>,
+}
+
+impl NavHalfEdge {
+    /// Index of next half-edge in the same triangle.
+    #[inline]
+    pub fn next(index: usize) -> usize {
+        index - index % 3 + (index + 1) % 3
+    }
+
+    /// Index of previous half-edge in the same triangle.
+    #[inline]
+    pub fn prev(index: usize) -> usize {
+        index - index % 3 + (index + 2) % 3
+    }
+
+    /// Tells if half-edge lies on nav mesh boundary.
+    #[inline]
+    pub fn is_boundary(&self) -> bool {
+        self.twin.is_none()
+    }
+}
+
+/// Statistics of nav mesh triangles island, used to detect tiny islands left by baking errors
+/// or to pick the main one.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NavTriangleIslandStats {
+    /// Number of triangles in island.
+    pub triangles_count: usize,
+    /// Total area of island triangles.
+    pub area: Scalar,
+    /// Minimal corner of island bounding box.
+    pub bounds_min: NavVec3,
+    /// Maximal corner of island bounding box.
+    pub bounds_max: NavVec3,
+}
+
+/// Range of vertices pool, possibly shared between many nav meshes. Serialized as plain list of
+/// vertices in range.
+#[derive(Debug, Clone)]
+struct NavVertexBuffer {
+    pool: Arc<[NavVec3]>,
+    range: Range<usize>,
+}
+
+impl Default for NavVertexBuffer {
+    fn default() -> Self {
+        Self {
+            pool: Arc::new([]),
+            range: 0..0,
+        }
+    }
+}
+
+impl Deref for NavVertexBuffer {
+    type Target = [NavVec3];
+
+    fn deref(&self) -> &Self::Target {
+        &self.pool[self.range.clone()]
+    }
+}
+
+impl Serialize for NavVertexBuffer {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.deref().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NavVertexBuffer {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let vertices = Vec::<NavVec3>::deserialize(deserializer)?;
+        let range = 0..vertices.len();
+        Ok(Self {
+            pool: vertices.into(),
+            range,
+        })
+    }
 }
 
 /// Nav mesh object used to find shortest path between two points.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct NavMesh {
     id: NavMeshID,
-    vertices: Vec<NavVec3>,
+    vertices: NavVertexBuffer,
     triangles: Vec<NavTriangle>,
     areas: Vec<NavArea>,
     // {triangle connection: (distance sqr, vertex connection)}
     connections: HashMap<NavConnection, (Scalar, NavConnection)>,
-    graph: Graph<(), Scalar, Undirected>,
+    // off-mesh links edges are always placed after triangles connections edges.
+    graph: Graph<(), NavGraphEdge, Directed>,
     nodes: Vec<NodeIndex>,
     nodes_map: HashMap<NodeIndex, usize>,
-    rtree: RTree<NavSpatialObject>,
-    spatials: Vec<NavSpatialObject>,
+    #[serde(skip)]
+    half_edges: OnceLock<Vec<NavHalfEdge>>,
+    // distance of each vertice to nav mesh boundary, built on first use.
+    #[serde(skip)]
+    clearances: OnceLock<Vec<Scalar>>,
+    // island index of each triangle, built on first use.
+    #[serde(skip)]
+    islands: OnceLock<Vec<usize>>,
+    // area weighted average of normals of triangles sharing each vertice, built on first use.
+    #[serde(skip)]
+    normals: OnceLock<Vec<NavVec3>>,
+    // {triangle connection: portal geometry}, built on first use.
+    #[serde(skip)]
+    portals: OnceLock<HashMap<NavConnection, NavPortal>>,
+    // built on first spatial query when nav mesh is created in topology only mode.
+    #[serde(skip)]
+    spatial: OnceLock<NavSpatialIndex<NavSpatialObject>>,
     // {triangle index: [(from, to)]}
     hard_edges: HashMap<usize, Vec<(NavVec3, NavVec3)>>,
     origin: NavVec3,
+    #[serde(default)]
+    tolerance: NavTolerance,
+    #[serde(default)]
+    blockers: HashMap<NavBlockerID, NavBlocker>,
+    #[serde(default)]
+    bias_regions: Vec<NavBiasRegion>,
+    #[serde(default)]
+    off_mesh_links: Vec<(NavOffMeshLinkID, NavOffMeshLinkData)>,
+    // bumped whenever navigation data affecting paths changes.
+    #[serde(default)]
+    topology_version: u64,
+    // names of area types.
+    #[serde(default)]
+    area_names: Vec<(String, u8)>,
 }
 
 impl NavMesh {
@@ -278,38 +1115,192 @@ impl NavMesh {
     /// let mesh = NavMesh::new(vertices, triangles).unwrap();
     /// ```
     pub fn new(vertices: Vec<NavVec3>, triangles: Vec<NavTriangle>) -> NavResult<Self> {
-        let origin = vertices
-            .iter()
-            .cloned()
-            .fold(NavVec3::default(), |a, v| a + v)
-            / vertices.len() as Scalar;
+        let result = Self::new_topology_only(vertices, triangles)?;
+        result.build_spatials();
+        Ok(result)
+    }
 
-        let areas = iter!(triangles)
-            .enumerate()
-            .map(|(i, triangle)| {
-                if triangle.first >= vertices.len() as u32 {
-                    return Err(Error::TriangleVerticeIndexOutOfBounds(
-                        i as u32,
-                        0,
-                        triangle.first,
-                    ));
-                }
-                if triangle.second >= vertices.len() as u32 {
-                    return Err(Error::TriangleVerticeIndexOutOfBounds(
-                        i as u32,
-                        1,
-                        triangle.second,
-                    ));
-                }
-                if triangle.third >= vertices.len() as u32 {
-                    return Err(Error::TriangleVerticeIndexOutOfBounds(
-                        i as u32,
-                        2,
-                        triangle.third,
-                    ));
-                }
-                let first = vertices[triangle.first as usize];
-                let second = vertices[triangle.second as usize];
+    /// Create new nav mesh object from vertices and triangles, without building spatial
+    /// structures used by point queries - they get built on first use instead. Useful when nav
+    /// mesh is used only for triangle-level path finding.
+    ///
+    /// # Arguments
+    /// * `vertices` - list of vertices points.
+    /// * `triangles` - list of vertices indices that produces triangles.
+    ///
+    /// # Returns
+    /// `Ok` with nav mesh object or `Err` with `Error::TriangleVerticeIndexOutOfBounds` if input
+    /// data is invalid.
+    pub fn new_topology_only(
+        vertices: Vec<NavVec3>,
+        triangles: Vec<NavTriangle>,
+    ) -> NavResult<Self> {
+        let range = 0..vertices.len();
+        Self::build(
+            NavVertexBuffer {
+                pool: vertices.into(),
+                range,
+            },
+            triangles,
+        )
+    }
+
+    /// Create new nav mesh object that uses range of shared vertices pool, so multiple nav mesh
+    /// tiles can reference single vertices buffer.
+    ///
+    /// # Arguments
+    /// * `pool` - shared list of vertices points.
+    /// * `range` - range of vertices pool used by this nav mesh.
+    /// * `triangles` - list of vertices indices (relative to `range` start) that produces
+    ///   triangles.
+    ///
+    /// # Returns
+    /// `Ok` with nav mesh object or `Err` with `Error::VerticesRangeOutOfBounds` or
+    /// `Error::TriangleVerticeIndexOutOfBounds` if input data is invalid.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    /// use std::sync::Arc;
+    ///
+    /// let pool: Arc<[NavVec3]> = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (2.0, 0.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 0.0).into(), // 5
+    /// ]
+    /// .into();
+    ///
+    /// let left = NavMesh::with_shared_vertices(
+    ///     pool.clone(),
+    ///     0..4,
+    ///     vec![(0, 1, 2).into(), (2, 3, 0).into()],
+    /// )
+    /// .unwrap();
+    /// let right = NavMesh::with_shared_vertices(
+    ///     pool.clone(),
+    ///     1..6,
+    ///     vec![(0, 3, 4).into(), (4, 1, 0).into()],
+    /// )
+    /// .unwrap();
+    /// assert_eq!(left.vertices().len(), 4);
+    /// assert_eq!(right.vertices()[0], (1.0, 0.0, 0.0).into());
+    /// assert!(Arc::ptr_eq(left.shared_vertices().0, right.shared_vertices().0));
+    /// ```
+    pub fn with_shared_vertices(
+        pool: Arc<[NavVec3]>,
+        range: Range<usize>,
+        triangles: Vec<NavTriangle>,
+    ) -> NavResult<Self> {
+        if range.start > range.end || range.end > pool.len() {
+            return Err(Error::VerticesRangeOutOfBounds(
+                range.start,
+                range.end,
+                pool.len(),
+            ));
+        }
+        let result = Self::build(NavVertexBuffer { pool, range }, triangles)?;
+        result.build_spatials();
+        Ok(result)
+    }
+
+    /// Create new nav mesh object from vertices and triangles, with area type assigned to each
+    /// triangle.
+    ///
+    /// # Arguments
+    /// * `vertices` - list of vertices points.
+    /// * `triangles` - list of vertices indices that produces triangles.
+    /// * `area_types` - list of triangles area types, in range `[0; NAV_AREA_TYPES_COUNT)`.
+    ///
+    /// # Returns
+    /// `Ok` with nav mesh object or `Err` with `Error::AreaTypesCountDoesNotMatchTriangles`,
+    /// `Error::AreaTypeOutOfBounds` or `Error::TriangleVerticeIndexOutOfBounds` if input data is
+    /// invalid.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::with_area_types(vertices, triangles, vec![0, 1]).unwrap();
+    /// let options = NavPathOptions {
+    ///     area_mask: 0b01,
+    ///     ..Default::default()
+    /// };
+    /// assert!(mesh
+    ///     .find_path_triangles_with_options(0, 1, &options, |_, _, _| true)
+    ///     .is_none());
+    /// ```
+    pub fn with_area_types(
+        vertices: Vec<NavVec3>,
+        triangles: Vec<NavTriangle>,
+        area_types: Vec<u8>,
+    ) -> NavResult<Self> {
+        if area_types.len() != triangles.len() {
+            return Err(Error::AreaTypesCountDoesNotMatchTriangles(
+                area_types.len(),
+                triangles.len(),
+            ));
+        }
+        if let Some(index) = area_types
+            .iter()
+            .position(|t| *t as usize >= NAV_AREA_TYPES_COUNT)
+        {
+            return Err(Error::AreaTypeOutOfBounds(index as u32, area_types[index]));
+        }
+        let mut result = Self::new(vertices, triangles)?;
+        for (area, area_type) in result.areas.iter_mut().zip(area_types) {
+            area.area_type = area_type;
+        }
+        Ok(result)
+    }
+
+    fn build(buffer: NavVertexBuffer, triangles: Vec<NavTriangle>) -> NavResult<Self> {
+        let vertices: &[NavVec3] = &buffer;
+        let origin = vertices
+            .iter()
+            .cloned()
+            .fold(NavVec3::default(), |a, v| a + v)
+            / vertices.len() as Scalar;
+
+        let areas = iter!(triangles)
+            .enumerate()
+            .map(|(i, triangle)| {
+                if triangle.first >= vertices.len() as u32 {
+                    return Err(Error::TriangleVerticeIndexOutOfBounds(
+                        i as u32,
+                        0,
+                        triangle.first,
+                    ));
+                }
+                if triangle.second >= vertices.len() as u32 {
+                    return Err(Error::TriangleVerticeIndexOutOfBounds(
+                        i as u32,
+                        1,
+                        triangle.second,
+                    ));
+                }
+                if triangle.third >= vertices.len() as u32 {
+                    return Err(Error::TriangleVerticeIndexOutOfBounds(
+                        i as u32,
+                        2,
+                        triangle.third,
+                    ));
+                }
+                let first = vertices[triangle.first as usize];
+                let second = vertices[triangle.second as usize];
                 let third = vertices[triangle.third as usize];
                 let center = NavArea::calculate_center(first, second, third);
                 let radius = (first - center)
@@ -323,6 +1314,8 @@ impl NavMesh {
                     center,
                     radius,
                     radius_sqr: radius * radius,
+                    area_type: 0,
+                    depth: 0.0,
                 })
             })
             .collect::<NavResult<Vec<_>>>()?;
@@ -371,34 +1364,26 @@ impl NavMesh {
         })
         .collect::<HashMap<_, _>>();
 
-        let mut graph = Graph::<(), Scalar, Undirected>::new_undirected();
+        let mut graph = Graph::<(), NavGraphEdge, Directed>::new();
         let nodes = (0..triangles.len())
             .map(|_| graph.add_node(()))
             .collect::<Vec<_>>();
         graph.extend_with_edges(
-            iter!(connections)
-                .map(|(conn, (w, _))| (nodes[conn.0 as usize], nodes[conn.1 as usize], w))
+            connections
+                .iter()
+                .flat_map(|(conn, (weight, _))| {
+                    let a = nodes[conn.0 as usize];
+                    let b = nodes[conn.1 as usize];
+                    let edge = NavGraphEdge {
+                        weight: *weight,
+                        link: None,
+                    };
+                    [(a, b, edge), (b, a, edge)]
+                })
                 .collect::<Vec<_>>(),
         );
         let nodes_map = iter!(nodes).enumerate().map(|(i, n)| (*n, i)).collect();
 
-        let spatials = iter!(triangles)
-            .enumerate()
-            .map(|(index, triangle)| {
-                NavSpatialObject::new(
-                    index,
-                    vertices[triangle.first as usize],
-                    vertices[triangle.second as usize],
-                    vertices[triangle.third as usize],
-                )
-            })
-            .collect::<Vec<_>>();
-
-        let mut rtree = RTree::new();
-        for spatial in &spatials {
-            rtree.insert(spatial.clone());
-        }
-
         let hard_edges = iter!(triangles)
             .enumerate()
             .filter_map(|(index, triangle)| {
@@ -432,125 +1417,149 @@ impl NavMesh {
             })
             .collect::<HashMap<_, _>>();
 
+        let half_edges = Self::build_half_edges(&triangles);
+
         Ok(Self {
             id: ID::new(),
-            vertices,
+            vertices: buffer,
             triangles,
             areas,
             connections,
             graph,
             nodes,
             nodes_map,
-            rtree,
-            spatials,
+            half_edges: OnceLock::from(half_edges),
+            clearances: OnceLock::new(),
+            islands: OnceLock::new(),
+            normals: OnceLock::new(),
+            portals: OnceLock::new(),
+            spatial: OnceLock::new(),
             hard_edges,
             origin,
+            tolerance: Default::default(),
+            blockers: Default::default(),
+            bias_regions: Default::default(),
+            off_mesh_links: Default::default(),
+            topology_version: 0,
+            area_names: vec![],
         })
     }
 
-    pub fn thicken(&self, value: Scalar) -> NavResult<Self> {
-        let shifted = iter!(self.vertices)
+    fn build_half_edges(triangles: &[NavTriangle]) -> Vec<NavHalfEdge> {
+        let mut result = triangles
+            .iter()
             .enumerate()
-            .map(|(i, v)| {
-                let (mut n, c) = self
-                    .triangles
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(j, t)| {
-                        if t.first == i as u32 || t.second == i as u32 || t.third == i as u32 {
-                            Some(self.spatials[j].normal)
-                        } else {
-                            None
-                        }
+            .flat_map(|(index, t)| {
+                [(t.first, t.second), (t.second, t.third), (t.third, t.first)]
+                    .into_iter()
+                    .map(move |(origin, target)| NavHalfEdge {
+                        origin,
+                        target,
+                        triangle: index,
+                        twin: None,
                     })
-                    .fold((NavVec3::default(), 0), |a, v| (a.0 + v, a.1 + 1));
-                if c > 1 {
-                    n = n / c as Scalar;
-                }
-                *v + n.normalize() * value
             })
             .collect::<Vec<_>>();
-        Self::new(shifted, self.triangles.clone())
-    }
-
-    pub fn scale(&self, value: NavVec3, origin: Option<NavVec3>) -> NavResult<Self> {
-        let origin = origin.unwrap_or(self.origin);
-        let vertices = iter!(self.vertices)
-            .map(|v| (*v - origin) * value + origin)
-            .collect::<Vec<_>>();
-        Self::new(vertices, self.triangles.clone())
-    }
-
-    /// Nav mesh identifier.
-    #[inline]
-    pub fn id(&self) -> NavMeshID {
-        self.id
-    }
-
-    /// Nav mesh origin point.
-    #[inline]
-    pub fn origin(&self) -> NavVec3 {
-        self.origin
+        // {edge: [half-edge index]}
+        let mut lookup = HashMap::<NavConnection, Vec<usize>>::with_capacity(result.len());
+        for (index, edge) in result.iter().enumerate() {
+            lookup
+                .entry(NavConnection(edge.origin, edge.target))
+                .or_default()
+                .push(index);
+        }
+        for (index, edge) in result.iter_mut().enumerate() {
+            edge.twin = lookup[&NavConnection(edge.origin, edge.target)]
+                .iter()
+                .copied()
+                .find(|other| *other != index);
+        }
+        result
     }
 
-    /// Reference to list of nav mesh vertices points.
+    /// Half-edges of all nav mesh triangles. Half-edges of triangle `i` are at indices
+    /// `3 * i`, `3 * i + 1` and `3 * i + 2`.
     #[inline]
-    pub fn vertices(&self) -> &[NavVec3] {
-        &self.vertices
+    pub fn half_edges(&self) -> &[NavHalfEdge] {
+        self.half_edges
+            .get_or_init(|| Self::build_half_edges(&self.triangles))
     }
 
-    /// Reference to list of nav mesh triangles.
+    /// Get half-edge by index.
     #[inline]
-    pub fn triangles(&self) -> &[NavTriangle] {
-        &self.triangles
+    pub fn half_edge(&self, index: usize) -> Option<&NavHalfEdge> {
+        self.half_edges().get(index)
     }
 
-    /// Reference to list of nav mesh area descriptors.
+    /// Indices of triangle half-edges.
     #[inline]
-    pub fn areas(&self) -> &[NavArea] {
-        &self.areas
+    pub fn triangle_half_edges(&self, index: usize) -> [usize; 3] {
+        [index * 3, index * 3 + 1, index * 3 + 2]
     }
 
-    /// Set area cost by triangle index.
-    ///
-    /// # Arguments
-    /// * `index` - triangle index.
-    /// * `cost` - cost factor.
+    /// Extract closed loops of nav mesh boundary edges. Outer boundaries follow triangles winding
+    /// order, while holes go the opposite way.
     ///
     /// # Returns
-    /// Old area cost value.
-    #[inline]
-    pub fn set_area_cost(&mut self, index: usize, cost: Scalar) -> Scalar {
-        let area = &mut self.areas[index];
-        let old = area.cost;
-        let cost = cost.max(0.0);
-        area.cost = cost;
-        old
-    }
-
-    /// Find closest point on nav mesh.
+    /// List of loops, each being ordered list of boundary vertices indices (first vertex is not
+    /// repeated at the end).
     ///
-    /// # Arguments
-    /// * `point` - query point.
-    /// * `query` - query quality.
+    /// # Example
+    /// ```
+    /// use navmesh::*;
     ///
-    /// # Returns
-    /// `Some` with point on nav mesh if found or `None` otherwise.
-    pub fn closest_point(&self, point: NavVec3, query: NavQuery) -> Option<NavVec3> {
-        self.find_closest_triangle(point, query)
-            .map(|triangle| self.spatials[triangle].closest_point(point))
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// assert_eq!(mesh.boundary_loops(), vec![vec![0, 1, 2, 3]]);
+    /// ```
+    pub fn boundary_loops(&self) -> Vec<Vec<u32>> {
+        let half_edges = self.half_edges();
+        // {origin vertex: [boundary half-edge index]}
+        let mut outgoing = HashMap::<u32, Vec<usize>>::new();
+        for (index, edge) in half_edges.iter().enumerate() {
+            if edge.is_boundary() {
+                outgoing.entry(edge.origin).or_default().push(index);
+            }
+        }
+        let mut visited = vec![false; half_edges.len()];
+        let mut result = vec![];
+        for start in 0..half_edges.len() {
+            if visited[start] || !half_edges[start].is_boundary() {
+                continue;
+            }
+            let mut vertices = vec![];
+            let mut current = start;
+            loop {
+                visited[current] = true;
+                let edge = &half_edges[current];
+                vertices.push(edge.origin);
+                let next = outgoing
+                    .get(&edge.target)
+                    .and_then(|list| list.iter().copied().find(|index| !visited[*index]));
+                match next {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+            result.push(vertices);
+        }
+        result
     }
 
-    /// Find shortest path on nav mesh between two points.
-    ///
-    /// # Arguments
-    /// * `from` - query point from.
-    /// * `to` - query point to.
-    /// * `query` - query quality.
-    /// * `mode` - path finding quality.
+    /// Distances of vertices to nav mesh boundary, where boundary vertices have zero clearance.
     ///
     /// # Returns
-    /// `Some` with path points on nav mesh if found or `None` otherwise.
+    /// Clearance of each vertice.
     ///
     /// # Example
     /// ```
@@ -558,61 +1567,84 @@ impl NavMesh {
     ///
     /// let vertices = vec![
     ///     (0.0, 0.0, 0.0).into(), // 0
-    ///     (1.0, 0.0, 0.0).into(), // 1
-    ///     (2.0, 0.0, 1.0).into(), // 2
-    ///     (0.0, 1.0, 0.0).into(), // 3
-    ///     (1.0, 1.0, 0.0).into(), // 4
-    ///     (2.0, 1.0, 1.0).into(), // 5
+    ///     (2.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 2.0, 0.0).into(), // 2
+    ///     (0.0, 2.0, 0.0).into(), // 3
+    ///     (1.0, 1.5, 0.0).into(), // 4
     /// ];
     /// let triangles = vec![
     ///     (0, 1, 4).into(), // 0
-    ///     (4, 3, 0).into(), // 1
-    ///     (1, 2, 5).into(), // 2
-    ///     (5, 4, 1).into(), // 3
+    ///     (1, 2, 4).into(), // 1
+    ///     (2, 3, 4).into(), // 2
+    ///     (3, 0, 4).into(), // 3
     /// ];
     ///
     /// let mesh = NavMesh::new(vertices, triangles).unwrap();
-    /// let path = mesh
-    ///     .find_path(
-    ///         (0.0, 1.0, 0.0).into(),
-    ///         (1.5, 0.25, 0.5).into(),
-    ///         NavQuery::Accuracy,
-    ///         NavPathMode::MidPoints,
-    ///     )
-    ///     .unwrap();
-    /// assert_eq!(
-    ///     path.into_iter()
-    ///         .map(|v| (
-    ///             (v.x * 10.0) as i32,
-    ///             (v.y * 10.0) as i32,
-    ///             (v.z * 10.0) as i32,
-    ///         ))
-    ///         .collect::<Vec<_>>(),
-    ///     vec![(0, 10, 0), (10, 5, 0), (15, 2, 5),]
-    /// );
+    /// assert_eq!(mesh.vertices_clearances(), &[0.0, 0.0, 0.0, 0.0, 0.5]);
     /// ```
-    pub fn find_path(
-        &self,
-        from: NavVec3,
-        to: NavVec3,
-        query: NavQuery,
-        mode: NavPathMode,
-    ) -> Option<Vec<NavVec3>> {
-        self.find_path_custom(from, to, query, mode, |_, _, _| true)
+    pub fn vertices_clearances(&self) -> &[Scalar] {
+        self.clearances.get_or_init(|| {
+            let boundary = self
+                .half_edges()
+                .iter()
+                .filter(|edge| edge.is_boundary())
+                .map(|edge| (edge.origin, edge.target))
+                .collect::<Vec<_>>();
+            let mut result = vec![SCALAR_MAX; self.vertices.len()];
+            for (origin, target) in &boundary {
+                result[*origin as usize] = 0.0;
+                result[*target as usize] = 0.0;
+            }
+            for (index, clearance) in result.iter_mut().enumerate() {
+                if *clearance > 0.0 {
+                    let point = self.vertices[index];
+                    *clearance = boundary
+                        .iter()
+                        .map(|(a, b)| {
+                            point.distance_to_segment(
+                                self.vertices[*a as usize],
+                                self.vertices[*b as usize],
+                            )
+                        })
+                        .fold(SCALAR_MAX, Scalar::min);
+                }
+            }
+            result
+        })
     }
 
-    /// Find shortest path on nav mesh between two points, providing custom filtering function.
+    /// Smooth surface normals of vertices: area weighted average of normals of triangles that
+    /// share given vertice.
+    ///
+    /// # Returns
+    /// Normal of each vertice, zero for vertices not used by any triangle.
+    pub fn vertices_normals(&self) -> &[NavVec3] {
+        self.normals.get_or_init(|| {
+            let mut result = vec![NavVec3::default(); self.vertices.len()];
+            for (index, triangle) in self.triangles.iter().enumerate() {
+                let normal = self.spatials()[index].normal() * self.areas[index].size;
+                for vertice in [triangle.first, triangle.second, triangle.third] {
+                    result[vertice as usize] = result[vertice as usize] + normal;
+                }
+            }
+            for normal in &mut result {
+                if normal.sqr_magnitude() > 0.0 {
+                    *normal = normal.normalize();
+                }
+            }
+            result
+        })
+    }
+
+    /// Annotate path points with nav mesh surface orientation, i.e. to align characters with
+    /// terrain while following path.
     ///
     /// # Arguments
-    /// * `from` - query point from.
-    /// * `to` - query point to.
-    /// * `query` - query quality.
-    /// * `mode` - path finding quality.
-    /// * `filter` - closure that gives you a connection distance squared, first triangle index
-    ///   and second triangle index.
+    /// * `points` - path points lying on nav mesh.
+    /// * `up` - up axis that slopes are measured from (does not have to be normalized).
     ///
     /// # Returns
-    /// `Some` with path points on nav mesh if found or `None` otherwise.
+    /// Surface information of every point, or empty list if nav mesh has no triangles.
     ///
     /// # Example
     /// ```
@@ -621,250 +1653,4328 @@ impl NavMesh {
     /// let vertices = vec![
     ///     (0.0, 0.0, 0.0).into(), // 0
     ///     (1.0, 0.0, 0.0).into(), // 1
-    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (1.0, 1.0, 0.0).into(), // 2
     ///     (0.0, 1.0, 0.0).into(), // 3
-    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 0.0, 1.0).into(), // 4
     ///     (2.0, 1.0, 1.0).into(), // 5
     /// ];
     /// let triangles = vec![
-    ///     (0, 1, 4).into(), // 0
-    ///     (4, 3, 0).into(), // 1
-    ///     (1, 2, 5).into(), // 2
-    ///     (5, 4, 1).into(), // 3
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    ///     (1, 4, 5).into(), // 2
+    ///     (5, 2, 1).into(), // 3
     /// ];
     ///
     /// let mesh = NavMesh::new(vertices, triangles).unwrap();
-    /// let path = mesh
-    ///     .find_path_custom(
+    /// let path = vec![
+    ///     (0.0, 1.0, 0.0).into(),
+    ///     (1.0, 0.5, 0.0).into(),
+    ///     (2.0, 0.0, 1.0).into(),
+    /// ];
+    /// let surface = mesh.path_surface(&path, (0.0, 0.0, 1.0).into());
+    /// assert_eq!(surface.len(), 3);
+    /// assert_eq!(surface[0].triangle, 1);
+    /// assert_eq!(surface[0].normal, (0.0, 0.0, 1.0).into());
+    /// assert_eq!(surface[0].slope, 0.0);
+    /// // crease between flat floor and ramp gets normal in between.
+    /// assert!(surface[1].slope > 0.0 && surface[1].slope < surface[2].slope);
+    /// assert_eq!(surface[2].triangle, 2);
+    /// assert!((surface[2].slope - std::f32::consts::FRAC_PI_4 as Scalar).abs() < 1.0e-4);
+    /// ```
+    pub fn path_surface(&self, points: &[NavVec3], up: NavVec3) -> Vec<NavSurfacePoint> {
+        let normals = self.vertices_normals();
+        let up = up.normalize();
+        points
+            .iter()
+            .filter_map(|point| {
+                let triangle = self.rtree().nearest_neighbor(point)?.index;
+                let [u, v, w] = self.barycentric(triangle, *point);
+                let t = self.triangles[triangle];
+                let normal = normals[t.first as usize] * u
+                    + normals[t.second as usize] * v
+                    + normals[t.third as usize] * w;
+                let normal = if normal.sqr_magnitude() > 0.0 {
+                    normal.normalize()
+                } else {
+                    self.spatials()[triangle].normal()
+                };
+                Some(NavSurfacePoint {
+                    point: *point,
+                    triangle,
+                    normal,
+                    slope: normal.dot(up).clamp(-1.0, 1.0).acos(),
+                })
+            })
+            .collect()
+    }
+
+    // Point where path crosses portal edge, either its midpoint or point moved towards vertice
+    // with more clearance, by ratio of vertices clearances extended with half of edge length.
+    fn portal_crossing(&self, portal: &NavPortal, clearance: bool) -> NavVec3 {
+        if !clearance {
+            return portal.midpoint;
+        }
+        let NavConnection(a, b) = portal.edge;
+        let clearances = self.vertices_clearances();
+        let half = portal.length * 0.5;
+        let weight_a = clearances[a as usize].min(SCALAR_MAX * 0.5) + half;
+        let weight_b = clearances[b as usize].min(SCALAR_MAX * 0.5) + half;
+        if weight_a + weight_b <= 0.0 {
+            return portal.midpoint;
+        }
+        let (pa, pb) = (self.vertices[a as usize], self.vertices[b as usize]);
+        pa.lerp(pb, weight_b / (weight_a + weight_b))
+    }
+
+    /// Build spatial structures used by point queries if they are not built yet.
+    pub fn build_spatials(&self) {
+        self.spatial();
+    }
+
+    /// Tells if spatial structures used by point queries are already built.
+    #[inline]
+    pub fn has_spatials(&self) -> bool {
+        self.spatial.get().is_some()
+    }
+
+    fn spatial(&self) -> &NavSpatialIndex<NavSpatialObject> {
+        self.spatial.get_or_init(|| {
+            NavSpatialIndex::new(
+                iter!(self.triangles)
+                    .enumerate()
+                    .map(|(index, triangle)| {
+                        NavSpatialObject::new(
+                            index,
+                            self.vertices[triangle.first as usize],
+                            self.vertices[triangle.second as usize],
+                            self.vertices[triangle.third as usize],
+                        )
+                    })
+                    .collect(),
+            )
+        })
+    }
+
+    #[inline]
+    fn spatials(&self) -> &[NavSpatialObject] {
+        &self.spatial().objects
+    }
+
+    #[inline]
+    fn rtree(&self) -> &RTree<NavSpatialObject> {
+        &self.spatial().rtree
+    }
+
+    pub fn thicken(&self, value: Scalar) -> NavResult<Self> {
+        self.thicken_with(|_, _| value)
+    }
+
+    /// Same as `thicken` but with thickness varying over nav mesh.
+    ///
+    /// # Arguments
+    /// * `thickness` - closure that gives you vertex index and position, and returns distance
+    ///   this vertex gets shifted along its normal.
+    ///
+    /// # Returns
+    /// `Ok` with thickened nav mesh or `Err` with error if it could not be built.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let heights = [1.0, 1.0, 2.0, 2.0];
+    /// let mesh = mesh.thicken_with(|index, _| heights[index]).unwrap();
+    /// assert_eq!(mesh.vertices()[0], (0.0, 0.0, 1.0).into());
+    /// assert_eq!(mesh.vertices()[3], (0.0, 1.0, 2.0).into());
+    /// ```
+    pub fn thicken_with<F>(&self, thickness: F) -> NavResult<Self>
+    where
+        F: Fn(usize, NavVec3) -> Scalar + Sync,
+    {
+        let shifted = iter!(self.vertices)
+            .enumerate()
+            .map(|(i, v)| {
+                let (mut n, c) = self
+                    .triangles
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(j, t)| {
+                        if t.first == i as u32 || t.second == i as u32 || t.third == i as u32 {
+                            Some(self.spatials()[j].normal)
+                        } else {
+                            None
+                        }
+                    })
+                    .fold((NavVec3::default(), 0), |a, v| (a.0 + v, a.1 + 1));
+                if c > 1 {
+                    n = n / c as Scalar;
+                }
+                *v + n.normalize() * thickness(i, *v)
+            })
+            .collect::<Vec<_>>();
+        Self::new(shifted, self.triangles.clone())
+    }
+
+    /// Join multiple nav meshes into single connected one, welding coincident vertices so
+    /// triangles sharing edges across source meshes get connected. Area costs, types and depths,
+    /// off-mesh links, blockers and bias regions of source meshes are preserved, tolerance
+    /// settings are taken from the first mesh.
+    ///
+    /// # Arguments
+    /// * `meshes` - list of source nav meshes.
+    ///
+    /// # Returns
+    /// `Ok` with merged nav mesh or `Err` with error if it could not be built.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let a = NavMesh::new(
+    ///     vec![
+    ///         (0.0, 0.0, 0.0).into(),
+    ///         (1.0, 0.0, 0.0).into(),
+    ///         (1.0, 1.0, 0.0).into(),
     ///         (0.0, 1.0, 0.0).into(),
-    ///         (1.5, 0.25, 0.5).into(),
+    ///     ],
+    ///     vec![(0, 1, 2).into(), (2, 3, 0).into()],
+    /// )
+    /// .unwrap();
+    /// let b = NavMesh::new(
+    ///     vec![
+    ///         (1.0, 0.0, 0.0).into(),
+    ///         (2.0, 0.0, 0.0).into(),
+    ///         (2.0, 1.0, 0.0).into(),
+    ///         (1.0, 1.0, 0.0).into(),
+    ///     ],
+    ///     vec![(0, 1, 2).into(), (2, 3, 0).into()],
+    /// )
+    /// .unwrap();
+    /// let mesh = NavMesh::merge(&[a, b]).unwrap();
+    /// assert_eq!(mesh.vertices().len(), 6);
+    /// assert_eq!(mesh.triangles().len(), 4);
+    /// assert!(mesh.find_path_triangles(1, 2).is_some());
+    /// ```
+    pub fn merge(meshes: &[NavMesh]) -> NavResult<Self> {
+        let distance = meshes
+            .first()
+            .map(|mesh| mesh.tolerance.threshold(1))
+            .unwrap_or(ZERO_TRESHOLD);
+        Self::merge_with_weld_distance(meshes, distance)
+    }
+
+    /// Same as `merge` but with custom distance under which vertices are welded together.
+    ///
+    /// # Arguments
+    /// * `meshes` - list of source nav meshes.
+    /// * `distance` - vertices weld distance.
+    ///
+    /// # Returns
+    /// `Ok` with merged nav mesh or `Err` with error if it could not be built.
+    pub fn merge_with_weld_distance(meshes: &[NavMesh], distance: Scalar) -> NavResult<Self> {
+        let distance = distance.max(ZERO_TRESHOLD);
+        let cell = |v: NavVec3| {
+            (
+                (v.x / distance).floor() as i64,
+                (v.y / distance).floor() as i64,
+                (v.z / distance).floor() as i64,
+            )
+        };
+        let mut vertices = Vec::<NavVec3>::new();
+        let mut buckets = HashMap::<(i64, i64, i64), Vec<u32>>::new();
+        let mut triangles = vec![];
+        let mut areas = vec![];
+        for mesh in meshes {
+            let remap = mesh
+                .vertices
+                .iter()
+                .map(|v| {
+                    let (x, y, z) = cell(*v);
+                    for dx in -1..=1 {
+                        for dy in -1..=1 {
+                            for dz in -1..=1 {
+                                if let Some(found) = buckets
+                                    .get(&(x + dx, y + dy, z + dz))
+                                    .into_iter()
+                                    .flatten()
+                                    .find(|i| {
+                                        (vertices[**i as usize] - *v).sqr_magnitude()
+                                            <= distance * distance
+                                    })
+                                {
+                                    return *found;
+                                }
+                            }
+                        }
+                    }
+                    let index = vertices.len() as u32;
+                    vertices.push(*v);
+                    buckets.entry((x, y, z)).or_default().push(index);
+                    index
+                })
+                .collect::<Vec<_>>();
+            for (triangle, area) in mesh.triangles.iter().zip(mesh.areas.iter()) {
+                let t = NavTriangle {
+                    first: remap[triangle.first as usize],
+                    second: remap[triangle.second as usize],
+                    third: remap[triangle.third as usize],
+                };
+                // triangles collapsed by welding are dropped.
+                if t.first != t.second && t.second != t.third && t.third != t.first {
+                    triangles.push(t);
+                    areas.push((area.cost, area.area_type, area.depth));
+                }
+            }
+        }
+        let mut result = Self::new(vertices, triangles)?;
+        for (area, (cost, area_type, depth)) in result.areas.iter_mut().zip(areas) {
+            area.cost = cost;
+            area.area_type = area_type;
+            area.depth = depth;
+        }
+        if let Some(mesh) = meshes.first() {
+            result.tolerance = mesh.tolerance;
+        }
+        for mesh in meshes {
+            for (name, area_type) in &mesh.area_names {
+                if result.named_area_type(name).is_none() {
+                    result.set_area_name(name.to_owned(), *area_type);
+                }
+            }
+            for (index, (_, data)) in mesh.off_mesh_links.iter().enumerate() {
+                result
+                    .add_off_mesh_link(data.link)
+                    .ok_or(Error::CouldNotAttachOffMeshLink(index))?;
+            }
+            for blocker in mesh.blockers.values() {
+                result.add_blocker(*blocker);
+            }
+            for region in &mesh.bias_regions {
+                result.add_bias_region(*region);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Create copy of nav mesh mirrored by plane, i.e. to complete symmetric map authored as
+    /// half of it. Triangles winding is flipped so mirrored triangles keep facing the same side,
+    /// and off-mesh links, blockers and bias regions are mirrored too (links and blockers get
+    /// new identifiers).
+    ///
+    /// # Arguments
+    /// * `origin` - point on mirror plane.
+    /// * `normal` - mirror plane normal.
+    ///
+    /// # Returns
+    /// `Ok` with mirrored nav mesh or `Err` with error if it could not be built.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (1.0, 0.0, 0.0).into(), // 0
+    ///     (2.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 1.0, 0.0).into(), // 2
+    ///     (1.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let mirrored = mesh
+    ///     .mirrored((0.0, 0.0, 0.0).into(), (1.0, 0.0, 0.0).into())
+    ///     .unwrap();
+    /// assert_eq!(mirrored.vertices()[1], (-2.0, 0.0, 0.0).into());
+    /// let triangle = mirrored.triangles()[0];
+    /// assert_eq!((triangle.first, triangle.second, triangle.third), (0, 2, 1));
+    /// let merged = NavMesh::merge(&[mesh, mirrored]).unwrap();
+    /// assert_eq!(merged.triangles().len(), 4);
+    /// ```
+    pub fn mirrored(&self, origin: NavVec3, normal: NavVec3) -> NavResult<Self> {
+        let normal = normal.normalize();
+        let mirror = |v: NavVec3| v - normal * ((v - origin).dot(normal) * 2.0);
+        let vertices = iter!(self.vertices).map(|v| mirror(*v)).collect::<Vec<_>>();
+        let triangles = self
+            .triangles
+            .iter()
+            .map(|t| NavTriangle {
+                first: t.first,
+                second: t.third,
+                third: t.second,
+            })
+            .collect();
+        let mut result = Self::new(vertices, triangles)?;
+        for (area, source) in result.areas.iter_mut().zip(self.areas.iter()) {
+            area.cost = source.cost;
+            area.area_type = source.area_type;
+            area.depth = source.depth;
+        }
+        result.tolerance = self.tolerance;
+        result.area_names = self.area_names.clone();
+        for (index, (_, data)) in self.off_mesh_links.iter().enumerate() {
+            result
+                .add_off_mesh_link(NavOffMeshLink {
+                    from: mirror(data.link.from),
+                    to: mirror(data.link.to),
+                    ..data.link
+                })
+                .ok_or(Error::CouldNotAttachOffMeshLink(index))?;
+        }
+        for blocker in self.blockers.values() {
+            result.add_blocker(NavBlocker {
+                position: mirror(blocker.position),
+                radius: blocker.radius,
+            });
+        }
+        for region in &self.bias_regions {
+            result.add_bias_region(NavBiasRegion {
+                center: mirror(region.center),
+                ..*region
+            });
+        }
+        Ok(result)
+    }
+
+    pub fn scale(&self, value: NavVec3, origin: Option<NavVec3>) -> NavResult<Self> {
+        let origin = origin.unwrap_or(self.origin);
+        let vertices = iter!(self.vertices)
+            .map(|v| (*v - origin) * value + origin)
+            .collect::<Vec<_>>();
+        Self::new(vertices, self.triangles.clone())
+    }
+
+    /// Nav mesh identifier.
+    #[inline]
+    pub fn id(&self) -> NavMeshID {
+        self.id
+    }
+
+    /// Nav mesh origin point.
+    #[inline]
+    pub fn origin(&self) -> NavVec3 {
+        self.origin
+    }
+
+    /// Numerical tolerance settings.
+    #[inline]
+    pub fn tolerance(&self) -> NavTolerance {
+        self.tolerance
+    }
+
+    /// Size of world unit used by nav mesh data, internal thresholds are scaled by it.
+    #[inline]
+    pub fn unit_scale(&self) -> Scalar {
+        self.tolerance.unit_scale
+    }
+
+    /// Set size of world unit used by nav mesh data.
+    ///
+    /// # Arguments
+    /// * `value` - unit scale, i.e. `0.01` for nav mesh authored in centimeters.
+    #[inline]
+    pub fn set_unit_scale(&mut self, value: Scalar) {
+        self.tolerance.unit_scale = value.max(0.0);
+    }
+
+    /// Set numerical tolerance settings.
+    #[inline]
+    pub fn set_tolerance(&mut self, value: NavTolerance) {
+        self.tolerance = value;
+    }
+
+    /// Zero threshold (epsilon) used by same point, raycast and side tests.
+    #[inline]
+    pub fn epsilon(&self) -> Scalar {
+        self.tolerance.epsilon
+    }
+
+    /// Set zero threshold (epsilon) used by same point, raycast and side tests.
+    ///
+    /// # Arguments
+    /// * `value` - epsilon value, loosen it for big coordinates or tighten for precision.
+    #[inline]
+    pub fn set_epsilon(&mut self, value: Scalar) {
+        self.tolerance.epsilon = value.max(0.0);
+    }
+
+    /// Version of navigation data, bumped whenever area costs, types or depths, off-mesh links
+    /// or blockers change. Paths found with older version may be outdated.
+    #[inline]
+    pub fn topology_version(&self) -> u64 {
+        self.topology_version
+    }
+
+    /// Reference to list of nav mesh vertices points.
+    #[inline]
+    pub fn vertices(&self) -> &[NavVec3] {
+        &self.vertices
+    }
+
+    /// Shared vertices pool and range of it used by this nav mesh.
+    #[inline]
+    pub fn shared_vertices(&self) -> (&Arc<[NavVec3]>, Range<usize>) {
+        (&self.vertices.pool, self.vertices.range.clone())
+    }
+
+    /// Reference to list of nav mesh triangles.
+    #[inline]
+    pub fn triangles(&self) -> &[NavTriangle] {
+        &self.triangles
+    }
+
+    /// Reference to list of nav mesh area descriptors.
+    #[inline]
+    pub fn areas(&self) -> &[NavArea] {
+        &self.areas
+    }
+
+    /// Set area cost by triangle index.
+    ///
+    /// # Arguments
+    /// * `index` - triangle index.
+    /// * `cost` - cost factor.
+    ///
+    /// # Returns
+    /// Old area cost value.
+    #[inline]
+    pub fn set_area_cost(&mut self, index: usize, cost: Scalar) -> Scalar {
+        self.topology_version += 1;
+        let area = &mut self.areas[index];
+        let old = area.cost;
+        let cost = cost.max(0.0);
+        area.cost = cost;
+        old
+    }
+
+    /// Set area type by triangle index.
+    ///
+    /// # Arguments
+    /// * `index` - triangle index.
+    /// * `area_type` - area type, in range `[0; NAV_AREA_TYPES_COUNT)`.
+    ///
+    /// # Returns
+    /// `Some` with old area type or `None` if area type is out of bounds.
+    #[inline]
+    pub fn set_area_type(&mut self, index: usize, area_type: u8) -> Option<u8> {
+        if area_type as usize >= NAV_AREA_TYPES_COUNT {
+            return None;
+        }
+        self.topology_version += 1;
+        let area = &mut self.areas[index];
+        let old = area.area_type;
+        area.area_type = area_type;
+        Some(old)
+    }
+
+    /// Name area type (i.e. ground, water, climb), so queries can refer to area types by name.
+    /// Renaming area type or giving the same name to other area type replaces old entry.
+    ///
+    /// # Arguments
+    /// * `name` - area type name.
+    /// * `area_type` - named area type, in range `[0; NAV_AREA_TYPES_COUNT)`.
+    ///
+    /// # Returns
+    /// `false` if area type is out of bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 0.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mut mesh = NavMesh::with_area_types(vertices, triangles, vec![0, 0, 1, 1]).unwrap();
+    /// mesh.set_area_name("ground", 0);
+    /// mesh.set_area_name("water", 1);
+    /// assert_eq!(mesh.named_area_type("water"), Some(1));
+    /// assert_eq!(mesh.area_name(0), Some("ground"));
+    ///
+    /// let options = NavPathOptions {
+    ///     area_mask: mesh.area_mask(&["ground"]),
+    ///     ..Default::default()
+    /// };
+    /// let find = |options: &NavPathOptions| {
+    ///     mesh.find_path_ex(
+    ///         (0.1, 0.5, 0.0).into(),
+    ///         (1.9, 0.5, 0.0).into(),
     ///         NavQuery::Accuracy,
-    ///         NavPathMode::MidPoints,
-    ///         |_dist_sqr, _first_idx, _second_idx| true,
+    ///         NavPathMode::Funnel,
+    ///         options,
+    ///         |_, _, _| true,
+    ///     )
+    /// };
+    /// assert!(find(&options).is_none());
+    /// let result = find(&NavPathOptions::default()).unwrap();
+    /// assert_eq!(result.transitions.len(), 1);
+    /// assert_eq!(mesh.area_name(result.transitions[0].to), Some("water"));
+    /// ```
+    pub fn set_area_name(&mut self, name: impl Into<String>, area_type: u8) -> bool {
+        if area_type as usize >= NAV_AREA_TYPES_COUNT {
+            return false;
+        }
+        let name = name.into();
+        self.area_names
+            .retain(|(other, other_type)| *other != name && *other_type != area_type);
+        self.area_names.push((name, area_type));
+        true
+    }
+
+    /// Remove area type name.
+    ///
+    /// # Arguments
+    /// * `name` - area type name.
+    ///
+    /// # Returns
+    /// `Some` with area type that had this name or `None` if there was no such name.
+    pub fn remove_area_name(&mut self, name: &str) -> Option<u8> {
+        let index = self
+            .area_names
+            .iter()
+            .position(|(other, _)| other == name)?;
+        Some(self.area_names.remove(index).1)
+    }
+
+    /// Get area type by its name.
+    ///
+    /// # Arguments
+    /// * `name` - area type name.
+    ///
+    /// # Returns
+    /// `Some` with area type or `None` if there is no area type with this name.
+    pub fn named_area_type(&self, name: &str) -> Option<u8> {
+        self.area_names
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, area_type)| *area_type)
+    }
+
+    /// Get name of area type.
+    ///
+    /// # Arguments
+    /// * `area_type` - area type.
+    ///
+    /// # Returns
+    /// `Some` with area type name or `None` if area type is not named.
+    pub fn area_name(&self, area_type: u8) -> Option<&str> {
+        self.area_names
+            .iter()
+            .find(|(_, other)| *other == area_type)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Iterator over area types names and named area types.
+    pub fn area_names(&self) -> impl Iterator<Item = (&str, u8)> {
+        self.area_names
+            .iter()
+            .map(|(name, area_type)| (name.as_str(), *area_type))
+    }
+
+    /// Build area mask (see `NavPathOptions::area_mask`) that allows only area types with given
+    /// names. Unknown names are ignored.
+    ///
+    /// # Arguments
+    /// * `names` - names of allowed area types.
+    ///
+    /// # Returns
+    /// Bit mask of allowed area types.
+    pub fn area_mask(&self, names: &[&str]) -> u64 {
+        names
+            .iter()
+            .filter_map(|name| self.named_area_type(name))
+            .fold(0, |mask, area_type| mask | (1 << area_type))
+    }
+
+    /// Find changes of named area types along triangles corridor. Triangles of area types
+    /// without name are skipped, so passing through them between two triangles of the same
+    /// named area type is not a change.
+    ///
+    /// # Arguments
+    /// * `triangles` - corridor triangles indices (i.e. result of `find_path_triangles`).
+    ///
+    /// # Returns
+    /// Area type transitions in corridor order.
+    pub fn area_transitions(&self, triangles: &[usize]) -> Vec<NavAreaTransition> {
+        let mut last = None;
+        let mut result = vec![];
+        for (index, triangle) in triangles.iter().enumerate() {
+            let Some(area) = self.areas.get(*triangle) else {
+                continue;
+            };
+            if self.area_name(area.area_type).is_none() {
+                continue;
+            }
+            match last.replace(area.area_type) {
+                Some(from) if from != area.area_type => result.push(NavAreaTransition {
+                    index,
+                    from,
+                    to: area.area_type,
+                }),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// Set water depth by triangle index.
+    ///
+    /// # Arguments
+    /// * `index` - triangle index.
+    /// * `depth` - water depth, zero or less for dry land.
+    ///
+    /// # Returns
+    /// Old water depth.
+    #[inline]
+    pub fn set_area_depth(&mut self, index: usize, depth: Scalar) -> Scalar {
+        self.topology_version += 1;
+        let area = &mut self.areas[index];
+        let old = area.depth;
+        area.depth = depth.max(0.0);
+        old
+    }
+
+    /// Build render buffers of nav mesh triangles, i.e. to draw navigation overlay.
+    ///
+    /// # Arguments
+    /// * `split` - grouping of triangles into separate buffers.
+    ///
+    /// # Returns
+    /// Render meshes sorted by group, each with vertices deduplicated by position.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (2.0, 0.0, 0.0).into(), // 4
+    ///     (1.0, 0.0, 0.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    ///     (5, 4, 2).into(), // 2
+    /// ];
+    ///
+    /// let mut mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let render = mesh.render_mesh(NavRenderSplit::None);
+    /// assert_eq!(render.len(), 1);
+    /// assert_eq!(render[0].vertices.len(), 5);
+    /// assert_eq!(render[0].indices, vec![0, 1, 2, 2, 3, 0, 1, 4, 2]);
+    ///
+    /// mesh.set_area_type(2, 3);
+    /// let render = mesh.render_mesh(NavRenderSplit::AreaType);
+    /// assert_eq!(render.len(), 2);
+    /// assert_eq!(render[1].group, 3);
+    /// assert_eq!(render[1].indices, vec![0, 1, 2]);
+    /// ```
+    pub fn render_mesh(&self, split: NavRenderSplit) -> Vec<NavRenderMesh> {
+        let mut groups = match split {
+            NavRenderSplit::None => vec![(0, (0..self.triangles.len()).collect::<Vec<_>>())],
+            NavRenderSplit::AreaType => {
+                let mut groups = HashMap::<_, Vec<_>>::new();
+                for (index, area) in self.areas.iter().enumerate() {
+                    groups
+                        .entry(area.area_type as usize)
+                        .or_default()
+                        .push(index);
+                }
+                groups.into_iter().collect()
+            }
+            NavRenderSplit::Island => self
+                .find_triangle_islands()
+                .into_iter()
+                .enumerate()
+                .map(|(index, mut triangles)| {
+                    triangles.sort_unstable();
+                    (index, triangles)
+                })
+                .collect(),
+        };
+        groups.sort_by_key(|(group, _)| *group);
+        groups
+            .into_iter()
+            .map(|(group, triangles)| {
+                let mut result = NavRenderMesh {
+                    group,
+                    vertices: vec![],
+                    indices: Vec::with_capacity(triangles.len() * 3),
+                };
+                // vertices are matched by exact position bits.
+                let mut mapping = HashMap::new();
+                for index in triangles {
+                    let triangle = self.triangles[index];
+                    for vertice in [triangle.first, triangle.second, triangle.third] {
+                        let point = self.vertices[vertice as usize];
+                        let key = (point.x.to_bits(), point.y.to_bits(), point.z.to_bits());
+                        let index = *mapping.entry(key).or_insert_with(|| {
+                            result.vertices.push(point);
+                            result.vertices.len() as u32 - 1
+                        });
+                        result.indices.push(index);
+                    }
+                }
+                result
+            })
+            .collect()
+    }
+
+    /// Capture runtime state of nav mesh.
+    ///
+    /// # Returns
+    /// Dynamic state snapshot that can be restored later with `restore_dynamic_state`.
+    pub fn dynamic_state(&self) -> NavMeshDynamicState {
+        NavMeshDynamicState {
+            area_costs: self.areas.iter().map(|area| area.cost).collect(),
+            area_types: self.areas.iter().map(|area| area.area_type).collect(),
+            area_depths: self.areas.iter().map(|area| area.depth).collect(),
+            blockers: self
+                .blockers()
+                .map(|(id, blocker)| (id, *blocker))
+                .collect(),
+            bias_regions: self.bias_regions.clone(),
+        }
+    }
+
+    /// Restore runtime state of nav mesh captured with `dynamic_state`, keeping blockers
+    /// identifiers.
+    ///
+    /// # Arguments
+    /// * `state` - dynamic state snapshot.
+    ///
+    /// # Returns
+    /// `Ok` if state matches nav mesh triangles or `Err` otherwise, in which case nav mesh is
+    /// left unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let baked = NavMesh::new(vertices, triangles).unwrap();
+    /// let mut mesh = baked.clone();
+    /// mesh.set_area_cost(1, 3.0);
+    /// let id = mesh.add_blocker(NavBlocker {
+    ///     position: (0.5, 0.5, 0.0).into(),
+    ///     radius: 0.25,
+    /// });
+    /// let saved = mesh.dynamic_state();
+    ///
+    /// let mut loaded = baked.clone();
+    /// loaded.restore_dynamic_state(&saved).unwrap();
+    /// assert_eq!(loaded.areas()[1].cost, 3.0);
+    /// assert!(loaded.blocker(id).is_some());
+    /// ```
+    pub fn restore_dynamic_state(&mut self, state: &NavMeshDynamicState) -> NavResult<()> {
+        let count = self.areas.len();
+        for len in [
+            state.area_costs.len(),
+            state.area_types.len(),
+            state.area_depths.len(),
+        ] {
+            if len != count {
+                return Err(Error::DynamicStateAreasCountDoesNotMatchTriangles(
+                    len, count,
+                ));
+            }
+        }
+        if let Some(index) = state
+            .area_types
+            .iter()
+            .position(|area_type| *area_type as usize >= NAV_AREA_TYPES_COUNT)
+        {
+            return Err(Error::AreaTypeOutOfBounds(
+                index as u32,
+                state.area_types[index],
+            ));
+        }
+        for (index, area) in self.areas.iter_mut().enumerate() {
+            area.cost = state.area_costs[index].max(0.0);
+            area.area_type = state.area_types[index];
+            area.depth = state.area_depths[index].max(0.0);
+        }
+        self.blockers = state.blockers.iter().copied().collect();
+        self.bias_regions = state.bias_regions.clone();
+        self.topology_version += 1;
+        Ok(())
+    }
+
+    /// Calculate surface-area-weighted centroid of nav mesh.
+    ///
+    /// # Returns
+    /// `Some` with centroid point or `None` if nav mesh has no surface area.
+    pub fn centroid(&self) -> Option<NavVec3> {
+        let (sum, size) = self
+            .areas
+            .iter()
+            .fold((NavVec3::default(), 0.0), |(sum, size), area| {
+                (sum + area.center * area.size, size + area.size)
+            });
+        if size > self.tolerance.threshold(2) {
+            Some(sum / size)
+        } else {
+            None
+        }
+    }
+
+    /// Add spawn bias region.
+    ///
+    /// # Arguments
+    /// * `region` - bias region descriptor.
+    #[inline]
+    pub fn add_bias_region(&mut self, region: NavBiasRegion) {
+        self.bias_regions.push(region);
+    }
+
+    /// Remove all spawn bias regions.
+    #[inline]
+    pub fn clear_bias_regions(&mut self) {
+        self.bias_regions.clear();
+    }
+
+    /// Spawn bias regions list.
+    #[inline]
+    pub fn bias_regions(&self) -> &[NavBiasRegion] {
+        &self.bias_regions
+    }
+
+    /// Calculate sampling weight of triangle: its area size multiplied by weights of all bias
+    /// regions covering triangle center.
+    ///
+    /// # Arguments
+    /// * `index` - triangle index.
+    pub fn bias_weight(&self, index: usize) -> Scalar {
+        let area = &self.areas[index];
+        self.bias_regions
+            .iter()
+            .filter(|region| region.contains(area.center))
+            .fold(area.size, |weight, region| weight * region.weight.max(0.0))
+    }
+
+    /// Sample random point on nav mesh, with triangles chance of being chosen weighted by their
+    /// surface area and bias regions.
+    ///
+    /// # Arguments
+    /// * `rng` - random numbers generator.
+    ///
+    /// # Returns
+    /// `Some` with point on nav mesh or `None` if all triangles have zero weight.
+    #[cfg(feature = "random")]
+    pub fn sample_biased_point<R: RngCore>(&self, rng: &mut R) -> Option<NavVec3> {
+        let weights = (0..self.triangles.len())
+            .map(|index| self.bias_weight(index))
+            .collect::<Vec<_>>();
+        let index = Self::pick_weighted(&weights, rng)?;
+        Some(self.random_point_in_triangle(index, rng))
+    }
+
+    /// Sample uniformly distributed random point on nav mesh surface.
+    ///
+    /// # Arguments
+    /// * `rng` - random numbers generator.
+    ///
+    /// # Returns
+    /// `Some` with point on nav mesh or `None` if nav mesh has no surface area.
+    #[cfg(feature = "random")]
+    pub fn random_point<R: RngCore>(&self, rng: &mut R) -> Option<NavVec3> {
+        let weights = self.areas.iter().map(|area| area.size).collect::<Vec<_>>();
+        let index = Self::pick_weighted(&weights, rng)?;
+        Some(self.random_point_in_triangle(index, rng))
+    }
+
+    /// Sample uniformly distributed random point on nav mesh surface, within given distance from
+    /// center point.
+    ///
+    /// # Arguments
+    /// * `center` - sampling sphere center point.
+    /// * `radius` - sampling sphere radius.
+    /// * `rng` - random numbers generator.
+    ///
+    /// # Returns
+    /// `Some` with point on nav mesh or `None` if no nav mesh surface is within `radius`.
+    #[cfg(feature = "random")]
+    pub fn random_point_around<R: RngCore>(
+        &self,
+        center: NavVec3,
+        radius: Scalar,
+        rng: &mut R,
+    ) -> Option<NavVec3> {
+        const ATTEMPTS: usize = 32;
+
+        let radius_sqr = radius * radius;
+        let candidates = self
+            .rtree()
+            .lookup_in_circle(&center, &radius_sqr)
+            .into_iter()
+            .map(|spatial| spatial.index)
+            .collect::<Vec<_>>();
+        let weights = candidates
+            .iter()
+            .map(|index| self.areas[*index].size)
+            .collect::<Vec<_>>();
+        let mut fallback = None;
+        for _ in 0..ATTEMPTS {
+            let index = candidates[Self::pick_weighted(&weights, rng)?];
+            let point = self.random_point_in_triangle(index, rng);
+            if (point - center).sqr_magnitude() <= radius_sqr {
+                return Some(point);
+            }
+            fallback = Some(index);
+        }
+        fallback.map(|index| self.spatials()[index].closest_point(center))
+    }
+
+    /// Pick random wander target reachable from origin point, which path distance (not the
+    /// straight line distance) lies within given range.
+    ///
+    /// # Arguments
+    /// * `origin` - wandering agent position.
+    /// * `min_distance` - minimal path distance to target.
+    /// * `max_distance` - maximal path distance to target.
+    /// * `rng` - random numbers generator.
+    ///
+    /// # Returns
+    /// `Some` with point on nav mesh or `None` if no suitable point was found in limited number
+    /// of attempts.
+    #[cfg(feature = "random")]
+    pub fn wander_target<R: RngCore>(
+        &self,
+        origin: NavVec3,
+        min_distance: Scalar,
+        max_distance: Scalar,
+        rng: &mut R,
+    ) -> Option<NavVec3> {
+        const ATTEMPTS: usize = 32;
+
+        if max_distance < min_distance || max_distance <= 0.0 {
+            return None;
+        }
+        let options = NavPathOptions::default();
+        for _ in 0..ATTEMPTS {
+            // path is never shorter than straight line, so targets are sampled in range sphere.
+            let point = self.random_point_around(origin, max_distance, rng)?;
+            let length = self
+                .find_path_ex(
+                    origin,
+                    point,
+                    NavQuery::Accuracy,
+                    NavPathMode::Funnel,
+                    &options,
+                    |_, _, _| true,
+                )
+                .map(|result| result.length);
+            if let Some(length) = length {
+                if length >= min_distance && length <= max_distance {
+                    return Some(point);
+                }
+            }
+        }
+        None
+    }
+
+    #[cfg(feature = "random")]
+    fn pick_weighted<R: RngCore>(weights: &[Scalar], rng: &mut R) -> Option<usize> {
+        let total = weights.iter().sum::<Scalar>();
+        if total <= ZERO_TRESHOLD {
+            return None;
+        }
+        let mut value = random_unit(rng) * total;
+        for (index, weight) in weights.iter().enumerate() {
+            if *weight > 0.0 {
+                if value < *weight {
+                    return Some(index);
+                }
+                value -= weight;
+            }
+        }
+        weights.iter().rposition(|weight| *weight > 0.0)
+    }
+
+    #[cfg(feature = "random")]
+    fn random_point_in_triangle<R: RngCore>(&self, index: usize, rng: &mut R) -> NavVec3 {
+        let t = self.triangles[index];
+        let a = self.vertices[t.first as usize];
+        let b = self.vertices[t.second as usize];
+        let c = self.vertices[t.third as usize];
+        let u = random_unit(rng).sqrt();
+        let v = random_unit(rng);
+        a * (1.0 - u) + b * (u * (1.0 - v)) + c * (u * v)
+    }
+
+    /// Register off-mesh link. Link end points are attached to closest nav mesh triangles.
+    ///
+    /// # Arguments
+    /// * `link` - off-mesh link descriptor.
+    ///
+    /// # Returns
+    /// `Some` with registered link identifier or `None` if link end points cannot be attached
+    /// to nav mesh.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (2.0, 0.0, 0.0).into(), // 4
+    ///     (3.0, 0.0, 0.0).into(), // 5
+    ///     (3.0, 1.0, 0.0).into(), // 6
+    ///     (2.0, 1.0, 0.0).into(), // 7
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    ///     (4, 5, 6).into(), // 2
+    ///     (6, 7, 4).into(), // 3
+    /// ];
+    ///
+    /// let mut mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let id = mesh
+    ///     .add_off_mesh_link(NavOffMeshLink {
+    ///         from: (1.0, 0.5, 0.0).into(),
+    ///         to: (2.0, 0.5, 0.0).into(),
+    ///         cost: 1.0,
+    ///         bidirectional: false,
+    ///         kind: NavOffMeshLinkKind::Jump,
+    ///     })
+    ///     .unwrap();
+    /// let path = mesh
+    ///     .find_path_annotated(
+    ///         (0.5, 0.5, 0.0).into(),
+    ///         (2.5, 0.5, 0.0).into(),
+    ///         NavQuery::Accuracy,
+    ///         NavPathMode::Accuracy,
+    ///         &Default::default(),
+    ///         |_, _, _| true,
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     path.points,
+    ///     vec![
+    ///         (0.5, 0.5, 0.0).into(),
+    ///         (1.0, 0.5, 0.0).into(),
+    ///         (2.0, 0.5, 0.0).into(),
+    ///         (2.5, 0.5, 0.0).into(),
+    ///     ]
+    /// );
+    /// assert_eq!(path.segments[1].link.unwrap().id, id);
+    /// ```
+    pub fn add_off_mesh_link(&mut self, link: NavOffMeshLink) -> Option<NavOffMeshLinkID> {
+        let from_triangle = self.find_closest_triangle(link.from, NavQuery::Accuracy)?;
+        let to_triangle = self.find_closest_triangle(link.to, NavQuery::Accuracy)?;
+        let from_point = self.spatials()[from_triangle].closest_point(link.from);
+        let to_point = self.spatials()[to_triangle].closest_point(link.to);
+        let id = NavOffMeshLinkID::new();
+        self.off_mesh_links.push((
+            id,
+            NavOffMeshLinkData {
+                link,
+                from_triangle,
+                to_triangle,
+                from_point,
+                to_point,
+            },
+        ));
+        self.rebuild_off_mesh_links_edges();
+        Some(id)
+    }
+
+    /// Find pairs of nearby near-parallel boundary edges at small vertical offsets (curbs,
+    /// steps) and connect them with bidirectional off-mesh links of `Step` kind, so small height
+    /// discontinuities of baked nav mesh do not break its connectivity. Up axis is Z. Triangles
+    /// already connected with off-mesh link are skipped, so generating links again does not
+    /// duplicate them.
+    ///
+    /// # Arguments
+    /// * `settings` - generation settings.
+    ///
+    /// # Returns
+    /// Identifiers of generated off-mesh links.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 0.0, 0.2).into(), // 4
+    ///     (2.0, 0.0, 0.2).into(), // 5
+    ///     (2.0, 1.0, 0.2).into(), // 6
+    ///     (1.0, 1.0, 0.2).into(), // 7
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    ///     (4, 5, 6).into(), // 2
+    ///     (6, 7, 4).into(), // 3
+    /// ];
+    ///
+    /// let mut mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let links = mesh.generate_step_links(&Default::default());
+    /// assert_eq!(links.len(), 1);
+    /// assert!(mesh.find_path_triangles(1, 2).is_some());
+    /// ```
+    pub fn generate_step_links(&mut self, settings: &NavStepLinkSettings) -> Vec<NavOffMeshLinkID> {
+        let flat = |v: NavVec3| NavVec3::new(v.x, v.y, 0.0);
+        let half_edges = self.half_edges();
+        // triangles pairs already connected with off-mesh links are skipped, so generating step
+        // links again does not duplicate them.
+        let mut linked = self
+            .off_mesh_links
+            .iter()
+            .map(|(_, data)| {
+                let (a, b) = (data.from_triangle, data.to_triangle);
+                (a.min(b), a.max(b))
+            })
+            .collect::<HashSet<_>>();
+        let margin = NavVec3::new(settings.max_gap, settings.max_gap, settings.max_height);
+        let mut links = vec![];
+        for (i, edge) in half_edges.iter().enumerate() {
+            if !edge.is_boundary() {
+                continue;
+            }
+            let (ta, a0, a1) = (
+                edge.triangle,
+                self.vertices[edge.origin as usize],
+                self.vertices[edge.target as usize],
+            );
+            let da = flat(a1 - a0);
+            let length = da.magnitude();
+            if length < self.tolerance.threshold(1) {
+                continue;
+            }
+            let rect = BoundingRect::from_corners(&(a0.min(a1) - margin), &(a0.max(a1) + margin));
+            for spatial in self.rtree().lookup_in_rectangle(&rect) {
+                let tb = spatial.index;
+                if ta == tb
+                    || linked.contains(&(ta.min(tb), ta.max(tb)))
+                    || self
+                        .connections
+                        .contains_key(&NavConnection(ta as u32, tb as u32))
+                {
+                    continue;
+                }
+                // boundary edges of other triangle, each pair of edges is tested once.
+                for (j, other) in half_edges.iter().enumerate().skip(tb * 3).take(3) {
+                    if j <= i || !other.is_boundary() || linked.contains(&(ta.min(tb), ta.max(tb)))
+                    {
+                        continue;
+                    }
+                    let (b0, b1) = (
+                        self.vertices[other.origin as usize],
+                        self.vertices[other.target as usize],
+                    );
+                    let db = flat(b1 - b0);
+                    if db.magnitude() < self.tolerance.threshold(1)
+                        || da.normalize().dot(db.normalize()).abs() < settings.min_parallel
+                    {
+                        continue;
+                    }
+                    // overlap of edge B projected on edge A.
+                    let t0 = flat(b0).project(flat(a0), flat(a1));
+                    let t1 = flat(b1).project(flat(a0), flat(a1));
+                    let start = t0.min(t1).max(0.0);
+                    let end = t0.max(t1).min(1.0);
+                    if (end - start) * length < settings.min_overlap {
+                        continue;
+                    }
+                    let pa = NavVec3::unproject(a0, a1, (start + end) * 0.5);
+                    let pb = NavVec3::unproject(b0, b1, pa.project(b0, b1).clamp(0.0, 1.0));
+                    let offset = pb - pa;
+                    if flat(offset).magnitude() > settings.max_gap
+                        || offset.z.abs() > settings.max_height
+                    {
+                        continue;
+                    }
+                    // link ends are pulled slightly inside their triangles, so they snap to them.
+                    let from = pa + (self.areas[ta].center - pa) * 0.01;
+                    let to = pb + (self.areas[tb].center - pb) * 0.01;
+                    linked.insert((ta.min(tb), ta.max(tb)));
+                    links.push(NavOffMeshLink {
+                        from,
+                        to,
+                        cost: settings.cost,
+                        bidirectional: true,
+                        kind: NavOffMeshLinkKind::Step,
+                    });
+                }
+            }
+        }
+        links
+            .into_iter()
+            .filter_map(|link| self.add_off_mesh_link(link))
+            .collect()
+    }
+
+    /// Unregister off-mesh link.
+    ///
+    /// # Arguments
+    /// * `id` - off-mesh link identifier.
+    ///
+    /// # Returns
+    /// `Some` with removed link descriptor or `None` if link was not registered.
+    pub fn remove_off_mesh_link(&mut self, id: NavOffMeshLinkID) -> Option<NavOffMeshLink> {
+        let index = self.off_mesh_links.iter().position(|(i, _)| *i == id)?;
+        let (_, data) = self.off_mesh_links.remove(index);
+        self.rebuild_off_mesh_links_edges();
+        Some(data.link)
+    }
+
+    /// Get registered off-mesh link.
+    #[inline]
+    pub fn off_mesh_link(&self, id: NavOffMeshLinkID) -> Option<&NavOffMeshLink> {
+        self.off_mesh_link_data(id).map(|data| &data.link)
+    }
+
+    /// Iterator over registered off-mesh links.
+    #[inline]
+    pub fn off_mesh_links(&self) -> impl Iterator<Item = (NavOffMeshLinkID, &NavOffMeshLink)> {
+        self.off_mesh_links
+            .iter()
+            .map(|(id, data)| (*id, &data.link))
+    }
+
+    fn off_mesh_link_data(&self, id: NavOffMeshLinkID) -> Option<&NavOffMeshLinkData> {
+        self.off_mesh_links
+            .iter()
+            .find(|(i, _)| *i == id)
+            .map(|(_, data)| data)
+    }
+
+    fn rebuild_off_mesh_links_edges(&mut self) {
+        self.topology_version += 1;
+        self.islands = OnceLock::new();
+        while let Some(index) = self.graph.edge_count().checked_sub(1) {
+            let index = EdgeIndex::new(index);
+            if self.graph[index].link.is_none() {
+                break;
+            }
+            self.graph.remove_edge(index);
+        }
+        for (id, data) in &self.off_mesh_links {
+            if data.from_triangle == data.to_triangle {
+                continue;
+            }
+            let a = self.nodes[data.from_triangle];
+            let b = self.nodes[data.to_triangle];
+            let edge = NavGraphEdge {
+                weight: (data.to_point - data.from_point).sqr_magnitude(),
+                link: Some(*id),
+            };
+            self.graph.add_edge(a, b, edge);
+            if data.link.bidirectional {
+                self.graph.add_edge(b, a, edge);
+            }
+        }
+    }
+
+    /// Register blocker.
+    ///
+    /// # Arguments
+    /// * `blocker` - blocker descriptor.
+    ///
+    /// # Returns
+    /// Registered blocker identifier.
+    pub fn add_blocker(&mut self, blocker: NavBlocker) -> NavBlockerID {
+        self.topology_version += 1;
+        let id = NavBlockerID::new();
+        self.blockers.insert(id, blocker);
+        id
+    }
+
+    /// Unregister blocker.
+    ///
+    /// # Arguments
+    /// * `id` - blocker identifier.
+    ///
+    /// # Returns
+    /// `Some` with removed blocker descriptor or `None` if blocker was not registered.
+    pub fn remove_blocker(&mut self, id: NavBlockerID) -> Option<NavBlocker> {
+        let result = self.blockers.remove(&id);
+        if result.is_some() {
+            self.topology_version += 1;
+        }
+        result
+    }
+
+    /// Get registered blocker.
+    #[inline]
+    pub fn blocker(&self, id: NavBlockerID) -> Option<&NavBlocker> {
+        self.blockers.get(&id)
+    }
+
+    /// Get mutable reference to registered blocker, i.e. to move it.
+    #[inline]
+    pub fn blocker_mut(&mut self, id: NavBlockerID) -> Option<&mut NavBlocker> {
+        let result = self.blockers.get_mut(&id);
+        if result.is_some() {
+            self.topology_version += 1;
+        }
+        result
+    }
+
+    /// Iterator over registered blockers.
+    #[inline]
+    pub fn blockers(&self) -> impl Iterator<Item = (NavBlockerID, &NavBlocker)> {
+        self.blockers.iter().map(|(id, blocker)| (*id, blocker))
+    }
+
+    /// Sweep sphere of given radius along path (so each path segment forms capsule) and find
+    /// first penetration of nav mesh boundary edges or registered blockers.
+    ///
+    /// # Arguments
+    /// * `path` - path points.
+    /// * `radius` - swept sphere radius.
+    ///
+    /// # Returns
+    /// `Some` with first penetration if found or `None` if path is clear.
+    pub fn sweep_path(&self, path: &[NavVec3], radius: Scalar) -> Option<NavSweepHit> {
+        let mut traveled = 0.0;
+        for (segment, pair) in path.windows(2).enumerate() {
+            let (from, to) = (pair[0], pair[1]);
+            let min = from.min(to) - radius;
+            let max = from.max(to) + radius;
+            let mut best: Option<(Scalar, NavSweepObstacle)> = None;
+            let mut consider = |t: Option<Scalar>, obstacle| {
+                if let Some(t) = t {
+                    if best.map(|(b, _)| t < b).unwrap_or(true) {
+                        best = Some((t, obstacle));
+                    }
+                }
+            };
+            for spatial in self
+                .rtree()
+                .lookup_in_rectangle(&BoundingRect::from_corners(&min, &max))
+            {
+                if let Some(edges) = self.hard_edges.get(&spatial.index) {
+                    for (a, b) in edges {
+                        let t = Self::sweep_edge(from, to, radius, *a, *b);
+                        consider(t, NavSweepObstacle::Boundary(*a, *b));
+                    }
+                }
+            }
+            for (id, blocker) in &self.blockers {
+                let t = Self::sweep_point(from, to, radius + blocker.radius, blocker.position);
+                consider(t, NavSweepObstacle::Blocker(*id));
+            }
+            let length = (to - from).magnitude();
+            if let Some((t, obstacle)) = best {
+                return Some(NavSweepHit {
+                    segment,
+                    position: NavVec3::unproject(from, to, t),
+                    distance: traveled + length * t,
+                    obstacle,
+                });
+            }
+            traveled += length;
+        }
+        None
+    }
+
+    // Finds first factor on segment at which sphere moving along it touches point.
+    fn sweep_point(from: NavVec3, to: NavVec3, radius: Scalar, point: NavVec3) -> Option<Scalar> {
+        Self::sweep_offset(from - point, to - from, radius)
+    }
+
+    // Finds first factor on segment at which sphere moving along it touches edge, which is where
+    // moving point enters capsule made of edge and sphere radius: either its cylinder or one of
+    // its end caps.
+    fn sweep_edge(
+        from: NavVec3,
+        to: NavVec3,
+        radius: Scalar,
+        a: NavVec3,
+        b: NavVec3,
+    ) -> Option<Scalar> {
+        let edge = b - a;
+        let length = edge.magnitude();
+        if length < ZERO_TRESHOLD {
+            return Self::sweep_point(from, to, radius, a);
+        }
+        if (Self::point_on_line(a, b, from).0 - from).magnitude() < radius {
+            return Some(0.0);
+        }
+        let axis = edge / length;
+        let direction = to - from;
+        let offset = from - a;
+        // cylinder is entered when distance to edge line (perpendicular part of offset) drops
+        // to radius, while moving point projects inside of edge.
+        let side = Self::sweep_offset(
+            offset - axis * offset.dot(axis),
+            direction - axis * direction.dot(axis),
+            radius,
+        )
+        .filter(|t| {
+            let along = (offset + direction * *t).dot(axis);
+            (0.0..=length).contains(&along)
+        });
+        [
+            side,
+            Self::sweep_point(from, to, radius, a),
+            Self::sweep_point(from, to, radius, b),
+        ]
+        .into_iter()
+        .flatten()
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+
+    // Finds first factor in `[0; 1]` at which `offset + direction * factor` gets shorter than
+    // radius.
+    fn sweep_offset(offset: NavVec3, direction: NavVec3, radius: Scalar) -> Option<Scalar> {
+        let c = offset.sqr_magnitude() - radius * radius;
+        if c < 0.0 {
+            return Some(0.0);
+        }
+        let a = direction.sqr_magnitude();
+        if a < ZERO_TRESHOLD {
+            return None;
+        }
+        let b = 2.0 * offset.dot(direction);
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let t = (-b - discriminant.sqrt()) / (2.0 * a);
+        if (0.0..=1.0).contains(&t) {
+            Some(t)
+        } else {
+            None
+        }
+    }
+
+    /// Re-check path found earlier against current nav mesh state, so agents can keep
+    /// following paths that are still valid after world change instead of replanning them.
+    ///
+    /// # Arguments
+    /// * `path` - path to validate.
+    /// * `options` - path finding options that path was found with.
+    /// * `topology_version` - nav mesh topology version at the time path was found. When it
+    ///   matches current version, path is treated as valid without further checks.
+    ///
+    /// # Returns
+    /// `Some` with index of first segment that crosses nav mesh boundary, disallowed area or
+    /// blocker, or traverses removed off-mesh link, or `None` if whole path is still valid.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (2.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mut mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let version = mesh.topology_version();
+    /// let path = NavPath::new(vec![
+    ///     (0.25, 0.5, 0.0).into(),
+    ///     (1.0, 0.5, 0.0).into(),
+    ///     (1.75, 0.5, 0.0).into(),
+    /// ]);
+    /// let options = NavPathOptions::default();
+    /// assert_eq!(mesh.validate_path(&path, &options, version), None);
+    /// mesh.add_blocker(NavBlocker {
+    ///     position: (1.5, 0.5, 0.0).into(),
+    ///     radius: 0.1,
+    /// });
+    /// assert_eq!(mesh.validate_path(&path, &options, version), Some(1));
+    /// ```
+    pub fn validate_path(
+        &self,
+        path: &NavPath,
+        options: &NavPathOptions,
+        topology_version: u64,
+    ) -> Option<usize> {
+        if topology_version == self.topology_version {
+            return None;
+        }
+        path.segments
+            .iter()
+            .zip(path.points.windows(2))
+            .position(|(segment, pair)| {
+                !self.is_path_segment_valid(pair[0], pair[1], segment.link, options)
+            })
+    }
+
+    fn is_path_segment_valid(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        link: Option<NavPathLink>,
+        options: &NavPathOptions,
+    ) -> bool {
+        if let Some(link) = link {
+            return self
+                .off_mesh_link_data(link.id)
+                .map(|data| {
+                    self.is_area_allowed(data.from_triangle, options)
+                        && self.is_area_allowed(data.to_triangle, options)
+                })
+                .unwrap_or(false);
+        }
+        let blocked = self
+            .blockers
+            .iter()
+            .any(|(_, blocker)| blocker.position.distance_to_segment(from, to) < blocker.radius);
+        if blocked {
+            return false;
+        }
+        // every triangle crossed by segment has to be allowed, not only these at sample points.
+        let mut allowed = true;
+        let clear = self.is_segment_clear_walk(from, to, |triangle| {
+            allowed = allowed && self.is_area_allowed(triangle, options);
+        });
+        clear && allowed
+    }
+
+    /// Cast ray along nav mesh surface, walking triangles from start point towards end point.
+    ///
+    /// # Arguments
+    /// * `from` - ray start point.
+    /// * `to` - ray end point.
+    ///
+    /// # Returns
+    /// `Some` with first blocking boundary edge hit if found or `None` if end point can be
+    /// reached in straight line (also when start point cannot be projected on nav mesh).
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// assert_eq!(mesh.raycast((0.1, 0.1, 0.0).into(), (0.9, 0.9, 0.0).into()), None);
+    /// let hit = mesh
+    ///     .raycast((0.5, 0.5, 0.0).into(), (2.0, 0.5, 0.0).into())
+    ///     .unwrap();
+    /// assert_eq!(hit.point, (1.0, 0.5, 0.0).into());
+    /// ```
+    pub fn raycast(&self, from: NavVec3, to: NavVec3) -> Option<NavRaycastHit> {
+        self.raycast_walk(from, to, |_| {})
+    }
+
+    // Same as `raycast` but reports every triangle that ray walks through, in order.
+    fn raycast_walk<F>(&self, from: NavVec3, to: NavVec3, mut visit: F) -> Option<NavRaycastHit>
+    where
+        F: FnMut(usize),
+    {
+        let mut triangle = self.find_closest_triangle(from, NavQuery::Accuracy)?;
+        let mut point = self.spatials()[triangle].closest_point(from);
+        let mut entry = None;
+        let epsilon = self.tolerance.threshold(0);
+        for _ in 0..=self.triangles.len() {
+            visit(triangle);
+            let normal = self.spatials()[triangle].normal();
+            let target = to.project_on_plane(point, normal);
+            let direction = target - point;
+            if direction.sqr_magnitude() < self.tolerance.threshold(2) * epsilon {
+                return None;
+            }
+            let half_edges = self.half_edges();
+            let exit = self
+                .triangle_half_edges(triangle)
+                .into_iter()
+                .filter(|index| entry != Some(*index))
+                .filter_map(|index| {
+                    let edge = &half_edges[index];
+                    let va = self.vertices[edge.origin as usize];
+                    let vb = self.vertices[edge.target as usize];
+                    let edge = vb - va;
+                    let denominator = direction.cross(edge).dot(normal);
+                    if denominator.abs() < self.tolerance.threshold(2) {
+                        return None;
+                    }
+                    let factor = (va - point).cross(edge).dot(normal) / denominator;
+                    let along = (va - point).cross(direction).dot(normal) / denominator;
+                    if factor > -epsilon && (-epsilon..=1.0 + epsilon).contains(&along) {
+                        Some((factor, index))
+                    } else {
+                        None
+                    }
+                })
+                .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+            let (factor, index) = exit?;
+            if factor >= 1.0 {
+                return None;
+            }
+            point = point + direction * factor;
+            let edge = &half_edges[index];
+            match edge.twin {
+                Some(twin) => {
+                    triangle = half_edges[twin].triangle;
+                    entry = Some(twin);
+                }
+                None => {
+                    return Some(NavRaycastHit {
+                        point,
+                        edge: (
+                            self.vertices[edge.origin as usize],
+                            self.vertices[edge.target as usize],
+                        ),
+                        triangle,
+                    })
+                }
+            }
+        }
+        None
+    }
+
+    /// Cast ray in 3D space against nav mesh triangles (not constrained to nav mesh surface),
+    /// i.e. to pick destination under mouse cursor.
+    ///
+    /// # Arguments
+    /// * `origin` - ray origin.
+    /// * `direction` - ray direction (does not have to be normalized).
+    /// * `max_dist` - maximum distance along ray.
+    ///
+    /// # Returns
+    /// `Some` with hit nearest to ray origin if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let hit = mesh
+    ///     .raycast_3d((0.75, 0.25, 5.0).into(), (0.0, 0.0, -1.0).into(), 10.0)
+    ///     .unwrap();
+    /// assert_eq!(hit.point, (0.75, 0.25, 0.0).into());
+    /// assert_eq!(hit.triangle, 0);
+    /// assert_eq!(hit.distance, 5.0);
+    /// assert_eq!(
+    ///     mesh.raycast_3d((0.75, 0.25, 5.0).into(), (0.0, 0.0, -1.0).into(), 4.0),
+    ///     None,
+    /// );
+    /// ```
+    pub fn raycast_3d(
+        &self,
+        origin: NavVec3,
+        direction: NavVec3,
+        max_dist: Scalar,
+    ) -> Option<NavRay3dHit> {
+        self.raycast_3d_filtered(origin, direction, max_dist, |_| true)
+    }
+
+    // nearest ray hit of triangle accepted by filter, ray passes through rejected triangles.
+    fn raycast_3d_filtered<F>(
+        &self,
+        origin: NavVec3,
+        direction: NavVec3,
+        max_dist: Scalar,
+        mut filter: F,
+    ) -> Option<NavRay3dHit>
+    where
+        F: FnMut(usize) -> bool,
+    {
+        let threshold = self.tolerance.threshold(1);
+        if direction.sqr_magnitude() < threshold * threshold || max_dist <= 0.0 {
+            return None;
+        }
+        let direction = direction.normalize();
+        let end = origin + direction * max_dist;
+        let bounds = self.rtree().mbr()?;
+        let (lower, upper) = (bounds.lower(), bounds.upper());
+        // walk only through part of ray that lies within nav mesh bounds.
+        let (mut from, to) = [
+            (origin.x, direction.x, lower.x, upper.x),
+            (origin.y, direction.y, lower.y, upper.y),
+            (origin.z, direction.z, lower.z, upper.z),
+        ]
+        .into_iter()
+        .try_fold((0.0 as Scalar, max_dist), |(from, to), (o, d, lo, hi)| {
+            let (lo, hi) = (lo - threshold, hi + threshold);
+            if d.abs() < threshold {
+                return if (lo..=hi).contains(&o) {
+                    Some((from, to))
+                } else {
+                    None
+                };
+            }
+            let (a, b) = ((lo - o) / d, (hi - o) / d);
+            Some((from.max(a.min(b)), to.min(a.max(b))))
+        })
+        .filter(|(from, to)| from <= to)?;
+        // step of roughly triangle size, so each step looks up only few triangles.
+        let step =
+            ((upper - lower).magnitude() / (self.triangles.len() as Scalar).sqrt()).max(threshold);
+        loop {
+            let next = (from + step).min(to);
+            let rect = BoundingRect::from_corners(
+                &(origin + direction * from),
+                &(origin + direction * next),
+            );
+            let hit = self
+                .rtree()
+                .lookup_in_rectangle(&rect)
+                .into_iter()
+                .filter(|object| filter(object.index))
+                .filter_map(|object| {
+                    let point =
+                        NavVec3::raycast_triangle(origin, end, object.a, object.b, object.c)?;
+                    Some(NavRay3dHit {
+                        point,
+                        triangle: object.index,
+                        distance: (point - origin).magnitude(),
+                    })
+                })
+                // triangles hit further along ray may be preceded by ones from next steps.
+                .filter(|hit| hit.distance <= next + threshold)
+                .min_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
+            if hit.is_some() || next >= to {
+                return hit;
+            }
+            from = next;
+        }
+    }
+
+    /// Pick path destination with camera ray (click-to-move). Ray is cast against nav mesh
+    /// triangles and passes through areas not allowed by options, so nearest walkable hit gets
+    /// picked (i.e. floor below blocked roof). When ray hits no walkable area, its nearest hit
+    /// is snapped to the closest allowed point within snap radius.
+    ///
+    /// # Arguments
+    /// * `origin` - camera ray origin.
+    /// * `direction` - camera ray direction (does not have to be normalized).
+    /// * `max_dist` - maximum distance along ray.
+    /// * `snap_radius` - maximal distance between hit point and picked destination.
+    /// * `options` - path options that tell which areas are walkable.
+    ///
+    /// # Returns
+    /// `Some` with destination ready to use as path goal or `None` if ray misses nav mesh or
+    /// there is no walkable point within snap radius.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::with_area_types(vertices, triangles, vec![0, 1]).unwrap();
+    /// let options = NavPathOptions {
+    ///     area_mask: 1,
+    ///     ..Default::default()
+    /// };
+    /// let down = NavVec3::new(0.0, 0.0, -1.0);
+    /// let goal = mesh
+    ///     .pick_destination((0.75, 0.25, 5.0).into(), down, 10.0, 0.5, &options)
+    ///     .unwrap();
+    /// assert_eq!(goal.point, (0.75, 0.25, 0.0).into());
+    /// assert_eq!(goal.triangle, 0);
+    /// let goal = mesh
+    ///     .pick_destination((0.25, 0.5, 5.0).into(), down, 10.0, 0.5, &options)
+    ///     .unwrap();
+    /// assert_eq!(goal.triangle, 0);
+    /// assert!(mesh
+    ///     .pick_destination((0.25, 0.75, 5.0).into(), down, 10.0, 0.1, &options)
+    ///     .is_none());
+    /// ```
+    pub fn pick_destination(
+        &self,
+        origin: NavVec3,
+        direction: NavVec3,
+        max_dist: Scalar,
+        snap_radius: Scalar,
+        options: &NavPathOptions,
+    ) -> Option<NavClosestPoint> {
+        let walkable = self.raycast_3d_filtered(origin, direction, max_dist, |index| {
+            self.is_area_allowed(index, options)
+        });
+        let (triangle, point) = if let Some(hit) = walkable {
+            (hit.triangle, hit.point)
+        } else {
+            let hit = self.raycast_3d(origin, direction, max_dist)?;
+            let radius_sqr = snap_radius.max(0.0) * snap_radius.max(0.0);
+            let object = self
+                .rtree()
+                .nearest_neighbor_iterator(&hit.point)
+                .take_while(|object| object.distance2(&hit.point) <= radius_sqr)
+                .find(|object| self.is_area_allowed(object.index, options))?;
+            (object.index, object.closest_point(hit.point))
+        };
+        Some(NavClosestPoint {
+            point,
+            triangle,
+            barycentric: self.barycentric(triangle, point),
+        })
+    }
+
+    /// Find closest point on nav mesh.
+    ///
+    /// # Arguments
+    /// * `point` - query point.
+    /// * `query` - query quality or query options.
+    ///
+    /// # Returns
+    /// `Some` with point on nav mesh if found or `None` otherwise.
+    pub fn closest_point(
+        &self,
+        point: NavVec3,
+        query: impl Into<NavQueryOptions>,
+    ) -> Option<NavVec3> {
+        self.snap_point(point, &query.into())
+            .map(|(_, point)| point)
+    }
+
+    /// Find closest point on nav mesh together with triangle that contains it.
+    ///
+    /// # Arguments
+    /// * `point` - query point.
+    /// * `query` - query quality or query options.
+    ///
+    /// # Returns
+    /// `Some` with closest point information if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let closest = mesh
+    ///     .closest_point_ex((1.0, 0.0, 1.0).into(), NavQuery::Accuracy)
+    ///     .unwrap();
+    /// assert_eq!(closest.point, (1.0, 0.0, 0.0).into());
+    /// assert_eq!(closest.triangle, 0);
+    /// assert_eq!(closest.barycentric, [0.0, 1.0, 0.0]);
+    /// ```
+    pub fn closest_point_ex(
+        &self,
+        point: NavVec3,
+        query: impl Into<NavQueryOptions>,
+    ) -> Option<NavClosestPoint> {
+        let (triangle, point) = self.snap_point(point, &query.into())?;
+        Some(NavClosestPoint {
+            point,
+            triangle,
+            barycentric: self.barycentric(triangle, point),
+        })
+    }
+
+    /// Find nav mesh point near target, from which target is within given range and line of
+    /// sight. Useful for approaching targets that lie off nav mesh, i.e. inside buildings or
+    /// holes ("move into attack range").
+    ///
+    /// Only few candidate points of each triangle within range are tested: point of triangle
+    /// closest to target, triangle center and triangle vertices. They are tested in order of
+    /// their distance to target, so found point is the closest one only when point of triangle
+    /// closest to target has line of sight. Candidates are not checked for being reachable from
+    /// any other point - use path finding for that.
+    ///
+    /// # Arguments
+    /// * `target` - target point.
+    /// * `range` - maximal distance between target and found point.
+    /// * `line_of_sight` - closure that gives you nav mesh point and target, and tells if target
+    ///   is visible from that point.
+    ///
+    /// # Returns
+    /// `Some` with the closest candidate point that has line of sight to target or `None` if
+    /// there is none.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let approach = mesh
+    ///     .approach_point((2.0, 0.5, 0.0).into(), 1.5, |_from, _to| true)
+    ///     .unwrap();
+    /// assert_eq!(approach.point, (1.0, 0.5, 0.0).into());
+    /// assert!(mesh
+    ///     .approach_point((3.0, 0.5, 0.0).into(), 1.5, |_from, _to| true)
+    ///     .is_none());
+    /// ```
+    pub fn approach_point<F>(
+        &self,
+        target: NavVec3,
+        range: Scalar,
+        mut line_of_sight: F,
+    ) -> Option<NavClosestPoint>
+    where
+        F: FnMut(NavVec3, NavVec3) -> bool,
+    {
+        let range_sqr = range * range;
+        let mut candidates = vec![];
+        for object in self.rtree().nearest_neighbor_iterator(&target) {
+            if object.distance2(&target) > range_sqr {
+                break;
+            }
+            let triangle = self.triangles[object.index];
+            candidates.push((object.index, object.closest_point(target)));
+            candidates.push((object.index, self.areas[object.index].center));
+            for index in [triangle.first, triangle.second, triangle.third] {
+                candidates.push((object.index, self.vertices[index as usize]));
+            }
+        }
+        let mut candidates = candidates
+            .into_iter()
+            .map(|(triangle, point)| ((point - target).sqr_magnitude(), triangle, point))
+            .filter(|(distance, _, _)| *distance <= range_sqr)
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        candidates
+            .into_iter()
+            .find(|(_, _, point)| line_of_sight(*point, target))
+            .map(|(_, triangle, point)| NavClosestPoint {
+                point,
+                triangle,
+                barycentric: self.barycentric(triangle, point),
+            })
+    }
+
+    /// Check if point lies on nav mesh surface, without snapping it.
+    ///
+    /// # Arguments
+    /// * `point` - query point.
+    /// * `horizontal_tolerance` - maximal distance from triangle along its surface.
+    /// * `vertical_tolerance` - maximal distance from triangle surface along its normal.
+    ///
+    /// # Returns
+    /// `Some` with index of triangle containing point or `None` if point is off nav mesh.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// assert_eq!(mesh.contains_point((0.75, 0.25, 0.1).into(), 0.0, 0.5), Some(0));
+    /// assert_eq!(mesh.contains_point((0.75, 0.25, 1.0).into(), 0.0, 0.5), None);
+    /// assert_eq!(mesh.contains_point((1.1, 0.5, 0.0).into(), 0.0, 0.5), None);
+    /// assert_eq!(mesh.contains_point((1.1, 0.5, 0.0).into(), 0.2, 0.5), Some(0));
+    /// ```
+    pub fn contains_point(
+        &self,
+        point: NavVec3,
+        horizontal_tolerance: Scalar,
+        vertical_tolerance: Scalar,
+    ) -> Option<usize> {
+        self.contains_point_within(point, horizontal_tolerance, Some(vertical_tolerance))
+    }
+
+    // Same as `contains_point`, but `None` vertical tolerance accepts point at any distance
+    // along triangle normal.
+    fn contains_point_within(
+        &self,
+        point: NavVec3,
+        horizontal_tolerance: Scalar,
+        vertical_tolerance: Option<Scalar>,
+    ) -> Option<usize> {
+        let horizontal = horizontal_tolerance.max(0.0) + self.tolerance.threshold(1);
+        let vertical =
+            vertical_tolerance.map(|vertical| vertical.max(0.0) + self.tolerance.threshold(1));
+        let limit = vertical.map(|vertical| horizontal * horizontal + vertical * vertical);
+        for object in self.rtree().nearest_neighbor_iterator(&point) {
+            if limit.is_some_and(|limit| object.distance2(&point) > limit) {
+                break;
+            }
+            let normal = object.normal();
+            let offset = point - object.closest_point(point);
+            let along_normal = offset.dot(normal);
+            let within_vertical = match vertical {
+                Some(vertical) => along_normal.abs() <= vertical,
+                None => true,
+            };
+            if within_vertical && (offset - normal * along_normal).magnitude() <= horizontal {
+                return Some(object.index);
+            }
+        }
+        None
+    }
+
+    /// Find triangles containing each of given points, i.e. to keep many agents associated
+    /// with triangles they stand on. Points are tested in parallel when `parallel` feature is
+    /// enabled.
+    ///
+    /// # Arguments
+    /// * `points` - query points.
+    ///
+    /// # Returns
+    /// For every point: `Some` with index of the nearest triangle that contains point projected
+    /// along triangle normal, or `None` if point is outside of nav mesh.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let points = vec![
+    ///     (0.75, 0.25, 0.5).into(),
+    ///     (0.25, 0.75, 0.0).into(),
+    ///     (1.5, 0.5, 0.0).into(),
+    /// ];
+    /// assert_eq!(mesh.which_triangle(&points), vec![Some(0), Some(1), None]);
+    /// ```
+    pub fn which_triangle(&self, points: &[NavVec3]) -> Vec<Option<usize>> {
+        iter!(points)
+            .map(|point| self.contains_point_within(*point, 0.0, None))
+            .collect()
+    }
+
+    /// Probe walkability of nav mesh around agent position, replacing separate closest point,
+    /// area, clearance and island queries movement controllers make every frame.
+    ///
+    /// # Arguments
+    /// * `position` - agent position.
+    /// * `radius` - maximal distance of position from nav mesh surface to be considered on it.
+    ///
+    /// # Returns
+    /// `Some` with probe result or `None` if nav mesh is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (2.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 2.0, 0.0).into(), // 2
+    ///     (0.0, 2.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let probe = mesh.probe((1.5, 0.25, 0.1).into(), 0.5).unwrap();
+    /// assert!(probe.on_mesh);
+    /// assert_eq!(probe.point, (1.5, 0.25, 0.0).into());
+    /// assert_eq!(probe.triangle, 0);
+    /// assert_eq!(probe.clearance, 0.25);
+    /// assert_eq!(probe.boundary_normal, Some((0.0, 1.0, 0.0).into()));
+    /// assert!(!mesh.probe((1.5, 0.25, 1.0).into(), 0.5).unwrap().on_mesh);
+    /// ```
+    pub fn probe(&self, position: NavVec3, radius: Scalar) -> Option<NavProbe> {
+        let rtree = self.rtree();
+        let object = rtree.nearest_neighbor(&position)?;
+        let point = object.closest_point(position);
+        let half_edges = self.half_edges();
+        let mut clearance = SCALAR_MAX;
+        let mut boundary_normal = None;
+        // boundary edges can not be closer than triangles that own them.
+        for object in rtree.nearest_neighbor_iterator(&point) {
+            if object.distance2(&point) > clearance * clearance {
+                break;
+            }
+            for index in self.triangle_half_edges(object.index) {
+                let edge = &half_edges[index];
+                if !edge.is_boundary() {
+                    continue;
+                }
+                let from = self.vertices[edge.origin as usize];
+                let to = self.vertices[edge.target as usize];
+                let distance = point.distance_to_segment(from, to);
+                if distance < clearance {
+                    clearance = distance;
+                    boundary_normal = Some(object.normal().cross(to - from).normalize());
+                }
+            }
+        }
+        Some(NavProbe {
+            on_mesh: (position - point).magnitude() <= radius.max(0.0),
+            point,
+            triangle: object.index,
+            area_type: self.areas[object.index].area_type,
+            clearance,
+            island: self.triangle_island(object.index).unwrap_or_default(),
+            boundary_normal,
+        })
+    }
+
+    /// Calculate barycentric coordinates of point relative to triangle vertices.
+    ///
+    /// # Arguments
+    /// * `index` - triangle index.
+    /// * `point` - point on triangle plane.
+    ///
+    /// # Returns
+    /// Weights of triangle first, second and third vertice.
+    pub fn barycentric(&self, index: usize, point: NavVec3) -> [Scalar; 3] {
+        let t = self.triangles[index];
+        let a = self.vertices[t.first as usize];
+        let b = self.vertices[t.second as usize];
+        let c = self.vertices[t.third as usize];
+        let ab = b - a;
+        let ac = c - a;
+        let ap = point - a;
+        let d00 = ab.dot(ab);
+        let d01 = ab.dot(ac);
+        let d11 = ac.dot(ac);
+        let d20 = ap.dot(ab);
+        let d21 = ap.dot(ac);
+        let denominator = d00 * d11 - d01 * d01;
+        if denominator.abs() < self.tolerance.threshold(4) {
+            return [1.0, 0.0, 0.0];
+        }
+        let v = (d11 * d20 - d01 * d21) / denominator;
+        let w = (d00 * d21 - d01 * d20) / denominator;
+        [1.0 - v - w, v, w]
+    }
+
+    /// Find shortest path on nav mesh between two points.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    ///
+    /// # Returns
+    /// `Some` with path points on nav mesh if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let path = mesh
+    ///     .find_path(
+    ///         (0.0, 1.0, 0.0).into(),
+    ///         (1.5, 0.25, 0.5).into(),
+    ///         NavQuery::Accuracy,
+    ///         NavPathMode::MidPoints,
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     path.into_iter()
+    ///         .map(|v| (
+    ///             (v.x * 10.0) as i32,
+    ///             (v.y * 10.0) as i32,
+    ///             (v.z * 10.0) as i32,
+    ///         ))
+    ///         .collect::<Vec<_>>(),
+    ///     vec![(0, 10, 0), (10, 5, 0), (15, 2, 5),]
+    /// );
+    /// ```
+    pub fn find_path(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        mode: NavPathMode,
+    ) -> Option<Vec<NavVec3>> {
+        self.find_path_custom(from, to, query, mode, |_, _, _| true)
+    }
+
+    /// Find shortest path on nav mesh between two points, providing custom filtering function.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    /// * `filter` - closure that gives you a connection distance squared, first triangle index
+    ///   and second triangle index, and returns `false` to give that connection maximal cost, so
+    ///   it is used only when there is no other way (use `find_path_excluding` to exclude
+    ///   connections from search instead).
+    ///
+    /// # Returns
+    /// `Some` with path points on nav mesh if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let path = mesh
+    ///     .find_path_custom(
+    ///         (0.0, 1.0, 0.0).into(),
+    ///         (1.5, 0.25, 0.5).into(),
+    ///         NavQuery::Accuracy,
+    ///         NavPathMode::MidPoints,
+    ///         |_dist_sqr, _first_idx, _second_idx| true,
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     path.into_iter()
+    ///         .map(|v| (
+    ///             (v.x * 10.0) as i32,
+    ///             (v.y * 10.0) as i32,
+    ///             (v.z * 10.0) as i32,
+    ///         ))
+    ///         .collect::<Vec<_>>(),
+    ///     vec![(0, 10, 0), (10, 5, 0), (15, 2, 5),]
+    /// );
+    /// ```
+    pub fn find_path_custom<F>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        mode: NavPathMode,
+        filter: F,
+    ) -> Option<Vec<NavVec3>>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.find_path_result(
+            from,
+            to,
+            query.into(),
+            mode,
+            &NavPathOptions::default(),
+            Self::penalize_rejected(filter),
+        )
+        .map(|result| result.path.points)
+    }
+
+    /// Find shortest path on nav mesh between two points, excluding connections rejected by
+    /// filtering function (i.e. triangles occupied by hazards).
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    /// * `filter` - closure that gives you a connection distance squared, first triangle index
+    ///   and second triangle index, and returns `false` to exclude that connection from search.
+    ///
+    /// # Returns
+    /// `Some` with path points on nav mesh if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let from = (0.0, 1.0, 0.0).into();
+    /// let to = (1.5, 0.25, 0.5).into();
+    /// let avoid = |_, _, next| next != 3;
+    /// assert!(mesh
+    ///     .find_path_excluding(from, to, NavQuery::Accuracy, NavPathMode::MidPoints, avoid)
+    ///     .is_none());
+    /// assert!(mesh
+    ///     .find_path_custom(from, to, NavQuery::Accuracy, NavPathMode::MidPoints, avoid)
+    ///     .is_some());
+    /// ```
+    pub fn find_path_excluding<F>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        mode: NavPathMode,
+        filter: F,
+    ) -> Option<Vec<NavVec3>>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.find_path_with_options(from, to, query, mode, &NavPathOptions::default(), filter)
+    }
+
+    /// Find shortest path on nav mesh between two points, providing additional options and
+    /// custom filtering function.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    /// * `options` - path finding options.
+    /// * `filter` - closure that gives you a connection distance squared, first triangle index
+    ///   and second triangle index, and returns `false` to exclude that connection from search.
+    ///
+    /// # Returns
+    /// `Some` with path points on nav mesh if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let options = NavPathOptions {
+    ///     jitter: Some(NavPathJitter {
+    ///         seed: 42,
+    ///         amount: 0.25,
+    ///     }),
+    ///     ..Default::default()
+    /// };
+    /// let path = mesh
+    ///     .find_path_with_options(
+    ///         (0.0, 1.0, 0.0).into(),
+    ///         (1.5, 0.25, 0.5).into(),
+    ///         NavQuery::Accuracy,
+    ///         NavPathMode::MidPoints,
+    ///         &options,
+    ///         |_dist_sqr, _first_idx, _second_idx| true,
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(path.len(), 3);
+    /// ```
+    pub fn find_path_with_options<F>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        mode: NavPathMode,
+        options: &NavPathOptions,
+        filter: F,
+    ) -> Option<Vec<NavVec3>>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.find_path_annotated(from, to, query, mode, options, filter)
+            .map(|path| path.points)
+    }
+
+    /// Find path on nav mesh between two points that avoids crowded triangles: cost of entering
+    /// triangle is multiplied by its occupancy cost factor.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    /// * `options` - path finding options.
+    /// * `occupancy` - agents count of nav mesh triangles.
+    /// * `weight` - additional cost factor added per agent occupying triangle.
+    ///
+    /// # Returns
+    /// `Some` with path points on nav mesh if found or `None` otherwise.
+    #[allow(clippy::too_many_arguments)]
+    pub fn find_path_congested(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        mode: NavPathMode,
+        options: &NavPathOptions,
+        occupancy: &NavOccupancy,
+        weight: Scalar,
+    ) -> Option<Vec<NavVec3>> {
+        if from.same_as_threshold(to, self.tolerance.threshold(2)) {
+            return None;
+        }
+        let query = query.into();
+        let (start, from) = self.snap_point(from, &query)?;
+        let (end, to) = self.snap_point(to, &query)?;
+        let corridor = self.search_triangles_weighted(
+            start,
+            end,
+            options,
+            |_, _, _| Some(0.0),
+            |index| occupancy.cost_factor(index, weight),
+            &NavZeroHeuristic,
+        )?;
+        if corridor.triangles.last() != Some(&end) {
+            return None;
+        }
+        let path = self.build_path(
+            from,
+            to,
+            &corridor.triangles,
+            &corridor.links,
+            mode,
+            options,
+        )?;
+        Some(path.points)
+    }
+
+    /// Find shortest path on nav mesh between two points, with path segments annotated with
+    /// traversed off-mesh links.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    /// * `options` - path finding options.
+    /// * `filter` - closure that gives you a connection distance squared, first triangle index
+    ///   and second triangle index, and returns `false` to exclude that connection from search.
+    ///
+    /// # Returns
+    /// `Some` with annotated path if found or `None` otherwise.
+    pub fn find_path_annotated<F>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        mode: NavPathMode,
+        options: &NavPathOptions,
+        filter: F,
+    ) -> Option<NavPath>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.find_path_ex(from, to, query, mode, options, filter)
+            .map(|result| result.path)
+    }
+
+    /// Find shortest path on nav mesh between two points, together with its metadata: traversed
+    /// triangles, length and cost.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    /// * `options` - path finding options.
+    /// * `filter` - closure that gives you a connection distance squared, first triangle index
+    ///   and second triangle index, and returns `false` to exclude that connection from search.
+    ///
+    /// # Returns
+    /// `Some` with path result if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let result = mesh
+    ///     .find_path_ex(
+    ///         (0.0, 0.5, 0.0).into(),
+    ///         (2.0, 0.5, 1.0).into(),
+    ///         NavQuery::Accuracy,
+    ///         NavPathMode::Accuracy,
+    ///         &NavPathOptions::default(),
+    ///         |_, _, _| true,
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(result.triangles, vec![1, 0, 3, 2]);
+    /// assert_eq!(result.points().len(), 3);
+    /// assert_eq!(result.segment_lengths().count(), 2);
+    /// assert!((result.length - (1.0 + 2.0_f32.sqrt() as Scalar)).abs() < 1.0e-5);
+    /// ```
+    pub fn find_path_ex<F>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        mode: NavPathMode,
+        options: &NavPathOptions,
+        mut filter: F,
+    ) -> Option<NavPathResult>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.find_path_result(from, to, query.into(), mode, options, |weight, a, b| {
+            filter(weight, a, b).then_some(0.0)
+        })
+    }
+
+    // Same as `find_path_ex` but `filter` gives extra cost of connection or `None` to exclude it
+    // from search.
+    fn find_path_result<F>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: NavQueryOptions,
+        mode: NavPathMode,
+        options: &NavPathOptions,
+        filter: F,
+    ) -> Option<NavPathResult>
+    where
+        F: FnMut(Scalar, usize, usize) -> Option<Scalar>,
+    {
+        if from.same_as_threshold(to, self.tolerance.threshold(2)) {
+            return None;
+        }
+        let (start, from) = self.snap_point(from, &query)?;
+        let (end, to) = self.snap_point(to, &query)?;
+        #[cfg(feature = "debug")]
+        crate::nav_debug::emit(|| crate::NavDebugFrame::SnappedEndpoints {
+            mesh: self.id,
+            from,
+            from_triangle: start,
+            to,
+            to_triangle: end,
+        });
+        let NavCorridor {
+            triangles,
+            links,
+            cost,
+        } = self.search_triangles_weighted(
+            start,
+            end,
+            options,
+            filter,
+            |_| 1.0,
+            &NavZeroHeuristic,
+        )?;
+        if triangles.is_empty() {
+            return None;
+        }
+        #[cfg(feature = "debug")]
+        crate::nav_debug::emit(|| crate::NavDebugFrame::Corridor {
+            mesh: self.id,
+            triangles: triangles.clone(),
+        });
+        let partial = triangles.last() != Some(&end);
+        let to = if partial {
+            self.spatials()[*triangles.last().unwrap()].closest_point(to)
+        } else {
+            to
+        };
+        let path = self.build_path(from, to, &triangles, &links, mode, options)?;
+        #[cfg(feature = "debug")]
+        crate::nav_debug::emit(|| crate::NavDebugFrame::Path {
+            mesh: self.id,
+            path: path.clone(),
+        });
+        Some(NavPathResult {
+            length: path.length(),
+            path,
+            transitions: self.area_transitions(&triangles),
+            triangles,
+            cost,
+            partial,
+            version: self.topology_version,
+        })
+    }
+
+    /// Calculate detour factor between two points: ratio of path length to straight line
+    /// distance. Values close to `1.0` mean points are connected almost directly, big values
+    /// flag convoluted routes.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `options` - path finding options.
+    ///
+    /// # Returns
+    /// `Some` with detour factor if path is found or `None` otherwise (also when points are
+    /// the same).
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (2.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let factor = mesh
+    ///     .detour_factor(
+    ///         (0.25, 0.5, 0.0).into(),
+    ///         (1.75, 0.5, 0.0).into(),
+    ///         NavQuery::Accuracy,
+    ///         &NavPathOptions::default(),
+    ///     )
+    ///     .unwrap();
+    /// assert!((factor - 1.0).abs() < 1.0e-4);
+    /// ```
+    pub fn detour_factor(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        options: &NavPathOptions,
+    ) -> Option<Scalar> {
+        let distance = (to - from).magnitude();
+        if distance < self.tolerance.threshold(1) {
+            return None;
+        }
+        let result =
+            self.find_path_ex(from, to, query, NavPathMode::Funnel, options, |_, _, _| {
+                true
+            })?;
+        Some(result.length / distance)
+    }
+
+    /// Calculate length of the shortest path along nav mesh surface between two points, without
+    /// building path.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `max_distance` - optional upper bound, triangles farther away from start point are not
+    ///   searched and longer paths are rejected early.
+    ///
+    /// # Returns
+    /// `Some` with path length if path is found (and is not longer than `max_distance`) or
+    /// `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (2.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let from = (0.25, 0.5, 0.0).into();
+    /// let to = (1.75, 0.5, 0.0).into();
+    /// let distance = mesh
+    ///     .geodesic_distance(from, to, NavQuery::Accuracy, None)
+    ///     .unwrap();
+    /// assert!((distance - 1.5).abs() < 1.0e-4);
+    /// assert_eq!(
+    ///     mesh.geodesic_distance(from, to, NavQuery::Accuracy, Some(1.0)),
+    ///     None
+    /// );
+    /// ```
+    pub fn geodesic_distance(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        max_distance: Option<Scalar>,
+    ) -> Option<Scalar> {
+        let query = query.into();
+        let (start, from) = self.snap_point(from, &query)?;
+        let (end, to) = self.snap_point(to, &query)?;
+        let max_distance = max_distance.unwrap_or(SCALAR_MAX);
+        if (to - from).magnitude() > max_distance {
+            return None;
+        }
+        if start == end {
+            return Some((to - from).magnitude());
+        }
+        let options = NavPathOptions::default();
+        // path through triangle is never shorter than straight line to that triangle.
+        let spatials = self.spatials();
+        let NavCorridor {
+            triangles, links, ..
+        } = self
+            .search_triangles_weighted(
+                start,
+                end,
+                &options,
+                |_, _, next| {
+                    ((spatials[next].closest_point(from) - from).magnitude() <= max_distance)
+                        .then_some(0.0)
+                },
+                |_| 1.0,
+                &NavZeroHeuristic,
+            )
+            .filter(|corridor| corridor.triangles.last() == Some(&end))?;
+        let mut result = 0.0;
+        let mut section = 0;
+        let mut point = from;
+        let mut add = |points: &[NavVec3]| {
+            result += points
+                .windows(2)
+                .map(|pair| (pair[1] - pair[0]).magnitude())
+                .sum::<Scalar>();
+            result <= max_distance
+        };
+        for index in 1..triangles.len() {
+            if let Some(data) = links[index].and_then(|id| self.off_mesh_link_data(id)) {
+                let points = self.find_path_section(
+                    point,
+                    data.from_point,
+                    &triangles[section..index],
+                    NavPathMode::Funnel,
+                )?;
+                if !add(&points) || !add(&[data.from_point, data.to_point]) {
+                    return None;
+                }
+                point = data.to_point;
+                section = index;
+            }
+        }
+        let points =
+            self.find_path_section(point, to, &triangles[section..], NavPathMode::Funnel)?;
+        if add(&points) {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    /// Find costs of paths from the nearest of source triangles to every triangle, the same as
+    /// costs of paths found with `find_path_triangles`. With `parallel` feature big nav meshes
+    /// get split into regions of consecutive triangles integrated in parallel.
+    ///
+    /// # Arguments
+    /// * `sources` - source triangles indices.
+    ///
+    /// # Returns
+    /// Path cost of each triangle, `None` for triangles not reachable from sources.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (2.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let distances = mesh.distance_map(&[0]);
+    /// assert_eq!(distances[0], Some(0.0));
+    /// assert_eq!(distances[1], Some(mesh.find_path_triangles(0, 1).unwrap().1));
+    /// ```
+    pub fn distance_map(&self, sources: &[usize]) -> Vec<Option<Scalar>> {
+        let chunk_size = if cfg!(feature = "parallel") {
+            INTEGRATE_CHUNK_TRIANGLES
+        } else {
+            self.triangles.len()
+        };
+        self.integrate_chunked(sources, chunk_size)
+            .into_iter()
+            .map(|cost| if cost < SCALAR_MAX { Some(cost) } else { None })
+            .collect()
+    }
+
+    // Path costs from source triangles, with triangles split into chunks integrated separately
+    // (in parallel with `parallel` feature), see `nav_search::integrate_chunked`.
+    pub(crate) fn integrate_chunked(&self, sources: &[usize], chunk_size: usize) -> Vec<Scalar> {
+        let options = NavPathOptions::default();
+        integrate_chunked(self.triangles.len(), sources, chunk_size, |a, next| {
+            for e in self.graph.edges(self.nodes[a]) {
+                let b = self.nodes_map[&e.target()];
+                if self.is_edge_passable(a, b, e.weight(), &options) {
+                    next(b, self.edge_cost(a, b, e.weight(), &options));
+                }
+            }
+        })
+        .0
+    }
+
+    /// Start time-sliced path finding on nav mesh between two points. Returned query performs
+    /// limited amount of work each time it gets updated, so long queries on big meshes can be
+    /// spread over multiple frames.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    /// * `options` - path finding options.
+    ///
+    /// # Returns
+    /// `Some` with sliced path query if both points were snapped to nav mesh or `None`
+    /// otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let mut query = mesh
+    ///     .find_path_sliced(
+    ///         (0.0, 1.0, 0.0).into(),
+    ///         (1.5, 0.25, 0.5).into(),
+    ///         NavQuery::Accuracy,
+    ///         NavPathMode::MidPoints,
+    ///         &NavPathOptions::default(),
+    ///     )
+    ///     .unwrap();
+    /// while query.update(1) == NavSlicedPathStatus::InProgress {}
+    /// assert_eq!(query.status(), NavSlicedPathStatus::Found);
+    /// assert_eq!(query.into_path().unwrap().points.len(), 3);
+    /// ```
+    pub fn find_path_sliced(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        mode: NavPathMode,
+        options: &NavPathOptions,
+    ) -> Option<NavSlicedPathQuery<'_>> {
+        if from.same_as_threshold(to, self.tolerance.threshold(2)) {
+            return None;
+        }
+        let query = query.into();
+        let (start, from) = self.snap_point(from, &query)?;
+        let (end, to) = self.snap_point(to, &query)?;
+        let mut open = BinaryHeap::new();
+        open.push(NavSlicedPathItem(0.0, start));
+        let mut costs = HashMap::new();
+        costs.insert(start, 0.0);
+        Some(NavSlicedPathQuery {
+            mesh: self,
+            from,
+            to,
+            end,
+            mode,
+            options: options.clone(),
+            open,
+            costs,
+            parents: HashMap::new(),
+            expansions: 0,
+            status: NavSlicedPathStatus::InProgress,
+            path: None,
+        })
+    }
+
+    /// Find path on nav mesh between two points and keep its triangles corridor, so path can be
+    /// cheaply repaired when its start or end point moves.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    /// * `options` - path finding options.
+    ///
+    /// # Returns
+    /// `Some` with path corridor if path was found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let mut corridor = mesh
+    ///     .find_path_corridor(
+    ///         (0.0, 1.0, 0.0).into(),
+    ///         (1.5, 0.25, 0.5).into(),
+    ///         NavQuery::Accuracy,
+    ///         NavPathMode::MidPoints,
+    ///         &NavPathOptions::default(),
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(corridor.triangles(), &[1, 0, 3, 2]);
+    /// assert!(corridor.move_position(&mesh, (0.75, 0.25, 0.0).into(), NavQuery::Accuracy));
+    /// assert_eq!(corridor.triangles(), &[0, 3, 2]);
+    /// ```
+    pub fn find_path_corridor(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        mode: NavPathMode,
+        options: &NavPathOptions,
+    ) -> Option<NavPathCorridor> {
+        let query = query.into();
+        let (start, from) = self.snap_point(from, &query)?;
+        let (end, to) = self.snap_point(to, &query)?;
+        let (triangles, _) =
+            self.find_path_triangles_with_options(start, end, options, |_, _, _| true)?;
+        if triangles.is_empty() {
+            return None;
+        }
+        let mut result = NavPathCorridor {
+            mesh: self.id,
+            position: from,
+            target: to,
+            triangles,
+            mode,
+            options: options.clone(),
+            repair_expansions: NavPathCorridor::DEFAULT_REPAIR_EXPANSIONS,
+            path: NavPath::default(),
+        };
+        result.path = result.corridor_path(self, &result.triangles, from, to)?;
+        Some(result)
+    }
+
+    // Dijkstra search from multiple source triangles to the closest triangle accepted by `goal`,
+    // giving up after `max_expansions` expanded triangles.
+    fn search_triangles_bounded<G>(
+        &self,
+        sources: &[usize],
+        mut goal: G,
+        options: &NavPathOptions,
+        max_expansions: usize,
+    ) -> Option<Vec<usize>>
+    where
+        G: FnMut(usize) -> bool,
+    {
+        let mut costs = HashMap::new();
+        let mut parents = HashMap::new();
+        let mut open = BinaryHeap::new();
+        for source in sources {
+            costs.insert(*source, 0.0);
+            open.push(NavSlicedPathItem(0.0, *source));
+        }
+        let mut expansions = 0;
+        while let Some(NavSlicedPathItem(cost, triangle)) = open.pop() {
+            if cost > costs.get(&triangle).copied().unwrap_or(SCALAR_MAX) {
+                continue;
+            }
+            if goal(triangle) {
+                let mut result = vec![triangle];
+                let mut current = triangle;
+                while let Some(parent) = parents.get(&current) {
+                    current = *parent;
+                    result.push(current);
+                }
+                result.reverse();
+                return Some(result);
+            }
+            expansions += 1;
+            if expansions > max_expansions {
+                return None;
+            }
+            for edge in self.graph.edges(self.nodes[triangle]) {
+                let next = self.nodes_map[&edge.target()];
+                if !self.is_edge_passable(triangle, next, edge.weight(), options) {
+                    continue;
+                }
+                let next_cost = cost + self.edge_cost(triangle, next, edge.weight(), options);
+                if next_cost < costs.get(&next).copied().unwrap_or(SCALAR_MAX) {
+                    costs.insert(next, next_cost);
+                    parents.insert(next, triangle);
+                    open.push(NavSlicedPathItem(next_cost, next));
+                }
+            }
+        }
+        None
+    }
+
+    // Builds path through corridor triangles, where `links` are off-mesh links taken to enter
+    // each of them. Gives `None` if consecutive triangles are neither adjacent nor joined by
+    // given off-mesh link (i.e. corridor got outdated by removed link).
+    fn build_path(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        triangles: &[usize],
+        links: &[Option<NavOffMeshLinkID>],
+        mode: NavPathMode,
+        options: &NavPathOptions,
+    ) -> Option<NavPath> {
+        let threshold = self.tolerance.threshold(2);
+        let mut result = NavPath::default();
+        let mut section = 0;
+        let mut point = from;
+        for index in 1..triangles.len() {
+            let (a, b) = (triangles[index - 1], triangles[index]);
+            let link = match links.get(index).copied().flatten() {
+                Some(id) => {
+                    let data = self.off_mesh_link_data(id)?;
+                    if (data.from_triangle, data.to_triangle) != (a, b)
+                        && (data.from_triangle, data.to_triangle) != (b, a)
+                    {
+                        return None;
+                    }
+                    Some((id, data))
+                }
+                None if self
+                    .connections
+                    .contains_key(&NavConnection(a as u32, b as u32)) =>
+                {
+                    None
+                }
+                None => return None,
+            };
+            if let Some((id, data)) = link {
+                let points = self.find_path_section(
+                    point,
+                    data.from_point,
+                    &triangles[section..index],
+                    mode,
+                )?;
+                for p in self.shorten_path_if(points, options) {
+                    result.push_threshold(p, None, threshold);
+                }
+                result.push_threshold(
+                    data.to_point,
+                    Some(NavPathLink {
+                        id,
+                        kind: data.link.kind,
+                    }),
+                    threshold,
+                );
+                point = data.to_point;
+                section = index;
+            }
+        }
+        let points = self.find_path_section(point, to, &triangles[section..], mode)?;
+        for p in self.shorten_path_if(points, options) {
+            result.push_threshold(p, None, threshold);
+        }
+        Some(result)
+    }
+
+    fn shorten_path_if(&self, points: Vec<NavVec3>, options: &NavPathOptions) -> Vec<NavVec3> {
+        if options.shorten {
+            self.shorten_path(&points)
+        } else {
+            points
+        }
+    }
+
+    /// Shorten path by iteratively removing waypoints whenever straight segment between their
+    /// neighbors stays on nav mesh surface and does not hit its boundary.
+    ///
+    /// # Arguments
+    /// * `path` - path points.
+    ///
+    /// # Returns
+    /// Shortened path points.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (2.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 2.0, 0.0).into(), // 2
+    ///     (0.0, 2.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let path = mesh.shorten_path(&[
+    ///     (0.5, 0.5, 0.0).into(),
+    ///     (1.0, 1.5, 0.0).into(),
+    ///     (1.5, 0.5, 0.0).into(),
+    ///     (1.5, 1.5, 0.0).into(),
+    /// ]);
+    /// assert_eq!(path, vec![(0.5, 0.5, 0.0).into(), (1.5, 1.5, 0.0).into()]);
+    /// ```
+    pub fn shorten_path(&self, path: &[NavVec3]) -> Vec<NavVec3> {
+        let mut result = path.to_vec();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            let mut index = 1;
+            while index + 1 < result.len() {
+                if self.is_segment_clear(result[index - 1], result[index + 1]) {
+                    result.remove(index);
+                    changed = true;
+                } else {
+                    index += 1;
+                }
+            }
+        }
+        result
+    }
+
+    /// Project nav grid path onto nav mesh covering the same space and shorten it, so paths
+    /// planned on cheap grid get smooth nav mesh motion quality.
+    ///
+    /// # Arguments
+    /// * `path` - nav grid path cells.
+    /// * `query` - query quality or query options used to snap cells points on nav mesh.
+    /// * `cell_point` - closure that gives you grid cell col-row and returns its world point.
+    ///
+    /// # Returns
+    /// Shortened path points. Cells that can not be snapped on nav mesh are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (2.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 2.0, 0.0).into(), // 2
+    ///     (0.0, 2.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let grid = NavGrid::new(2, 2, vec![true; 4]).unwrap();
+    /// let path = grid.find_path((0, 0), (1, 1)).unwrap();
+    /// let path = mesh.smooth_grid_path(&path, NavQuery::Accuracy, |(col, row)| {
+    ///     NavVec3::new(col as Scalar + 0.5, row as Scalar + 0.5, 0.1)
+    /// });
+    /// assert_eq!(path, vec![(0.5, 0.5, 0.0).into(), (1.5, 1.5, 0.0).into()]);
+    /// ```
+    pub fn smooth_grid_path<F>(
+        &self,
+        path: &[(usize, usize)],
+        query: impl Into<NavQueryOptions>,
+        mut cell_point: F,
+    ) -> Vec<NavVec3>
+    where
+        F: FnMut((usize, usize)) -> NavVec3,
+    {
+        let options = query.into();
+        let mut points = Vec::<NavVec3>::with_capacity(path.len());
+        for cell in path {
+            if let Some((_, point)) = self.snap_point(cell_point(*cell), &options) {
+                if points
+                    .last()
+                    .map(|last| !last.same_as(point))
+                    .unwrap_or(true)
+                {
+                    points.push(point);
+                }
+            }
+        }
+        self.shorten_path(&points)
+    }
+
+    pub(crate) fn is_segment_clear(&self, from: NavVec3, to: NavVec3) -> bool {
+        self.is_segment_clear_walk(from, to, |_| {})
+    }
+
+    // Same as `is_segment_clear` but reports every triangle that segment goes through.
+    fn is_segment_clear_walk<F>(&self, from: NavVec3, to: NavVec3, visit: F) -> bool
+    where
+        F: FnMut(usize),
+    {
+        if self.raycast_walk(from, to, visit).is_some() {
+            return false;
+        }
+        // straight segment between different surface levels cuts through the air or ground.
+        let middle = (from + to) * 0.5;
+        let tolerance = ((to - from).magnitude() * 1.0e-3).max(self.tolerance.threshold(1));
+        self.closest_point(middle, NavQuery::Accuracy)
+            .map(|point| (point - middle).magnitude() <= tolerance)
+            .unwrap_or(false)
+    }
+
+    // Off-mesh links taken to enter each of corridor triangles, found without search results
+    // as cheapest passable edges between consecutive triangles. Gives `None` if any of
+    // consecutive triangles are not connected by passable edge.
+    fn corridor_links(
+        &self,
+        triangles: &[usize],
+        options: &NavPathOptions,
+    ) -> Option<Vec<Option<NavOffMeshLinkID>>> {
+        Some(Some(None))
+            .into_iter()
+            .chain(triangles.windows(2).map(|pair| {
+                let (from, to) = (pair[0], pair[1]);
+                let edge = self
+                    .graph
+                    .edges_connecting(*self.nodes.get(from)?, *self.nodes.get(to)?)
+                    .filter(|e| self.is_edge_passable(from, to, e.weight(), options))
+                    .map(|e| (self.edge_cost(from, to, e.weight(), options), e.weight()))
+                    .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())?
+                    .1;
+                Some(edge.link)
+            }))
+            .collect()
+    }
+
+    fn find_path_section(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        triangles: &[usize],
+        mode: NavPathMode,
+    ) -> Option<Vec<NavVec3>> {
+        if triangles.len() < 2 {
+            return Some(vec![from, to]);
+        }
+        match mode {
+            NavPathMode::Accuracy => self.find_path_accuracy(from, to, triangles),
+            NavPathMode::MidPoints => self.find_path_midpoints(from, to, triangles, false),
+            NavPathMode::ClearanceMidPoints => self.find_path_midpoints(from, to, triangles, true),
+            NavPathMode::Funnel => self.find_path_funnel(from, to, triangles),
+        }
+    }
+
+    fn find_path_accuracy(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        triangles: &[usize],
+    ) -> Option<Vec<NavVec3>> {
+        #[derive(Debug)]
+        enum Node {
+            Point(NavVec3),
+            // (a, b, normal)
+            LevelChange(NavVec3, NavVec3, NavVec3),
+        }
+
+        // TODO: reduce allocations.
+        if triangles.len() == 2 {
+            let NavConnection(a, b) = self
+                .connections
+                .get(&NavConnection(triangles[0] as u32, triangles[1] as u32))?
+                .1;
+            let a = self.vertices[a as usize];
+            let b = self.vertices[b as usize];
+            let n = self.spatials()[triangles[0]].normal();
+            let m = self.spatials()[triangles[1]].normal();
+            if !self.is_line_between_points(from, to, a, b, n) {
+                let da = (from - a).sqr_magnitude();
+                let db = (from - b).sqr_magnitude();
+                let point = if da < db { a } else { b };
+                return Some(vec![from, point, to]);
+            } else if n.dot(m) < 1.0 - ZERO_TRESHOLD {
+                let n = (b - a).normalize().cross(n);
+                if let Some(point) = NavVec3::raycast_line(from, to, a, b, n) {
+                    return Some(vec![from, point, to]);
+                }
+            }
+            return Some(vec![from, to]);
+        }
+        let mut start = from;
+        let mut last_normal = self.spatials()[triangles[0]].normal();
+        let mut nodes = Vec::with_capacity(triangles.len() - 1);
+        for triplets in triangles.windows(3) {
+            let NavConnection(a, b) = self
+                .connections
+                .get(&NavConnection(triplets[0] as u32, triplets[1] as u32))?
+                .1;
+            let a = self.vertices[a as usize];
+            let b = self.vertices[b as usize];
+            let NavConnection(c, d) = self
+                .connections
+                .get(&NavConnection(triplets[1] as u32, triplets[2] as u32))?
+                .1;
+            let c = self.vertices[c as usize];
+            let d = self.vertices[d as usize];
+            let normal = self.spatials()[triplets[1]].normal();
+            let old_last_normal = last_normal;
+            last_normal = normal;
+            if !self.is_line_between_points(start, c, a, b, normal)
+                || !self.is_line_between_points(start, d, a, b, normal)
+            {
+                let da = (start - a).sqr_magnitude();
+                let db = (start - b).sqr_magnitude();
+                start = if da < db { a } else { b };
+                nodes.push(Node::Point(start));
+            } else if old_last_normal.dot(normal) < 1.0 - ZERO_TRESHOLD {
+                let normal = self.spatials()[triplets[0]].normal();
+                let normal = (b - a).normalize().cross(normal);
+                nodes.push(Node::LevelChange(a, b, normal));
+            }
+        }
+        {
+            let NavConnection(a, b) = self
+                .connections
+                .get(&NavConnection(
+                    triangles[triangles.len() - 2] as u32,
+                    triangles[triangles.len() - 1] as u32,
+                ))?
+                .1;
+            let a = self.vertices[a as usize];
+            let b = self.vertices[b as usize];
+            let n = self.spatials()[triangles[triangles.len() - 2]].normal();
+            let m = self.spatials()[triangles[triangles.len() - 1]].normal();
+            if !self.is_line_between_points(start, to, a, b, n) {
+                let da = (start - a).sqr_magnitude();
+                let db = (start - b).sqr_magnitude();
+                let point = if da < db { a } else { b };
+                nodes.push(Node::Point(point));
+            } else if n.dot(m) < 1.0 - ZERO_TRESHOLD {
+                let n = (b - a).normalize().cross(n);
+                nodes.push(Node::LevelChange(a, b, n));
+            }
+        }
+
+        let mut points = Vec::with_capacity(nodes.len() + 2);
+        points.push(from);
+        let mut point = from;
+        for i in 0..nodes.len() {
+            match nodes[i] {
+                Node::Point(p) => {
+                    point = p;
+                    points.push(p);
+                }
+                Node::LevelChange(a, b, n) => {
+                    let next = nodes
+                        .iter()
+                        .skip(i + 1)
+                        .find_map(|n| match n {
+                            Node::Point(p) => Some(*p),
+                            _ => None,
+                        })
+                        .unwrap_or(to);
+                    if let Some(p) = NavVec3::raycast_line(point, next, a, b, n) {
+                        points.push(p);
+                    }
+                }
+            }
+        }
+        points.push(to);
+        points.dedup();
+        Some(points)
+    }
+
+    fn find_path_funnel(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        triangles: &[usize],
+    ) -> Option<Vec<NavVec3>> {
+        let portals = self.corridor_portals(triangles)?;
+        let normal = self.spatials()[triangles[triangles.len() - 1]].normal();
+        let result = funnel(from, to, &portals, normal, self.tolerance.threshold(2));
+        #[cfg(feature = "debug")]
+        crate::nav_debug::emit(|| crate::NavDebugFrame::FunnelApexes {
+            mesh: self.id,
+            points: result.clone(),
+        });
+        Some(result)
+    }
+
+    #[inline]
+    fn is_line_between_points(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        a: NavVec3,
+        b: NavVec3,
+        normal: NavVec3,
+    ) -> bool {
+        NavVec3::is_line_between_points_threshold(
+            from,
+            to,
+            a,
+            b,
+            normal,
+            self.tolerance.threshold(2),
+        )
+    }
+
+    #[inline]
+    pub(crate) fn triangle_normal(&self, index: usize) -> NavVec3 {
+        self.spatials()[index].normal()
+    }
+
+    /// Build portals of triangles corridor as (left, right, normal) triplets, oriented along
+    /// the direction of travel, where normal is a normal of triangle before the portal. Gives
+    /// `None` if any of consecutive triangles are not adjacent.
+    pub(crate) fn corridor_portals(
+        &self,
+        triangles: &[usize],
+    ) -> Option<Vec<(NavVec3, NavVec3, NavVec3)>> {
+        triangles
+            .windows(2)
+            .map(|pair| {
+                let NavConnection(a, b) = self
+                    .connections
+                    .get(&NavConnection(pair[0] as u32, pair[1] as u32))?
+                    .1;
+                let t = self.triangles[pair[0]];
+                let winding = (t.first == a && t.second == b)
+                    || (t.second == a && t.third == b)
+                    || (t.third == a && t.first == b);
+                let (left, right) = if winding { (b, a) } else { (a, b) };
+                Some((
+                    self.vertices[left as usize],
+                    self.vertices[right as usize],
+                    self.spatials()[pair[0]].normal(),
+                ))
+            })
+            .collect()
+    }
+
+    fn find_path_midpoints(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        triangles: &[usize],
+        clearance: bool,
+    ) -> Option<Vec<NavVec3>> {
+        if triangles.len() == 2 {
+            let portal = self
+                .portals()
+                .get(&NavConnection(triangles[0] as u32, triangles[1] as u32))?;
+            let NavConnection(a, b) = portal.edge;
+            let crossing = self.portal_crossing(portal, clearance);
+            let a = self.vertices[a as usize];
+            let b = self.vertices[b as usize];
+            let n = self.spatials()[triangles[0]].normal();
+            let m = self.spatials()[triangles[1]].normal();
+            if n.dot(m) < 1.0 - ZERO_TRESHOLD || !self.is_line_between_points(from, to, a, b, n) {
+                return Some(vec![from, crossing, to]);
+            } else {
+                return Some(vec![from, to]);
+            }
+        }
+        let mut start = from;
+        let mut last_normal = self.spatials()[triangles[0]].normal();
+        let mut points = Vec::with_capacity(triangles.len() + 1);
+        points.push(from);
+        for triplets in triangles.windows(3) {
+            let portal = self
+                .portals()
+                .get(&NavConnection(triplets[0] as u32, triplets[1] as u32))?;
+            let NavConnection(a, b) = portal.edge;
+            let point = self.portal_crossing(portal, clearance);
+            let a = self.vertices[a as usize];
+            let b = self.vertices[b as usize];
+            let normal = self.spatials()[triplets[1]].normal();
+            let old_last_normal = last_normal;
+            last_normal = normal;
+            if old_last_normal.dot(normal) < 1.0 - ZERO_TRESHOLD {
+                start = point;
+                points.push(start);
+            } else {
+                let next = self
+                    .portals()
+                    .get(&NavConnection(triplets[1] as u32, triplets[2] as u32))?;
+                let end = self.portal_crossing(next, clearance);
+                if !self.is_line_between_points(start, end, a, b, normal) {
+                    start = point;
+                    points.push(start);
+                }
+            }
+        }
+        {
+            let portal = self.portals().get(&NavConnection(
+                triangles[triangles.len() - 2] as u32,
+                triangles[triangles.len() - 1] as u32,
+            ))?;
+            let NavConnection(a, b) = portal.edge;
+            let crossing = self.portal_crossing(portal, clearance);
+            let a = self.vertices[a as usize];
+            let b = self.vertices[b as usize];
+            let n = self.spatials()[triangles[triangles.len() - 2]].normal();
+            let m = self.spatials()[triangles[triangles.len() - 1]].normal();
+            if n.dot(m) < 1.0 - ZERO_TRESHOLD || !self.is_line_between_points(start, to, a, b, n) {
+                points.push(crossing);
+            }
+        }
+        points.push(to);
+        points.dedup();
+        Some(points)
+    }
+
+    /// Find shortest path on nav mesh between two points.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    ///
+    /// # Returns
+    /// `Some` with path points on nav mesh and path length if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let path = mesh.find_path_triangles(1, 2).unwrap().0;
+    /// assert_eq!(path, vec![1, 0, 3, 2]);
+    /// ```
+    #[inline]
+    pub fn find_path_triangles(&self, from: usize, to: usize) -> Option<(Vec<usize>, Scalar)> {
+        self.find_path_triangles_custom(from, to, |_, _, _| true)
+    }
+
+    /// Find shortest path on nav mesh between two points, providing custom filtering function.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `mode` - path finding quality.
+    /// * `filter` - closure that gives you a connection distance squared, first triangle index
+    ///   and second triangle index, and returns `false` to give that connection maximal cost, so
+    ///   it is used only when there is no other way (use `find_path_triangles_excluding` to
+    ///   exclude connections from search instead).
+    ///
+    /// # Returns
+    /// `Some` with path points on nav mesh and path length if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let path = mesh.find_path_triangles_custom(
+    ///     1,
+    ///     2,
+    ///     |_dist_sqr, _first_idx, _second_idx| true
+    /// ).unwrap().0;
+    /// assert_eq!(path, vec![1, 0, 3, 2]);
+    /// ```
+    #[inline]
+    pub fn find_path_triangles_custom<F>(
+        &self,
+        from: usize,
+        to: usize,
+        filter: F,
+    ) -> Option<(Vec<usize>, Scalar)>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.search_triangles_weighted(
+            from,
+            to,
+            &NavPathOptions::default(),
+            Self::penalize_rejected(filter),
+            |_| 1.0,
+            &NavZeroHeuristic,
+        )
+        .filter(|corridor| corridor.triangles.last() == Some(&to))
+        .map(|corridor| (corridor.triangles, corridor.cost))
+    }
+
+    /// Find shortest path on nav mesh between two triangles, excluding connections rejected by
+    /// filtering function.
+    ///
+    /// # Arguments
+    /// * `from` - triangle index from.
+    /// * `to` - triangle index to.
+    /// * `filter` - closure that gives you a connection distance squared, first triangle index
+    ///   and second triangle index, and returns `false` to exclude that connection from search.
+    ///
+    /// # Returns
+    /// `Some` with path triangles on nav mesh and path cost if found or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// assert!(mesh
+    ///     .find_path_triangles_excluding(1, 2, |_, _, next| next != 3)
+    ///     .is_none());
+    /// let (path, cost) = mesh
+    ///     .find_path_triangles_custom(1, 2, |_, _, next| next != 3)
+    ///     .unwrap();
+    /// assert_eq!(path, vec![1, 0, 3, 2]);
+    /// assert!(cost >= Scalar::MAX);
+    /// ```
+    #[inline]
+    pub fn find_path_triangles_excluding<F>(
+        &self,
+        from: usize,
+        to: usize,
+        filter: F,
+    ) -> Option<(Vec<usize>, Scalar)>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.find_path_triangles_with_options(from, to, &NavPathOptions::default(), filter)
+    }
+
+    // Turns filter of `find_path_custom` into search filter that gives rejected connections
+    // maximal cost.
+    fn penalize_rejected<F>(mut filter: F) -> impl FnMut(Scalar, usize, usize) -> Option<Scalar>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        move |weight, a, b| {
+            Some(if filter(weight, a, b) {
+                0.0
+            } else {
+                SCALAR_MAX
+            })
+        }
+    }
+
+    /// Find shortest path on nav mesh between two triangles, providing additional options and
+    /// custom filtering function.
+    ///
+    /// # Arguments
+    /// * `from` - triangle index from.
+    /// * `to` - triangle index to.
+    /// * `options` - path finding options.
+    /// * `filter` - closure that gives you a connection distance squared, first triangle index
+    ///   and second triangle index, and returns `false` to exclude that connection from search.
+    ///
+    /// # Returns
+    /// `Some` with path triangles on nav mesh and path cost if found or `None` otherwise (also
+    /// when search runs out of `max_expansions` budget before reaching `to`).
+    pub fn find_path_triangles_with_options<F>(
+        &self,
+        from: usize,
+        to: usize,
+        options: &NavPathOptions,
+        filter: F,
+    ) -> Option<(Vec<usize>, Scalar)>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.find_path_triangles_with_heuristic(from, to, options, &NavZeroHeuristic, filter)
+    }
+
+    /// Find shortest path on nav mesh between two triangles, guided by custom heuristic.
+    ///
+    /// # Arguments
+    /// * `from` - triangle index from.
+    /// * `to` - triangle index to.
+    /// * `options` - path finding options.
+    /// * `heuristic` - estimate of cost between triangles, given their indices and centers.
+    /// * `filter` - closure that gives you a connection distance squared, first triangle index
+    ///   and second triangle index, and returns `false` to exclude that connection from search.
+    ///
+    /// # Returns
+    /// `Some` with path triangles on nav mesh and path cost if found or `None` otherwise (also
+    /// when search runs out of `max_expansions` budget before reaching `to`).
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    ///     (1.0, 1.0, 0.0).into(), // 4
+    ///     (2.0, 1.0, 1.0).into(), // 5
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 4).into(), // 0
+    ///     (4, 3, 0).into(), // 1
+    ///     (1, 2, 5).into(), // 2
+    ///     (5, 4, 1).into(), // 3
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let path = mesh
+    ///     .find_path_triangles_with_heuristic(
+    ///         1,
+    ///         2,
+    ///         &NavPathOptions::default(),
+    ///         &|_, _, _: NavVec3, _: NavVec3| 0.0,
+    ///         |_, _, _| true,
     ///     )
-    ///     .unwrap();
-    /// assert_eq!(
-    ///     path.into_iter()
-    ///         .map(|v| (
-    ///             (v.x * 10.0) as i32,
-    ///             (v.y * 10.0) as i32,
-    ///             (v.z * 10.0) as i32,
-    ///         ))
-    ///         .collect::<Vec<_>>(),
-    ///     vec![(0, 10, 0), (10, 5, 0), (15, 2, 5),]
-    /// );
+    ///     .unwrap()
+    ///     .0;
+    /// assert_eq!(path, vec![1, 0, 3, 2]);
     /// ```
-    pub fn find_path_custom<F>(
+    pub fn find_path_triangles_with_heuristic<H, F>(
         &self,
-        from: NavVec3,
-        to: NavVec3,
-        query: NavQuery,
-        mode: NavPathMode,
+        from: usize,
+        to: usize,
+        options: &NavPathOptions,
+        heuristic: &H,
         filter: F,
-    ) -> Option<Vec<NavVec3>>
+    ) -> Option<(Vec<usize>, Scalar)>
     where
+        H: NavHeuristic,
         F: FnMut(Scalar, usize, usize) -> bool,
     {
-        if from.same_as(to) {
-            return None;
-        }
-        let start = self.find_closest_triangle(from, query)?;
-        let end = self.find_closest_triangle(to, query)?;
-        let from = self.spatials[start].closest_point(from);
-        let to = self.spatials[end].closest_point(to);
-        let (triangles, _) = self.find_path_triangles_custom(start, end, filter)?;
-        if triangles.is_empty() {
-            return None;
-        } else if triangles.len() == 1 {
-            return Some(vec![from, to]);
-        }
-        match mode {
-            NavPathMode::Accuracy => Some(self.find_path_accuracy(from, to, &triangles)),
-            NavPathMode::MidPoints => Some(self.find_path_midpoints(from, to, &triangles)),
-        }
+        let mut filter = filter;
+        self.search_triangles_weighted(
+            from,
+            to,
+            options,
+            |weight, a, b| filter(weight, a, b).then_some(0.0),
+            |_| 1.0,
+            heuristic,
+        )
+        .filter(|corridor| corridor.triangles.last() == Some(&to))
+        .map(|corridor| (corridor.triangles, corridor.cost))
     }
 
-    fn find_path_accuracy(&self, from: NavVec3, to: NavVec3, triangles: &[usize]) -> Vec<NavVec3> {
-        #[derive(Debug)]
-        enum Node {
-            Point(NavVec3),
-            // (a, b, normal)
-            LevelChange(NavVec3, NavVec3, NavVec3),
+    // A* over triangles with connection costs multiplied by weight of target triangle, giving
+    // corridor triangles, off-mesh links taken to enter them and cost. `filter` gives extra cost
+    // of connection or `None` to exclude it from search. Result is partial corridor (not ending
+    // at `to`) when expansions budget runs out.
+    fn search_triangles_weighted<F, W, H>(
+        &self,
+        from: usize,
+        to: usize,
+        options: &NavPathOptions,
+        filter: F,
+        weight: W,
+        heuristic: &H,
+    ) -> Option<NavCorridor>
+    where
+        F: FnMut(Scalar, usize, usize) -> Option<Scalar>,
+        W: Fn(usize) -> Scalar,
+        H: NavHeuristic,
+    {
+        if !options.hazards.is_empty() || options.max_expansions.is_some() {
+            return self.search_triangles_timed(from, to, options, filter, weight, heuristic);
         }
-
-        // TODO: reduce allocations.
-        if triangles.len() == 2 {
-            let NavConnection(a, b) =
-                self.connections[&NavConnection(triangles[0] as u32, triangles[1] as u32)].1;
-            let a = self.vertices[a as usize];
-            let b = self.vertices[b as usize];
-            let n = self.spatials[triangles[0]].normal();
-            let m = self.spatials[triangles[1]].normal();
-            if !NavVec3::is_line_between_points(from, to, a, b, n) {
-                let da = (from - a).sqr_magnitude();
-                let db = (from - b).sqr_magnitude();
-                let point = if da < db { a } else { b };
-                return vec![from, point, to];
-            } else if n.dot(m) < 1.0 - ZERO_TRESHOLD {
-                let n = (b - a).normalize().cross(n);
-                if let Some(point) = NavVec3::raycast_line(from, to, a, b, n) {
-                    return vec![from, point, to];
+        let mut filter = filter;
+        let goal = self.areas[to].center;
+        // cheapest edge between triangles is the one path goes through.
+        let mut chosen = HashMap::<(usize, usize), (Scalar, Option<NavOffMeshLinkID>)>::new();
+        let (cost, triangles) = astar(
+            from,
+            to,
+            |a, visit| {
+                for e in self.graph.edges(self.nodes[a]) {
+                    let b = self.nodes_map[&e.target()];
+                    if !self.is_edge_passable(a, b, e.weight(), options) {
+                        continue;
+                    }
+                    if let Some(extra) = filter(e.weight().weight, a, b) {
+                        let cost = (self.edge_cost(a, b, e.weight(), options) + extra) * weight(b);
+                        let entry = chosen.entry((a, b)).or_insert((cost, e.weight().link));
+                        if cost < entry.0 {
+                            *entry = (cost, e.weight().link);
+                        }
+                        visit(b, cost);
+                    }
                 }
+            },
+            |index| heuristic.estimate(index, to, self.areas[index].center, goal),
+        )?;
+        let links = Some(None)
+            .into_iter()
+            .chain(
+                triangles
+                    .windows(2)
+                    .map(|pair| chosen[&(pair[0], pair[1])].1),
+            )
+            .collect();
+        Some(NavCorridor {
+            triangles,
+            links,
+            cost,
+        })
+    }
+
+    // A* over triangles that tracks distance traveled along triangles centers, so costs of
+    // connections traversed inside of hazards get penalized while hazards last. Stops after
+    // expansions budget is exceeded, giving corridor to expanded triangle closest to the goal.
+    fn search_triangles_timed<F, W, H>(
+        &self,
+        from: usize,
+        to: usize,
+        options: &NavPathOptions,
+        mut filter: F,
+        weight: W,
+        heuristic: &H,
+    ) -> Option<NavCorridor>
+    where
+        F: FnMut(Scalar, usize, usize) -> Option<Scalar>,
+        W: Fn(usize) -> Scalar,
+        H: NavHeuristic,
+    {
+        let speed = options.speed.max(ZERO_TRESHOLD);
+        // triangle -> (cost, distance traveled).
+        let mut costs = HashMap::new();
+        let mut parents = HashMap::new();
+        let mut open = BinaryHeap::new();
+        let goal = self.areas[to].center;
+        let estimate = |index: usize| heuristic.estimate(index, to, self.areas[index].center, goal);
+        costs.insert(from, (0.0, 0.0));
+        open.push(NavSlicedPathItem(estimate(from), from));
+        let mut closed = HashSet::new();
+        let mut expansions = 0;
+        // (distance to goal, triangle) of expanded triangle closest to goal.
+        let mut closest = (SCALAR_MAX, from);
+        // triangle -> (parent triangle, off-mesh link taken from parent).
+        let unwind = |parents: &HashMap<usize, (usize, Option<NavOffMeshLinkID>)>,
+                      costs: &HashMap<usize, (Scalar, Scalar)>,
+                      mut current: usize| {
+            let cost = costs[&current].0;
+            let mut triangles = vec![current];
+            let mut links = vec![];
+            while let Some((parent, link)) = parents.get(&current) {
+                current = *parent;
+                triangles.push(current);
+                links.push(*link);
             }
-            return vec![from, to];
-        }
-        let mut start = from;
-        let mut last_normal = self.spatials[triangles[0]].normal();
-        let mut nodes = Vec::with_capacity(triangles.len() - 1);
-        for triplets in triangles.windows(3) {
-            let NavConnection(a, b) =
-                self.connections[&NavConnection(triplets[0] as u32, triplets[1] as u32)].1;
-            let a = self.vertices[a as usize];
-            let b = self.vertices[b as usize];
-            let NavConnection(c, d) =
-                self.connections[&NavConnection(triplets[1] as u32, triplets[2] as u32)].1;
-            let c = self.vertices[c as usize];
-            let d = self.vertices[d as usize];
-            let normal = self.spatials[triplets[1]].normal();
-            let old_last_normal = last_normal;
-            last_normal = normal;
-            if !NavVec3::is_line_between_points(start, c, a, b, normal)
-                || !NavVec3::is_line_between_points(start, d, a, b, normal)
-            {
-                let da = (start - a).sqr_magnitude();
-                let db = (start - b).sqr_magnitude();
-                start = if da < db { a } else { b };
-                nodes.push(Node::Point(start));
-            } else if old_last_normal.dot(normal) < 1.0 - ZERO_TRESHOLD {
-                let normal = self.spatials[triplets[0]].normal();
-                let normal = (b - a).normalize().cross(normal);
-                nodes.push(Node::LevelChange(a, b, normal));
+            links.push(None);
+            triangles.reverse();
+            links.reverse();
+            NavCorridor {
+                triangles,
+                links,
+                cost,
             }
-        }
-        {
-            let NavConnection(a, b) = self.connections[&NavConnection(
-                triangles[triangles.len() - 2] as u32,
-                triangles[triangles.len() - 1] as u32,
-            )]
-                .1;
-            let a = self.vertices[a as usize];
-            let b = self.vertices[b as usize];
-            let n = self.spatials[triangles[triangles.len() - 2]].normal();
-            let m = self.spatials[triangles[triangles.len() - 1]].normal();
-            if !NavVec3::is_line_between_points(start, to, a, b, n) {
-                let da = (start - a).sqr_magnitude();
-                let db = (start - b).sqr_magnitude();
-                let point = if da < db { a } else { b };
-                nodes.push(Node::Point(point));
-            } else if n.dot(m) < 1.0 - ZERO_TRESHOLD {
-                let n = (b - a).normalize().cross(n);
-                nodes.push(Node::LevelChange(a, b, n));
+        };
+        while let Some(NavSlicedPathItem(_, triangle)) = open.pop() {
+            if !closed.insert(triangle) {
+                continue;
             }
-        }
-
-        let mut points = Vec::with_capacity(nodes.len() + 2);
-        points.push(from);
-        let mut point = from;
-        for i in 0..nodes.len() {
-            match nodes[i] {
-                Node::Point(p) => {
-                    point = p;
-                    points.push(p);
+            let (cost, traveled) = costs[&triangle];
+            if triangle == to {
+                return Some(unwind(&parents, &costs, to));
+            }
+            if let Some(max_expansions) = options.max_expansions {
+                if expansions >= max_expansions {
+                    let (_, triangle) = closest;
+                    return Some(unwind(&parents, &costs, triangle));
                 }
-                Node::LevelChange(a, b, n) => {
-                    let next = nodes
-                        .iter()
-                        .skip(i + 1)
-                        .find_map(|n| match n {
-                            Node::Point(p) => Some(*p),
-                            _ => None,
-                        })
-                        .unwrap_or(to);
-                    if let Some(p) = NavVec3::raycast_line(point, next, a, b, n) {
-                        points.push(p);
-                    }
+                expansions += 1;
+                let distance = (self.areas[triangle].center - goal).sqr_magnitude();
+                if distance < closest.0 {
+                    closest = (distance, triangle);
                 }
             }
-        }
-        points.push(to);
-        points.dedup();
-        points
-    }
-
-    fn find_path_midpoints(&self, from: NavVec3, to: NavVec3, triangles: &[usize]) -> Vec<NavVec3> {
-        if triangles.len() == 2 {
-            let NavConnection(a, b) =
-                self.connections[&NavConnection(triangles[0] as u32, triangles[1] as u32)].1;
-            let a = self.vertices[a as usize];
-            let b = self.vertices[b as usize];
-            let n = self.spatials[triangles[0]].normal();
-            let m = self.spatials[triangles[1]].normal();
-            if n.dot(m) < 1.0 - ZERO_TRESHOLD || !NavVec3::is_line_between_points(from, to, a, b, n)
-            {
-                return vec![from, (a + b) * 0.5, to];
-            } else {
-                return vec![from, to];
-            }
-        }
-        let mut start = from;
-        let mut last_normal = self.spatials[triangles[0]].normal();
-        let mut points = Vec::with_capacity(triangles.len() + 1);
-        points.push(from);
-        for triplets in triangles.windows(3) {
-            let NavConnection(a, b) =
-                self.connections[&NavConnection(triplets[0] as u32, triplets[1] as u32)].1;
-            let a = self.vertices[a as usize];
-            let b = self.vertices[b as usize];
-            let point = (a + b) * 0.5;
-            let normal = self.spatials[triplets[1]].normal();
-            let old_last_normal = last_normal;
-            last_normal = normal;
-            if old_last_normal.dot(normal) < 1.0 - ZERO_TRESHOLD {
-                start = point;
-                points.push(start);
-            } else {
-                let NavConnection(c, d) =
-                    self.connections[&NavConnection(triplets[1] as u32, triplets[2] as u32)].1;
-                let c = self.vertices[c as usize];
-                let d = self.vertices[d as usize];
-                let end = (c + d) * 0.5;
-                if !NavVec3::is_line_between_points(start, end, a, b, normal) {
-                    start = point;
-                    points.push(start);
+            for edge in self.graph.edges(self.nodes[triangle]) {
+                let next = self.nodes_map[&edge.target()];
+                if !self.is_edge_passable(triangle, next, edge.weight(), options) {
+                    continue;
+                }
+                let Some(extra) = filter(edge.weight().weight, triangle, next) else {
+                    continue;
+                };
+                let (a, b) = match edge
+                    .weight()
+                    .link
+                    .and_then(|id| self.off_mesh_link_data(id))
+                {
+                    Some(data) => (data.from_point, data.to_point),
+                    None => (self.areas[triangle].center, self.areas[next].center),
+                };
+                let distance = (b - a).magnitude();
+                let start_time = traveled / speed;
+                let end_time = (traveled + distance) / speed;
+                let penalty = options
+                    .hazards
+                    .iter()
+                    .filter(|hazard| hazard.intersects(a, b, start_time, end_time))
+                    .map(|hazard| hazard.cost.max(0.0))
+                    .sum::<Scalar>();
+                let next_cost = cost
+                    + (self.edge_cost(triangle, next, edge.weight(), options) + extra)
+                        * weight(next)
+                        * (1.0 + penalty);
+                if costs
+                    .get(&next)
+                    .map(|(cost, _)| next_cost < *cost)
+                    .unwrap_or(true)
+                {
+                    costs.insert(next, (next_cost, traveled + distance));
+                    parents.insert(next, (triangle, edge.weight().link));
+                    open.push(NavSlicedPathItem(next_cost + estimate(next), next));
                 }
             }
         }
-        {
-            let NavConnection(a, b) = self.connections[&NavConnection(
-                triangles[triangles.len() - 2] as u32,
-                triangles[triangles.len() - 1] as u32,
-            )]
-                .1;
-            let a = self.vertices[a as usize];
-            let b = self.vertices[b as usize];
-            let n = self.spatials[triangles[triangles.len() - 2]].normal();
-            let m = self.spatials[triangles[triangles.len() - 1]].normal();
-            if n.dot(m) < 1.0 - ZERO_TRESHOLD
-                || !NavVec3::is_line_between_points(start, to, a, b, n)
-            {
-                points.push((a + b) * 0.5);
-            }
+        None
+    }
+
+    /// Get precomputed geometry of portal edge shared by two neighbor triangles.
+    ///
+    /// # Arguments
+    /// * `from` - first triangle index.
+    /// * `to` - second triangle index.
+    ///
+    /// # Returns
+    /// `Some` with portal if triangles are connected or `None` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let vertices = vec![
+    ///     (0.0, 0.0, 0.0).into(), // 0
+    ///     (1.0, 0.0, 0.0).into(), // 1
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (0.0, 1.0, 0.0).into(), // 3
+    /// ];
+    /// let triangles = vec![
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    /// ];
+    ///
+    /// let mesh = NavMesh::new(vertices, triangles).unwrap();
+    /// let portal = mesh.portal(0, 1).unwrap();
+    /// assert_eq!(portal.midpoint, (0.5, 0.5, 0.0).into());
+    /// assert!((portal.length - (2.0 as Scalar).sqrt()).abs() < 1.0e-6);
+    /// assert_eq!(mesh.portal(0, 0), None);
+    /// ```
+    pub fn portal(&self, from: usize, to: usize) -> Option<&NavPortal> {
+        self.portals().get(&NavConnection(from as u32, to as u32))
+    }
+
+    /// Precomputed geometry of all portal edges, keyed by connection of triangles sharing them
+    /// (triangles order does not matter).
+    pub fn portals(&self) -> &HashMap<NavConnection, NavPortal> {
+        self.portals.get_or_init(|| {
+            self.connections
+                .iter()
+                .map(|(triangles, (_, edge))| {
+                    let a = self.vertices[edge.0 as usize];
+                    let b = self.vertices[edge.1 as usize];
+                    let portal = NavPortal {
+                        edge: *edge,
+                        length: (b - a).magnitude(),
+                        midpoint: (a + b) * 0.5,
+                    };
+                    (*triangles, portal)
+                })
+                .collect()
+        })
+    }
+
+    /// Calculate width of portal edge shared by two neighbor triangles.
+    ///
+    /// # Arguments
+    /// * `from` - first triangle index.
+    /// * `to` - second triangle index.
+    ///
+    /// # Returns
+    /// `Some` with portal edge length if triangles are connected or `None` otherwise.
+    pub fn portal_width(&self, from: usize, to: usize) -> Option<Scalar> {
+        self.portal(from, to).map(|portal| portal.length)
+    }
+
+    /// Calculate widths of portal edges crossed by triangles corridor.
+    ///
+    /// # Arguments
+    /// * `triangles` - corridor triangles indices (i.e. result of `find_path_triangles`).
+    ///
+    /// # Returns
+    /// `Some` with width of each crossed portal edge if all consecutive triangles are connected
+    /// or `None` otherwise.
+    pub fn corridor_widths(&self, triangles: &[usize]) -> Option<Vec<Scalar>> {
+        triangles
+            .windows(2)
+            .map(|pair| self.portal_width(pair[0], pair[1]))
+            .collect()
+    }
+
+    /// Calculate minimal width of triangles corridor.
+    ///
+    /// # Arguments
+    /// * `triangles` - corridor triangles indices (i.e. result of `find_path_triangles`).
+    ///
+    /// # Returns
+    /// `Some` with the narrowest crossed portal edge width if corridor crosses any portal edge
+    /// or `None` otherwise.
+    pub fn corridor_min_width(&self, triangles: &[usize]) -> Option<Scalar> {
+        self.corridor_widths(triangles)?
+            .into_iter()
+            .fold(None, |a: Option<Scalar>, w| Some(a.map_or(w, |a| a.min(w))))
+    }
+
+    fn edge_cost(
+        &self,
+        from: usize,
+        to: usize,
+        edge: &NavGraphEdge,
+        options: &NavPathOptions,
+    ) -> Scalar {
+        let jitter = options
+            .jitter
+            .as_ref()
+            .map(|jitter| jitter.factor(from, to))
+            .unwrap_or(1.0);
+        let tabu = options
+            .tabu
+            .as_ref()
+            .map(|tabu| tabu.factor(from, to))
+            .unwrap_or(1.0);
+        let cost = match edge.link.and_then(|id| self.off_mesh_link_data(id)) {
+            Some(data) => data.link.cost.max(0.0),
+            None => self.area_cost(from, options) * self.area_cost(to, options),
+        };
+        edge.weight * cost * jitter * tabu
+    }
+
+    fn is_edge_passable(
+        &self,
+        from: usize,
+        to: usize,
+        edge: &NavGraphEdge,
+        options: &NavPathOptions,
+    ) -> bool {
+        if edge.link.is_some() {
+            self.is_area_allowed(from, options) && self.is_area_allowed(to, options)
+        } else {
+            self.is_connection_passable(from, to, options)
         }
-        points.push(to);
-        points.dedup();
-        points
     }
 
-    /// Find shortest path on nav mesh between two points.
-    ///
-    /// # Arguments
-    /// * `from` - query point from.
-    /// * `to` - query point to.
-    /// * `query` - query quality.
-    /// * `mode` - path finding quality.
+    fn is_connection_passable(&self, from: usize, to: usize, options: &NavPathOptions) -> bool {
+        if !self.is_area_allowed(from, options) || !self.is_area_allowed(to, options) {
+            return false;
+        }
+        options.min_passage_width <= 0.0
+            || self
+                .portal_width(from, to)
+                .map(|width| width >= options.min_passage_width)
+                .unwrap_or(false)
+    }
+
+    fn is_area_allowed(&self, index: usize, options: &NavPathOptions) -> bool {
+        let area = &self.areas[index];
+        options.is_area_type_allowed(area.area_type) && options.is_depth_allowed(area.depth)
+    }
+
+    fn area_cost(&self, index: usize, options: &NavPathOptions) -> Scalar {
+        let area = &self.areas[index];
+        area.cost * options.area_type_cost(area.area_type)
+    }
+
+    pub fn find_triangle_islands(&self) -> Vec<Vec<usize>> {
+        tarjan_scc(&self.graph)
+            .into_iter()
+            .map(|v| {
+                v.into_iter()
+                    .filter_map(|n| self.nodes_map.get(&n).copied())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+
+    /// Calculate statistics of triangle islands.
     ///
     /// # Returns
-    /// `Some` with path points on nav mesh and path length if found or `None` otherwise.
+    /// Statistics of every island, in the same order as `find_triangle_islands` returns them.
     ///
     /// # Example
     /// ```
@@ -872,40 +5982,87 @@ impl NavMesh {
     ///
     /// let vertices = vec![
     ///     (0.0, 0.0, 0.0).into(), // 0
-    ///     (1.0, 0.0, 0.0).into(), // 1
-    ///     (2.0, 0.0, 1.0).into(), // 2
+    ///     (2.0, 0.0, 0.0).into(), // 1
+    ///     (2.0, 1.0, 0.0).into(), // 2
     ///     (0.0, 1.0, 0.0).into(), // 3
-    ///     (1.0, 1.0, 0.0).into(), // 4
-    ///     (2.0, 1.0, 1.0).into(), // 5
+    ///     (5.0, 0.0, 0.0).into(), // 4
+    ///     (6.0, 0.0, 0.0).into(), // 5
+    ///     (5.0, 1.0, 1.0).into(), // 6
     /// ];
     /// let triangles = vec![
-    ///     (0, 1, 4).into(), // 0
-    ///     (4, 3, 0).into(), // 1
-    ///     (1, 2, 5).into(), // 2
-    ///     (5, 4, 1).into(), // 3
+    ///     (0, 1, 2).into(), // 0
+    ///     (2, 3, 0).into(), // 1
+    ///     (4, 5, 6).into(), // 2
     /// ];
     ///
     /// let mesh = NavMesh::new(vertices, triangles).unwrap();
-    /// let path = mesh.find_path_triangles(1, 2).unwrap().0;
-    /// assert_eq!(path, vec![1, 0, 3, 2]);
+    /// let mut stats = mesh.triangle_islands_stats();
+    /// stats.sort_by_key(|stats| stats.triangles_count);
+    /// assert_eq!(stats.len(), 2);
+    /// assert_eq!(stats[1].triangles_count, 2);
+    /// assert!((stats[1].area - 2.0).abs() < 1.0e-6);
+    /// assert_eq!(stats[1].bounds_min, (0.0, 0.0, 0.0).into());
+    /// assert_eq!(stats[1].bounds_max, (2.0, 1.0, 0.0).into());
+    /// assert_eq!(stats[0].bounds_max, (6.0, 1.0, 1.0).into());
     /// ```
-    #[inline]
-    pub fn find_path_triangles(&self, from: usize, to: usize) -> Option<(Vec<usize>, Scalar)> {
-        self.find_path_triangles_custom(from, to, |_, _, _| true)
+    pub fn triangle_islands_stats(&self) -> Vec<NavTriangleIslandStats> {
+        self.find_triangle_islands()
+            .into_iter()
+            .map(|island| {
+                let mut result = NavTriangleIslandStats {
+                    triangles_count: island.len(),
+                    area: 0.0,
+                    bounds_min: NavVec3::new(SCALAR_MAX, SCALAR_MAX, SCALAR_MAX),
+                    bounds_max: NavVec3::new(-SCALAR_MAX, -SCALAR_MAX, -SCALAR_MAX),
+                };
+                for index in island {
+                    result.area += self.areas[index].size;
+                    let triangle = self.triangles[index];
+                    for vertice in [triangle.first, triangle.second, triangle.third] {
+                        let point = self.vertices[vertice as usize];
+                        result.bounds_min = result.bounds_min.min(point);
+                        result.bounds_max = result.bounds_max.max(point);
+                    }
+                }
+                result
+            })
+            .collect()
     }
 
-    /// Find shortest path on nav mesh between two points, providing custom filtering function.
+    /// Get index of triangle island that contains given triangle.
     ///
     /// # Arguments
-    /// * `from` - query point from.
-    /// * `to` - query point to.
-    /// * `query` - query quality.
-    /// * `mode` - path finding quality.
-    /// * `filter` - closure that gives you a connection distance squared, first triangle index
-    ///   and second triangle index.
+    /// * `index` - triangle index.
     ///
     /// # Returns
-    /// `Some` with path points on nav mesh and path length if found or `None` otherwise.
+    /// `Some` with index of island in order given by `find_triangle_islands` or `None` if
+    /// triangle does not exist.
+    pub fn triangle_island(&self, index: usize) -> Option<usize> {
+        self.islands
+            .get_or_init(|| {
+                let mut result = vec![0; self.triangles.len()];
+                for (island, triangles) in self.find_triangle_islands().into_iter().enumerate() {
+                    for triangle in triangles {
+                        result[triangle] = island;
+                    }
+                }
+                result
+            })
+            .get(index)
+            .copied()
+    }
+
+    /// Build nav islands graph out of nav mesh connected components (off-mesh links registered
+    /// on nav mesh are not taken into account), glued together with given links (i.e. elevators
+    /// or boats). Islands are indices of connected components (see `triangle_components`),
+    /// portals are links end points, and portals of the same component are connected with
+    /// distance between them measured along triangles centers.
+    ///
+    /// # Arguments
+    /// * `links` - links between nav mesh components, with costs scaling their lengths.
+    ///
+    /// # Returns
+    /// Nav islands graph, links whose end points cannot be projected on nav mesh are skipped.
     ///
     /// # Example
     /// ```
@@ -914,84 +6071,183 @@ impl NavMesh {
     /// let vertices = vec![
     ///     (0.0, 0.0, 0.0).into(), // 0
     ///     (1.0, 0.0, 0.0).into(), // 1
-    ///     (2.0, 0.0, 1.0).into(), // 2
-    ///     (0.0, 1.0, 0.0).into(), // 3
-    ///     (1.0, 1.0, 0.0).into(), // 4
-    ///     (2.0, 1.0, 1.0).into(), // 5
+    ///     (1.0, 1.0, 0.0).into(), // 2
+    ///     (5.0, 0.0, 0.0).into(), // 3
+    ///     (6.0, 0.0, 0.0).into(), // 4
+    ///     (6.0, 1.0, 0.0).into(), // 5
     /// ];
     /// let triangles = vec![
-    ///     (0, 1, 4).into(), // 0
-    ///     (4, 3, 0).into(), // 1
-    ///     (1, 2, 5).into(), // 2
-    ///     (5, 4, 1).into(), // 3
+    ///     (0, 1, 2).into(), // 0
+    ///     (3, 4, 5).into(), // 1
     /// ];
     ///
     /// let mesh = NavMesh::new(vertices, triangles).unwrap();
-    /// let path = mesh.find_path_triangles_custom(
-    ///     1,
-    ///     2,
-    ///     |_dist_sqr, _first_idx, _second_idx| true
-    /// ).unwrap().0;
-    /// assert_eq!(path, vec![1, 0, 3, 2]);
+    /// let boat = NavOffMeshLink {
+    ///     from: (1.0, 0.0, 0.0).into(),
+    ///     to: (5.0, 0.0, 0.0).into(),
+    ///     cost: 2.0,
+    ///     bidirectional: false,
+    ///     kind: NavOffMeshLinkKind::Custom(0),
+    /// };
+    /// let islands = mesh.build_islands(&[boat]);
+    /// let components = mesh.triangle_components();
+    /// let from = NavIslandPortal {
+    ///     island: components[0],
+    ///     portal: Some(NavLinkPortal::From(0)),
+    /// };
+    /// let to = NavIslandPortal {
+    ///     island: components[1],
+    ///     portal: Some(NavLinkPortal::To(0)),
+    /// };
+    /// let (distance, _) = islands.find_path(&from, &to).unwrap();
+    /// assert!((distance - 8.0).abs() < 1.0e-4);
+    /// assert!(islands.find_path(&to, &from).is_none());
     /// ```
-    #[inline]
-    pub fn find_path_triangles_custom<F>(
-        &self,
-        from: usize,
-        to: usize,
-        mut filter: F,
-    ) -> Option<(Vec<usize>, Scalar)>
-    where
-        F: FnMut(Scalar, usize, usize) -> bool,
-    {
-        let to = self.nodes[to];
-        astar(
-            &self.graph,
-            self.nodes[from],
-            |n| n == to,
-            |e| {
-                let a = self.nodes_map[&e.source()];
-                let b = self.nodes_map[&e.target()];
-                let w = *e.weight();
-                if filter(w, a, b) {
-                    let a = self.areas[a].cost;
-                    let b = self.areas[b].cost;
-                    w * a * b
+    pub fn build_islands(&self, links: &[NavOffMeshLink]) -> NavIslands<usize, NavLinkPortal> {
+        let components = self.triangle_components();
+        // (island portal, its triangle, its point on nav mesh)
+        let mut portals = Vec::with_capacity(links.len() * 2);
+        let mut connections = vec![];
+        for (index, link) in links.iter().enumerate() {
+            let from = self.snap_point(link.from, &NavQuery::Accuracy.into());
+            let to = self.snap_point(link.to, &NavQuery::Accuracy.into());
+            let (Some((from_triangle, from_point)), Some((to_triangle, to_point))) = (from, to)
+            else {
+                continue;
+            };
+            let from = NavIslandPortal {
+                island: components[from_triangle],
+                portal: Some(NavLinkPortal::From(index)),
+            };
+            let to = NavIslandPortal {
+                island: components[to_triangle],
+                portal: Some(NavLinkPortal::To(index)),
+            };
+            let distance = (to_point - from_point).magnitude() * link.cost.max(0.0);
+            connections.push(NavIslandsConnection {
+                from: from.clone(),
+                to: to.clone(),
+                distance,
+            });
+            if link.bidirectional {
+                connections.push(NavIslandsConnection {
+                    from: to.clone(),
+                    to: from.clone(),
+                    distance,
+                });
+            }
+            portals.push((from, from_triangle, from_point));
+            portals.push((to, to_triangle, to_point));
+        }
+        for (from, from_triangle, from_point) in &portals {
+            let distances = self.adjacency_distances(*from_triangle);
+            for (to, to_triangle, to_point) in &portals {
+                if from == to || from.island != to.island || distances[*to_triangle] >= SCALAR_MAX {
+                    continue;
+                }
+                let distance = if from_triangle == to_triangle {
+                    (*to_point - *from_point).magnitude()
                 } else {
-                    SCALAR_MAX
+                    (self.areas[*from_triangle].center - *from_point).magnitude()
+                        + distances[*to_triangle]
+                        + (*to_point - self.areas[*to_triangle].center).magnitude()
+                };
+                connections.push(NavIslandsConnection {
+                    from: from.clone(),
+                    to: to.clone(),
+                    distance,
+                });
+            }
+        }
+        NavIslands::new(connections, false)
+    }
+
+    // Distances along triangles centers from source triangle to every triangle (`SCALAR_MAX` for
+    // not reached ones), following only triangles adjacency and not off-mesh links.
+    fn adjacency_distances(&self, source: usize) -> Vec<Scalar> {
+        let options = NavPathOptions::default();
+        let count = self.triangles.len();
+        integrate_chunked(count, &[source], count, |a, next| {
+            for e in self.graph.edges(self.nodes[a]) {
+                let b = self.nodes_map[&e.target()];
+                if e.weight().link.is_none() && self.is_edge_passable(a, b, e.weight(), &options) {
+                    next(b, (self.areas[b].center - self.areas[a].center).magnitude());
                 }
-            },
-            |_| 0.0,
-        )
-        .map(|(c, v)| (iter!(v).map(|v| self.nodes_map[v]).collect(), c))
+            }
+        })
+        .0
     }
 
-    pub fn find_triangle_islands(&self) -> Vec<Vec<usize>> {
-        tarjan_scc(&self.graph)
-            .into_iter()
-            .map(|v| {
-                v.into_iter()
-                    .filter_map(|n| self.nodes_map.get(&n).copied())
-                    .collect::<Vec<_>>()
-            })
-            .filter(|v| !v.is_empty())
-            .collect()
+    /// Find connected components of nav mesh triangles, not taking off-mesh links into account.
+    ///
+    /// # Returns
+    /// Index of connected component of each triangle.
+    pub fn triangle_components(&self) -> Vec<usize> {
+        let half_edges = self.half_edges();
+        let mut result = vec![usize::MAX; self.triangles.len()];
+        let mut component = 0;
+        for start in 0..self.triangles.len() {
+            if result[start] != usize::MAX {
+                continue;
+            }
+            result[start] = component;
+            let mut open = vec![start];
+            while let Some(triangle) = open.pop() {
+                for index in self.triangle_half_edges(triangle) {
+                    if let Some(twin) = half_edges[index].twin {
+                        let next = half_edges[twin].triangle;
+                        if result[next] == usize::MAX {
+                            result[next] = component;
+                            open.push(next);
+                        }
+                    }
+                }
+            }
+            component += 1;
+        }
+        result
     }
 
     /// Find closest triangle on nav mesh closest to given point.
     ///
     /// # Arguments
     /// * `point` - query point.
-    /// * `query` - query quality.
+    /// * `query` - query quality or query options.
     ///
     /// # Returns
     /// `Some` with nav mesh triangle index if found or `None` otherwise.
-    pub fn find_closest_triangle(&self, point: NavVec3, query: NavQuery) -> Option<usize> {
+    pub fn find_closest_triangle(
+        &self,
+        point: NavVec3,
+        query: impl Into<NavQueryOptions>,
+    ) -> Option<usize> {
+        self.snap_point(point, &query.into())
+            .map(|(triangle, _)| triangle)
+    }
+
+    // Finds closest triangle and point projected on it, respecting query tolerances.
+    fn snap_point(&self, point: NavVec3, options: &NavQueryOptions) -> Option<(usize, NavVec3)> {
+        let triangle = self.nearest_triangle(point, options.query)?;
+        let spatial = &self.spatials()[triangle];
+        let projected = spatial.closest_point(point);
+        let offset = point - projected;
+        if offset.magnitude() <= options.on_mesh_tolerance {
+            return Some((triangle, point));
+        }
+        let vertical = offset.dot(spatial.normal());
+        let horizontal = (offset - spatial.normal() * vertical).magnitude();
+        if vertical.abs() > options.vertical_tolerance || horizontal > options.snap_distance {
+            return None;
+        }
+        Some((triangle, projected))
+    }
+
+    fn nearest_triangle(&self, point: NavVec3, query: NavQuery) -> Option<usize> {
         match query {
-            NavQuery::Accuracy => self.rtree.nearest_neighbor(&point).map(|t| t.index),
-            NavQuery::ClosestFirst => self.rtree.close_neighbor(&point).map(|t| t.index),
+            NavQuery::Accuracy => self.rtree().nearest_neighbor(&point).map(|t| t.index),
+            NavQuery::ClosestFirst => self.rtree().close_neighbor(&point).map(|t| t.index),
             NavQuery::Closest => self
-                .rtree
+                .rtree()
                 .nearest_neighbors(&point)
                 .into_iter()
                 .map(|o| (o.distance2(&point), o))
@@ -1124,3 +6380,404 @@ impl NavMesh {
         }
     }
 }
+
+/// Status of time-sliced path query.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavSlicedPathStatus {
+    /// Query needs more updates to complete.
+    InProgress,
+    /// Path was found.
+    Found,
+    /// There is no path between query points.
+    NotFound,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct NavSlicedPathItem(Scalar, usize);
+
+impl Eq for NavSlicedPathItem {}
+
+impl PartialOrd for NavSlicedPathItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NavSlicedPathItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.partial_cmp(&self.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// Resumable path query on nav mesh, created with `NavMesh::find_path_sliced`.
+#[derive(Debug, Clone)]
+pub struct NavSlicedPathQuery<'a> {
+    mesh: &'a NavMesh,
+    from: NavVec3,
+    to: NavVec3,
+    end: usize,
+    mode: NavPathMode,
+    options: NavPathOptions,
+    open: BinaryHeap<NavSlicedPathItem>,
+    costs: HashMap<usize, Scalar>,
+    parents: HashMap<usize, usize>,
+    expansions: usize,
+    status: NavSlicedPathStatus,
+    path: Option<NavPath>,
+}
+
+impl<'a> NavSlicedPathQuery<'a> {
+    /// Nav mesh this query runs on.
+    #[inline]
+    pub fn mesh(&self) -> &'a NavMesh {
+        self.mesh
+    }
+
+    /// Current query status.
+    #[inline]
+    pub fn status(&self) -> NavSlicedPathStatus {
+        self.status
+    }
+
+    /// Total number of triangles expanded so far.
+    #[inline]
+    pub fn expansions(&self) -> usize {
+        self.expansions
+    }
+
+    /// Found path, available once query status is `Found`.
+    #[inline]
+    pub fn path(&self) -> Option<&NavPath> {
+        self.path.as_ref()
+    }
+
+    /// Consume query and take found path.
+    #[inline]
+    pub fn into_path(self) -> Option<NavPath> {
+        self.path
+    }
+
+    /// Advance query.
+    ///
+    /// # Arguments
+    /// * `budget` - maximal number of triangles to expand during this call.
+    ///
+    /// # Returns
+    /// Query status after update.
+    pub fn update(&mut self, budget: usize) -> NavSlicedPathStatus {
+        let mesh = self.mesh;
+        let mut remaining = budget;
+        while self.status == NavSlicedPathStatus::InProgress && remaining > 0 {
+            let NavSlicedPathItem(cost, triangle) = match self.open.pop() {
+                Some(item) => item,
+                None => {
+                    self.status = NavSlicedPathStatus::NotFound;
+                    break;
+                }
+            };
+            if cost > self.costs.get(&triangle).copied().unwrap_or(SCALAR_MAX) {
+                continue;
+            }
+            remaining -= 1;
+            self.expansions += 1;
+            if triangle == self.end {
+                let mut triangles = vec![triangle];
+                let mut current = triangle;
+                while let Some(parent) = self.parents.get(&current) {
+                    current = *parent;
+                    triangles.push(current);
+                }
+                triangles.reverse();
+                self.path = mesh
+                    .corridor_links(&triangles, &self.options)
+                    .and_then(|links| {
+                        mesh.build_path(
+                            self.from,
+                            self.to,
+                            &triangles,
+                            &links,
+                            self.mode,
+                            &self.options,
+                        )
+                    });
+                self.status = if self.path.is_some() {
+                    NavSlicedPathStatus::Found
+                } else {
+                    NavSlicedPathStatus::NotFound
+                };
+                break;
+            }
+            for edge in mesh.graph.edges(mesh.nodes[triangle]) {
+                let next = mesh.nodes_map[&edge.target()];
+                if !mesh.is_edge_passable(triangle, next, edge.weight(), &self.options) {
+                    continue;
+                }
+                let next_cost = cost + mesh.edge_cost(triangle, next, edge.weight(), &self.options);
+                if next_cost < self.costs.get(&next).copied().unwrap_or(SCALAR_MAX) {
+                    self.costs.insert(next, next_cost);
+                    self.parents.insert(next, triangle);
+                    self.open.push(NavSlicedPathItem(next_cost, next));
+                }
+            }
+        }
+        self.status
+    }
+}
+
+/// Path on nav mesh together with triangles corridor it goes through, that can be repaired
+/// locally when its start or end point moves, instead of searching for the whole path again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavPathCorridor {
+    mesh: NavMeshID,
+    position: NavVec3,
+    target: NavVec3,
+    triangles: Vec<usize>,
+    mode: NavPathMode,
+    options: NavPathOptions,
+    repair_expansions: usize,
+    path: NavPath,
+}
+
+impl NavPathCorridor {
+    /// Default limit of triangles expanded by local repair.
+    pub const DEFAULT_REPAIR_EXPANSIONS: usize = 256;
+
+    /// Identifier of nav mesh this corridor was found on.
+    #[inline]
+    pub fn mesh_id(&self) -> NavMeshID {
+        self.mesh
+    }
+
+    /// Current path start point.
+    #[inline]
+    pub fn position(&self) -> NavVec3 {
+        self.position
+    }
+
+    /// Current path end point.
+    #[inline]
+    pub fn target(&self) -> NavVec3 {
+        self.target
+    }
+
+    /// Corridor triangles indices, from the one containing position to the one containing target.
+    #[inline]
+    pub fn triangles(&self) -> &[usize] {
+        &self.triangles
+    }
+
+    /// Current path.
+    #[inline]
+    pub fn path(&self) -> &NavPath {
+        &self.path
+    }
+
+    /// Limit of triangles expanded by local repair before falling back to full search.
+    #[inline]
+    pub fn repair_expansions(&self) -> usize {
+        self.repair_expansions
+    }
+
+    /// Set limit of triangles expanded by local repair before falling back to full search.
+    #[inline]
+    pub fn set_repair_expansions(&mut self, value: usize) {
+        self.repair_expansions = value;
+    }
+
+    /// Move path start point, reusing corridor triangles where possible.
+    ///
+    /// # Arguments
+    /// * `mesh` - nav mesh this corridor was found on.
+    /// * `position` - new path start point.
+    /// * `query` - query quality or query options.
+    ///
+    /// # Returns
+    /// `true` if path was updated or `false` if position could not be reached (corridor stays
+    /// unchanged then).
+    pub fn move_position(
+        &mut self,
+        mesh: &NavMesh,
+        position: NavVec3,
+        query: impl Into<NavQueryOptions>,
+    ) -> bool {
+        if mesh.id() != self.mesh {
+            return false;
+        }
+        let (triangle, position) = match mesh.snap_point(position, &query.into()) {
+            Some(result) => result,
+            None => return false,
+        };
+        let Some(end) = self.triangles.last().copied() else {
+            return false;
+        };
+        let triangles = if let Some(index) = self.triangles.iter().rposition(|t| *t == triangle) {
+            self.triangles[index..].to_vec()
+        } else {
+            let corridor = &self.triangles;
+            let repair = mesh
+                .search_triangles_bounded(
+                    &[triangle],
+                    |t| corridor.contains(&t),
+                    &self.options,
+                    self.repair_expansions,
+                )
+                .and_then(|mut path| {
+                    let last = *path.last()?;
+                    let index = corridor.iter().rposition(|t| *t == last)?;
+                    path.extend_from_slice(&corridor[(index + 1)..]);
+                    Some(path)
+                });
+            match repair.or_else(|| {
+                mesh.find_path_triangles_with_options(triangle, end, &self.options, |_, _, _| true)
+                    .map(|(path, _)| path)
+            }) {
+                Some(triangles) if !triangles.is_empty() => triangles,
+                _ => return false,
+            }
+        };
+        let Some((triangles, path)) = self.repath(mesh, triangles, position, self.target) else {
+            return false;
+        };
+        self.triangles = triangles;
+        self.position = position;
+        self.path = path;
+        true
+    }
+
+    /// Move path end point, reusing corridor triangles where possible.
+    ///
+    /// # Arguments
+    /// * `mesh` - nav mesh this corridor was found on.
+    /// * `target` - new path end point.
+    /// * `query` - query quality or query options.
+    ///
+    /// # Returns
+    /// `true` if path was updated or `false` if target could not be reached (corridor stays
+    /// unchanged then).
+    pub fn move_target(
+        &mut self,
+        mesh: &NavMesh,
+        target: NavVec3,
+        query: impl Into<NavQueryOptions>,
+    ) -> bool {
+        if mesh.id() != self.mesh {
+            return false;
+        }
+        let (triangle, target) = match mesh.snap_point(target, &query.into()) {
+            Some(result) => result,
+            None => return false,
+        };
+        let Some(start) = self.triangles.first().copied() else {
+            return false;
+        };
+        let triangles = if let Some(index) = self.triangles.iter().position(|t| *t == triangle) {
+            self.triangles[..=index].to_vec()
+        } else {
+            let corridor = &self.triangles;
+            let repair = mesh
+                .search_triangles_bounded(
+                    corridor,
+                    |t| t == triangle,
+                    &self.options,
+                    self.repair_expansions,
+                )
+                .and_then(|path| {
+                    let first = *path.first()?;
+                    let index = corridor.iter().position(|t| *t == first)?;
+                    let mut result = corridor[..index].to_vec();
+                    result.extend(path);
+                    Some(result)
+                });
+            match repair.or_else(|| {
+                mesh.find_path_triangles_with_options(start, triangle, &self.options, |_, _, _| {
+                    true
+                })
+                .map(|(path, _)| path)
+            }) {
+                Some(triangles) if !triangles.is_empty() => triangles,
+                _ => return false,
+            }
+        };
+        let Some((triangles, path)) = self.repath(mesh, triangles, self.position, target) else {
+            return false;
+        };
+        self.triangles = triangles;
+        self.target = target;
+        self.path = path;
+        true
+    }
+
+    /// Search for the whole corridor again between current position and target. Local repairs
+    /// keep reusing old corridor, so path may drift from the shortest one over time and should be
+    /// replanned from time to time.
+    ///
+    /// # Arguments
+    /// * `mesh` - nav mesh this corridor was found on.
+    ///
+    /// # Returns
+    /// `true` if path was found or `false` otherwise (corridor stays unchanged then).
+    pub fn replan(&mut self, mesh: &NavMesh) -> bool {
+        if mesh.id() != self.mesh {
+            return false;
+        }
+        let (Some(start), Some(end)) = (self.triangles.first(), self.triangles.last()) else {
+            return false;
+        };
+        match mesh.find_path_triangles_with_options(*start, *end, &self.options, |_, _, _| true) {
+            Some((triangles, _)) if !triangles.is_empty() => {
+                let Some(path) = self.corridor_path(mesh, &triangles, self.position, self.target)
+                else {
+                    return false;
+                };
+                self.triangles = triangles;
+                self.path = path;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Builds path through reused corridor triangles, or through triangles found by full search
+    // between its ends if they are no longer connected (i.e. off-mesh link got removed).
+    fn repath(
+        &self,
+        mesh: &NavMesh,
+        triangles: Vec<usize>,
+        position: NavVec3,
+        target: NavVec3,
+    ) -> Option<(Vec<usize>, NavPath)> {
+        if let Some(path) = self.corridor_path(mesh, &triangles, position, target) {
+            return Some((triangles, path));
+        }
+        let (start, end) = (*triangles.first()?, *triangles.last()?);
+        let (triangles, _) =
+            mesh.find_path_triangles_with_options(start, end, &self.options, |_, _, _| true)?;
+        if triangles.is_empty() {
+            return None;
+        }
+        let path = self.corridor_path(mesh, &triangles, position, target)?;
+        Some((triangles, path))
+    }
+
+    // Builds path through given triangles, `None` if they are no longer connected.
+    fn corridor_path(
+        &self,
+        mesh: &NavMesh,
+        triangles: &[usize],
+        position: NavVec3,
+        target: NavVec3,
+    ) -> Option<NavPath> {
+        let links = mesh.corridor_links(triangles, &self.options)?;
+        if position.same_as_threshold(target, mesh.tolerance.threshold(2)) {
+            return Some(NavPath::new(vec![position]));
+        }
+        mesh.build_path(
+            position,
+            target,
+            triangles,
+            &links,
+            self.mode,
+            &self.options,
+        )
+    }
+}
@@ -1,6 +1,9 @@
-use crate::{Error, NavConnection, NavResult, NavVec3, Scalar};
+use crate::{
+    nav_search::astar, Error, NavConnection, NavHeuristic, NavMesh, NavPath, NavResult,
+    NavSpatialIndex, NavTolerance, NavVec3, NavZeroHeuristic, Scalar, ZERO_TRESHOLD,
+};
 use petgraph::{
-    algo::{astar, tarjan_scc},
+    algo::{has_path_connecting, tarjan_scc},
     graph::NodeIndex,
     visit::EdgeRef,
     Graph, Undirected,
@@ -8,12 +11,8 @@ use petgraph::{
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use spade::{rtree::RTree, BoundingRect, SpatialObject};
-use std::collections::HashMap;
-#[cfg(not(feature = "scalar64"))]
-use std::f32::MAX as SCALAR_MAX;
-#[cfg(feature = "scalar64")]
-use std::f64::MAX as SCALAR_MAX;
+use spade::{BoundingRect, SpatialObject};
+use std::{collections::HashMap, sync::OnceLock};
 use typid::ID;
 
 #[cfg(feature = "parallel")]
@@ -29,7 +28,7 @@ macro_rules! iter {
     };
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NavSpatialConnection {
     pub connection: NavConnection,
     pub index: usize,
@@ -78,8 +77,114 @@ impl SpatialObject for NavSpatialConnection {
 /// Nav net identifier.
 pub type NavNetID = ID<NavNet>;
 
+/// Traffic lanes of nav net connection. Forward lanes go from first to second connection vertex,
+/// backward lanes go the opposite way and each direction keeps to its right side.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavLanes {
+    pub forward: u32,
+    pub backward: u32,
+    pub width: Scalar,
+}
+
+impl Default for NavLanes {
+    fn default() -> Self {
+        Self {
+            forward: 1,
+            backward: 1,
+            width: 0.0,
+        }
+    }
+}
+
+/// Placement of nav net vertices generated from nav mesh (see `NavNet::from_navmesh`).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavNetWaypoints {
+    /// Vertex at center of every triangle, connected with centers of neighbor triangles.
+    #[default]
+    TriangleCenters,
+    /// Vertex at midpoint of every edge shared by two triangles, connected with midpoints of
+    /// other shared edges of the same triangles.
+    EdgeMidpoints,
+}
+
+/// Smoothing pass applied to nav net path, so agents do not make sharp turns at junctions.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NavNetSmoothing {
+    /// Keep path hugging net vertices.
+    #[default]
+    None,
+    /// Replace every corner with two points, cutting it no further than max deviation from
+    /// original corner.
+    CornerCut { max_deviation: Scalar },
+    /// Chaikin subdivision iterations, path endpoints are preserved.
+    Chaikin { iterations: usize },
+}
+
+impl NavNetSmoothing {
+    pub fn apply(&self, points: &[NavVec3]) -> Vec<NavVec3> {
+        match *self {
+            Self::None => points.to_vec(),
+            Self::CornerCut { max_deviation } => Self::corner_cut(points, max_deviation),
+            Self::Chaikin { iterations } => {
+                let mut result = points.to_vec();
+                for _ in 0..iterations {
+                    result = Self::chaikin(&result);
+                }
+                result
+            }
+        }
+    }
+
+    fn corner_cut(points: &[NavVec3], max_deviation: Scalar) -> Vec<NavVec3> {
+        if points.len() < 3 || max_deviation <= 0.0 {
+            return points.to_vec();
+        }
+        let mut result = Vec::with_capacity(points.len() * 2);
+        result.push(points[0]);
+        for window in points.windows(3) {
+            let (prev, corner, next) = (window[0], window[1], window[2]);
+            let to_prev = prev - corner;
+            let to_next = next - corner;
+            let prev_length = to_prev.magnitude();
+            let next_length = to_next.magnitude();
+            let to_prev = to_prev.normalize();
+            let to_next = to_next.normalize();
+            // cosine of half angle between segments, chord midpoint lies that much closer.
+            let half_cos = ((1.0 + to_prev.dot(to_next)) * 0.5).max(0.0).sqrt();
+            if half_cos < ZERO_TRESHOLD || prev_length <= 0.0 || next_length <= 0.0 {
+                result.push(corner);
+                continue;
+            }
+            // cut at most half of every segment so neighbor cuts do not overlap.
+            let distance = (max_deviation / half_cos)
+                .min(prev_length * 0.5)
+                .min(next_length * 0.5);
+            result.push(corner + to_prev * distance);
+            result.push(corner + to_next * distance);
+        }
+        result.push(points[points.len() - 1]);
+        result
+    }
+
+    fn chaikin(points: &[NavVec3]) -> Vec<NavVec3> {
+        if points.len() < 3 {
+            return points.to_vec();
+        }
+        let mut result = Vec::with_capacity(points.len() * 2);
+        result.push(points[0]);
+        for pair in points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            result.push(a * 0.75 + b * 0.25);
+            result.push(a * 0.25 + b * 0.75);
+        }
+        result.push(points[points.len() - 1]);
+        result
+    }
+}
+
+/// Nav net with optional user payload (i.e. speed limit or name) of every vertex.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
-pub struct NavNet {
+pub struct NavNet<T = ()> {
     id: NavNetID,
     vertices: Vec<NavVec3>,
     connections: Vec<NavConnection>,
@@ -88,13 +193,33 @@ pub struct NavNet {
     graph: Graph<(), Scalar, Undirected>,
     nodes: Vec<NodeIndex>,
     nodes_map: HashMap<NodeIndex, usize>,
-    rtree: RTree<NavSpatialConnection>,
-    spatials: Vec<NavSpatialConnection>,
+    // built on first spatial query when nav net is created in topology only mode.
+    #[serde(skip)]
+    spatial: OnceLock<NavSpatialIndex<NavSpatialConnection>>,
     origin: NavVec3,
+    #[serde(default)]
+    tolerance: NavTolerance,
+    // connections without entry use default lanes.
+    #[serde(default)]
+    lanes: Vec<NavLanes>,
+    // one payload per vertex, nets serialized before payloads were introduced have none.
+    #[serde(default = "Vec::new")]
+    payloads: Vec<T>,
 }
 
 impl NavNet {
     pub fn new(vertices: Vec<NavVec3>, connections: Vec<NavConnection>) -> NavResult<Self> {
+        let result = Self::new_topology_only(vertices, connections)?;
+        result.build_spatials();
+        Ok(result)
+    }
+
+    /// Create nav net without building spatial structures used by point queries - they get built
+    /// on first use instead.
+    pub fn new_topology_only(
+        vertices: Vec<NavVec3>,
+        connections: Vec<NavConnection>,
+    ) -> NavResult<Self> {
         let origin = vertices
             .iter()
             .cloned()
@@ -129,23 +254,7 @@ impl NavNet {
                 .collect::<Vec<_>>(),
         );
         let nodes_map = iter!(nodes).enumerate().map(|(i, n)| (*n, i)).collect();
-
-        let spatials = iter!(connections)
-            .enumerate()
-            .map(|(i, connection)| {
-                NavSpatialConnection::new(
-                    *connection,
-                    i,
-                    vertices[connection.0 as usize],
-                    vertices[connection.1 as usize],
-                )
-            })
-            .collect::<Vec<_>>();
-
-        let mut rtree = RTree::new();
-        for spatial in &spatials {
-            rtree.insert(spatial.clone());
-        }
+        let payloads = vec![(); vertices.len()];
 
         Ok(Self {
             id: ID::default(),
@@ -156,18 +265,151 @@ impl NavNet {
             graph,
             nodes,
             nodes_map,
-            rtree,
-            spatials,
+            spatial: OnceLock::new(),
             origin,
+            tolerance: Default::default(),
+            lanes: vec![],
+            payloads,
         })
     }
 
-    pub fn scale(&self, value: NavVec3, origin: Option<NavVec3>) -> NavResult<Self> {
+    /// Create sparse waypoints net with connections following nav mesh triangles adjacency.
+    pub fn from_navmesh(mesh: &NavMesh, waypoints: NavNetWaypoints) -> NavResult<Self> {
+        let half_edges = mesh.half_edges();
+        let mut vertices = vec![];
+        let mut connections = vec![];
+        match waypoints {
+            NavNetWaypoints::TriangleCenters => {
+                vertices.extend(mesh.areas().iter().map(|area| area.center));
+                for edge in half_edges {
+                    if let Some(twin) = edge.twin {
+                        let other = half_edges[twin].triangle;
+                        if edge.triangle < other {
+                            connections.push(NavConnection(edge.triangle as u32, other as u32));
+                        }
+                    }
+                }
+            }
+            NavNetWaypoints::EdgeMidpoints => {
+                // {half-edge index: vertex index}, shared by both twins.
+                let mut midpoints = HashMap::with_capacity(half_edges.len());
+                for (index, edge) in half_edges.iter().enumerate() {
+                    if let Some(twin) = edge.twin {
+                        if let Some(vertex) = midpoints.get(&twin).copied() {
+                            midpoints.insert(index, vertex);
+                        } else {
+                            let a = mesh.vertices()[edge.origin as usize];
+                            let b = mesh.vertices()[edge.target as usize];
+                            midpoints.insert(index, vertices.len() as u32);
+                            vertices.push((a + b) * 0.5);
+                        }
+                    }
+                }
+                for triangle in 0..mesh.triangles().len() {
+                    let shared = mesh
+                        .triangle_half_edges(triangle)
+                        .into_iter()
+                        .filter_map(|index| midpoints.get(&index).copied())
+                        .collect::<Vec<_>>();
+                    for (i, a) in shared.iter().enumerate() {
+                        for b in &shared[(i + 1)..] {
+                            connections.push(NavConnection(*a, *b));
+                        }
+                    }
+                }
+            }
+        }
+        Self::new(vertices, connections)
+    }
+}
+
+impl<T> NavNet<T> {
+    /// Attach payload to every vertex. Returns `Err` with
+    /// `Error::PayloadsCountDoesNotMatchVertices` if payloads count does not match vertices.
+    pub fn with_payloads<U>(self, payloads: Vec<U>) -> NavResult<NavNet<U>> {
+        if payloads.len() != self.vertices.len() {
+            return Err(Error::PayloadsCountDoesNotMatchVertices(
+                payloads.len(),
+                self.vertices.len(),
+            ));
+        }
+        Ok(self.replace_payloads(payloads))
+    }
+
+    fn replace_payloads<U>(self, payloads: Vec<U>) -> NavNet<U> {
+        NavNet {
+            id: self.id,
+            vertices: self.vertices,
+            connections: self.connections,
+            distances: self.distances,
+            costs: self.costs,
+            graph: self.graph,
+            nodes: self.nodes,
+            nodes_map: self.nodes_map,
+            spatial: self.spatial,
+            origin: self.origin,
+            tolerance: self.tolerance,
+            lanes: self.lanes,
+            payloads,
+        }
+    }
+
+    #[inline]
+    pub fn payloads(&self) -> &[T] {
+        &self.payloads
+    }
+
+    #[inline]
+    pub fn payload(&self, index: usize) -> Option<&T> {
+        self.payloads.get(index)
+    }
+
+    #[inline]
+    pub fn payload_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.payloads.get_mut(index)
+    }
+
+    pub fn build_spatials(&self) {
+        self.spatial();
+    }
+
+    #[inline]
+    pub fn has_spatials(&self) -> bool {
+        self.spatial.get().is_some()
+    }
+
+    fn spatial(&self) -> &NavSpatialIndex<NavSpatialConnection> {
+        self.spatial.get_or_init(|| {
+            // payloads do not have to be shared between threads.
+            let vertices = &self.vertices;
+            NavSpatialIndex::new(
+                iter!(self.connections)
+                    .enumerate()
+                    .map(|(i, connection)| {
+                        NavSpatialConnection::new(
+                            *connection,
+                            i,
+                            vertices[connection.0 as usize],
+                            vertices[connection.1 as usize],
+                        )
+                    })
+                    .collect(),
+            )
+        })
+    }
+
+    pub fn scale(&self, value: NavVec3, origin: Option<NavVec3>) -> NavResult<Self>
+    where
+        T: Clone,
+    {
         let origin = origin.unwrap_or(self.origin);
         let vertices = iter!(self.vertices)
             .map(|v| (*v - origin) * value + origin)
             .collect::<Vec<_>>();
-        Self::new(vertices, self.connections.clone())
+        let mut result = NavNet::new(vertices, self.connections.clone())?
+            .replace_payloads(self.payloads.clone());
+        result.lanes = self.lanes.clone();
+        Ok(result)
     }
 
     #[inline]
@@ -180,6 +422,36 @@ impl NavNet {
         self.origin
     }
 
+    #[inline]
+    pub fn tolerance(&self) -> NavTolerance {
+        self.tolerance
+    }
+
+    #[inline]
+    pub fn unit_scale(&self) -> Scalar {
+        self.tolerance.unit_scale
+    }
+
+    #[inline]
+    pub fn set_unit_scale(&mut self, value: Scalar) {
+        self.tolerance.unit_scale = value.max(0.0);
+    }
+
+    #[inline]
+    pub fn set_tolerance(&mut self, value: NavTolerance) {
+        self.tolerance = value;
+    }
+
+    #[inline]
+    pub fn epsilon(&self) -> Scalar {
+        self.tolerance.epsilon
+    }
+
+    #[inline]
+    pub fn set_epsilon(&mut self, value: Scalar) {
+        self.tolerance.epsilon = value.max(0.0);
+    }
+
     #[inline]
     pub fn vertices(&self) -> &[NavVec3] {
         &self.vertices
@@ -208,82 +480,251 @@ impl NavNet {
         Some(old)
     }
 
+    /// Add vertice without connections. Returns its index.
+    pub fn add_vertex(&mut self, point: NavVec3) -> usize
+    where
+        T: Default,
+    {
+        self.add_vertex_with_payload(point, T::default())
+    }
+
+    /// Add vertice with payload and without connections. Returns its index.
+    pub fn add_vertex_with_payload(&mut self, point: NavVec3, payload: T) -> usize {
+        let index = self.vertices.len();
+        self.payloads.push(payload);
+        self.origin = (self.origin * index as Scalar + point) / (index + 1) as Scalar;
+        self.vertices.push(point);
+        self.costs.push(1.0);
+        let node = self.graph.add_node(());
+        self.nodes.push(node);
+        self.nodes_map.insert(node, index);
+        index
+    }
+
+    /// Connect two vertices, updating spatial structures in place if they are already built.
+    /// Returns index of new connection.
+    pub fn add_connection(&mut self, connection: NavConnection) -> NavResult<usize> {
+        let index = self.connections.len();
+        if connection.0 as usize >= self.vertices.len() {
+            return Err(Error::ConnectionVerticeIndexOutOfBounds(
+                index as u32,
+                0,
+                connection.0,
+            ));
+        }
+        if connection.1 as usize >= self.vertices.len() {
+            return Err(Error::ConnectionVerticeIndexOutOfBounds(
+                index as u32,
+                1,
+                connection.1,
+            ));
+        }
+        let a = self.vertices[connection.0 as usize];
+        let b = self.vertices[connection.1 as usize];
+        let distance = (b - a).sqr_magnitude();
+        self.connections.push(connection);
+        self.distances.push(distance);
+        self.graph.add_edge(
+            self.nodes[connection.0 as usize],
+            self.nodes[connection.1 as usize],
+            distance,
+        );
+        if let Some(spatial) = self.spatial.get_mut() {
+            let object = NavSpatialConnection::new(connection, index, a, b);
+            spatial.rtree.insert(object.clone());
+            spatial.objects.push(object);
+        }
+        Ok(index)
+    }
+
+    /// Remove connection, updating spatial structures in place if they are already built. Last
+    /// connection takes index of removed one.
+    pub fn remove_connection(&mut self, index: usize) -> Option<NavConnection> {
+        if index >= self.connections.len() {
+            return None;
+        }
+        let last = self.connections.len() - 1;
+        let connection = self.connections.swap_remove(index);
+        self.distances.swap_remove(index);
+        if index < self.lanes.len() {
+            self.lanes.resize(last + 1, Default::default());
+            self.lanes.swap_remove(index);
+        }
+        // graph edges are stored in connections order and get removed the same way.
+        self.graph
+            .remove_edge(petgraph::graph::EdgeIndex::new(index));
+        if let Some(spatial) = self.spatial.get_mut() {
+            let removed = spatial.objects.swap_remove(index);
+            spatial.rtree.remove(&removed);
+            if index != last {
+                let moved = &mut spatial.objects[index];
+                spatial.rtree.remove(moved);
+                moved.index = index;
+                spatial.rtree.insert(moved.clone());
+            }
+        }
+        Some(connection)
+    }
+
+    #[inline]
+    pub fn lanes(&self, index: usize) -> NavLanes {
+        self.lanes.get(index).copied().unwrap_or_default()
+    }
+
+    pub fn set_lanes(&mut self, index: usize, lanes: NavLanes) -> Option<NavLanes> {
+        if index >= self.connections.len() {
+            return None;
+        }
+        if self.lanes.len() < self.connections.len() {
+            self.lanes
+                .resize(self.connections.len(), Default::default());
+        }
+        Some(std::mem::replace(&mut self.lanes[index], lanes))
+    }
+
+    /// Offset path found on this nav net laterally to the right of travel direction, so it
+    /// follows given lane (`0` is the lane closest to connection center line). Lane index is
+    /// clamped to lanes count of each connection in travel direction.
+    pub fn offset_path_by_lane(&self, path: &[NavVec3], lane: u32, up: NavVec3) -> Vec<NavVec3> {
+        let threshold = self.tolerance.threshold(1);
+        let offsets = path
+            .windows(2)
+            .map(|pair| {
+                let direction = pair[1] - pair[0];
+                if direction.magnitude() < threshold {
+                    return NavVec3::default();
+                }
+                let index = match self.find_closest_connection((pair[0] + pair[1]) * 0.5) {
+                    Some(index) => index,
+                    None => return NavVec3::default(),
+                };
+                let connection = self.connections[index];
+                let lanes = self.lanes(index);
+                let along =
+                    self.vertices[connection.1 as usize] - self.vertices[connection.0 as usize];
+                let count = if along.dot(direction) >= 0.0 {
+                    lanes.forward
+                } else {
+                    lanes.backward
+                };
+                if count == 0 {
+                    return NavVec3::default();
+                }
+                let side = direction.cross(up).normalize();
+                side * (lanes.width * (lane.min(count - 1) as Scalar + 0.5))
+            })
+            .collect::<Vec<_>>();
+        path.iter()
+            .enumerate()
+            .map(|(index, point)| {
+                let before = index.checked_sub(1).and_then(|i| offsets.get(i));
+                let after = offsets.get(index);
+                let offset = match (before, after) {
+                    (Some(a), Some(b)) => (*a + *b) * 0.5,
+                    (Some(a), None) | (None, Some(a)) => *a,
+                    (None, None) => NavVec3::default(),
+                };
+                *point + offset
+            })
+            .collect()
+    }
+
     pub fn closest_point(&self, point: NavVec3) -> Option<NavVec3> {
         let index = self.find_closest_connection(point)?;
-        Some(self.spatials[index].closest_point(point))
+        Some(self.spatial().objects[index].closest_point(point))
     }
 
     pub fn find_closest_connection(&self, point: NavVec3) -> Option<usize> {
-        self.rtree.nearest_neighbor(&point).map(|c| c.index)
+        self.spatial()
+            .rtree
+            .nearest_neighbor(&point)
+            .map(|c| c.index)
+    }
+
+    /// Indices of connections not farther than `radius` from point, nearest first.
+    pub fn connections_within(&self, point: NavVec3, radius: Scalar) -> Vec<usize> {
+        let radius_sqr = radius * radius;
+        self.spatial()
+            .rtree
+            .nearest_neighbor_iterator(&point)
+            .take_while(|c| c.distance2(&point) <= radius_sqr)
+            .map(|c| c.index)
+            .collect()
+    }
+
+    /// Indices of up to `k` connections nearest to point, nearest first.
+    pub fn k_nearest_connections(&self, point: NavVec3, k: usize) -> Vec<usize> {
+        self.spatial()
+            .rtree
+            .nearest_neighbor_iterator(&point)
+            .take(k)
+            .map(|c| c.index)
+            .collect()
     }
 
     pub fn find_path(&self, from: NavVec3, to: NavVec3) -> Option<Vec<NavVec3>> {
         self.find_path_custom(from, to, |_, _, _| true)
     }
 
+    /// Find path together with its segments lengths and total length (see `NavPath::length`),
+    /// i.e. for travel time estimates.
     // filter params: connection distance sqr, first vertex index, second vertex index.
-    pub fn find_path_custom<F>(
+    pub fn find_path_annotated<F>(&self, from: NavVec3, to: NavVec3, filter: F) -> Option<NavPath>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.find_path_custom(from, to, filter).map(NavPath::new)
+    }
+
+    /// Find path and apply smoothing pass to it, i.e. for vehicles following the net.
+    // filter params: connection distance sqr, first vertex index, second vertex index.
+    pub fn find_path_smoothed<F>(
         &self,
         from: NavVec3,
         to: NavVec3,
-        mut filter: F,
+        smoothing: NavNetSmoothing,
+        filter: F,
     ) -> Option<Vec<NavVec3>>
     where
         F: FnMut(Scalar, usize, usize) -> bool,
     {
-        let start_index = self.find_closest_connection(from)?;
-        let end_index = self.find_closest_connection(to)?;
-        let start_connection = self.connections[start_index];
-        let end_connection = self.connections[end_index];
-        let start_point = self.spatials[start_index].closest_point(from);
-        let end_point = self.spatials[end_index].closest_point(to);
-        if start_index == end_index {
-            return Some(vec![start_point, end_point]);
-        } else if start_point.same_as(end_point) {
-            return Some(vec![start_point]);
-        }
-        let start_vertice = {
-            let a = self.vertices[start_connection.0 as usize];
-            let b = self.vertices[start_connection.1 as usize];
-            if (a - start_point).sqr_magnitude() < (b - start_point).sqr_magnitude() {
-                start_connection.0 as usize
-            } else {
-                start_connection.1 as usize
-            }
-        };
-        let end_vertice = {
-            let a = self.vertices[end_connection.0 as usize];
-            let b = self.vertices[end_connection.1 as usize];
-            if (a - end_point).sqr_magnitude() < (b - end_point).sqr_magnitude() {
-                end_connection.0 as usize
-            } else {
-                end_connection.1 as usize
-            }
+        self.find_path_custom(from, to, filter)
+            .map(|points| smoothing.apply(&points))
+    }
+
+    /// Find path that does not go through connections rejected by filter, i.e. temporarily
+    /// blocked ones. Path endpoints snap to the closest accepted connections.
+    // filter params: connection distance sqr, first vertex index, second vertex index.
+    pub fn find_path_custom<F>(&self, from: NavVec3, to: NavVec3, filter: F) -> Option<Vec<NavVec3>>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.find_path_with_heuristic(from, to, &NavZeroHeuristic, filter)
+    }
+
+    /// Same as `find_path_custom` but search is guided by custom heuristic, given vertices
+    /// indices and points.
+    pub fn find_path_with_heuristic<H, F>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        heuristic: &H,
+        filter: F,
+    ) -> Option<Vec<NavVec3>>
+    where
+        H: NavHeuristic,
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        let (start_point, end_point, indices) = self.search(from, to, heuristic, filter)?;
+        let indices = match indices {
+            // points on the same connection are connected directly even if they are the same.
+            None => return Some(vec![start_point, end_point]),
+            Some(indices) if indices.is_empty() => return Some(vec![start_point]),
+            Some(indices) => indices,
         };
-        let start_node = *self.nodes.get(start_vertice)?;
-        let end_node = *self.nodes.get(end_vertice)?;
-        let nodes = astar(
-            &self.graph,
-            start_node,
-            |n| n == end_node,
-            |e| {
-                let a = self.nodes_map[&e.source()];
-                let b = self.nodes_map[&e.target()];
-                let w = *e.weight();
-                if filter(w, a, b) {
-                    let a = self.costs[a];
-                    let b = self.costs[b];
-                    w * a * b
-                } else {
-                    SCALAR_MAX
-                }
-            },
-            |_| 0.0,
-        )?
-        .1;
-        let mut points = nodes
+        let mut points = indices
             .into_iter()
-            .map(|n| self.vertices[self.nodes_map[&n]])
+            .map(|index| self.vertices[index])
             .collect::<Vec<_>>();
         if points.len() > 2 {
             {
@@ -312,15 +753,148 @@ impl NavNet {
         Some(points)
     }
 
+    /// Find indices of vertices that path goes through, i.e. to read their payloads.
+    /// Empty list means both points snap to the same connection or to the same point.
+    // filter params: connection distance sqr, first vertex index, second vertex index.
+    pub fn find_path_vertices<F>(&self, from: NavVec3, to: NavVec3, filter: F) -> Option<Vec<usize>>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.search(from, to, &NavZeroHeuristic, filter)
+            .map(|(_, _, indices)| indices.unwrap_or_default())
+    }
+
+    /// Find payloads of vertices that path goes through, in path order.
+    /// Empty list means both points snap to the same connection or to the same point, `None`
+    /// means there is no path or nav net has no payloads (i.e. it was deserialized from data
+    /// saved before payloads were introduced).
+    // filter params: connection distance sqr, first vertex index, second vertex index.
+    pub fn find_path_payloads<F>(&self, from: NavVec3, to: NavVec3, filter: F) -> Option<Vec<&T>>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.find_path_vertices(from, to, filter)?
+            .into_iter()
+            .map(|index| self.payloads.get(index))
+            .collect()
+    }
+
+    // returns start point, end point and visited vertices indices, `None` if both points are on
+    // the same connection and empty if points are the same.
+    fn search<H, F>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        heuristic: &H,
+        mut filter: F,
+    ) -> Option<(NavVec3, NavVec3, Option<Vec<usize>>)>
+    where
+        H: NavHeuristic,
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        let start_index = self.find_closest_connection_filtered(from, &mut filter)?;
+        let end_index = self.find_closest_connection_filtered(to, &mut filter)?;
+        let start_connection = self.connections[start_index];
+        let end_connection = self.connections[end_index];
+        let start_point = self.spatial().objects[start_index].closest_point(from);
+        let end_point = self.spatial().objects[end_index].closest_point(to);
+        if start_index == end_index {
+            return Some((start_point, end_point, None));
+        } else if start_point.same_as_threshold(end_point, self.tolerance.threshold(2)) {
+            return Some((start_point, end_point, Some(vec![])));
+        }
+        let start_vertice = {
+            let a = self.vertices[start_connection.0 as usize];
+            let b = self.vertices[start_connection.1 as usize];
+            if (a - start_point).sqr_magnitude() < (b - start_point).sqr_magnitude() {
+                start_connection.0 as usize
+            } else {
+                start_connection.1 as usize
+            }
+        };
+        let end_vertice = {
+            let a = self.vertices[end_connection.0 as usize];
+            let b = self.vertices[end_connection.1 as usize];
+            if (a - end_point).sqr_magnitude() < (b - end_point).sqr_magnitude() {
+                end_connection.0 as usize
+            } else {
+                end_connection.1 as usize
+            }
+        };
+        self.nodes.get(start_vertice)?;
+        self.nodes.get(end_vertice)?;
+        let indices = astar(
+            start_vertice,
+            end_vertice,
+            |a, visit| {
+                for e in self.graph.edges(self.nodes[a]) {
+                    let b = self.nodes_map[&if e.source() == self.nodes[a] {
+                        e.target()
+                    } else {
+                        e.source()
+                    }];
+                    let w = *e.weight();
+                    if filter(w, a, b) {
+                        visit(b, w * self.costs[a] * self.costs[b]);
+                    }
+                }
+            },
+            |index| {
+                heuristic.estimate(
+                    index,
+                    end_vertice,
+                    self.vertices[index],
+                    self.vertices[end_vertice],
+                )
+            },
+        )?
+        .1;
+        Some((start_point, end_point, Some(indices)))
+    }
+
+    fn find_closest_connection_filtered<F>(&self, point: NavVec3, filter: &mut F) -> Option<usize>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.spatial()
+            .rtree
+            .nearest_neighbor_iterator(&point)
+            .find(|c| {
+                filter(
+                    self.distances[c.index],
+                    c.connection.0 as usize,
+                    c.connection.1 as usize,
+                )
+            })
+            .map(|c| c.index)
+    }
+
     pub fn find_islands(&self) -> Vec<Vec<NavVec3>> {
+        self.find_vertices_islands()
+            .into_iter()
+            .map(|v| v.into_iter().map(|i| self.vertices[i]).collect())
+            .collect()
+    }
+
+    /// Groups of connected vertices indices, in the same order as `find_islands` gives points.
+    /// Vertices without connections form their own islands.
+    pub fn find_vertices_islands(&self) -> Vec<Vec<usize>> {
         tarjan_scc(&self.graph)
             .into_iter()
             .map(|v| {
                 v.into_iter()
-                    .filter_map(|n| self.nodes_map.get(&n).map(|i| self.vertices[*i]))
+                    .filter_map(|n| self.nodes_map.get(&n).copied())
                     .collect::<Vec<_>>()
             })
             .filter(|v| !v.is_empty())
             .collect()
     }
+
+    /// Tells if path between vertices exists.
+    pub fn are_vertices_connected(&self, from: usize, to: usize) -> bool {
+        match (self.nodes.get(from), self.nodes.get(to)) {
+            (Some(a), Some(b)) => has_path_connecting(&self.graph, *a, *b, None),
+            _ => false,
+        }
+    }
 }
@@ -0,0 +1,87 @@
+use crate::Scalar;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Sparse counters of agents occupying nav mesh triangles or nav grid cells (addressed by
+/// triangle index or cell index respectively). Agents register into area they stand on and
+/// out of it when they leave, so crowded areas can be detected and avoided.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mut occupancy = NavOccupancy::default();
+/// occupancy.enter(3);
+/// occupancy.enter(3);
+/// occupancy.relocate(Some(3), Some(4));
+/// assert_eq!(occupancy.count(3), 1);
+/// assert_eq!(occupancy.count(4), 1);
+/// assert!(occupancy.is_crowded(3, 1));
+/// assert!(!occupancy.is_crowded(5, 1));
+/// assert_eq!(occupancy.cost_factor(3, 0.5), 1.5);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NavOccupancy {
+    counts: HashMap<usize, usize>,
+}
+
+impl NavOccupancy {
+    #[inline]
+    pub fn count(&self, index: usize) -> usize {
+        self.counts.get(&index).copied().unwrap_or(0)
+    }
+
+    /// Tells if area holds at least `capacity` agents.
+    #[inline]
+    pub fn is_crowded(&self, index: usize, capacity: usize) -> bool {
+        self.count(index) >= capacity
+    }
+
+    /// Register agent into area.
+    pub fn enter(&mut self, index: usize) {
+        *self.counts.entry(index).or_default() += 1;
+    }
+
+    /// Unregister agent from area. Returns `false` if area was not occupied.
+    pub fn leave(&mut self, index: usize) -> bool {
+        match self.counts.get_mut(&index) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(&index);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move agent between areas, where `None` means agent is outside of any area.
+    pub fn relocate(&mut self, from: Option<usize>, to: Option<usize>) {
+        if from == to {
+            return;
+        }
+        if let Some(from) = from {
+            self.leave(from);
+        }
+        if let Some(to) = to {
+            self.enter(to);
+        }
+    }
+
+    /// Iterator over occupied areas and their agents count.
+    pub fn occupied(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.counts.iter().map(|(index, count)| (*index, *count))
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
+
+    /// Congestion cost multiplier of area: `1 + count * weight` (clamped at zero).
+    #[inline]
+    pub fn cost_factor(&self, index: usize, weight: Scalar) -> Scalar {
+        (1.0 + self.count(index) as Scalar * weight).max(0.0)
+    }
+}
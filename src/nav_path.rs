@@ -0,0 +1,280 @@
+use crate::{NavOffMeshLinkID, NavOffMeshLinkKind, NavVec3, Scalar, ZERO_TRESHOLD};
+use serde::{Deserialize, Serialize};
+use std::hash::Hasher;
+
+// FNV-1a, cheap and stable across runs, unlike `DefaultHasher`. Values are written as fixed width
+// little endian bytes, so hashes do not depend on target pointer width or endianness either.
+struct NavContentHasher(u64);
+
+impl Default for NavContentHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for NavContentHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Off-mesh link traversed by path segment.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NavPathLink {
+    /// Off-mesh link identifier.
+    pub id: NavOffMeshLinkID,
+    /// Off-mesh link kind, used to decide how agent should traverse it.
+    pub kind: NavOffMeshLinkKind,
+}
+
+/// Change of named area type (see `NavMesh::set_area_name`) along path triangles corridor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NavAreaTransition {
+    /// Index of first corridor triangle of entered area type.
+    pub index: usize,
+    /// Area type that is left.
+    pub from: u8,
+    /// Area type that is entered.
+    pub to: u8,
+}
+
+/// Path segment between two consecutive path points.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NavPathSegment {
+    /// Segment length.
+    pub length: Scalar,
+    /// `Some` with off-mesh link if this segment traverses it or `None` if segment goes along
+    /// nav surface.
+    pub link: Option<NavPathLink>,
+}
+
+/// Path points annotated with information about segments between them.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavPath {
+    /// Path points.
+    pub points: Vec<NavVec3>,
+    /// Path segments, where segment `i` goes from point `i` to point `i + 1`.
+    pub segments: Vec<NavPathSegment>,
+}
+
+impl NavPath {
+    /// Create path from points, with all segments going along nav surface.
+    pub fn new(points: Vec<NavVec3>) -> Self {
+        let mut result = Self::default();
+        for point in points {
+            result.push(point, None);
+        }
+        result
+    }
+
+    /// Append point to the path. Point same as the last one is skipped unless it is reached
+    /// with off-mesh link.
+    ///
+    /// # Arguments
+    /// * `point` - path point.
+    /// * `link` - off-mesh link used to reach `point` from the last path point.
+    pub fn push(&mut self, point: NavVec3, link: Option<NavPathLink>) {
+        self.push_threshold(point, link, ZERO_TRESHOLD);
+    }
+
+    /// Same as `push` but with custom squared distance under which points are treated as the
+    /// same.
+    pub fn push_threshold(&mut self, point: NavVec3, link: Option<NavPathLink>, threshold: Scalar) {
+        if let Some(last) = self.points.last().copied() {
+            if link.is_none() && last.same_as_threshold(point, threshold) {
+                return;
+            }
+            self.segments.push(NavPathSegment {
+                length: (point - last).magnitude(),
+                link,
+            });
+        }
+        self.points.push(point);
+    }
+
+    /// Total path length.
+    pub fn length(&self) -> Scalar {
+        self.segments.iter().map(|segment| segment.length).sum()
+    }
+
+    /// Create path going the opposite way. Off-mesh links annotations are kept, so make sure
+    /// traversed links are bidirectional before following reversed path.
+    pub fn reversed(&self) -> Self {
+        Self {
+            points: self.points.iter().rev().copied().collect(),
+            segments: self.segments.iter().rev().copied().collect(),
+        }
+    }
+
+    /// Create path going along this path and then along `other` path. Paths are joined with
+    /// surface segment if `other` does not start where this path ends.
+    pub fn concat(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        let mut segments = other.segments.iter();
+        for (index, point) in other.points.iter().enumerate() {
+            let link = if index == 0 {
+                None
+            } else {
+                segments.next().and_then(|segment| segment.link)
+            };
+            result.push(*point, link);
+        }
+        result
+    }
+
+    /// Create path that goes along this path only up to given distance from its start. Path is
+    /// cut in the middle of surface segment, but segment traversing off-mesh link is either kept
+    /// whole or dropped, because agent can not stop in the middle of a link.
+    ///
+    /// # Arguments
+    /// * `distance` - distance along path.
+    pub fn trim_to_distance(&self, distance: Scalar) -> Self {
+        let mut result = Self::default();
+        let mut points = self.points.iter();
+        if let Some(point) = points.next() {
+            result.points.push(*point);
+        }
+        let mut remaining = distance.max(0.0);
+        for (segment, point) in self.segments.iter().zip(points) {
+            if segment.length <= remaining {
+                result.points.push(*point);
+                result.segments.push(*segment);
+                remaining -= segment.length;
+                continue;
+            }
+            if segment.link.is_none() && remaining > 0.0 {
+                let last = *result.points.last().unwrap();
+                let point = NavVec3::unproject(last, *point, remaining / segment.length);
+                result.push(point, None);
+            }
+            break;
+        }
+        result
+    }
+
+    /// Iterator over off-mesh links traversed by path, with indices of segments that traverse
+    /// them.
+    pub fn links(&self) -> impl Iterator<Item = (usize, &NavPathLink)> {
+        self.segments
+            .iter()
+            .enumerate()
+            .filter_map(|(index, segment)| Some((index, segment.link.as_ref()?)))
+    }
+
+    /// Cheap hash of path content: points snapped to grid of given cell size and traversed
+    /// off-mesh links, so paths that differ only by tiny numerical noise get the same hash.
+    /// Points lying close to grid cells borders can still end up in different cells.
+    ///
+    /// # Arguments
+    /// * `quantum` - size of grid cell that points are snapped to, zero compares exact points.
+    pub fn content_hash(&self, quantum: Scalar) -> u64 {
+        let mut hasher = NavContentHasher::default();
+        self.hash_content(quantum, &mut hasher);
+        hasher.finish()
+    }
+
+    fn hash_content(&self, quantum: Scalar, hasher: &mut NavContentHasher) {
+        hasher.write(&(self.points.len() as u64).to_le_bytes());
+        for point in &self.points {
+            for value in [point.x, point.y, point.z] {
+                if quantum > 0.0 {
+                    hasher.write(&((value / quantum).round() as i64).to_le_bytes());
+                } else {
+                    // negative zero gets the same bytes as zero.
+                    hasher.write(&(value + 0.0).to_le_bytes());
+                }
+            }
+        }
+        for segment in &self.segments {
+            match &segment.link {
+                Some(link) => {
+                    hasher.write_u8(1);
+                    hasher.write(link.id.uuid().as_bytes());
+                    match link.kind {
+                        NavOffMeshLinkKind::Jump => hasher.write_u8(0),
+                        NavOffMeshLinkKind::Ladder => hasher.write_u8(1),
+                        NavOffMeshLinkKind::Teleport => hasher.write_u8(2),
+                        NavOffMeshLinkKind::Step => hasher.write_u8(3),
+                        NavOffMeshLinkKind::Custom(kind) => {
+                            hasher.write_u8(4);
+                            hasher.write(&kind.to_le_bytes());
+                        }
+                    }
+                }
+                None => hasher.write_u8(0),
+            }
+        }
+    }
+}
+
+/// Path found on nav mesh together with its metadata.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NavPathResult {
+    /// Annotated path.
+    pub path: NavPath,
+    /// Traversed triangles indices, from start triangle to end triangle.
+    pub triangles: Vec<usize>,
+    /// Total path length.
+    pub length: Scalar,
+    /// Path cost used by path finding, including area costs and off-mesh link costs.
+    pub cost: Scalar,
+    /// Tells if search ran out of expansions budget and path leads only towards the goal.
+    #[serde(default)]
+    pub partial: bool,
+    /// Named area types changes along traversed triangles.
+    #[serde(default)]
+    pub transitions: Vec<NavAreaTransition>,
+    /// Nav mesh topology version (see `NavMesh::topology_version`) path was found with.
+    #[serde(default)]
+    pub version: u64,
+}
+
+impl NavPathResult {
+    /// Path points.
+    #[inline]
+    pub fn points(&self) -> &[NavVec3] {
+        &self.path.points
+    }
+
+    /// Lengths of path segments, where segment `i` goes from point `i` to point `i + 1`.
+    pub fn segment_lengths(&self) -> impl Iterator<Item = Scalar> + '_ {
+        self.path.segments.iter().map(|segment| segment.length)
+    }
+
+    /// Cheap hash of path content (see `NavPath::content_hash`) combined with traversed
+    /// triangles and topology version, so replanned path that is effectively the same as
+    /// previous one can be detected and movement orders or network updates skipped.
+    ///
+    /// # Arguments
+    /// * `quantum` - size of grid cell that points are snapped to, zero compares exact points.
+    pub fn content_hash(&self, quantum: Scalar) -> u64 {
+        let mut hasher = NavContentHasher::default();
+        hasher.write(&self.version.to_le_bytes());
+        hasher.write(&(self.triangles.len() as u64).to_le_bytes());
+        for triangle in &self.triangles {
+            hasher.write(&(*triangle as u64).to_le_bytes());
+        }
+        hasher.write_u8(self.partial as u8);
+        self.path.hash_content(quantum, &mut hasher);
+        hasher.finish()
+    }
+}
+
+impl From<NavPathResult> for NavPath {
+    fn from(result: NavPathResult) -> Self {
+        result.path
+    }
+}
+
+impl From<NavPath> for Vec<NavVec3> {
+    fn from(path: NavPath) -> Self {
+        path.points
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{NavMesh, NavMeshID, NavPathMode, NavQueryOptions, NavVec3, Scalar};
+use std::collections::{HashMap, VecDeque};
+
+// lattice cell of endpoint together with nav mesh triangle it snaps to.
+type NavPathCacheCell = (i64, i64, i64, Option<usize>);
+
+/// Opt-in memoization of nav mesh paths by endpoints snapped to lattice of given cell size, so
+/// bursts of nearly identical queries (i.e. from clustered agents heading to the same place)
+/// reuse path found for the first of them. Endpoints are also keyed by triangle they snap to, so
+/// points from the same cell on both sides of a wall never share paths. Paths given for cache hits
+/// have their first and last points replaced with queried endpoints, and if any of these patched
+/// end segments is not clear, path is searched again instead. Cache is bound to single nav mesh
+/// and gets cleared whenever its topology version changes.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let mesh = NavMesh::new(
+///     vec![
+///         (0.0, 0.0, 0.0).into(),
+///         (4.0, 0.0, 0.0).into(),
+///         (4.0, 1.0, 0.0).into(),
+///         (0.0, 1.0, 0.0).into(),
+///     ],
+///     vec![(0, 1, 2).into(), (2, 3, 0).into()],
+/// )
+/// .unwrap();
+/// let mut cache = NavPathCache::new(0.5, NavQuery::Accuracy, NavPathMode::Funnel);
+/// let from = NavVec3::new(0.1, 0.1, 0.0);
+/// let to = NavVec3::new(3.9, 0.9, 0.0);
+/// cache.find_path(&mesh, from, to).unwrap();
+/// let from = NavVec3::new(0.2, 0.1, 0.0);
+/// let path = cache.find_path(&mesh, from, to).unwrap();
+/// assert_eq!(path, vec![from, to]);
+/// assert_eq!(cache.hits(), 1);
+/// assert_eq!(cache.misses(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct NavPathCache {
+    cell_size: Scalar,
+    query: NavQueryOptions,
+    mode: NavPathMode,
+    capacity: usize,
+    // nav mesh identifier and topology version of cached paths.
+    mesh: Option<(NavMeshID, u64)>,
+    paths: HashMap<(NavPathCacheCell, NavPathCacheCell), Option<Vec<NavVec3>>>,
+    // cached keys from oldest to newest, for evicting oldest paths.
+    order: VecDeque<(NavPathCacheCell, NavPathCacheCell)>,
+    hits: usize,
+    misses: usize,
+}
+
+impl NavPathCache {
+    /// Create cache with endpoints lattice cell size and query settings used to find paths.
+    pub fn new(cell_size: Scalar, query: impl Into<NavQueryOptions>, mode: NavPathMode) -> Self {
+        Self {
+            cell_size: cell_size.max(Scalar::EPSILON),
+            query: query.into(),
+            mode,
+            capacity: 1024,
+            mesh: None,
+            paths: Default::default(),
+            order: Default::default(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    #[inline]
+    pub fn cell_size(&self) -> Scalar {
+        self.cell_size
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Set max number of cached paths, oldest paths are evicted first.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    /// Number of cached paths (including cached failures).
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    #[inline]
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    #[inline]
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    pub fn clear(&mut self) {
+        self.mesh = None;
+        self.paths.clear();
+        self.order.clear();
+    }
+
+    /// Find path on nav mesh, reusing path cached for endpoints in the same lattice cells.
+    pub fn find_path(
+        &mut self,
+        mesh: &NavMesh,
+        from: NavVec3,
+        to: NavVec3,
+    ) -> Option<Vec<NavVec3>> {
+        let version = (mesh.id(), mesh.topology_version());
+        if self.mesh != Some(version) {
+            self.clear();
+            self.mesh = Some(version);
+        }
+        let key = (self.cell(mesh, from), self.cell(mesh, to));
+        if let Some(path) = self.paths.get(&key) {
+            match path {
+                Some(path) => {
+                    if let Some(path) = Self::patch(mesh, path, from, to) {
+                        self.hits += 1;
+                        return Some(path);
+                    }
+                }
+                None => {
+                    self.hits += 1;
+                    return None;
+                }
+            }
+        }
+        self.misses += 1;
+        let path = mesh.find_path(from, to, self.query, self.mode);
+        if self.paths.insert(key, path.clone()).is_none() {
+            self.order.push_back(key);
+        }
+        self.evict();
+        path
+    }
+
+    // Replace cached path endpoints with queried ones, as long as new end segments are clear.
+    fn patch(mesh: &NavMesh, path: &[NavVec3], from: NavVec3, to: NavVec3) -> Option<Vec<NavVec3>> {
+        let mut path = path.to_vec();
+        let count = path.len();
+        if count < 2 {
+            return None;
+        }
+        path[0] = from;
+        path[count - 1] = to;
+        if !mesh.is_segment_clear(path[0], path[1])
+            || !mesh.is_segment_clear(path[count - 2], path[count - 1])
+        {
+            return None;
+        }
+        Some(path)
+    }
+
+    fn cell(&self, mesh: &NavMesh, point: NavVec3) -> NavPathCacheCell {
+        (
+            (point.x / self.cell_size).floor() as i64,
+            (point.y / self.cell_size).floor() as i64,
+            (point.z / self.cell_size).floor() as i64,
+            mesh.find_closest_triangle(point, self.query),
+        )
+    }
+
+    fn evict(&mut self) {
+        while self.paths.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(key) => {
+                    self.paths.remove(&key);
+                }
+                None => break,
+            }
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use crate::{
+    funnel, nav_search::astar, Error, NavClosestPoint, NavConnection, NavMesh, NavQueryOptions,
+    NavResult, NavTriangle, NavVec3, Scalar, ZERO_TRESHOLD,
+};
+use petgraph::{graph::NodeIndex, visit::EdgeRef, Directed, Graph};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use typid::ID;
+
+/// Nav poly mesh identifier.
+pub type NavPolyMeshID = ID<NavPolyMesh>;
+
+/// Convex polygon made of vertices indices, in the same winding order for all polygons.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NavPolygon {
+    /// Polygon vertices indices.
+    pub indices: Vec<u32>,
+}
+
+impl From<Vec<u32>> for NavPolygon {
+    fn from(indices: Vec<u32>) -> Self {
+        Self { indices }
+    }
+}
+
+impl From<&[u32]> for NavPolygon {
+    fn from(indices: &[u32]) -> Self {
+        Self {
+            indices: indices.to_vec(),
+        }
+    }
+}
+
+/// Nav mesh made of convex polygons instead of triangles. Path finding goes over polygons, so
+/// big open areas need far fewer cells to search, while point queries are handled by internal
+/// triangulated nav mesh.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let vertices = vec![
+///     (0.0, 0.0, 0.0).into(), // 0
+///     (1.0, 0.0, 0.0).into(), // 1
+///     (2.0, 0.0, 0.0).into(), // 2
+///     (2.0, 1.0, 0.0).into(), // 3
+///     (1.0, 1.0, 0.0).into(), // 4
+///     (0.0, 1.0, 0.0).into(), // 5
+///     (1.0, 2.0, 0.0).into(), // 6
+///     (2.0, 2.0, 0.0).into(), // 7
+/// ];
+/// let polygons = vec![
+///     vec![0, 1, 4, 5].into(),    // 0
+///     vec![1, 2, 3, 4].into(),    // 1
+///     vec![4, 3, 7, 6].into(),    // 2
+/// ];
+///
+/// let mesh = NavPolyMesh::new(vertices, polygons).unwrap();
+/// assert_eq!(mesh.nav_mesh().triangles().len(), 6);
+/// assert_eq!(mesh.find_path_polygons(0, 2).unwrap().0, vec![0, 1, 2]);
+/// let path = mesh
+///     .find_path((0.5, 0.5, 0.0).into(), (1.5, 1.5, 0.0).into(), NavQuery::Accuracy)
+///     .unwrap();
+/// assert_eq!(path, vec![(0.5, 0.5, 0.0).into(), (1.5, 1.5, 0.0).into()]);
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct NavPolyMesh {
+    id: NavPolyMeshID,
+    polygons: Vec<NavPolygon>,
+    centers: Vec<NavVec3>,
+    normals: Vec<NavVec3>,
+    costs: Vec<Scalar>,
+    // {polygons connection: portal vertices in first polygon winding order}
+    portals: HashMap<(usize, usize), (u32, u32)>,
+    graph: Graph<(), Scalar, Directed>,
+    nodes: Vec<NodeIndex>,
+    nodes_map: HashMap<NodeIndex, usize>,
+    mesh: NavMesh,
+    // polygon index of each triangle of internal nav mesh.
+    triangle_polygons: Vec<usize>,
+}
+
+impl NavPolyMesh {
+    /// Create nav poly mesh from vertices and convex polygons. Returns `Err` if any polygon has
+    /// less than 3 vertices, points to vertex out of bounds or is not convex.
+    pub fn new(vertices: Vec<NavVec3>, polygons: Vec<NavPolygon>) -> NavResult<Self> {
+        let mut centers = Vec::with_capacity(polygons.len());
+        let mut normals = Vec::with_capacity(polygons.len());
+        let mut triangles = vec![];
+        let mut triangle_polygons = vec![];
+        for (index, polygon) in polygons.iter().enumerate() {
+            let count = polygon.indices.len();
+            if count < 3 {
+                return Err(Error::PolygonVerticesCountTooSmall(index as u32, count));
+            }
+            for (local, global) in polygon.indices.iter().enumerate() {
+                if *global as usize >= vertices.len() {
+                    return Err(Error::PolygonVerticeIndexOutOfBounds(
+                        index as u32,
+                        local as u32,
+                        *global,
+                    ));
+                }
+            }
+            let points = polygon
+                .indices
+                .iter()
+                .map(|i| vertices[*i as usize])
+                .collect::<Vec<_>>();
+            // Newell's method works for polygons with collinear vertices.
+            let normal = (0..count)
+                .fold(NavVec3::default(), |a, i| {
+                    let p = points[i];
+                    let q = points[(i + 1) % count];
+                    a + NavVec3::new(
+                        (p.y - q.y) * (p.z + q.z),
+                        (p.z - q.z) * (p.x + q.x),
+                        (p.x - q.x) * (p.y + q.y),
+                    )
+                })
+                .normalize();
+            let convex = (0..count).all(|i| {
+                let a = points[i];
+                let b = points[(i + 1) % count];
+                let c = points[(i + 2) % count];
+                (b - a).cross(c - b).dot(normal) >= -ZERO_TRESHOLD
+            });
+            if !convex {
+                return Err(Error::PolygonNotConvex(index as u32));
+            }
+            centers.push(points.iter().fold(NavVec3::default(), |a, v| a + *v) / count as Scalar);
+            normals.push(normal);
+            for i in 1..(count - 1) {
+                triangles.push(NavTriangle {
+                    first: polygon.indices[0],
+                    second: polygon.indices[i],
+                    third: polygon.indices[i + 1],
+                });
+                triangle_polygons.push(index);
+            }
+        }
+
+        // {edge: [(polygon index, edge vertices in polygon winding order)]}
+        let mut edges = HashMap::<NavConnection, Vec<(usize, (u32, u32))>>::new();
+        for (index, polygon) in polygons.iter().enumerate() {
+            let count = polygon.indices.len();
+            for i in 0..count {
+                let a = polygon.indices[i];
+                let b = polygon.indices[(i + 1) % count];
+                edges
+                    .entry(NavConnection(a, b))
+                    .or_default()
+                    .push((index, (a, b)));
+            }
+        }
+        let mut portals = HashMap::new();
+        for list in edges.values() {
+            for (a, edge) in list {
+                for (b, _) in list {
+                    if a != b {
+                        portals.insert((*a, *b), *edge);
+                    }
+                }
+            }
+        }
+
+        let mut graph = Graph::<(), Scalar, Directed>::with_capacity(polygons.len(), portals.len());
+        let nodes = (0..polygons.len())
+            .map(|_| graph.add_node(()))
+            .collect::<Vec<_>>();
+        for (a, b) in portals.keys() {
+            graph.add_edge(
+                nodes[*a],
+                nodes[*b],
+                (centers[*b] - centers[*a]).sqr_magnitude(),
+            );
+        }
+        let nodes_map = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let mesh = NavMesh::new(vertices, triangles)?;
+        Ok(Self {
+            id: ID::new(),
+            costs: vec![1.0; polygons.len()],
+            polygons,
+            centers,
+            normals,
+            portals,
+            graph,
+            nodes,
+            nodes_map,
+            mesh,
+            triangle_polygons,
+        })
+    }
+
+    /// Nav poly mesh identifier.
+    #[inline]
+    pub fn id(&self) -> NavPolyMeshID {
+        self.id
+    }
+
+    /// Vertices shared by polygons.
+    #[inline]
+    pub fn vertices(&self) -> &[NavVec3] {
+        self.mesh.vertices()
+    }
+
+    /// Convex polygons.
+    #[inline]
+    pub fn polygons(&self) -> &[NavPolygon] {
+        &self.polygons
+    }
+
+    /// Center point of polygon.
+    #[inline]
+    pub fn polygon_center(&self, index: usize) -> Option<NavVec3> {
+        self.centers.get(index).copied()
+    }
+
+    /// Normal of polygon plane.
+    #[inline]
+    pub fn polygon_normal(&self, index: usize) -> Option<NavVec3> {
+        self.normals.get(index).copied()
+    }
+
+    /// Costs of traversing each polygon, multiplying connections distances.
+    #[inline]
+    pub fn polygons_costs(&self) -> &[Scalar] {
+        &self.costs
+    }
+
+    /// Set cost of traversing polygon (clamped at zero). Returns previous cost.
+    pub fn set_polygon_cost(&mut self, index: usize, cost: Scalar) -> Option<Scalar> {
+        let c = self.costs.get_mut(index)?;
+        let old = *c;
+        *c = cost.max(0.0);
+        Some(old)
+    }
+
+    /// Internal triangulated nav mesh, used for point queries.
+    #[inline]
+    pub fn nav_mesh(&self) -> &NavMesh {
+        &self.mesh
+    }
+
+    /// Polygon that given triangle of internal nav mesh belongs to.
+    #[inline]
+    pub fn triangle_polygon(&self, triangle: usize) -> Option<usize> {
+        self.triangle_polygons.get(triangle).copied()
+    }
+
+    /// Portal edge shared by two neighbor polygons: (from, to) in first polygon winding order.
+    pub fn portal(&self, from: usize, to: usize) -> Option<(NavVec3, NavVec3)> {
+        let (a, b) = self.portals.get(&(from, to))?;
+        let vertices = self.mesh.vertices();
+        Some((vertices[*a as usize], vertices[*b as usize]))
+    }
+
+    /// Indices of polygons sharing portal edge with given polygon.
+    pub fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.nodes
+            .get(index)
+            .into_iter()
+            .flat_map(move |node| self.graph.neighbors(*node))
+            .map(move |node| self.nodes_map[&node])
+    }
+
+    /// Find closest point on nav poly mesh.
+    pub fn closest_point(
+        &self,
+        point: NavVec3,
+        query: impl Into<NavQueryOptions>,
+    ) -> Option<NavVec3> {
+        self.mesh.closest_point(point, query)
+    }
+
+    /// Find polygon closest to given point, together with closest point on it.
+    pub fn find_closest_polygon(
+        &self,
+        point: NavVec3,
+        query: impl Into<NavQueryOptions>,
+    ) -> Option<(usize, NavVec3)> {
+        let NavClosestPoint {
+            point, triangle, ..
+        } = self.mesh.closest_point_ex(point, query)?;
+        Some((self.triangle_polygons[triangle], point))
+    }
+
+    /// Find shortest path on nav poly mesh between two points.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    ///
+    /// # Returns
+    /// `Some` with path points on nav poly mesh if found or `None` otherwise.
+    pub fn find_path(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+    ) -> Option<Vec<NavVec3>> {
+        self.find_path_custom(from, to, query, |_, _, _| true)
+    }
+
+    /// Find shortest path on nav poly mesh between two points, providing custom filtering
+    /// function.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    /// * `filter` - closure that gives you a connection distance squared, first polygon index
+    ///   and second polygon index, and returns `false` to exclude that connection from search.
+    ///
+    /// # Returns
+    /// `Some` with path points on nav poly mesh if found or `None` otherwise.
+    pub fn find_path_custom<F>(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+        filter: F,
+    ) -> Option<Vec<NavVec3>>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        let threshold = self.mesh.tolerance().threshold(2);
+        let query = query.into();
+        let (start, from) = self.find_closest_polygon(from, query)?;
+        let (end, to) = self.find_closest_polygon(to, query)?;
+        let (polygons, _) = self.find_path_polygons_custom(start, end, filter)?;
+        if polygons.len() < 2 {
+            return Some(vec![from, to]);
+        }
+        let vertices = self.mesh.vertices();
+        let portals = polygons
+            .windows(2)
+            .map(|pair| {
+                let (a, b) = self.portals[&(pair[0], pair[1])];
+                (
+                    vertices[b as usize],
+                    vertices[a as usize],
+                    self.normals[pair[0]],
+                )
+            })
+            .collect::<Vec<_>>();
+        let normal = self.normals[end];
+        Some(funnel(from, to, &portals, normal, threshold))
+    }
+
+    /// Find shortest path on nav poly mesh between two polygons.
+    ///
+    /// # Arguments
+    /// * `from` - polygon index from.
+    /// * `to` - polygon index to.
+    ///
+    /// # Returns
+    /// `Some` with polygons indices and path cost if found or `None` otherwise.
+    #[inline]
+    pub fn find_path_polygons(&self, from: usize, to: usize) -> Option<(Vec<usize>, Scalar)> {
+        self.find_path_polygons_custom(from, to, |_, _, _| true)
+    }
+
+    /// Find shortest path on nav poly mesh between two polygons, providing custom filtering
+    /// function.
+    ///
+    /// # Arguments
+    /// * `from` - polygon index from.
+    /// * `to` - polygon index to.
+    /// * `filter` - closure that gives you a connection distance squared, first polygon index
+    ///   and second polygon index, and returns `false` to exclude that connection from search.
+    ///
+    /// # Returns
+    /// `Some` with polygons indices and path cost if found or `None` otherwise.
+    pub fn find_path_polygons_custom<F>(
+        &self,
+        from: usize,
+        to: usize,
+        mut filter: F,
+    ) -> Option<(Vec<usize>, Scalar)>
+    where
+        F: FnMut(Scalar, usize, usize) -> bool,
+    {
+        self.nodes.get(from)?;
+        self.nodes.get(to)?;
+        astar(
+            from,
+            to,
+            |a, next| {
+                for e in self.graph.edges(self.nodes[a]) {
+                    let b = self.nodes_map[&e.target()];
+                    let w = *e.weight();
+                    if filter(w, a, b) {
+                        next(b, w * self.costs[a] * self.costs[b]);
+                    }
+                }
+            },
+            |_| 0.0,
+        )
+        .map(|(c, v)| (v, c))
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{NavMesh, NavVec3, Scalar};
+use serde::{Deserialize, Serialize};
+
+/// Reason why path should be requested again.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavReplanReason {
+    /// Agent drifted too far away from path.
+    Drift,
+    /// Path is older than allowed age.
+    Age,
+    /// Nav mesh topology version differs from the one path was found with.
+    TopologyChanged,
+    /// Remaining part of path goes through one of nav mesh blockers.
+    Blocked,
+}
+
+/// Policy deciding when agent following path should automatically request new one.
+/// Every check is optional and disabled checks are never reported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavReplanPolicy {
+    /// Maximal distance between agent position and path before replanning.
+    pub max_drift: Option<Scalar>,
+    /// Maximal path age (in units used by caller, usually seconds) before replanning.
+    pub max_age: Option<Scalar>,
+    /// Replan when nav mesh topology version changes.
+    pub check_topology: bool,
+    /// Agent radius used to test remaining path against nav mesh blockers.
+    pub blocked_radius: Option<Scalar>,
+}
+
+impl Default for NavReplanPolicy {
+    fn default() -> Self {
+        Self {
+            max_drift: None,
+            max_age: None,
+            check_topology: true,
+            blocked_radius: None,
+        }
+    }
+}
+
+impl NavReplanPolicy {
+    /// Decide if path should be replanned.
+    ///
+    /// # Arguments
+    /// * `mesh` - nav mesh that path was found on.
+    /// * `path` - path points.
+    /// * `position` - current agent position.
+    /// * `age` - time elapsed since path was found.
+    /// * `topology_version` - nav mesh topology version at the time path was found.
+    ///
+    /// # Returns
+    /// `Some` with first reason found (in order: topology, age, drift, blocked) or `None` if
+    /// path is still valid.
+    ///
+    /// # Example
+    /// ```
+    /// use navmesh::*;
+    ///
+    /// let mut mesh = NavMesh::new(
+    ///     vec![
+    ///         (0.0, 0.0, 0.0).into(),
+    ///         (4.0, 0.0, 0.0).into(),
+    ///         (4.0, 1.0, 0.0).into(),
+    ///         (0.0, 1.0, 0.0).into(),
+    ///     ],
+    ///     vec![(0, 1, 2).into(), (2, 3, 0).into()],
+    /// )
+    /// .unwrap();
+    /// let version = mesh.topology_version();
+    /// let path = vec![NavVec3::new(0.0, 0.5, 0.0), NavVec3::new(4.0, 0.5, 0.0)];
+    /// let policy = NavReplanPolicy {
+    ///     max_drift: Some(1.0),
+    ///     ..Default::default()
+    /// };
+    /// let position = NavVec3::new(1.0, 0.5, 0.0);
+    /// assert_eq!(policy.evaluate(&mesh, &path, position, 0.0, version), None);
+    /// mesh.add_blocker(NavBlocker {
+    ///     position: NavVec3::new(2.0, 0.5, 0.0),
+    ///     radius: 0.25,
+    /// });
+    /// assert_eq!(
+    ///     policy.evaluate(&mesh, &path, position, 0.0, version),
+    ///     Some(NavReplanReason::TopologyChanged),
+    /// );
+    /// ```
+    pub fn evaluate(
+        &self,
+        mesh: &NavMesh,
+        path: &[NavVec3],
+        position: NavVec3,
+        age: Scalar,
+        topology_version: u64,
+    ) -> Option<NavReplanReason> {
+        if self.check_topology && mesh.topology_version() != topology_version {
+            return Some(NavReplanReason::TopologyChanged);
+        }
+        if let Some(max_age) = self.max_age {
+            if age > max_age {
+                return Some(NavReplanReason::Age);
+            }
+        }
+        let (distance, segment) = Self::closest_segment(path, position)?;
+        if let Some(max_drift) = self.max_drift {
+            if distance > max_drift {
+                return Some(NavReplanReason::Drift);
+            }
+        }
+        if let Some(radius) = self.blocked_radius {
+            let blocked = mesh.blockers().any(|(_, blocker)| {
+                let limit = blocker.radius + radius;
+                path[segment..]
+                    .windows(2)
+                    .any(|pair| blocker.position.distance_to_segment(pair[0], pair[1]) < limit)
+            });
+            if blocked {
+                return Some(NavReplanReason::Blocked);
+            }
+        }
+        None
+    }
+
+    fn closest_segment(path: &[NavVec3], position: NavVec3) -> Option<(Scalar, usize)> {
+        match path.len() {
+            0 => None,
+            1 => Some(((path[0] - position).magnitude(), 0)),
+            _ => path
+                .windows(2)
+                .enumerate()
+                .map(|(i, pair)| (position.distance_to_segment(pair[0], pair[1]), i))
+                .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap()),
+        }
+    }
+}
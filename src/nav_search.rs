@@ -0,0 +1,323 @@
+use crate::{Scalar, SCALAR_MAX};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    hash::Hash,
+};
+
+#[cfg(feature = "parallel")]
+macro_rules! iter {
+    ($v:expr) => {
+        $v.par_iter()
+    };
+}
+#[cfg(not(feature = "parallel"))]
+macro_rules! iter {
+    ($v:expr) => {
+        $v.iter()
+    };
+}
+
+#[cfg(feature = "parallel")]
+macro_rules! chunks_mut {
+    ($v:expr, $n:expr) => {
+        $v.par_chunks_mut($n)
+    };
+}
+#[cfg(not(feature = "parallel"))]
+macro_rules! chunks_mut {
+    ($v:expr, $n:expr) => {
+        $v.chunks_mut($n)
+    };
+}
+
+// Node reached from other chunk: (node, cost, previous node).
+type NavIntegrateSeed = (usize, Scalar, Option<usize>);
+
+// Open list entry (priority, node) of searches, ordered so binary heap pops lowest priority first.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct NavSearchItem(pub Scalar, pub usize);
+
+impl Eq for NavSearchItem {}
+
+impl PartialOrd for NavSearchItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NavSearchItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.partial_cmp(&self.0).unwrap_or(Ordering::Equal)
+    }
+}
+
+// Search state of node, valid only while its stamp matches stamp of current search.
+#[derive(Debug, Default, Copy, Clone)]
+struct NavSearchNode {
+    stamp: u32,
+    cost: Scalar,
+    estimate: Scalar,
+    parent: usize,
+}
+
+// Open list and nodes states of single search, kept allocated between searches. Nodes states
+// are addressed by node index and get invalidated all at once by bumping search stamp, so
+// starting new search does not need to clear them.
+#[derive(Debug, Default)]
+struct NavSearchBuffers {
+    open: BinaryHeap<NavSearchItem>,
+    nodes: Vec<NavSearchNode>,
+    stamp: u32,
+}
+
+impl NavSearchBuffers {
+    fn begin(&mut self) {
+        self.open.clear();
+        self.stamp = self.stamp.wrapping_add(1);
+        if self.stamp == 0 {
+            // stamps wrapped around so states of old searches could become valid again.
+            for node in &mut self.nodes {
+                node.stamp = 0;
+            }
+            self.stamp = 1;
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<&NavSearchNode> {
+        self.nodes
+            .get(index)
+            .filter(|node| node.stamp == self.stamp)
+    }
+
+    fn set(&mut self, index: usize, cost: Scalar, estimate: Scalar, parent: usize) {
+        if index >= self.nodes.len() {
+            self.nodes.resize(index + 1, Default::default());
+        }
+        self.nodes[index] = NavSearchNode {
+            stamp: self.stamp,
+            cost,
+            estimate,
+            parent,
+        };
+    }
+}
+
+thread_local! {
+    // stack of free buffers, so searches started from search callbacks get their own buffers.
+    static BUFFERS: RefCell<Vec<NavSearchBuffers>> = const { RefCell::new(vec![]) };
+}
+
+/// A* search over nodes addressed by dense indices, reusing open list and nodes states
+/// allocated by previous searches of the same thread.
+///
+/// `expand` gets node index and callback that has to be called with index and cost of each
+/// node reachable from it. Gives total cost and nodes from `start` to `goal`.
+pub(crate) fn astar<E, H>(
+    start: usize,
+    goal: usize,
+    mut expand: E,
+    mut heuristic: H,
+) -> Option<(Scalar, Vec<usize>)>
+where
+    E: FnMut(usize, &mut dyn FnMut(usize, Scalar)),
+    H: FnMut(usize) -> Scalar,
+{
+    let mut buffers = BUFFERS
+        .with(|pool| pool.borrow_mut().pop())
+        .unwrap_or_default();
+    buffers.begin();
+    let result = search(&mut buffers, start, goal, &mut expand, &mut heuristic);
+    buffers.open.clear();
+    BUFFERS.with(|pool| pool.borrow_mut().push(buffers));
+    result
+}
+
+/// Same as `astar`, but over nodes addressed by keys (i.e. cells coordinates), that get mapped to
+/// indices as they are discovered.
+pub(crate) fn astar_keyed<K, E, H>(
+    start: K,
+    goal: K,
+    mut expand: E,
+    mut heuristic: H,
+) -> Option<(Scalar, Vec<K>)>
+where
+    K: Copy + Eq + Hash,
+    E: FnMut(K, &mut dyn FnMut(K, Scalar)),
+    H: FnMut(K) -> Scalar,
+{
+    if start == goal {
+        return Some((0.0, vec![start]));
+    }
+    // discovered keys and their indices, start and goal get first two.
+    let keys = RefCell::new((vec![start, goal], HashMap::from([(start, 0), (goal, 1)])));
+    let index_of = |key: K| {
+        let (list, map) = &mut *keys.borrow_mut();
+        *map.entry(key).or_insert_with(|| {
+            list.push(key);
+            list.len() - 1
+        })
+    };
+    let (cost, path) = astar(
+        0,
+        1,
+        |index, next| {
+            let key = keys.borrow().0[index];
+            expand(key, &mut |key, step| next(index_of(key), step));
+        },
+        |index| {
+            let key = keys.borrow().0[index];
+            heuristic(key)
+        },
+    )?;
+    let list = &keys.borrow().0;
+    Some((cost, path.into_iter().map(|index| list[index]).collect()))
+}
+
+fn search<E, H>(
+    buffers: &mut NavSearchBuffers,
+    start: usize,
+    goal: usize,
+    expand: &mut E,
+    heuristic: &mut H,
+) -> Option<(Scalar, Vec<usize>)>
+where
+    E: FnMut(usize, &mut dyn FnMut(usize, Scalar)),
+    H: FnMut(usize) -> Scalar,
+{
+    let estimate = heuristic(start);
+    buffers.set(start, 0.0, estimate, usize::MAX);
+    buffers.open.push(NavSearchItem(estimate, start));
+    while let Some(NavSearchItem(estimate, node)) = buffers.open.pop() {
+        let NavSearchNode {
+            cost,
+            estimate: best,
+            ..
+        } = *buffers.get(node)?;
+        if estimate > best {
+            continue;
+        }
+        if node == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while current != start {
+                current = buffers.get(current)?.parent;
+                path.push(current);
+            }
+            path.reverse();
+            return Some((cost, path));
+        }
+        expand(node, &mut |next, step| {
+            let next_cost = cost + step;
+            if buffers
+                .get(next)
+                .map(|known| next_cost < known.cost)
+                .unwrap_or(true)
+            {
+                let estimate = next_cost + heuristic(next);
+                buffers.set(next, next_cost, estimate, node);
+                buffers.open.push(NavSearchItem(estimate, next));
+            }
+        });
+    }
+    None
+}
+
+/// Dijkstra flood fill over `count` nodes addressed by indices, starting at `sources`. Nodes
+/// are split into chunks of `chunk_size` consecutive indices, each integrated separately (in
+/// parallel with `parallel` feature) from nodes reached from other chunks in previous pass.
+/// Only chunks that got improved this way are integrated in next pass, and after as many passes
+/// as there are chunks the rest gets integrated as single chunk, so number of passes is
+/// bounded. Chunks never read state of other chunks and reached nodes are merged in chunks
+/// order, so result does not depend on order in which chunks get processed.
+///
+/// `expand` gets node index and callback that has to be called with index and cost of each
+/// node reachable from it. Gives accumulated cost (`SCALAR_MAX` for nodes not reached) and
+/// previous node of every node.
+pub(crate) fn integrate_chunked<E>(
+    count: usize,
+    sources: &[usize],
+    chunk_size: usize,
+    expand: E,
+) -> (Vec<Scalar>, Vec<Option<usize>>)
+where
+    E: Fn(usize, &mut dyn FnMut(usize, Scalar)) + Sync,
+{
+    let mut costs = vec![SCALAR_MAX; count];
+    let mut parents = vec![None; count];
+    if count == 0 {
+        return (costs, parents);
+    }
+    let mut chunk_size = chunk_size.clamp(1, count);
+    let chunks = count.div_ceil(chunk_size);
+    let mut seeds = vec![vec![]; chunks];
+    for source in sources.iter().filter(|source| **source < count) {
+        seeds[source / chunk_size].push((*source, 0.0, None));
+    }
+    let mut passes = 0;
+    while seeds.iter().any(|seeds| !seeds.is_empty()) {
+        passes += 1;
+        if passes > chunks && chunk_size < count {
+            seeds = vec![seeds.into_iter().flatten().collect()];
+            chunk_size = count;
+        }
+        let reached = chunks_mut!(costs, chunk_size)
+            .zip(chunks_mut!(parents, chunk_size))
+            .zip(iter!(seeds))
+            .enumerate()
+            .map(|(chunk, ((costs, parents), seeds))| {
+                integrate_chunk(chunk * chunk_size, costs, parents, seeds, &expand)
+            })
+            .collect::<Vec<_>>();
+        seeds = vec![vec![]; count.div_ceil(chunk_size)];
+        for (index, cost, parent) in reached.into_iter().flatten() {
+            if cost < costs[index] {
+                seeds[index / chunk_size].push((index, cost, parent));
+            }
+        }
+    }
+    (costs, parents)
+}
+
+// Dijkstra limited to nodes of single chunk, starting at seeds that improve their costs. Gives
+// nodes of other chunks reached from this chunk.
+fn integrate_chunk<E>(
+    offset: usize,
+    costs: &mut [Scalar],
+    parents: &mut [Option<usize>],
+    seeds: &[NavIntegrateSeed],
+    expand: &E,
+) -> Vec<NavIntegrateSeed>
+where
+    E: Fn(usize, &mut dyn FnMut(usize, Scalar)),
+{
+    let range = offset..(offset + costs.len());
+    let mut reached = vec![];
+    let mut open = BinaryHeap::new();
+    for (index, cost, parent) in seeds.iter().copied() {
+        if cost < costs[index - offset] {
+            costs[index - offset] = cost;
+            parents[index - offset] = parent;
+            open.push(NavSearchItem(cost, index));
+        }
+    }
+    while let Some(NavSearchItem(cost, index)) = open.pop() {
+        if cost > costs[index - offset] {
+            continue;
+        }
+        expand(index, &mut |next, step| {
+            let next_cost = cost + step;
+            if !range.contains(&next) {
+                reached.push((next, next_cost, Some(index)));
+            } else if next_cost < costs[next - offset] {
+                costs[next - offset] = next_cost;
+                parents[next - offset] = Some(index);
+                open.push(NavSearchItem(next_cost, next));
+            }
+        });
+    }
+    reached
+}
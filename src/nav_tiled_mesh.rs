@@ -0,0 +1,397 @@
+use crate::{funnel, NavMesh, NavQueryOptions, NavVec3, Scalar, ZERO_TRESHOLD};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+/// Tile coordinate on XY plane: (column, row).
+pub type NavTileCoord = (i32, i32);
+
+/// Triangle of specific tile.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NavTileNode {
+    /// Tile coordinate.
+    pub tile: NavTileCoord,
+    /// Triangle index in tile nav mesh.
+    pub triangle: usize,
+}
+
+/// Portal between triangles of neighbor tiles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavTileLink {
+    /// Target triangle.
+    pub target: NavTileNode,
+    /// Portal segment shared by both triangles: (from, to).
+    pub portal: (NavVec3, NavVec3),
+}
+
+/// Nav mesh composed of fixed-size tiles that can be added, removed or replaced at runtime,
+/// with path finding crossing tile borders. Tiles are regular nav meshes in world space, which
+/// boundary edges lying on the same border segment get connected.
+///
+/// # Example
+/// ```
+/// use navmesh::*;
+///
+/// let tile = |x: Scalar| {
+///     NavMesh::new(
+///         vec![
+///             (x, 0.0, 0.0).into(),
+///             (x + 1.0, 0.0, 0.0).into(),
+///             (x + 1.0, 1.0, 0.0).into(),
+///             (x, 1.0, 0.0).into(),
+///         ],
+///         vec![(0, 1, 2).into(), (2, 3, 0).into()],
+///     )
+///     .unwrap()
+/// };
+///
+/// let mut mesh = NavTiledMesh::new(1.0);
+/// mesh.add_tile((0, 0), tile(0.0));
+/// mesh.add_tile((1, 0), tile(1.0));
+/// let path = mesh
+///     .find_path((0.5, 0.5, 0.0).into(), (1.5, 0.5, 0.0).into(), NavQuery::Accuracy)
+///     .unwrap();
+/// assert_eq!(path, vec![(0.5, 0.5, 0.0).into(), (1.5, 0.5, 0.0).into()]);
+/// mesh.remove_tile((1, 0));
+/// assert!(mesh
+///     .find_path((0.5, 0.5, 0.0).into(), (1.5, 0.5, 0.0).into(), NavQuery::Accuracy)
+///     .is_none());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct NavTiledMesh {
+    tile_size: Scalar,
+    tiles: HashMap<NavTileCoord, NavMesh>,
+    links: HashMap<NavTileNode, Vec<NavTileLink>>,
+}
+
+impl NavTiledMesh {
+    /// Create empty tiled nav mesh.
+    ///
+    /// # Arguments
+    /// * `tile_size` - size of tile on X and Y axis.
+    pub fn new(tile_size: Scalar) -> Self {
+        Self {
+            tile_size: tile_size.max(ZERO_TRESHOLD),
+            tiles: Default::default(),
+            links: Default::default(),
+        }
+    }
+
+    /// Size of tile on X and Y axis.
+    #[inline]
+    pub fn tile_size(&self) -> Scalar {
+        self.tile_size
+    }
+
+    /// Coordinate of tile that covers given point.
+    #[inline]
+    pub fn tile_coord(&self, point: NavVec3) -> NavTileCoord {
+        (
+            (point.x / self.tile_size).floor() as i32,
+            (point.y / self.tile_size).floor() as i32,
+        )
+    }
+
+    /// Get tile nav mesh.
+    #[inline]
+    pub fn tile(&self, coord: NavTileCoord) -> Option<&NavMesh> {
+        self.tiles.get(&coord)
+    }
+
+    /// Iterator over tiles.
+    #[inline]
+    pub fn tiles(&self) -> impl Iterator<Item = (NavTileCoord, &NavMesh)> {
+        self.tiles.iter().map(|(coord, mesh)| (*coord, mesh))
+    }
+
+    /// Portals leading from given triangle to triangles of neighbor tiles.
+    #[inline]
+    pub fn tile_links(&self, node: NavTileNode) -> &[NavTileLink] {
+        self.links.get(&node).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Add or replace tile.
+    ///
+    /// # Arguments
+    /// * `coord` - tile coordinate.
+    /// * `mesh` - tile nav mesh in world space.
+    ///
+    /// # Returns
+    /// `Some` with replaced tile nav mesh or `None` if there was no tile at `coord`.
+    pub fn add_tile(&mut self, coord: NavTileCoord, mesh: NavMesh) -> Option<NavMesh> {
+        self.unlink_tile(coord);
+        let result = self.tiles.insert(coord, mesh);
+        for neighbor in Self::neighbors(coord) {
+            self.link_tiles(coord, neighbor);
+        }
+        result
+    }
+
+    /// Remove tile.
+    ///
+    /// # Arguments
+    /// * `coord` - tile coordinate.
+    ///
+    /// # Returns
+    /// `Some` with removed tile nav mesh or `None` if there was no tile at `coord`.
+    pub fn remove_tile(&mut self, coord: NavTileCoord) -> Option<NavMesh> {
+        self.unlink_tile(coord);
+        self.tiles.remove(&coord)
+    }
+
+    /// Find closest point on tile that covers query point.
+    ///
+    /// # Arguments
+    /// * `point` - query point.
+    /// * `query` - query quality or query options.
+    ///
+    /// # Returns
+    /// `Some` with triangle and point on it if found or `None` otherwise.
+    pub fn closest_point(
+        &self,
+        point: NavVec3,
+        query: impl Into<NavQueryOptions>,
+    ) -> Option<(NavTileNode, NavVec3)> {
+        let tile = self.tile_coord(point);
+        let closest = self.tiles.get(&tile)?.closest_point_ex(point, query)?;
+        Some((
+            NavTileNode {
+                tile,
+                triangle: closest.triangle,
+            },
+            closest.point,
+        ))
+    }
+
+    /// Find shortest path between two points, crossing tiles borders. Path is pulled tight
+    /// through corridor portals.
+    ///
+    /// # Arguments
+    /// * `from` - query point from.
+    /// * `to` - query point to.
+    /// * `query` - query quality or query options.
+    ///
+    /// # Returns
+    /// `Some` with path points if found or `None` otherwise.
+    pub fn find_path(
+        &self,
+        from: NavVec3,
+        to: NavVec3,
+        query: impl Into<NavQueryOptions>,
+    ) -> Option<Vec<NavVec3>> {
+        let query = query.into();
+        let (start, from) = self.closest_point(from, query)?;
+        let (end, to) = self.closest_point(to, query)?;
+        let (nodes, _) = self.find_path_triangles(start, end)?;
+        if nodes.len() < 2 {
+            return Some(vec![from, to]);
+        }
+        let portals = self.corridor_portals(&nodes)?;
+        let last = nodes[nodes.len() - 1];
+        let normal = self.tiles[&last.tile].triangle_normal(last.triangle);
+        let threshold = self.tiles[&last.tile].tolerance().threshold(2);
+        Some(funnel(from, to, &portals, normal, threshold))
+    }
+
+    /// Find shortest path between two tile triangles.
+    ///
+    /// # Arguments
+    /// * `from` - triangle from.
+    /// * `to` - triangle to.
+    ///
+    /// # Returns
+    /// `Some` with path triangles and path cost if found or `None` otherwise.
+    pub fn find_path_triangles(
+        &self,
+        from: NavTileNode,
+        to: NavTileNode,
+    ) -> Option<(Vec<NavTileNode>, Scalar)> {
+        #[derive(PartialEq)]
+        struct Item(Scalar, NavTileNode);
+
+        impl Eq for Item {}
+
+        impl PartialOrd for Item {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Item {
+            fn cmp(&self, other: &Self) -> Ordering {
+                other.0.partial_cmp(&self.0).unwrap_or(Ordering::Equal)
+            }
+        }
+
+        self.tiles.get(&from.tile)?.areas().get(from.triangle)?;
+        self.tiles.get(&to.tile)?.areas().get(to.triangle)?;
+        let mut costs = HashMap::<NavTileNode, Scalar>::new();
+        let mut parents = HashMap::<NavTileNode, NavTileNode>::new();
+        let mut open = BinaryHeap::new();
+        costs.insert(from, 0.0);
+        open.push(Item(0.0, from));
+        while let Some(Item(cost, node)) = open.pop() {
+            if node == to {
+                let mut result = vec![node];
+                let mut current = node;
+                while let Some(parent) = parents.get(&current) {
+                    result.push(*parent);
+                    current = *parent;
+                }
+                result.reverse();
+                return Some((result, cost));
+            }
+            if costs.get(&node).map(|c| cost > *c).unwrap_or(false) {
+                continue;
+            }
+            for (next, weight) in self.node_neighbors(node) {
+                let next_cost = cost + weight;
+                if costs.get(&next).map(|c| next_cost < *c).unwrap_or(true) {
+                    costs.insert(next, next_cost);
+                    parents.insert(next, node);
+                    open.push(Item(next_cost, next));
+                }
+            }
+        }
+        None
+    }
+
+    fn node_neighbors(&self, node: NavTileNode) -> Vec<(NavTileNode, Scalar)> {
+        let mesh = &self.tiles[&node.tile];
+        let area = &mesh.areas()[node.triangle];
+        let half_edges = mesh.half_edges();
+        let inner = mesh
+            .triangle_half_edges(node.triangle)
+            .into_iter()
+            .filter_map(|index| half_edges[index].twin)
+            .map(|twin| {
+                let other = &mesh.areas()[half_edges[twin].triangle];
+                (
+                    NavTileNode {
+                        tile: node.tile,
+                        triangle: other.triangle as usize,
+                    },
+                    (other.center - area.center).sqr_magnitude() * area.cost * other.cost,
+                )
+            });
+        let outer = self.tile_links(node).iter().map(|link| {
+            let other = &self.tiles[&link.target.tile].areas()[link.target.triangle];
+            (
+                link.target,
+                (other.center - area.center).sqr_magnitude() * area.cost * other.cost,
+            )
+        });
+        inner.chain(outer).collect()
+    }
+
+    fn corridor_portals(&self, nodes: &[NavTileNode]) -> Option<Vec<(NavVec3, NavVec3, NavVec3)>> {
+        nodes
+            .windows(2)
+            .map(|pair| {
+                let (a, b) = (pair[0], pair[1]);
+                let mesh = &self.tiles[&a.tile];
+                if a.tile == b.tile {
+                    return mesh
+                        .corridor_portals(&[a.triangle, b.triangle])?
+                        .first()
+                        .copied();
+                }
+                let normal = mesh.triangle_normal(a.triangle);
+                let center = mesh.areas()[a.triangle].center;
+                let (p, q) = self
+                    .tile_links(a)
+                    .iter()
+                    .find(|link| link.target == b)
+                    .map(|link| link.portal)?;
+                if (p - center).cross(q - center).dot(normal) > 0.0 {
+                    Some((q, p, normal))
+                } else {
+                    Some((p, q, normal))
+                }
+            })
+            .collect()
+    }
+
+    fn neighbors(coord: NavTileCoord) -> [NavTileCoord; 4] {
+        [
+            (coord.0 - 1, coord.1),
+            (coord.0 + 1, coord.1),
+            (coord.0, coord.1 - 1),
+            (coord.0, coord.1 + 1),
+        ]
+    }
+
+    fn unlink_tile(&mut self, coord: NavTileCoord) {
+        self.links.retain(|node, links| {
+            links.retain(|link| link.target.tile != coord);
+            node.tile != coord && !links.is_empty()
+        });
+    }
+
+    fn link_tiles(&mut self, first: NavTileCoord, second: NavTileCoord) {
+        let (a, b) = match (self.tiles.get(&first), self.tiles.get(&second)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return,
+        };
+        let threshold = ZERO_TRESHOLD.max(self.tile_size * 1.0e-5);
+        let boundary = |mesh: &NavMesh| {
+            mesh.half_edges()
+                .iter()
+                .filter(|edge| edge.is_boundary())
+                .map(|edge| {
+                    (
+                        edge.triangle,
+                        mesh.vertices()[edge.origin as usize],
+                        mesh.vertices()[edge.target as usize],
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let boundary_b = boundary(b);
+        let mut found = vec![];
+        for (triangle_a, p, q) in boundary(a) {
+            let length = (q - p).magnitude();
+            if length < threshold {
+                continue;
+            }
+            for (triangle_b, r, s) in &boundary_b {
+                let tr = r.project(p, q);
+                let ts = s.project(p, q);
+                if (NavVec3::unproject(p, q, tr) - *r).magnitude() > threshold
+                    || (NavVec3::unproject(p, q, ts) - *s).magnitude() > threshold
+                {
+                    continue;
+                }
+                let start = tr.min(ts).max(0.0);
+                let end = tr.max(ts).min(1.0);
+                if (end - start) * length <= threshold {
+                    continue;
+                }
+                let portal = (
+                    NavVec3::unproject(p, q, start),
+                    NavVec3::unproject(p, q, end),
+                );
+                let node_a = NavTileNode {
+                    tile: first,
+                    triangle: triangle_a,
+                };
+                let node_b = NavTileNode {
+                    tile: second,
+                    triangle: *triangle_b,
+                };
+                found.push((node_a, node_b, portal));
+            }
+        }
+        for (node_a, node_b, portal) in found {
+            self.links.entry(node_a).or_default().push(NavTileLink {
+                target: node_b,
+                portal,
+            });
+            self.links.entry(node_b).or_default().push(NavTileLink {
+                target: node_a,
+                portal,
+            });
+        }
+    }
+}
@@ -30,7 +30,13 @@ impl NavVec3 {
 
     #[inline]
     pub fn same_as(self, other: Self) -> bool {
-        (other - self).sqr_magnitude() < ZERO_TRESHOLD
+        self.same_as_threshold(other, ZERO_TRESHOLD)
+    }
+
+    /// Tells if squared distance between points is below `threshold`.
+    #[inline]
+    pub fn same_as_threshold(self, other: Self, threshold: Scalar) -> bool {
+        (other - self).sqr_magnitude() < threshold
     }
 
     #[inline]
@@ -97,6 +103,15 @@ impl NavVec3 {
         )
     }
 
+    /// Distance from point to closest point on line segment.
+    pub fn distance_to_segment(self, from: Self, to: Self) -> Scalar {
+        if (to - from).sqr_magnitude() < ZERO_TRESHOLD {
+            return (self - from).magnitude();
+        }
+        let t = self.project(from, to).clamp(0.0, 1.0);
+        (Self::unproject(from, to, t) - self).magnitude()
+    }
+
     #[inline]
     pub fn distance_to_plane(self, origin: Self, normal: Self) -> Scalar {
         normal.dot(self - origin)
@@ -129,7 +144,7 @@ impl NavVec3 {
 
     pub fn raycast_line(from: Self, to: Self, a: Self, b: Self, normal: Self) -> Option<Self> {
         let p = Self::raycast_plane(from, to, a, normal)?;
-        let t = p.project(a, b).max(0.0).min(1.0);
+        let t = p.project(a, b).clamp(0.0, 1.0);
         Some(Self::unproject(a, b, t))
     }
 
@@ -210,14 +225,26 @@ impl NavVec3 {
     }
 
     pub fn is_line_between_points(from: Self, to: Self, a: Self, b: Self, normal: Self) -> bool {
+        Self::is_line_between_points_threshold(from, to, a, b, normal, ZERO_TRESHOLD)
+    }
+
+    /// Same as `is_line_between_points` but with custom threshold of side tests.
+    pub fn is_line_between_points_threshold(
+        from: Self,
+        to: Self,
+        a: Self,
+        b: Self,
+        normal: Self,
+        threshold: Scalar,
+    ) -> bool {
         let n = (to - from).cross(normal);
-        let sa = Self::side(n.dot(a - from));
-        let sb = Self::side(n.dot(b - from));
+        let sa = Self::side(n.dot(a - from), threshold);
+        let sb = Self::side(n.dot(b - from), threshold);
         sa != sb
     }
 
-    fn side(v: Scalar) -> i8 {
-        if v.abs() < ZERO_TRESHOLD {
+    fn side(v: Scalar, threshold: Scalar) -> i8 {
+        if v.abs() < threshold {
             0
         } else {
             v.signum() as i8